@@ -0,0 +1,40 @@
+//! Decodes arbitrary bytes as a framed handshake packet, the first thing read from any
+//! connection. Anything that decodes has to encode back to the same packet.
+
+#![no_main]
+
+use std::io::Cursor;
+
+use ferrumc_codec::dec::{read_packet_header, MAX_STRING_BYTES};
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::packets::Handshake;
+use futures::executor::block_on;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok((packet_id, mut body)) = block_on(read_packet_header(Cursor::new(data))) else {
+        return;
+    };
+    if packet_id.get_val() != 0x00 {
+        return;
+    }
+    let Ok(handshake) = block_on(Handshake::read(&mut body)) else {
+        return;
+    };
+    assert!(handshake.server_address.len() <= MAX_STRING_BYTES);
+
+    let mut encoded = Vec::new();
+    block_on(async {
+        handshake.protocol_version.net_encode(&mut encoded).await?;
+        handshake.server_address.net_encode(&mut encoded).await?;
+        handshake.server_port.net_encode(&mut encoded).await?;
+        handshake.next_state.net_encode(&mut encoded).await
+    })
+    .unwrap();
+    let decoded = block_on(Handshake::read(&mut Cursor::new(&encoded))).unwrap();
+    // Overlong VarInts decode fine, so only their values are compared
+    assert_eq!(decoded.protocol_version.get_val(), handshake.protocol_version.get_val());
+    assert_eq!(decoded.server_address, handshake.server_address);
+    assert_eq!(decoded.server_port, handshake.server_port);
+    assert_eq!(decoded.next_state.get_val(), handshake.next_state.get_val());
+});
@@ -0,0 +1,29 @@
+//! Decodes arbitrary bytes as a framed packet of the status state. The status request has no
+//! fields, so only the ping is decoded; anything that decodes has to encode back to the same ping.
+
+#![no_main]
+
+use std::io::Cursor;
+
+use ferrumc_codec::dec::read_packet_header;
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::packets::PingRequest;
+use futures::executor::block_on;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok((packet_id, mut body)) = block_on(read_packet_header(Cursor::new(data))) else {
+        return;
+    };
+    if packet_id.get_val() != 0x01 {
+        return;
+    }
+    let Ok(ping) = block_on(PingRequest::read(&mut body)) else {
+        return;
+    };
+
+    let mut encoded = Vec::new();
+    block_on(ping.payload.net_encode(&mut encoded)).unwrap();
+    let decoded = block_on(PingRequest::read(&mut Cursor::new(&encoded))).unwrap();
+    assert_eq!(decoded, ping);
+});
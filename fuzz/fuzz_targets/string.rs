@@ -0,0 +1,23 @@
+//! Decodes arbitrary bytes as a length-prefixed string, which must never allocate more than the
+//! protocol allows. Anything that decodes has to encode back to the same string.
+
+#![no_main]
+
+use std::io::Cursor;
+
+use ferrumc_codec::dec::{read_string, MAX_STRING_BYTES};
+use ferrumc_codec::enc::NetEncode;
+use futures::executor::block_on;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(string) = block_on(read_string(&mut Cursor::new(data), MAX_STRING_BYTES)) else {
+        return;
+    };
+    assert!(string.len() <= MAX_STRING_BYTES);
+
+    let mut encoded = Vec::new();
+    block_on(string.net_encode(&mut encoded)).unwrap();
+    let decoded = block_on(read_string(&mut Cursor::new(&encoded), MAX_STRING_BYTES)).unwrap();
+    assert_eq!(decoded, string);
+});
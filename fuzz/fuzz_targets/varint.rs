@@ -0,0 +1,24 @@
+//! Decodes arbitrary bytes as a VarInt, which never takes more than 5 bytes. Anything that
+//! decodes has to encode back to the same value.
+
+#![no_main]
+
+use std::io::Cursor;
+
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use futures::executor::block_on;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(varint) = block_on(VarInt::read(&mut Cursor::new(data))) else {
+        return;
+    };
+    assert!(varint.get_len() <= 5);
+
+    let mut encoded = Vec::new();
+    block_on(varint.net_encode(&mut encoded)).unwrap();
+    // Overlong encodings of small values decode fine, so only the value is compared
+    let decoded = block_on(VarInt::read(&mut Cursor::new(&encoded))).unwrap();
+    assert_eq!(decoded.get_val(), varint.get_val());
+});
@@ -0,0 +1,22 @@
+//! Decodes arbitrary bytes as a VarLong. Anything that decodes has to encode back to the same
+//! value.
+
+#![no_main]
+
+use std::io::Cursor;
+
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varlong::Varlong;
+use futures::executor::block_on;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(varlong) = block_on(Varlong::read(&mut Cursor::new(data))) else {
+        return;
+    };
+
+    let mut encoded = Vec::new();
+    block_on(varlong.net_encode(&mut encoded)).unwrap();
+    let decoded = block_on(Varlong::read(&mut Cursor::new(&encoded))).unwrap();
+    assert_eq!(i64::from(decoded), i64::from(varlong));
+});
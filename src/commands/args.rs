@@ -0,0 +1,304 @@
+//! Splitting commands into arguments, and reading those as typed values.
+//!
+//! Parse failures are [Error::InvalidArgument]s, whose message is shown to the player as is.
+
+use std::str::FromStr;
+
+use crate::utils::components::rotation::Rotation;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Splits a command on whitespace, keeping `"quoted strings"` together.
+///
+/// Inside quotes, `\"` and `\\` escape a quote and a backslash.
+pub fn tokenize(input: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut token = String::new();
+        if c == '"' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(escaped @ ('"' | '\\')) => token.push(escaped),
+                        Some(other) => {
+                            token.push('\\');
+                            token.push(other);
+                        }
+                        None => return Err(unterminated()),
+                    },
+                    Some(other) => token.push(other),
+                    None => return Err(unterminated()),
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn unterminated() -> Error {
+    Error::InvalidArgument("Unterminated quoted string".to_string())
+}
+
+/// Reads a command's arguments in order, as the types the command expects.
+///
+/// The `name` the readers take is only used in their error messages, e.g.
+/// `Missing argument <player>`.
+pub struct Arguments {
+    args: Vec<String>,
+    next: usize,
+}
+
+impl Arguments {
+    pub fn new(args: Vec<String>) -> Self {
+        Self { args, next: 0 }
+    }
+
+    /// How many arguments haven't been read yet.
+    pub fn remaining(&self) -> usize {
+        self.args.len() - self.next
+    }
+
+    pub fn string(&mut self, name: &str) -> Result<String> {
+        self.optional_string()
+            .ok_or_else(|| Error::InvalidArgument(format!("Missing argument <{}>", name)))
+    }
+
+    pub fn optional_string(&mut self) -> Option<String> {
+        let arg = self.args.get(self.next)?.clone();
+        self.next += 1;
+        Some(arg)
+    }
+
+    /// Every argument that hasn't been read yet, joined back together with spaces.
+    pub fn rest(&mut self) -> String {
+        let rest = self.args[self.next..].join(" ");
+        self.next = self.args.len();
+        rest
+    }
+
+    pub fn int(&mut self, name: &str) -> Result<i32> {
+        self.parse(name, "an integer")
+    }
+
+    pub fn number(&mut self, name: &str) -> Result<f64> {
+        self.parse(name, "a number")
+    }
+
+    fn parse<T: FromStr>(&mut self, name: &str, expected: &str) -> Result<T> {
+        let arg = self.string(name)?;
+        arg.parse().map_err(|_| {
+            Error::InvalidArgument(format!(
+                "Expected {} for <{}>, got \"{}\"",
+                expected, name, arg
+            ))
+        })
+    }
+
+    /// Three coordinates, each either absolute, `~` relative to `origin`, or all three `^` local to
+    /// where the sender is looking (left, up, forwards).
+    pub fn coordinates(
+        &mut self,
+        name: &str,
+        origin: &Position,
+        rotation: &Rotation,
+    ) -> Result<(f64, f64, f64)> {
+        let mut coordinates = [Coordinate::Absolute(0.0); 3];
+        for coordinate in &mut coordinates {
+            *coordinate = Coordinate::parse(name, &self.string(name)?)?;
+        }
+
+        let local = coordinates
+            .iter()
+            .filter(|c| matches!(c, Coordinate::Local(_)))
+            .count();
+        let origin = (origin.x as f64, origin.y as f64, origin.z as f64);
+
+        match (local, coordinates) {
+            (0, [x, y, z]) => Ok((x.resolve(origin.0), y.resolve(origin.1), z.resolve(origin.2))),
+            (3, [Coordinate::Local(left), Coordinate::Local(up), Coordinate::Local(forwards)]) => {
+                Ok(resolve_local(origin, rotation, (left, up, forwards)))
+            }
+            _ => Err(Error::InvalidArgument(format!(
+                "<{}> can't mix local (^) and world coordinates",
+                name
+            ))),
+        }
+    }
+
+    /// Like [Arguments::coordinates], rounded down to the block they're in.
+    pub fn block_position(
+        &mut self,
+        name: &str,
+        origin: &Position,
+        rotation: &Rotation,
+    ) -> Result<(i64, i64, i64)> {
+        let (x, y, z) = self.coordinates(name, origin, rotation)?;
+        Ok((x.floor() as i64, y.floor() as i64, z.floor() as i64))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Coordinate {
+    Absolute(f64),
+    Relative(f64),
+    Local(f64),
+}
+
+impl Coordinate {
+    fn parse(name: &str, arg: &str) -> Result<Self> {
+        let invalid = || {
+            Error::InvalidArgument(format!(
+                "Expected a coordinate for <{}>, got \"{}\"",
+                name, arg
+            ))
+        };
+        let offset = |rest: &str| {
+            if rest.is_empty() {
+                Ok(0.0)
+            } else {
+                rest.parse::<f64>().map_err(|_| invalid())
+            }
+        };
+
+        if let Some(rest) = arg.strip_prefix('~') {
+            Ok(Coordinate::Relative(offset(rest)?))
+        } else if let Some(rest) = arg.strip_prefix('^') {
+            Ok(Coordinate::Local(offset(rest)?))
+        } else {
+            Ok(Coordinate::Absolute(arg.parse().map_err(|_| invalid())?))
+        }
+    }
+
+    fn resolve(self, origin: f64) -> f64 {
+        match self {
+            Coordinate::Absolute(value) => value,
+            Coordinate::Relative(offset) | Coordinate::Local(offset) => origin + offset,
+        }
+    }
+}
+
+/// Moves `origin` along the axes of where `rotation` is looking, the same way vanilla does.
+fn resolve_local(
+    origin: (f64, f64, f64),
+    rotation: &Rotation,
+    (left, up, forwards): (f64, f64, f64),
+) -> (f64, f64, f64) {
+    let yaw = (rotation.yaw as f64 + 90.0).to_radians();
+    let pitch = -(rotation.pitch as f64).to_radians();
+    let pitch_up = (-(rotation.pitch as f64) + 90.0).to_radians();
+
+    let forwards_axis = (yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos());
+    let up_axis = (
+        yaw.cos() * pitch_up.cos(),
+        pitch_up.sin(),
+        yaw.sin() * pitch_up.cos(),
+    );
+    // forwards x up, flipped
+    let left_axis = (
+        -(forwards_axis.1 * up_axis.2 - forwards_axis.2 * up_axis.1),
+        -(forwards_axis.2 * up_axis.0 - forwards_axis.0 * up_axis.2),
+        -(forwards_axis.0 * up_axis.1 - forwards_axis.1 * up_axis.0),
+    );
+
+    (
+        origin.0 + forwards_axis.0 * forwards + up_axis.0 * up + left_axis.0 * left,
+        origin.1 + forwards_axis.1 * forwards + up_axis.1 * up + left_axis.1 * left,
+        origin.2 + forwards_axis.2 * forwards + up_axis.2 * up + left_axis.2 * left,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(input: &str) -> Arguments {
+        Arguments::new(tokenize(input).unwrap())
+    }
+
+    fn assert_close(actual: (f64, f64, f64), expected: (f64, f64, f64)) {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(
+            close(actual.0, expected.0) && close(actual.1, expected.1) && close(actual.2, expected.2),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_quoted_strings() {
+        assert_eq!(
+            tokenize(r#"kick Bob "no \"griefing\" please"  extra"#).unwrap(),
+            vec!["kick", "Bob", r#"no "griefing" please"#, "extra"]
+        );
+        assert_eq!(tokenize(r#"say """#).unwrap(), vec!["say", ""]);
+        assert!(matches!(
+            tokenize(r#"say "oops"#),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_relative_coordinates() {
+        let origin = Position::new(10, 64, -5);
+        let rotation = Rotation::new(0.0, 0.0);
+
+        let coordinates = args("~ ~2 ~-0.5").coordinates("pos", &origin, &rotation);
+        assert_close(coordinates.unwrap(), (10.0, 66.0, -5.5));
+
+        let coordinates = args("3 ~ 7").coordinates("pos", &origin, &rotation);
+        assert_close(coordinates.unwrap(), (3.0, 64.0, 7.0));
+
+        assert!(args("^ ~ ^").coordinates("pos", &origin, &rotation).is_err());
+    }
+
+    #[test]
+    fn test_local_coordinates() {
+        let origin = Position::new(0, 64, 0);
+
+        // Yaw 0 looks towards +z, so left is +x
+        let rotation = Rotation::new(0.0, 0.0);
+        let coordinates = args("^1 ^2 ^3").coordinates("pos", &origin, &rotation);
+        assert_close(coordinates.unwrap(), (1.0, 66.0, 3.0));
+
+        // Looking straight up, forwards is +y
+        let rotation = Rotation::new(0.0, -90.0);
+        let coordinates = args("^ ^ ^5").coordinates("pos", &origin, &rotation);
+        assert_close(coordinates.unwrap(), (0.0, 69.0, 0.0));
+    }
+
+    #[test]
+    fn test_int_parse_error() {
+        let mut arguments = args("12 twelve");
+        assert_eq!(arguments.int("count").unwrap(), 12);
+
+        let Err(Error::InvalidArgument(message)) = arguments.int("count") else {
+            panic!("Expected an invalid argument error");
+        };
+        assert_eq!(message, "Expected an integer for <count>, got \"twelve\"");
+
+        let Err(Error::InvalidArgument(message)) = arguments.int("count") else {
+            panic!("Expected an invalid argument error");
+        };
+        assert_eq!(message, "Missing argument <count>");
+    }
+}
@@ -0,0 +1,49 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use tracing::{error, info};
+
+use crate::commands::{Command, CommandContext};
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// `/backup`: Export the database to `backups/<world>-<unix timestamp>.ferrumc-backup`.
+pub struct BackupCommand;
+
+#[async_trait]
+impl Command for BackupCommand {
+    fn name(&self) -> &'static str {
+        "backup"
+    }
+
+    fn permission_level(&self) -> u8 {
+        4
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Error::Generic(e.to_string()))?
+            .as_secs();
+        let dir = get_global_config().paths.backups()?;
+        tokio::fs::create_dir_all(&dir).await?;
+        let path = dir.join(format!(
+            "{}-{}.ferrumc-backup",
+            get_global_config().world,
+            timestamp
+        ));
+
+        ctx.reply("Starting backup...").await?;
+
+        match ctx.state.database.export(&path).await {
+            Ok(entries) => {
+                info!("Exported {} entries to {}", entries, path.display());
+                ctx.reply(format!("Backup saved to {}", path.display())).await
+            }
+            Err(e) => {
+                error!("Backup failed: {}", e);
+                ctx.reply(format!("Backup failed: {}", e)).await
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Tab completion of commands, answering the client's suggestion requests.
+
+use crate::commands::{get_command, permission_level, ALL_COMMANDS};
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::components::player::online_players;
+use crate::utils::prelude::*;
+
+/// Ways to complete the word being typed, which is `length` long and starts at `start`.
+#[derive(Debug, PartialEq)]
+pub struct Completions {
+    pub start: usize,
+    pub length: usize,
+    pub matches: Vec<String>,
+}
+
+/// Completes `text`, a partly typed command including the leading `/`.
+///
+/// The first word completes to the commands the sender may run, later ones to whatever that
+/// command suggests with [crate::commands::Command::suggest].
+pub async fn complete(
+    text: &str,
+    sender: ConnectionId,
+    state: &GlobalState,
+) -> Result<Completions> {
+    let input = text.strip_prefix('/').unwrap_or(text);
+    let word_start = input.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = &input[word_start..];
+
+    let mut completions = Completions {
+        start: text.len() - input.len() + word_start,
+        length: word.len(),
+        matches: Vec::new(),
+    };
+
+    let level = permission_level(sender, state).await?;
+    let candidates = if word_start == 0 {
+        ALL_COMMANDS
+            .iter()
+            .filter(|command| command.permission_level() <= level)
+            .map(|command| command.name().to_string())
+            .collect()
+    } else {
+        let mut previous = input[..word_start].split_whitespace();
+        let Some(command) = previous.next().and_then(get_command) else {
+            return Ok(completions);
+        };
+        if command.permission_level() > level {
+            return Ok(completions);
+        }
+        command.suggest(previous.count(), state).await
+    };
+
+    let word = word.to_lowercase();
+    completions.matches = candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&word))
+        .collect();
+    completions.matches.sort();
+
+    Ok(completions)
+}
+
+/// The names of everyone online, for commands that take a player.
+pub async fn player_names(state: &GlobalState) -> Vec<String> {
+    online_players(&state.world)
+        .await
+        .into_iter()
+        .map(|player| player.username)
+        .collect()
+}
+
+/// [player_names] for the argument at `index` if it's the player argument at `player_index`,
+/// otherwise nothing. Matching them against what's typed is left to [complete].
+pub async fn suggest_player_at(
+    index: usize,
+    player_index: usize,
+    state: &GlobalState,
+) -> Vec<String> {
+    match index == player_index {
+        true => player_names(state).await,
+        false => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, set_op_level, test_state};
+
+    #[tokio::test]
+    async fn test_complete_command_name() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Alice").await;
+        set_op_level(&state, player, 4).await;
+
+        let completions = complete("/ki", player, &state).await.unwrap();
+        assert_eq!(
+            completions,
+            Completions {
+                start: 1,
+                length: 2,
+                matches: vec!["kick".to_string()],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_complete_player_name() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Alice").await;
+        let (_, _alex_client) = add_test_player(&state, "Alex").await;
+        let (_, _bob_client) = add_test_player(&state, "Bob").await;
+        set_op_level(&state, player, 4).await;
+
+        let completions = complete("/kick al", player, &state).await.unwrap();
+        assert_eq!(completions.start, 6);
+        assert_eq!(completions.length, 2);
+        assert_eq!(completions.matches, vec!["Alex", "Alice"]);
+
+        // The reason isn't a player
+        let completions = complete("/kick Bob a", player, &state).await.unwrap();
+        assert!(completions.matches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_permitted_commands() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Alice").await;
+
+        let completions = complete("/", player, &state).await.unwrap();
+        assert!(completions.matches.contains(&"list".to_string()));
+        assert!(!completions.matches.contains(&"kick".to_string()));
+
+        let completions = complete("/kick A", player, &state).await.unwrap();
+        assert!(completions.matches.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::{Command, CommandContext};
+use crate::net::packets::outgoing::change_difficulty::ChangeDifficulty;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+use crate::world::difficulty::Difficulty;
+
+/// `/difficulty [difficulty]`: Show the difficulty, or change it for everyone.
+pub struct DifficultyCommand;
+
+#[async_trait]
+impl Command for DifficultyCommand {
+    fn name(&self) -> &'static str {
+        "difficulty"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let Some(name) = ctx.arguments().optional_string() else {
+            let difficulty = ctx.state.difficulty.get();
+            return ctx.reply(format!("The difficulty is {}", difficulty)).await;
+        };
+        let Ok(difficulty) = name.parse::<Difficulty>() else {
+            return ctx.reply(format!("Unknown difficulty: {}", name)).await;
+        };
+
+        ctx.state.difficulty.set(difficulty);
+        broadcast(&ChangeDifficulty::new(difficulty), &ctx.state, None).await?;
+        info!("Set the difficulty to {}", difficulty);
+        ctx.reply(format!("The difficulty has been set to {}", difficulty))
+            .await
+    }
+
+    async fn suggest(&self, index: usize, _state: &GlobalState) -> Vec<String> {
+        match index {
+            0 => Difficulty::ALL.iter().map(|d| d.to_string()).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+    use crate::world::difficulty::Difficulty;
+
+    #[tokio::test]
+    async fn test_set_difficulty() {
+        let state = test_state().await;
+        let (operator, mut client) = add_test_player(&state, "Operator").await;
+        set_op_level(&state, operator, 2).await;
+        assert_eq!(state.difficulty.get(), Difficulty::Normal);
+
+        dispatch("difficulty hard", operator, state.clone()).await.unwrap();
+        assert_eq!(state.difficulty.get(), Difficulty::Hard);
+
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x0C);
+        assert_eq!(body, vec![3, 0]);
+        let (_, reply) = read_packet(&mut client).await;
+        assert!(String::from_utf8_lossy(&reply).contains("set to hard"));
+    }
+}
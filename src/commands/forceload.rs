@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::{Command, CommandContext};
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+const USAGE: &str = "Usage: /forceload add|remove <x> <z> or /forceload query [<x> <z>]";
+
+/// `/forceload add|remove <x> <z>` or `/forceload query [<x> <z>]`: Keep the chunk containing a
+/// block position loaded without any players near it, see [crate::database::forceload].
+pub struct ForceloadCommand;
+
+#[async_trait]
+impl Command for ForceloadCommand {
+    fn name(&self) -> &'static str {
+        "forceload"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.arguments();
+        let action = args.string("action")?;
+        if !["add", "remove", "query"].contains(&action.as_str()) {
+            return ctx.reply(USAGE).await;
+        }
+        if action == "query" && args.remaining() == 0 {
+            let mut chunks: Vec<String> = ctx
+                .state
+                .database
+                .force_loaded_chunks()
+                .iter()
+                .map(|chunk| format!("[{}, {}]", chunk.x, chunk.z))
+                .collect();
+            chunks.sort();
+            return ctx
+                .reply(format!("{} force-loaded chunks: {}", chunks.len(), chunks.join(", ")))
+                .await;
+        }
+
+        let chunk = (args.int("x")? >> 4, args.int("z")? >> 4);
+        let database = &ctx.state.database;
+        let config = get_global_config();
+        let dimension = config.default_dimension.as_str();
+        let message = match action.as_str() {
+            "add" => match database.force_load(chunk.0, chunk.1, dimension).await? {
+                true => {
+                    info!("Force-loaded chunk {:?}", chunk);
+                    format!("Chunk [{}, {}] is now force-loaded", chunk.0, chunk.1)
+                }
+                false => format!("Chunk [{}, {}] is already force-loaded", chunk.0, chunk.1),
+            },
+            "remove" => match database.unforce_load(chunk.0, chunk.1, dimension).await? {
+                true => {
+                    info!("Stopped force-loading chunk {:?}", chunk);
+                    format!("Chunk [{}, {}] is no longer force-loaded", chunk.0, chunk.1)
+                }
+                false => format!("Chunk [{}, {}] isn't force-loaded", chunk.0, chunk.1),
+            },
+            "query" => match database.is_force_loaded(chunk.0, chunk.1, dimension) {
+                true => format!("Chunk [{}, {}] is force-loaded", chunk.0, chunk.1),
+                false => format!("Chunk [{}, {}] isn't force-loaded", chunk.0, chunk.1),
+            },
+            _ => unreachable!("Checked above"),
+        };
+        ctx.reply(message).await
+    }
+
+    async fn suggest(&self, index: usize, _state: &GlobalState) -> Vec<String> {
+        match index {
+            0 => vec!["add".to_string(), "remove".to_string(), "query".to_string()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+
+    #[tokio::test]
+    async fn test_forceload_add_and_remove() {
+        let state = test_state().await;
+        let (operator, mut client) = add_test_player(&state, "Operator").await;
+        set_op_level(&state, operator, 2).await;
+
+        dispatch("forceload add 100 -20", operator, state.clone()).await.unwrap();
+        assert!(state.database.is_force_loaded(6, -2, "overworld"));
+        let (_, reply) = read_packet(&mut client).await;
+        assert!(String::from_utf8_lossy(&reply).contains("Chunk [6, -2] is now force-loaded"));
+
+        dispatch("forceload remove 100 -20", operator, state.clone()).await.unwrap();
+        assert!(!state.database.is_force_loaded(6, -2, "overworld"));
+    }
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::completion::player_names;
+use crate::commands::{Command, CommandContext};
+use crate::net::packets::outgoing::game_event::GameEvent;
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::GameMode;
+use crate::utils::components::player::Player;
+use crate::utils::prelude::*;
+
+/// `/gamemode <mode> [player]`: Change your own game mode, or another online player's.
+pub struct GameModeCommand;
+
+#[async_trait]
+impl Command for GameModeCommand {
+    fn name(&self) -> &'static str {
+        "gamemode"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.arguments();
+        let name = args.string("mode")?;
+        let Ok(mode) = name.parse::<GameMode>() else {
+            return ctx.reply(format!("Unknown game mode: {}", name)).await;
+        };
+
+        let target = match args.optional_string() {
+            Some(player) => match ctx.state.connections.by_name(&player) {
+                Some(conn) => conn.read().await.id,
+                None => {
+                    return ctx.reply(format!("No player named {} is online", player)).await;
+                }
+            },
+            None => ctx.sender,
+        };
+
+        ctx.state
+            .world
+            .get_component_storage()
+            .insert(target, mode);
+        let conn = ctx.state.connections.get_connection(target)?;
+        conn.read()
+            .await
+            .send_packet(GameEvent::change_game_mode(mode))
+            .await?;
+
+        let username = ctx.state.world.get_component::<Player>(target).await?.username.clone();
+        info!("Set {}'s game mode to {}", username, mode);
+        ctx.reply(format!("Set {}'s game mode to {}", username, mode)).await
+    }
+
+    async fn suggest(&self, index: usize, state: &GlobalState) -> Vec<String> {
+        match index {
+            0 => GameMode::ALL.iter().map(|mode| mode.to_string()).collect(),
+            1 => player_names(state).await,
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+    use crate::utils::components::gamemode::GameMode;
+
+    #[tokio::test]
+    async fn test_change_other_players_game_mode() {
+        let state = test_state().await;
+        let (operator, mut operator_client) = add_test_player(&state, "Operator").await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+        set_op_level(&state, operator, 2).await;
+
+        dispatch("gamemode creative Player", operator, state.clone()).await.unwrap();
+
+        let mode = *state.world.get_component::<GameMode>(player).await.unwrap();
+        assert_eq!(mode, GameMode::Creative);
+        let (packet_id, _) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x1F);
+        let (_, reply) = read_packet(&mut operator_client).await;
+        assert!(String::from_utf8_lossy(&reply).contains("Set Player's game mode to creative"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_game_mode() {
+        let state = test_state().await;
+        let (operator, mut client) = add_test_player(&state, "Operator").await;
+        set_op_level(&state, operator, 2).await;
+
+        dispatch("gamemode hardcore", operator, state.clone()).await.unwrap();
+
+        assert!(state.world.get_component::<GameMode>(operator).await.is_err());
+        let (_, reply) = read_packet(&mut client).await;
+        assert!(String::from_utf8_lossy(&reply).contains("Unknown game mode"));
+    }
+}
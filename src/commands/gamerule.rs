@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::{Command, CommandContext};
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+use crate::world::gamerules::DEFAULTS;
+
+/// `/gamerule <rule> [value]`: Show a gamerule, or change it.
+pub struct GameRuleCommand;
+
+#[async_trait]
+impl Command for GameRuleCommand {
+    fn name(&self) -> &'static str {
+        "gamerule"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.arguments();
+        let rule = args.string("rule")?;
+        let gamerules = &ctx.state.gamerules;
+
+        let Some(value) = args.optional_string() else {
+            let Some(value) = gamerules.get(&rule) else {
+                return ctx.reply(format!("Unknown gamerule: {}", rule)).await;
+            };
+            return ctx
+                .reply(format!("Gamerule {} is currently set to: {}", rule, value))
+                .await;
+        };
+
+        let value = gamerules.set(&ctx.state.database, &rule, &value).await?;
+        info!("Set gamerule {} to {}", rule, value);
+        ctx.reply(format!("Gamerule {} is now set to: {}", rule, value))
+            .await
+    }
+
+    async fn suggest(&self, index: usize, _state: &GlobalState) -> Vec<String> {
+        match index {
+            0 => DEFAULTS.iter().map(|(name, _)| name.to_string()).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpStream;
+
+    use crate::commands::dispatch;
+    use crate::state::GlobalState;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+    use crate::world::gamerules::{GameRuleValue, DO_DAYLIGHT_CYCLE};
+
+    /// Runs a command and returns the reply.
+    async fn run(state: &GlobalState, sender: u32, client: &mut TcpStream, input: &str) -> String {
+        dispatch(input, sender, state.clone()).await.unwrap();
+        let (_, body) = read_packet(client).await;
+        String::from_utf8_lossy(&body).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_set_and_query_gamerule() {
+        let state = test_state().await;
+        let (operator, mut client) = add_test_player(&state, "Operator").await;
+        set_op_level(&state, operator, 2).await;
+        let client = &mut client;
+
+        let reply = run(&state, operator, client, "gamerule doDaylightCycle false").await;
+        assert!(reply.contains("now set to: false"));
+        assert!(!state.gamerules.is_enabled(DO_DAYLIGHT_CYCLE));
+
+        let reply = run(&state, operator, client, "gamerule doDaylightCycle sometimes").await;
+        assert!(reply.contains("true or false"));
+        let reply = run(&state, operator, client, "gamerule noSuchRule true").await;
+        assert!(reply.contains("Unknown gamerule"));
+        assert!(!state.gamerules.is_enabled(DO_DAYLIGHT_CYCLE));
+
+        let reply = run(&state, operator, client, "gamerule randomTickSpeed").await;
+        assert!(reply.contains("currently set to: 3"));
+        run(&state, operator, client, "gamerule randomTickSpeed 7").await;
+        let value = state.gamerules.get("randomTickSpeed");
+        assert_eq!(value, Some(GameRuleValue::Int(7)));
+    }
+}
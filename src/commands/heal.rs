@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+
+use crate::commands::completion::suggest_player_at;
+use crate::commands::{Command, CommandContext};
+use crate::net::player_health::{set_food, set_health};
+use crate::state::GlobalState;
+use crate::utils::components::health::{DEFAULT_SATURATION, MAX_FOOD, MAX_HEALTH};
+use crate::utils::components::player::Player;
+use crate::utils::prelude::*;
+
+/// `/heal [player]`: Fill your own health and hunger bars, or another online player's.
+pub struct HealCommand;
+
+#[async_trait]
+impl Command for HealCommand {
+    fn name(&self) -> &'static str {
+        "heal"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let target = match ctx.arguments().optional_string() {
+            Some(player) => match ctx.state.connections.by_name(&player) {
+                Some(conn) => conn.read().await.id,
+                None => {
+                    return ctx.reply(format!("No player named {} is online", player)).await;
+                }
+            },
+            None => ctx.sender,
+        };
+
+        set_health(target, MAX_HEALTH, &ctx.state).await?;
+        set_food(target, MAX_FOOD, DEFAULT_SATURATION, &ctx.state).await?;
+
+        let username = ctx.state.world.get_component::<Player>(target).await?.username.clone();
+        ctx.reply(format!("Healed {}", username)).await
+    }
+
+    async fn suggest(&self, index: usize, state: &GlobalState) -> Vec<String> {
+        suggest_player_at(index, 0, state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::net::player_health::set_health;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+    use crate::utils::components::health::{Health, MAX_HEALTH};
+
+    #[tokio::test]
+    async fn test_heal_other_player() {
+        let state = test_state().await;
+        let (operator, mut operator_client) = add_test_player(&state, "Operator").await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+        set_op_level(&state, operator, 2).await;
+        set_health(player, 4.0, &state).await.unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x57);
+
+        dispatch("heal Player", operator, state.clone()).await.unwrap();
+
+        let health = *state.world.get_component::<Health>(player).await.unwrap();
+        assert_eq!(health.get(), MAX_HEALTH);
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x57);
+        assert_eq!(body[..4], MAX_HEALTH.to_be_bytes());
+        let (_, reply) = read_packet(&mut operator_client).await;
+        assert!(String::from_utf8_lossy(&reply).contains("Healed Player"));
+    }
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::completion::suggest_player_at;
+use crate::commands::{Command, CommandContext};
+use crate::net::drop_conn;
+use crate::net::packets::outgoing::disconnect::Disconnect;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+const DEFAULT_REASON: &str = "Kicked by an operator";
+
+/// `/kick <player> [reason]`: Disconnect a player, showing them the reason.
+pub struct KickCommand;
+
+#[async_trait]
+impl Command for KickCommand {
+    fn name(&self) -> &'static str {
+        "kick"
+    }
+
+    fn permission_level(&self) -> u8 {
+        3
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.arguments();
+        let name = args.string("player")?;
+        let reason = match args.rest() {
+            reason if reason.is_empty() => DEFAULT_REASON.to_string(),
+            reason => reason,
+        };
+
+        let Some(conn) = ctx.state.connections.by_name(&name) else {
+            return ctx.reply(format!("No player named {} is online", name)).await;
+        };
+        let conn_id = {
+            let conn = conn.read().await;
+            conn.send_packet(Disconnect::new(reason.as_str())).await?;
+            conn.id
+        };
+        drop_conn(conn_id, ctx.state.clone()).await?;
+        info!("Kicked {}: {}", name, reason);
+
+        if conn_id != ctx.sender {
+            ctx.reply(format!("Kicked {}: {}", name, reason)).await?;
+        }
+        Ok(())
+    }
+
+    async fn suggest(&self, index: usize, state: &GlobalState) -> Vec<String> {
+        suggest_player_at(index, 0, state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+    use crate::utils::components::player::Player;
+
+    #[tokio::test]
+    async fn test_kick_player() {
+        let state = test_state().await;
+        let (operator, _operator_client) = add_test_player(&state, "Operator").await;
+        let (target, mut target_client) = add_test_player(&state, "Target").await;
+        set_op_level(&state, operator, 3).await;
+
+        dispatch("kick target no griefing", operator, state.clone()).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut target_client).await;
+        assert_eq!(packet_id, 0x1A);
+        assert!(String::from_utf8_lossy(&body).contains("no griefing"));
+
+        assert!(state.world.get_component::<Player>(target).await.is_err());
+        assert!(state.connections.by_name("Target").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_kick_unknown_player() {
+        let state = test_state().await;
+        let (operator, mut operator_client) = add_test_player(&state, "Operator").await;
+        set_op_level(&state, operator, 3).await;
+
+        dispatch("kick Nobody", operator, state.clone()).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut operator_client).await;
+        assert_eq!(packet_id, 0x64);
+        assert!(String::from_utf8_lossy(&body).contains("No player named Nobody"));
+        assert!(state.connections.get_connection(operator).is_ok());
+    }
+}
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+
+use crate::commands::{Command, CommandContext};
+use crate::utils::components::player::online_players;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// `/list`: Show how many players are online, and who.
+pub struct ListCommand;
+
+#[async_trait]
+impl Command for ListCommand {
+    fn name(&self) -> &'static str {
+        "list"
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let players = online_players(&ctx.state.world).await;
+        let names: Vec<&str> = players.iter().map(|player| player.get_username()).collect();
+
+        ctx.reply(format!(
+            "{}/{} players online: {}",
+            players.len(),
+            get_global_config().max_players,
+            names.join(", ")
+        ))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+    use crate::utils::config::get_global_config;
+
+    #[tokio::test]
+    async fn test_list_players() {
+        let state = test_state().await;
+        let (sender, mut client) = add_test_player(&state, "Alice").await;
+        add_test_player(&state, "Bob").await;
+
+        dispatch("list", sender, state.clone()).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x64);
+        let message = String::from_utf8_lossy(&body);
+        let count = format!("2/{} players online", get_global_config().max_players);
+        assert!(message.contains(&count));
+        assert!(message.contains("Alice"));
+        assert!(message.contains("Bob"));
+    }
+}
@@ -0,0 +1,215 @@
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+use crate::commands::args::{tokenize, Arguments};
+use crate::database::ops::MAX_OP_LEVEL;
+use crate::net::packets::outgoing::system_chat_message::SystemChatMessage;
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+pub mod args;
+pub mod backup;
+pub mod completion;
+pub mod difficulty;
+pub mod forceload;
+pub mod gamemode;
+pub mod gamerule;
+pub mod heal;
+pub mod kick;
+pub mod list;
+pub mod op;
+pub mod region;
+pub mod save_all;
+pub mod seed;
+pub mod tick;
+pub mod tp;
+pub mod weather;
+
+/// Everything a command gets to know about its invocation.
+pub struct CommandContext {
+    /// The connection (and entity) id of the player that ran the command.
+    pub sender: ConnectionId,
+    /// The arguments following the command name, split with [tokenize].
+    pub args: Vec<String>,
+    pub state: GlobalState,
+}
+
+impl CommandContext {
+    /// Send a chat message back to the player that ran the command.
+    pub async fn reply(&self, message: impl Into<String>) -> Result<()> {
+        reply_to(self.sender, &self.state, message).await
+    }
+
+    /// The arguments, to be read as typed values.
+    pub fn arguments(&self) -> Arguments {
+        Arguments::new(self.args.clone())
+    }
+}
+
+async fn reply_to(
+    sender: ConnectionId,
+    state: &GlobalState,
+    message: impl Into<String>,
+) -> Result<()> {
+    let conn = state.connections.get_connection(sender)?;
+    let conn = conn.read().await;
+    conn.send_packet(SystemChatMessage::new(message)).await
+}
+
+#[async_trait]
+pub trait Command: Send + Sync {
+    /// The name used to run the command, without the leading `/`.
+    fn name(&self) -> &'static str;
+    /// The permission level needed to run the command, from 0 (everyone) to 4, like vanilla's op
+    /// levels.
+    fn permission_level(&self) -> u8 {
+        0
+    }
+    async fn execute(&self, ctx: CommandContext) -> Result<()>;
+    /// Possible values for the argument at `index`, for tab completion. Only the ones matching
+    /// what's been typed are sent to the client.
+    async fn suggest(&self, _index: usize, _state: &GlobalState) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+pub static ALL_COMMANDS: &[&dyn Command] = &[
+    &backup::BackupCommand,
+    &difficulty::DifficultyCommand,
+    &forceload::ForceloadCommand,
+    &gamemode::GameModeCommand,
+    &gamerule::GameRuleCommand,
+    &heal::HealCommand,
+    &kick::KickCommand,
+    &list::ListCommand,
+    &op::OpCommand,
+    &op::DeopCommand,
+    &region::RegionCommand,
+    &save_all::SaveAllCommand,
+    &seed::SeedCommand,
+    &tick::TickCommand,
+    &tp::TpCommand,
+    &weather::WeatherCommand,
+];
+
+/// Find a command by name.
+pub fn get_command(name: &str) -> Option<&'static dyn Command> {
+    ALL_COMMANDS
+        .iter()
+        .find(|command| command.name() == name)
+        .copied()
+}
+
+/// Parse and run a command sent by a player.
+///
+/// `input` is the raw command without the leading `/`, e.g. `backup` or `tp 0 64 0`.
+/// Invalid arguments are reported back to the sender.
+pub async fn dispatch(input: &str, sender: ConnectionId, state: GlobalState) -> Result<()> {
+    let parts = match tokenize(input) {
+        Ok(parts) => parts,
+        Err(e) => return reply_to(sender, &state, e.to_string()).await,
+    };
+    let mut parts = parts.into_iter();
+    let Some(name) = parts.next() else {
+        return Ok(());
+    };
+
+    let ctx = CommandContext {
+        sender,
+        args: parts.collect(),
+        state,
+    };
+
+    let Some(command) = get_command(&name) else {
+        debug!("Unknown command: /{}", name);
+        return ctx.reply(format!("Unknown command: /{}", name)).await;
+    };
+
+    if permission_level(sender, &ctx.state).await? < command.permission_level() {
+        debug!("{} isn't allowed to run /{}", sender, name);
+        return ctx.reply("Insufficient permission").await;
+    }
+
+    let state = ctx.state.clone();
+    match command.execute(ctx).await {
+        Ok(()) => {}
+        Err(Error::InvalidArgument(message)) => reply_to(sender, &state, message).await?,
+        Err(e) => warn!("Command /{} failed: {}", name, e),
+    }
+
+    Ok(())
+}
+
+/// The permission level of a player: the highest level if they're listed in the config's `ops`,
+/// otherwise whatever they've been given with `/op`.
+pub async fn permission_level(player: ConnectionId, state: &GlobalState) -> Result<u8> {
+    let Ok(player) = state.world.get_component::<Player>(player).await else {
+        return Ok(0);
+    };
+    let (uuid, username) = (player.uuid, player.username.clone());
+    drop(player);
+
+    let config_op = get_global_config()
+        .ops
+        .iter()
+        .any(|op| op.eq_ignore_ascii_case(&username));
+    if config_op {
+        return Ok(MAX_OP_LEVEL);
+    }
+
+    state.database.get_op_level(uuid).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+
+    #[tokio::test]
+    async fn test_denied_without_permission() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Alice").await;
+        let (_, _target_client) = add_test_player(&state, "Bob").await;
+
+        dispatch("kick Bob", player, state.clone()).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x64);
+        assert!(String::from_utf8_lossy(&body).contains("Insufficient permission"));
+        assert!(state.connections.by_name("Bob").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_allowed_with_permission() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Alice").await;
+        let (_, _target_client) = add_test_player(&state, "Bob").await;
+        set_op_level(&state, player, 3).await;
+
+        dispatch("kick Bob", player, state.clone()).await.unwrap();
+
+        assert!(state.connections.by_name("Bob").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_op_and_deop() {
+        let state = test_state().await;
+        let (operator, _client) = add_test_player(&state, "Alice").await;
+        let (player, _player_client) = add_test_player(&state, "Bob").await;
+        set_op_level(&state, operator, 4).await;
+
+        dispatch("op Bob 2", operator, state.clone()).await.unwrap();
+        assert_eq!(permission_level(player, &state).await.unwrap(), 2);
+
+        // Level 2 isn't enough to op or deop anyone
+        dispatch("op Alice 4", player, state.clone()).await.unwrap();
+        dispatch("deop Alice", player, state.clone()).await.unwrap();
+        assert_eq!(permission_level(operator, &state).await.unwrap(), 4);
+
+        dispatch("deop Bob", operator, state.clone()).await.unwrap();
+        assert_eq!(permission_level(player, &state).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::completion::suggest_player_at;
+use crate::commands::{permission_level, Command, CommandContext};
+use crate::database::ops::MAX_OP_LEVEL;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::prelude::*;
+
+/// The level needed to change anyone's permission level.
+const OP_COMMAND_LEVEL: u8 = 3;
+
+/// `/op <player> [level]`: Give an online player a permission level, the highest by default.
+///
+/// Nobody can give out a higher level than their own.
+pub struct OpCommand;
+
+#[async_trait]
+impl Command for OpCommand {
+    fn name(&self) -> &'static str {
+        "op"
+    }
+
+    fn permission_level(&self) -> u8 {
+        OP_COMMAND_LEVEL
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.arguments();
+        let name = args.string("player")?;
+        let level = match args.remaining() {
+            0 => MAX_OP_LEVEL as i32,
+            _ => args.int("level")?,
+        };
+
+        let own_level = permission_level(ctx.sender, &ctx.state).await?;
+        if !(1..=own_level as i32).contains(&level) {
+            return ctx.reply(format!("The level must be between 1 and {}", own_level)).await;
+        }
+
+        let Some(uuid) = online_uuid(&ctx, &name).await? else {
+            return ctx.reply(format!("No player named {} is online", name)).await;
+        };
+        ctx.state.database.set_op_level(uuid, level as u8).await?;
+        info!("Made {} an operator (level {})", name, level);
+        ctx.reply(format!("Made {} an operator (level {})", name, level)).await
+    }
+
+    async fn suggest(&self, index: usize, state: &GlobalState) -> Vec<String> {
+        suggest_player_at(index, 0, state).await
+    }
+}
+
+/// `/deop <player>`: Take away an online player's permission level.
+///
+/// Nobody can deop a player with a higher level than their own.
+pub struct DeopCommand;
+
+#[async_trait]
+impl Command for DeopCommand {
+    fn name(&self) -> &'static str {
+        "deop"
+    }
+
+    fn permission_level(&self) -> u8 {
+        OP_COMMAND_LEVEL
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let name = ctx.arguments().string("player")?;
+        let Some(uuid) = online_uuid(&ctx, &name).await? else {
+            return ctx.reply(format!("No player named {} is online", name)).await;
+        };
+
+        let own_level = permission_level(ctx.sender, &ctx.state).await?;
+        if ctx.state.database.get_op_level(uuid).await? > own_level {
+            return ctx.reply(format!("{} has a higher level than you", name)).await;
+        }
+
+        ctx.state.database.set_op_level(uuid, 0).await?;
+        info!("Made {} no longer an operator", name);
+        ctx.reply(format!("Made {} no longer an operator", name)).await
+    }
+
+    async fn suggest(&self, index: usize, state: &GlobalState) -> Vec<String> {
+        suggest_player_at(index, 0, state).await
+    }
+}
+
+/// The UUID of the online player with this name.
+pub(super) async fn online_uuid(ctx: &CommandContext, name: &str) -> Result<Option<u128>> {
+    let Some(conn) = ctx.state.connections.by_name(name) else {
+        return Ok(None);
+    };
+    let conn_id = conn.read().await.id;
+    let player = ctx.state.world.get_component::<Player>(conn_id).await?;
+    Ok(Some(player.uuid))
+}
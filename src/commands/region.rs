@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::completion::player_names;
+use crate::commands::op::online_uuid;
+use crate::commands::{Command, CommandContext};
+use crate::database::protection::ProtectedRegion;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+const USAGE: &str = "Usage: /region define <name> <x1> <y1> <z1> <x2> <y2> <z2>, \
+    /region allow|deny <name> <player>, /region remove <name> or /region list";
+
+/// `/region define|allow|deny|remove|list`: Protect cuboids of the world from block changes by
+/// anyone but their members, see [crate::world::protection].
+pub struct RegionCommand;
+
+#[async_trait]
+impl Command for RegionCommand {
+    fn name(&self) -> &'static str {
+        "region"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.arguments();
+        let action = args.string("action")?;
+        let database = &ctx.state.database;
+
+        if action == "list" {
+            let regions = database.protected_regions().await?;
+            let names: Vec<&str> = regions.iter().map(|region| region.name.as_str()).collect();
+            return ctx
+                .reply(format!("{} protected regions: {}", names.len(), names.join(", ")))
+                .await;
+        }
+        if !["define", "allow", "deny", "remove"].contains(&action.as_str()) {
+            return ctx.reply(USAGE).await;
+        }
+
+        let name = args.string("name")?;
+        let message = match action.as_str() {
+            "define" => {
+                let a = (args.int("x1")?, args.int("y1")?, args.int("z1")?);
+                let b = (args.int("x2")?, args.int("y2")?, args.int("z2")?);
+                // Redefining a region keeps its members
+                let members = database
+                    .get_protected_region(&name)
+                    .await?
+                    .map(|region| region.members)
+                    .unwrap_or_default();
+                let region = ProtectedRegion {
+                    members,
+                    ..ProtectedRegion::new(&name, a, b)
+                };
+                database.save_protected_region(&region).await?;
+                info!("Protected region {} from {:?} to {:?}", name, region.min, region.max);
+                format!("Region {} is now protected", name)
+            }
+            "allow" | "deny" => {
+                let player = args.string("player")?;
+                let Some(mut region) = database.get_protected_region(&name).await? else {
+                    return ctx.reply(format!("There's no region named {}", name)).await;
+                };
+                let Some(uuid) = online_uuid(&ctx, &player).await? else {
+                    return ctx.reply(format!("No player named {} is online", player)).await;
+                };
+                region.members.retain(|member| *member != uuid);
+                if action == "allow" {
+                    region.members.push(uuid);
+                }
+                database.save_protected_region(&region).await?;
+                match action.as_str() {
+                    "allow" => format!("{} can now change blocks in {}", player, name),
+                    _ => format!("{} can no longer change blocks in {}", player, name),
+                }
+            }
+            "remove" => match database.remove_protected_region(&name).await? {
+                true => {
+                    info!("Removed protected region {}", name);
+                    format!("Region {} is no longer protected", name)
+                }
+                false => format!("There's no region named {}", name),
+            },
+            _ => unreachable!("Checked above"),
+        };
+        ctx.reply(message).await
+    }
+
+    async fn suggest(&self, index: usize, state: &GlobalState) -> Vec<String> {
+        match index {
+            0 => ["define", "allow", "deny", "remove", "list"]
+                .map(String::from)
+                .to_vec(),
+            2 => player_names(state).await,
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+    use crate::utils::components::player::Player;
+    use crate::world::protection::can_edit;
+
+    #[tokio::test]
+    async fn test_define_and_allow() {
+        let state = test_state().await;
+        let (operator, mut client) = add_test_player(&state, "Operator").await;
+        let (builder, _builder_client) = add_test_player(&state, "Builder").await;
+        set_op_level(&state, operator, 2).await;
+
+        dispatch("region define spawn 10 80 10 -10 60 -10", operator, state.clone())
+            .await
+            .unwrap();
+        let (_, reply) = read_packet(&mut client).await;
+        assert!(String::from_utf8_lossy(&reply).contains("Region spawn is now protected"));
+        assert!(!can_edit(builder, (0, 64, 0), &state).await.unwrap());
+        assert!(can_edit(builder, (0, 90, 0), &state).await.unwrap());
+
+        dispatch("region allow spawn Builder", operator, state.clone())
+            .await
+            .unwrap();
+        assert!(can_edit(builder, (0, 64, 0), &state).await.unwrap());
+        let uuid = state.world.get_component::<Player>(builder).await.unwrap().uuid;
+        let region = state.database.get_protected_region("spawn").await.unwrap().unwrap();
+        assert_eq!(region.members, vec![uuid]);
+
+        dispatch("region remove spawn", operator, state.clone()).await.unwrap();
+        assert!(state.database.protected_regions().await.unwrap().is_empty());
+    }
+}
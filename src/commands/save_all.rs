@@ -0,0 +1,75 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+use tracing::{debug, error, info};
+
+use crate::commands::{Command, CommandContext};
+use crate::utils::prelude::*;
+
+/// `/save-all`: Write every chunk changed in memory to the database and flush it to disk.
+///
+/// The save runs in a task of its own, so neither the tick nor the sender's connection waits on it.
+pub struct SaveAllCommand;
+
+#[async_trait]
+impl Command for SaveAllCommand {
+    fn name(&self) -> &'static str {
+        "save-all"
+    }
+
+    fn permission_level(&self) -> u8 {
+        4
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        ctx.reply("Saving the world...").await?;
+
+        tokio::spawn(async move {
+            let start = Instant::now();
+            let message = match ctx.state.database.save_all().await {
+                Ok(chunks) => {
+                    let elapsed = start.elapsed();
+                    info!("Saved {} chunks in {:?}", chunks, elapsed);
+                    format!("Saved {} chunks in {:?}", chunks, elapsed)
+                }
+                Err(e) => {
+                    error!("Saving failed: {}", e);
+                    format!("Saving failed: {}", e)
+                }
+            };
+            if let Err(e) = ctx.reply(message).await {
+                debug!("Failed to report save result: {}", e);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::database::tests::test_chunk;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+
+    #[tokio::test]
+    async fn test_save_all_persists_chunks() {
+        let state = test_state().await;
+        let (operator, mut client) = add_test_player(&state, "Operator").await;
+        set_op_level(&state, operator, 4).await;
+        for x in 0..3 {
+            state.database.cache_chunk(test_chunk(x, 0)).await;
+        }
+        assert_eq!(state.database.chunk_count().await.unwrap(), 0);
+
+        dispatch("save-all", operator, state.clone()).await.unwrap();
+
+        let (_, started) = read_packet(&mut client).await;
+        assert!(String::from_utf8_lossy(&started).contains("Saving the world"));
+        let (_, done) = read_packet(&mut client).await;
+        assert!(String::from_utf8_lossy(&done).contains("Saved 3 chunks"));
+
+        assert_eq!(state.database.chunk_count().await.unwrap(), 3);
+        assert_eq!(state.database.dirty_chunk_count(), 0);
+    }
+}
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+
+use crate::commands::{Command, CommandContext};
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// `/seed`: Show the world seed.
+pub struct SeedCommand;
+
+#[async_trait]
+impl Command for SeedCommand {
+    fn name(&self) -> &'static str {
+        "seed"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let seed = ctx
+            .state
+            .database
+            .world_seed(get_global_config().seed)
+            .await?;
+        ctx.reply(format!("Seed: [{}]", seed)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+    use crate::utils::config::get_global_config;
+
+    #[tokio::test]
+    async fn test_seed_command() {
+        let state = test_state().await;
+        let (operator, mut client) = add_test_player(&state, "Operator").await;
+        set_op_level(&state, operator, 2).await;
+        let seed = state
+            .database
+            .world_seed(get_global_config().seed)
+            .await
+            .unwrap();
+
+        dispatch("seed", operator, state.clone()).await.unwrap();
+
+        let (_, body) = read_packet(&mut client).await;
+        assert!(String::from_utf8_lossy(&body).contains(&format!("Seed: [{}]", seed)));
+    }
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::{Command, CommandContext};
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+const USAGE: &str = "Usage: /tick query|freeze|unfreeze, /tick rate <rate> or /tick step [ticks]";
+
+/// `/tick query|freeze|unfreeze`, `/tick rate <rate>` or `/tick step [ticks]`: Change how fast
+/// the game runs, or stop it and run it a few ticks at a time, see [crate::world::ticks].
+pub struct TickCommand;
+
+#[async_trait]
+impl Command for TickCommand {
+    fn name(&self) -> &'static str {
+        "tick"
+    }
+
+    fn permission_level(&self) -> u8 {
+        3
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.arguments();
+        let ticks = &ctx.state.ticks;
+        let message = match args.string("action")?.as_str() {
+            "query" => format!(
+                "The game runs at {} ticks per second{}, {} ticks have run",
+                ticks.rate(),
+                if ticks.is_frozen() { " and is frozen" } else { "" },
+                ticks.count()
+            ),
+            "freeze" => {
+                ticks.set_frozen(true);
+                info!("Froze the game");
+                "The game is frozen".to_string()
+            }
+            "unfreeze" => {
+                ticks.set_frozen(false);
+                info!("Unfroze the game");
+                "The game is running again".to_string()
+            }
+            "rate" => {
+                let rate = args.int("rate")?;
+                ticks.set_rate(rate.try_into().unwrap_or(0))?;
+                info!("Set the tick rate to {}", rate);
+                format!("The game now runs at {} ticks per second", rate)
+            }
+            "step" => {
+                let steps = match args.remaining() {
+                    0 => 1,
+                    _ => args.int("ticks")?,
+                };
+                if steps < 1 {
+                    return ctx.reply("Must step at least 1 tick").await;
+                }
+                ticks.step(steps as u32)?;
+                format!("Stepping {} ticks", steps)
+            }
+            _ => USAGE.to_string(),
+        };
+        ctx.reply(message).await
+    }
+
+    async fn suggest(&self, index: usize, _state: &GlobalState) -> Vec<String> {
+        match index {
+            0 => ["query", "freeze", "unfreeze", "rate", "step"].map(String::from).to_vec(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, set_op_level, test_state};
+
+    #[tokio::test]
+    async fn test_frozen_game_only_runs_stepped_ticks() {
+        let state = test_state().await;
+        let (operator, _client) = add_test_player(&state, "Operator").await;
+        set_op_level(&state, operator, 3).await;
+        let ticks = &state.ticks;
+
+        dispatch("tick freeze", operator, state.clone()).await.unwrap();
+        let ran = (0..20).filter(|_| ticks.advance()).count();
+        assert_eq!(ran, 0);
+        assert_eq!(ticks.count(), 0);
+
+        dispatch("tick step 5", operator, state.clone()).await.unwrap();
+        let ran = (0..20).filter(|_| ticks.advance()).count();
+        assert_eq!(ran, 5);
+        assert_eq!(ticks.count(), 5);
+
+        dispatch("tick rate 40", operator, state.clone()).await.unwrap();
+        assert_eq!(ticks.rate(), 40);
+        dispatch("tick unfreeze", operator, state.clone()).await.unwrap();
+        assert!(ticks.advance());
+    }
+}
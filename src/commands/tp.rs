@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+
+use crate::commands::completion::suggest_player_at;
+use crate::commands::{Command, CommandContext};
+use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::constants::world_bounds::{MAX_HORIZONTAL, MAX_Y, MIN_Y};
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+const USAGE: &str = "Usage: /tp <x> <y> <z> or /tp <player>";
+
+/// `/tp <x> <y> <z>` or `/tp <player>`: Teleport to a position or to another player.
+///
+/// Coordinates can be relative, see [crate::commands::args::Arguments::coordinates].
+pub struct TpCommand;
+
+#[async_trait]
+impl Command for TpCommand {
+    fn name(&self) -> &'static str {
+        "tp"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let destination = match ctx.args.as_slice() {
+            [_, _, _] => {
+                let component_storage = ctx.state.world.get_component_storage();
+                let origin = component_storage.get::<Position>(ctx.sender).await?;
+                let rotation = component_storage.get::<Rotation>(ctx.sender).await?;
+                let destination = ctx
+                    .arguments()
+                    .block_position("destination", &origin, &rotation)?;
+                drop((origin, rotation));
+
+                match in_bounds(destination) {
+                    Ok(destination) => destination,
+                    Err(message) => return ctx.reply(message).await,
+                }
+            }
+            [name] => {
+                let Some(conn) = ctx.state.connections.by_name(name) else {
+                    return ctx.reply(format!("No player named {} is online", name)).await;
+                };
+                let target = conn.read().await.id;
+                let position = ctx.state.world.get_component::<Position>(target).await?;
+                (*position).clone()
+            }
+            _ => return ctx.reply(USAGE).await,
+        };
+
+        teleport(ctx.sender, &destination, &ctx.state).await?;
+        ctx.reply(format!("Teleported to {}", destination)).await
+    }
+
+    async fn suggest(&self, index: usize, state: &GlobalState) -> Vec<String> {
+        suggest_player_at(index, 0, state).await
+    }
+}
+
+/// Rejects block coordinates outside the world.
+fn in_bounds((x, y, z): (i64, i64, i64)) -> std::result::Result<Position, String> {
+    let horizontal = -(MAX_HORIZONTAL as i64)..=MAX_HORIZONTAL as i64;
+    if !horizontal.contains(&x) || !horizontal.contains(&z) {
+        return Err(format!(
+            "x and z must be between -{} and {}",
+            MAX_HORIZONTAL, MAX_HORIZONTAL
+        ));
+    }
+    if !(MIN_Y as i64..=MAX_Y as i64).contains(&y) {
+        return Err(format!("y must be between {} and {}", MIN_Y, MAX_Y));
+    }
+
+    Ok(Position::new(x as i32, y as i16, z as i32))
+}
+
+/// Moves a player, telling their client where they are now.
+async fn teleport(
+    player: ConnectionId,
+    destination: &Position,
+    state: &GlobalState,
+) -> Result<()> {
+    let component_storage = state.world.get_component_storage();
+    *component_storage.get_mut::<Position>(player).await? = destination.clone();
+    let rotation = component_storage.get::<Rotation>(player).await?;
+    let packet = SynchronizePlayerPosition::new(destination, &*rotation);
+    drop(rotation);
+
+    let conn = state.connections.get_connection(player)?;
+    let conn = conn.read().await;
+    conn.send_packet(packet).await
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpStream;
+
+    use super::*;
+    use crate::commands::dispatch;
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+
+    /// Reads the Synchronize Player Position packet the client was sent, returning its x, y and z.
+    async fn read_teleport(client: &mut TcpStream) -> (f64, f64, f64) {
+        let (packet_id, body) = read_packet(client).await;
+        assert_eq!(packet_id, 0x3C);
+        let coordinate = |i: usize| f64::from_be_bytes(body[i * 8..i * 8 + 8].try_into().unwrap());
+        (coordinate(0), coordinate(1), coordinate(2))
+    }
+
+    async fn position_of(state: &GlobalState, player: u32) -> (i32, i16, i32) {
+        let position = state.world.get_component::<Position>(player).await.unwrap();
+        (position.x, position.y, position.z)
+    }
+
+    #[tokio::test]
+    async fn test_tp_to_coordinates() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Alice").await;
+        set_op_level(&state, player, 2).await;
+
+        dispatch("tp 100 -20 -300", player, state.clone()).await.unwrap();
+
+        assert_eq!(read_teleport(&mut client).await, (100.0, -20.0, -300.0));
+        assert_eq!(position_of(&state, player).await, (100, -20, -300));
+    }
+
+    #[tokio::test]
+    async fn test_tp_relative() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Alice").await;
+        set_op_level(&state, player, 2).await;
+
+        dispatch("tp ~1 ~ ~-2", player, state.clone()).await.unwrap();
+
+        assert_eq!(read_teleport(&mut client).await, (1.0, 64.0, -2.0));
+    }
+
+    #[tokio::test]
+    async fn test_tp_to_player() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Alice").await;
+        set_op_level(&state, player, 2).await;
+        let (target, _target_client) = add_test_player(&state, "Bob").await;
+        *state
+            .world
+            .get_component_storage()
+            .get_mut::<Position>(target)
+            .await
+            .unwrap() = Position::new(5, 70, 5);
+
+        dispatch("tp bob", player, state.clone()).await.unwrap();
+
+        assert_eq!(read_teleport(&mut client).await, (5.0, 70.0, 5.0));
+        assert_eq!(position_of(&state, player).await, (5, 70, 5));
+    }
+
+    #[tokio::test]
+    async fn test_tp_out_of_bounds() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Alice").await;
+        set_op_level(&state, player, 2).await;
+
+        dispatch("tp 0 400 0", player, state.clone()).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x64);
+        assert!(String::from_utf8_lossy(&body).contains("y must be between"));
+        assert_eq!(position_of(&state, player).await, (0, 64, 0));
+
+        assert!(in_bounds((30_000_001, 64, 0)).is_err());
+        assert!(in_bounds((-30_000_000, -64, 0)).is_ok());
+    }
+}
@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::commands::{Command, CommandContext};
+use crate::net::systems::weather::broadcast_weather;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+use crate::world::weather::Weather;
+
+/// `/weather <clear|rain|thunder> [seconds]`: Change the weather, for a while or until it
+/// changes on its own.
+pub struct WeatherCommand;
+
+#[async_trait]
+impl Command for WeatherCommand {
+    fn name(&self) -> &'static str {
+        "weather"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.arguments();
+        let name = args.string("weather")?;
+        let Ok(weather) = name.parse::<Weather>() else {
+            return ctx.reply(format!("Unknown weather: {}", name)).await;
+        };
+        let duration = match args.remaining() {
+            0 => None,
+            _ => match args.int("seconds")? {
+                seconds if seconds > 0 => Some((seconds as u32).saturating_mul(20)),
+                _ => return ctx.reply("The duration must be at least 1 second").await,
+            },
+        };
+
+        let config = get_global_config();
+        ctx.state
+            .weather
+            .set(&config.default_dimension, weather, duration);
+        broadcast_weather(&ctx.state, weather).await?;
+        info!("Set the weather to {}", weather);
+        ctx.reply(format!("Set the weather to {}", weather)).await
+    }
+
+    async fn suggest(&self, index: usize, _state: &GlobalState) -> Vec<String> {
+        match index {
+            0 => Weather::ALL.iter().map(|w| w.to_string()).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::dispatch;
+    use crate::net::packets::outgoing::game_event::{BEGIN_RAINING, RAIN_LEVEL_CHANGE};
+    use crate::tests::helpers::{add_test_player, read_packet, set_op_level, test_state};
+    use crate::world::weather::Weather;
+
+    #[tokio::test]
+    async fn test_rain_is_broadcast() {
+        let state = test_state().await;
+        let (operator, mut operator_client) = add_test_player(&state, "Operator").await;
+        let (_, mut client) = add_test_player(&state, "Player").await;
+        set_op_level(&state, operator, 2).await;
+
+        dispatch("weather rain 60", operator, state.clone()).await.unwrap();
+        assert_eq!(state.weather.get("overworld"), Weather::Rain);
+
+        for client in [&mut operator_client, &mut client] {
+            let (packet_id, body) = read_packet(client).await;
+            assert_eq!(packet_id, 0x1F);
+            assert_eq!(body[0], BEGIN_RAINING);
+            let (_, body) = read_packet(client).await;
+            assert_eq!(body[0], RAIN_LEVEL_CHANGE);
+            assert_eq!(body[1..], 1.0f32.to_be_bytes());
+        }
+    }
+}
@@ -132,11 +132,9 @@ fn generate_encode_impl(
 
                     let __packet_data = bytes_.into_inner();
                     let __length = ferrumc_codec::network_types::varint::VarInt::new(__packet_data.len() as i32);
-                    let mut __cursor = std::io::Cursor::new(Vec::new());
-                    __length.net_encode(&mut __cursor).await?;
-                    __cursor.write_all(&__packet_data).await?;
-                    let __encoded = __cursor.into_inner();
-                    bytes_out.write_all(&__encoded).await?;
+                    // Written straight to the output, no need to copy the packet into yet another buffer
+                    __length.net_encode(bytes_out).await?;
+                    bytes_out.write_all(&__packet_data).await?;
 
                     Ok(())
                 }
@@ -137,8 +137,20 @@ pub fn bake(input: TokenStream) -> TokenStream {
 
             match_arms.push(quote! {
                 (#packet_id, #state) => {
-                    let packet= #struct_path::net_decode(cursor).await?;
-                    packet.handle(conn_id, state).await?;
+                    let mut packet = #struct_path::net_decode(reader).await?;
+                    let handler: PacketHandler = Box::new(move |conn_id: u32, state: crate::state::GlobalState| {
+                        futures::future::FutureExt::boxed(async move {
+                            let verdict = crate::net::packets::middleware::intercept(
+                                &state, conn_id, #packet_id, #state, &mut packet,
+                            );
+                            if verdict == crate::net::packets::middleware::Verdict::Veto {
+                                tracing::trace!("Packet 0x{:02X} from {} was vetoed", #packet_id, conn_id);
+                                return Ok(());
+                            }
+                            packet.handle(conn_id, state).await
+                        })
+                    });
+                    Ok(Some(handler))
                 },
             });
 
@@ -161,10 +173,35 @@ pub fn bake(input: TokenStream) -> TokenStream {
     let match_arms = match_arms.into_iter();
 
     let output = quote! {
-        pub async fn handle_packet(packet_id: u8, conn_id: u32, conn_state: &crate::net::State, cursor: &mut std::io::Cursor<Vec<u8>>, state: crate::state::GlobalState) -> crate::utils::prelude::Result<()> {
+        /// A decoded packet, waiting to be handled.
+        pub type PacketHandler = Box<
+            dyn FnOnce(u32, crate::state::GlobalState) -> futures::future::BoxFuture<'static, crate::utils::prelude::Result<()>>
+                + Send,
+        >;
+
+        /// Decodes the packet with the given id straight from `reader`.
+        ///
+        /// Returns `None` if there is no packet registered for the id in the current state.
+        pub async fn decode_packet<R>(packet_id: u8, conn_state: &crate::net::State, reader: &mut R) -> crate::utils::prelude::Result<Option<PacketHandler>>
+        where
+            R: tokio::io::AsyncRead + Unpin,
+        {
             match (packet_id, conn_state.as_str()) {
                 #(#match_arms)*
-                _ => tracing::warn!("No packet found for ID: 0x{:02X} in state: {}", packet_id, conn_state.as_str()),
+                _ => {
+                    tracing::warn!("No packet found for ID: 0x{:02X} in state: {}", packet_id, conn_state.as_str());
+                    Ok(None)
+                }
+            }
+        }
+
+        /// Decodes and handles a packet in one go.
+        pub async fn handle_packet<R>(packet_id: u8, conn_id: u32, conn_state: &crate::net::State, reader: &mut R, state: crate::state::GlobalState) -> crate::utils::prelude::Result<()>
+        where
+            R: tokio::io::AsyncRead + Unpin,
+        {
+            if let Some(handler) = decode_packet(packet_id, conn_state, reader).await? {
+                handler(conn_id, state).await?;
             }
 
             Ok(())
@@ -0,0 +1,40 @@
+use tokio::io::{AsyncRead, AsyncReadExt, Take};
+
+use crate::network_types::varint::VarInt;
+use crate::prelude::*;
+
+/// The longest string the protocol allows, in bytes: 32767 UTF-16 code units of up to 3 bytes
+/// each in UTF-8.
+pub const MAX_STRING_BYTES: usize = 32767 * 3;
+
+/// Read a UTF-8 string prefixed with its length in bytes as a VarInt.
+///
+/// The length comes straight from the other side, so it's checked against `max_len` before
+/// anything is allocated for it.
+pub async fn read_string<R>(reader: &mut R, max_len: usize) -> Result<String>
+where
+    R: AsyncRead + Unpin,
+{
+    let length = VarInt::read(reader).await?.get_val();
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|length| *length <= max_len)
+        .ok_or(CodecError::InvalidStringLength(length))?;
+    let mut buf = vec![0u8; length];
+    reader.read_exact(&mut buf).await?;
+    Ok(String::from_utf8(buf)?)
+}
+
+/// Reads the length and id of the next packet from `reader`.
+///
+/// Returns the packet id and a reader limited to the rest of the packet, so the body can be decoded
+/// as it arrives instead of being read into a buffer first.
+pub async fn read_packet_header<R>(mut reader: R) -> Result<(VarInt, Take<R>)>
+where
+    R: AsyncRead + Unpin,
+{
+    let packet_length = VarInt::read(&mut reader).await?;
+    let mut body = reader.take(packet_length.get_val().max(0) as u64);
+    let packet_id = VarInt::read(&mut body).await?;
+    Ok((packet_id, body))
+}
@@ -13,6 +13,10 @@ pub enum CodecError {
     VarIntTooBig,
     #[error("VarLong too big")]
     VarLongTooBig,
+    #[error("Invalid string length: {0}")]
+    InvalidStringLength(i32),
+    #[error("Invalid UTF-8: {0}")]
+    InvalidUtf8(#[from] std::string::FromUtf8Error),
     #[error("Other error")]
     Other(String),
 }
@@ -3,6 +3,7 @@ pub mod prelude;
 pub mod enc;
 pub mod dec;
 pub mod network_types;
+pub mod packets;
 #[cfg(test)]
 mod tests;
 
@@ -35,16 +35,13 @@ pub struct VarInt {
 
 impl VarInt {
     pub fn new(value: i32) -> Self {
-        let bytes_required = if value < 128 && value >= -128 {
-            1
-        } else if value < 16384 && value >= -16384 {
-            2
-        } else if value < 2097152 && value >= -2097152 {
-            3
-        } else if value < 268435456 && value >= -268435456 {
-            4
-        } else {
-            5
+        // Negative values have the sign bit set, so they always take all 5 bytes
+        let bytes_required = match value as u32 {
+            0..=0x7F => 1,
+            0x80..=0x3FFF => 2,
+            0x4000..=0x1F_FFFF => 3,
+            0x20_0000..=0xFFF_FFFF => 4,
+            _ => 5,
         };
         VarInt {
             val: value,
@@ -225,4 +222,33 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(cursor.into_inner(), vec![0xff, 0xff, 0xff, 0xff, 0x0f]);
     }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn varint_round_trips(value: i32) {
+            let mut encoded = Vec::new();
+            block_on(write_varint(value, &mut encoded)).unwrap();
+            proptest::prop_assert!(encoded.len() <= 5);
+            proptest::prop_assert_eq!(VarInt::new(value).get_len(), encoded.len());
+
+            let mut cursor = Cursor::new(encoded.clone());
+            let decoded = block_on(VarInt::read(&mut cursor)).unwrap();
+            proptest::prop_assert_eq!(decoded, VarInt::new(value));
+            proptest::prop_assert_eq!(cursor.position() as usize, encoded.len());
+        }
+
+        #[test]
+        fn varint_never_reads_past_5_bytes(bytes: Vec<u8>) {
+            let mut cursor = Cursor::new(bytes);
+            let _ = block_on(VarInt::read(&mut cursor));
+            proptest::prop_assert!(cursor.position() <= 5);
+        }
+    }
 }
@@ -23,18 +23,14 @@ impl Varlong {
         T: AsyncRead + Unpin,
     {
         let mut val = 0;
-        let mut count = 0;
-        loop {
+        for i in 0..10 {
             let byte = cursor.read_u8().await.map_err(|e| CodecError::Io(e))?;
-            val |= ((byte & 0x7F) as i64) << (count * 7);
-            count += 1;
-            if count > 10 {
-                return Err(CodecError::VarLongTooBig);
-            }
+            val |= ((byte & 0x7F) as i64) << (i * 7);
             if (byte & 0x80) == 0 {
                 return Ok(Varlong(val));
             }
         }
+        Err(CodecError::VarLongTooBig)
     }
 }
 
@@ -223,4 +219,32 @@ mod tests {
             vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
         );
     }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn varlong_round_trips(value: i64) {
+            let mut encoded = Vec::new();
+            block_on(write_varlong(Varlong::new(value), &mut encoded)).unwrap();
+            proptest::prop_assert!(encoded.len() <= 10);
+
+            let mut cursor = Cursor::new(encoded.clone());
+            let decoded = block_on(Varlong::read(&mut cursor)).unwrap();
+            proptest::prop_assert_eq!(decoded, Varlong::new(value));
+            proptest::prop_assert_eq!(cursor.position() as usize, encoded.len());
+        }
+
+        #[test]
+        fn varlong_never_reads_past_10_bytes(bytes: Vec<u8>) {
+            let mut cursor = Cursor::new(bytes);
+            let _ = block_on(Varlong::read(&mut cursor));
+            proptest::prop_assert!(cursor.position() <= 10);
+        }
+    }
 }
@@ -0,0 +1,49 @@
+//! The serverbound packets of the handshake and status states, the first ones read from any
+//! connection. They're decoded here rather than in the server so the fuzz targets in `fuzz/` can
+//! reach them; the server's packets of those states delegate to these.
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::dec::{read_string, MAX_STRING_BYTES};
+use crate::network_types::varint::VarInt;
+use crate::prelude::*;
+
+/// The first packet of every connection, picking the state it continues in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handshake {
+    pub protocol_version: VarInt,
+    pub server_address: String,
+    pub server_port: u16,
+    pub next_state: VarInt,
+}
+
+impl Handshake {
+    pub async fn read<R>(reader: &mut R) -> Result<Self>
+    where
+        R: AsyncRead + Unpin,
+    {
+        Ok(Self {
+            protocol_version: VarInt::read(reader).await?,
+            server_address: read_string(reader, MAX_STRING_BYTES).await?,
+            server_port: reader.read_u16().await?,
+            next_state: VarInt::read(reader).await?,
+        })
+    }
+}
+
+/// Sent after the status request, which has no fields, to be answered with the same payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingRequest {
+    pub payload: i64,
+}
+
+impl PingRequest {
+    pub async fn read<R>(reader: &mut R) -> Result<Self>
+    where
+        R: AsyncRead + Unpin,
+    {
+        Ok(Self {
+            payload: reader.read_i64().await?,
+        })
+    }
+}
@@ -1,3 +1,9 @@
+use std::io::Cursor;
+
+use crate::dec::{read_string, MAX_STRING_BYTES};
+use crate::enc::NetEncode;
+use crate::error::CodecError;
+
 #[tokio::test]
 async fn test_encode_bool() {
     let mut buf = Vec::new();
@@ -7,3 +13,46 @@ async fn test_encode_bool() {
     false.net_encode(&mut buf).await.unwrap();
     assert_eq!(buf, vec![0]);
 }
+
+#[tokio::test]
+async fn test_string_round_trip() {
+    let mut buf = Vec::new();
+    "ferrumc".net_encode(&mut buf).await.unwrap();
+    let decoded = read_string(&mut Cursor::new(buf), MAX_STRING_BYTES).await.unwrap();
+    assert_eq!(decoded, "ferrumc");
+}
+
+#[tokio::test]
+async fn test_read_string_rejects_invalid_lengths() {
+    // -1, then i32::MAX with nothing after it
+    for input in [[0xFF, 0xFF, 0xFF, 0xFF, 0x0F], [0xFF, 0xFF, 0xFF, 0xFF, 0x07]] {
+        let decoded = read_string(&mut Cursor::new(input), MAX_STRING_BYTES).await;
+        assert!(matches!(decoded, Err(CodecError::InvalidStringLength(_))));
+    }
+
+    let mut buf = Vec::new();
+    "too long".net_encode(&mut buf).await.unwrap();
+    let decoded = read_string(&mut Cursor::new(buf), 4).await;
+    assert!(matches!(decoded, Err(CodecError::InvalidStringLength(8))));
+}
+
+#[tokio::test]
+async fn test_read_handshake_packet() {
+    use crate::dec::read_packet_header;
+    use crate::network_types::varint::VarInt;
+    use crate::packets::Handshake;
+
+    // Length, packet id, then the handshake body
+    let packet = [
+        0x10, 0x00, 0xFB, 0x05, 0x09, 0x31, 0x32, 0x37, 0x2E, 0x30, 0x2E, 0x30, 0x2E, 0x31, 0x63,
+        0xDD, 0x01,
+    ];
+    let (packet_id, mut body) = read_packet_header(Cursor::new(packet)).await.unwrap();
+    assert_eq!(packet_id, VarInt::new(0x00));
+    let handshake = Handshake::read(&mut body).await.unwrap();
+    assert_eq!(handshake.protocol_version, VarInt::new(763));
+    assert_eq!(handshake.server_address, "127.0.0.1");
+    assert_eq!(handshake.server_port, 25565);
+    assert_eq!(handshake.next_state, VarInt::new(1));
+    assert_eq!(body.limit(), 0);
+}
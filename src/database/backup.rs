@@ -0,0 +1,329 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use heed::types::Bytes;
+use heed::{Env, RoTxn};
+
+use crate::database::forceload::{read_force_loaded, ForceLoadReason};
+use crate::database::{spawn_blocking_db, Database};
+use crate::utils::error::Error;
+
+/// The first bytes of every backup file
+pub(super) const BACKUP_MAGIC: &[u8; 8] = b"FRMCBKUP";
+/// Bumped whenever the layout of the backup file changes
+pub(super) const BACKUP_VERSION: u32 = 2;
+/// Backups from before every table was included, holding only chunks.
+const CHUNKS_ONLY_VERSION: u32 = 1;
+
+/// Every table created by [Database::open], all of which are backed up.
+const BACKUP_TABLES: [&str; 7] = [
+    "chunks",
+    "ops",
+    "meta",
+    "forceloaded",
+    "playerdata",
+    "protection",
+    "gamerules",
+];
+
+/// Opens a table with its keys and values as they're stored. Not through
+/// [crate::database::Tables::open], which caches the handle with the table's usual types.
+fn open_raw(
+    db: &Env,
+    tx: &RoTxn,
+    name: &str,
+) -> Result<heed::Database<Bytes, Bytes>, heed::Error> {
+    db.open_database::<Bytes, Bytes>(tx, Some(name))?
+        .ok_or(heed::Error::Mdb(heed::MdbError::NotFound))
+}
+
+/// Runs `f` on the database thread pool. Its LMDB errors are passed on as they are, so running
+/// out of map space is retried like every other database task.
+async fn run_blocking<F>(db: Env, f: F) -> Result<u64, Error>
+where
+    F: Fn() -> Result<u64, Error> + Send + 'static,
+{
+    spawn_blocking_db(db, move || match f() {
+        Err(Error::LmdbError(e)) => Err(e),
+        other => Ok(other),
+    })
+    .await
+    .unwrap()?
+}
+
+impl Database {
+    /// Export every table into a single backup file at `path`
+    ///
+    /// The layout is:
+    /// - [BACKUP_MAGIC]
+    /// - [BACKUP_VERSION] as a little endian u32
+    /// - the number of tables as a little endian u32
+    /// - for each table: the length of its name (u8), the name, the number of entries (u64), and
+    ///   for each entry the length of the key (u32), the key, the length of the value (u32) and
+    ///   the value as stored
+    ///
+    /// Chunks changed since the last save aren't included, save them first.
+    /// Returns the number of chunks exported.
+    pub async fn export(&self, path: impl AsRef<Path>) -> Result<u64, Error> {
+        let db = self.db.clone();
+        let path = path.as_ref().to_path_buf();
+        run_blocking(self.db.clone(), move || Self::export_blocking(&db, &path)).await
+    }
+
+    fn export_blocking(db: &Env, path: &Path) -> Result<u64, Error> {
+        let ro_tx = db.read_txn()?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(BACKUP_MAGIC)?;
+        writer.write_u32::<LE>(BACKUP_VERSION)?;
+        writer.write_u32::<LE>(BACKUP_TABLES.len() as u32)?;
+
+        let mut chunks = 0;
+        for name in BACKUP_TABLES {
+            let table = open_raw(db, &ro_tx, name)?;
+            let count = table.len(&ro_tx)?;
+            if name == "chunks" {
+                chunks = count;
+            }
+
+            writer.write_u8(name.len() as u8)?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_u64::<LE>(count)?;
+            for entry in table.iter(&ro_tx)? {
+                let (key, value) = entry?;
+                writer.write_u32::<LE>(key.len() as u32)?;
+                writer.write_all(key)?;
+                writer.write_u32::<LE>(value.len() as u32)?;
+                writer.write_all(value)?;
+            }
+        }
+        writer.flush()?;
+
+        Ok(chunks)
+    }
+
+    /// Restore every table from a backup file created by [Database::export]
+    ///
+    /// Refuses to restore into a database that already contains chunks unless `force` is set,
+    /// in which case entries with the same key are overwritten. Chunks changed in memory since the
+    /// last save are dropped, so they can't hide or overwrite the restored ones.
+    /// Returns the number of chunks restored.
+    pub async fn import(&self, path: impl AsRef<Path>, force: bool) -> Result<u64, Error> {
+        if !force && self.chunk_count().await? > 0 {
+            return Err(Error::DatabaseError(
+                "Refusing to restore into a non-empty database".to_string(),
+            ));
+        }
+
+        let db = self.db.clone();
+        let path = path.as_ref().to_path_buf();
+        let count =
+            run_blocking(self.db.clone(), move || Self::import_blocking(&db, &path)).await?;
+
+        // Anything in memory is potentially stale now
+        self.dirty.clear();
+        self.cache.invalidate_all();
+        self.net_sections.clear();
+        let stored = read_force_loaded(&self.tables, &self.db)?;
+        self.force_loaded
+            .retain(|_, chunk| chunk.reason == ForceLoadReason::Spawn);
+        for entry in stored.iter() {
+            self.force_loaded
+                .entry(*entry.key())
+                .or_insert_with(|| entry.value().clone());
+        }
+
+        Ok(count)
+    }
+
+    fn import_blocking(db: &Env, path: &Path) -> Result<u64, Error> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != BACKUP_MAGIC {
+            return Err(Error::DatabaseError(format!(
+                "{} is not a FerrumC backup",
+                path.display()
+            )));
+        }
+        let version = reader.read_u32::<LE>()?;
+        let tables = match version {
+            BACKUP_VERSION => reader.read_u32::<LE>()?,
+            CHUNKS_ONLY_VERSION => 1,
+            _ => {
+                return Err(Error::DatabaseError(format!(
+                    "Unsupported backup version {} (expected {})",
+                    version, BACKUP_VERSION
+                )))
+            }
+        };
+
+        let mut rw_tx = db.write_txn()?;
+        let mut chunks = 0;
+        let (mut key, mut value) = (Vec::new(), Vec::new());
+        for _ in 0..tables {
+            let name = match version {
+                CHUNKS_ONLY_VERSION => "chunks".to_string(),
+                _ => {
+                    let mut name = vec![0; reader.read_u8()? as usize];
+                    reader.read_exact(&mut name)?;
+                    String::from_utf8_lossy(&name).into_owned()
+                }
+            };
+            if !BACKUP_TABLES.contains(&name.as_str()) {
+                return Err(Error::DatabaseError(format!(
+                    "Unknown table \"{}\" in backup",
+                    name
+                )));
+            }
+            let table = open_raw(db, &rw_tx, &name)?;
+
+            let count = reader.read_u64::<LE>()?;
+            if name == "chunks" {
+                chunks = count;
+            }
+            for _ in 0..count {
+                match version {
+                    // Chunk keys were always 8 bytes
+                    CHUNKS_ONLY_VERSION => key.resize(8, 0),
+                    _ => key.resize(reader.read_u32::<LE>()? as usize, 0),
+                }
+                reader.read_exact(&mut key)?;
+                value.resize(reader.read_u32::<LE>()? as usize, 0);
+                reader.read_exact(&mut value)?;
+                table.put(&mut rw_tx, &key, &value)?;
+            }
+        }
+
+        // Only commit once the whole file was read, so a truncated backup changes nothing
+        rw_tx.commit()?;
+
+        Ok(chunks)
+    }
+
+    /// The number of chunks stored in the persistent database
+    pub async fn chunk_count(&self) -> Result<u64, Error> {
+        let db = self.db.clone();
+        run_blocking(self.db.clone(), move || {
+            let ro_tx = db.read_txn()?;
+            Ok(open_raw(&db, &ro_tx, "chunks")?.len(&ro_tx)?)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::{temp_config, test_chunk};
+
+    #[tokio::test]
+    async fn test_export() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        for x in 0..4 {
+            database.insert_chunk(test_chunk(x, 0)).await.unwrap();
+        }
+
+        let path = std::env::temp_dir().join(format!("{}.ferrumc-backup", uuid::Uuid::new_v4()));
+        assert_eq!(database.export(&path).await.unwrap(), 4);
+
+        let mut file = File::open(&path).unwrap();
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic).unwrap();
+        assert_eq!(&magic, BACKUP_MAGIC);
+        assert_eq!(file.read_u32::<LE>().unwrap(), BACKUP_VERSION);
+        assert_eq!(file.read_u32::<LE>().unwrap(), BACKUP_TABLES.len() as u32);
+        let mut name = vec![0; file.read_u8().unwrap() as usize];
+        file.read_exact(&mut name).unwrap();
+        assert_eq!(name, b"chunks");
+        assert_eq!(file.read_u64::<LE>().unwrap(), database.chunk_count().await.unwrap());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import() {
+        let source = Database::open(&temp_config(), "world").await.unwrap();
+        for x in 0..4 {
+            source.insert_chunk(test_chunk(x, x)).await.unwrap();
+        }
+        let path = std::env::temp_dir().join(format!("{}.ferrumc-backup", uuid::Uuid::new_v4()));
+        source.export(&path).await.unwrap();
+
+        let target = Database::open(&temp_config(), "world").await.unwrap();
+        assert_eq!(target.import(&path, false).await.unwrap(), 4);
+        assert_eq!(target.chunk_count().await.unwrap(), 4);
+        for x in 0..4 {
+            let chunk = target.get_chunk(x, x, "overworld").await.unwrap();
+            assert_eq!(chunk, Some(test_chunk(x, x)));
+        }
+
+        // The target isn't empty anymore
+        assert!(target.import(&path, false).await.is_err());
+        assert_eq!(target.import(&path, true).await.unwrap(), 4);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_other_tables() {
+        let source = Database::open(&temp_config(), "world").await.unwrap();
+        let seed = source.world_seed(None).await.unwrap();
+        source.set_op_level(42, 4).await.unwrap();
+        source.store_gamerule("doDaylightCycle", "false").await.unwrap();
+        source.force_load(3, 3, "overworld").await.unwrap();
+        let path = std::env::temp_dir().join(format!("{}.ferrumc-backup", uuid::Uuid::new_v4()));
+        source.export(&path).await.unwrap();
+
+        let target = Database::open(&temp_config(), "world").await.unwrap();
+        target.import(&path, false).await.unwrap();
+        assert_eq!(target.world_seed(None).await.unwrap(), seed);
+        assert_eq!(target.get_op_level(42).await.unwrap(), 4);
+        assert_eq!(
+            target.stored_gamerules().await.unwrap(),
+            vec![("doDaylightCycle".to_string(), "false".to_string())]
+        );
+        assert!(target.is_force_loaded(3, 3, "overworld"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_drops_unsaved_chunks() {
+        let source = Database::open(&temp_config(), "world").await.unwrap();
+        source.insert_chunk(test_chunk(0, 0)).await.unwrap();
+        let path = std::env::temp_dir().join(format!("{}.ferrumc-backup", uuid::Uuid::new_v4()));
+        source.export(&path).await.unwrap();
+
+        let target = Database::open(&temp_config(), "world").await.unwrap();
+        let mut changed = test_chunk(0, 0);
+        changed.data_version += 1;
+        target.cache_chunk(changed).await;
+        target.import(&path, true).await.unwrap();
+
+        assert_eq!(target.dirty_chunk_count(), 0);
+        let chunk = target.get_chunk(0, 0, "overworld").await.unwrap();
+        assert_eq!(chunk, Some(test_chunk(0, 0)));
+        // Saving mustn't write the dropped chunk over the restored one either
+        target.save_all().await.unwrap();
+        let chunk = target.get_chunk(0, 0, "overworld").await.unwrap();
+        assert_eq!(chunk, Some(test_chunk(0, 0)));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_invalid_file() {
+        let path = std::env::temp_dir().join(format!("{}.ferrumc-backup", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"definitely not a backup").unwrap();
+
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        assert!(database.import(&path, false).await.is_err());
+        assert_eq!(database.chunk_count().await.unwrap(), 0);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
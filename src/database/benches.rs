@@ -0,0 +1,152 @@
+//! Benchmarks for storing and loading chunks.
+//!
+//! The server is a binary crate, so these can't live with the other criterion benches in
+//! `src/benches`. Run them with:
+//! `cargo test --release chunk_storage_benchmarks -- --ignored --nocapture`
+
+use std::collections::BTreeMap;
+
+use criterion::{black_box, Criterion};
+use tokio::runtime::Runtime;
+
+use crate::database::encoding::{Codec, Compression};
+use crate::database::tests::temp_config;
+use crate::database::Database;
+use crate::utils::hash::hash;
+use crate::world::chunk_format::{Biomes, BlockStates, Chunk, Heightmaps, Palette, Section};
+
+/// A fully generated overworld chunk: 24 sections with a small palette, lighting and heightmaps.
+pub(crate) fn representative_chunk(x: i32, z: i32) -> Chunk {
+    // Cheap deterministic noise, so the block data doesn't compress unrealistically well
+    let mut seed = 0x2545_F491_4F6C_DD1Du64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as i64
+    };
+
+    let deepslate = BTreeMap::from([("axis".to_string(), "y".to_string())]);
+    let palette = [
+        ("minecraft:stone", None),
+        ("minecraft:dirt", None),
+        ("minecraft:air", None),
+        ("minecraft:deepslate", Some(deepslate)),
+    ]
+    .into_iter()
+    .map(|(name, properties)| Palette {
+        name: name.to_string(),
+        properties,
+    })
+    .collect::<Vec<_>>();
+    // 16 random palette indices of 4 bits each
+    let entries = palette.len() as i64;
+    let mut next_long = || (0..16).fold(0, |long, i| long | next().rem_euclid(entries) << (i * 4));
+    let sections = (-4..20)
+        .map(|y| Section {
+            block_states: Some(BlockStates {
+                non_air_blocks: None,
+                bits_per_block: None,
+                // 4096 blocks at 4 bits each
+                data: Some((0..256).map(|_| next_long()).collect()),
+                palette: Some(palette.clone()),
+                net_palette: None,
+            }),
+            biomes: Some(Biomes {
+                palette: vec!["minecraft:plains".to_string()],
+            }),
+            y,
+            block_light: Some(vec![0; 2048]),
+            sky_light: Some(vec![-1; 2048]),
+        })
+        .collect();
+
+    Chunk {
+        dimension: Some("overworld".to_string()),
+        status: "full".to_string(),
+        data_version: 3465,
+        heightmaps: Some(Heightmaps {
+            motion_blocking: Some((0..37).map(|_| next()).collect()),
+            world_surface: Some((0..37).map(|_| next()).collect()),
+        }),
+        is_light_on: Some(1),
+        inhabited_time: Some(0),
+        y_pos: -4,
+        x_pos: x,
+        z_pos: z,
+        structures: None,
+        last_update: Some(0),
+        sections: Some(sections),
+    }
+}
+
+fn bench_encoding(c: &mut Criterion, runtime: &Runtime) {
+    let chunk = representative_chunk(0, 0);
+    let compressed = runtime
+        .block_on(Codec::compress_data(chunk.clone(), Compression::default()))
+        .unwrap();
+
+    c.bench_function("chunk serialize", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(Codec::compress_data(black_box(chunk.clone()), Compression::default()))
+                .unwrap()
+        })
+    });
+    c.bench_function("chunk deserialize", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(Codec::decompress_data::<Chunk>(black_box(
+                    compressed.clone(),
+                )))
+                .unwrap()
+        })
+    });
+}
+
+fn bench_database(c: &mut Criterion, runtime: &Runtime) {
+    let database = runtime
+        .block_on(Database::open(&temp_config(), "world"))
+        .unwrap();
+    let chunk = representative_chunk(0, 0);
+    runtime.block_on(database.insert_chunk(chunk.clone())).unwrap();
+    let key = hash(("overworld".to_string(), 0, 0));
+
+    c.bench_function("chunk update", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(database.update_chunk(black_box(chunk.clone())))
+                .unwrap()
+        })
+    });
+    c.bench_function("chunk get cached", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(database.get_chunk_shared(0, 0, black_box("overworld")))
+                .unwrap()
+        })
+    });
+    c.bench_function("chunk get uncached", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                database.cache.invalidate(&key).await;
+                database
+                    .get_chunk_shared(0, 0, black_box("overworld"))
+                    .await
+                    .unwrap()
+            })
+        })
+    });
+}
+
+#[test]
+#[ignore]
+fn chunk_storage_benchmarks() {
+    let runtime = Runtime::new().unwrap();
+    let mut criterion = Criterion::default();
+
+    bench_encoding(&mut criterion, &runtime);
+    bench_database(&mut criterion, &runtime);
+
+    criterion.final_summary();
+}
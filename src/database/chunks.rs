@@ -2,17 +2,24 @@ use byteorder::LE;
 use heed::types::Bytes;
 use heed::{types::U64, Env};
 use moka::future::Cache;
-use std::sync::Arc;
-use tokio::runtime::Handle;
+use std::sync::{Arc, Weak};
 use tracing::{trace, warn};
 
-use super::spawn_blocking_db;
-use crate::database::encoding::ZstdCodec;
+use super::{spawn_blocking_db, Tables};
+use crate::database::encoding::Codec;
 use crate::world::importing::SerializedChunk;
+use crate::utils::config::get_global_config;
 use crate::{
     database::Database, utils::error::Error, utils::hash::hash, world::chunk_format::Chunk,
 };
 
+/// A chunk's sections encoded by [Chunk::to_network_sections], along with the cached chunk they
+/// were encoded from.
+pub(super) struct NetSections {
+    chunk: Weak<Chunk>,
+    encoded: Arc<Vec<u8>>,
+}
+
 impl Database {
     // Close the database
     pub fn close(self) {
@@ -21,13 +28,15 @@ impl Database {
     }
 
     /// Fetch chunk from database
-    async fn get_chunk_from_database(db: &Env, key: &u64) -> Result<Option<Chunk>, heed::Error> {
+    async fn get_chunk_from_database(
+        tables: &Tables,
+        db: &Env,
+        key: &u64,
+    ) -> Result<Option<Chunk>, heed::Error> {
         let data = {
             // Initialize read transaction and open chunks table
             let ro_tx = db.read_txn()?;
-            let database = db
-                .open_database::<U64<LE>, Bytes>(&ro_tx, Some("chunks"))?
-                .expect("No table \"chunks\" found. The database should have been initialized");
+            let database = tables.open::<U64<LE>, Bytes>(db, &ro_tx, "chunks")?;
 
             // Attempt to fetch chunk from table
             let data = database.get(&ro_tx, key)?;
@@ -38,7 +47,7 @@ impl Database {
 
         // Now, proceed with the async operation without holding `ro_tx`
         if let Some(data) = data {
-            let chunk = ZstdCodec::decompress_data::<Chunk>(data).await.expect("Failed to decompress chunk");
+            let chunk = Codec::decompress_data::<Chunk>(data).await.expect("Failed to decompress chunk");
             Ok(Some(chunk))
         } else {
             Ok(None)
@@ -46,26 +55,19 @@ impl Database {
 
     }
 
-    /// Insert a single chunk into database
-    fn insert_chunk_into_database(db: &Env, chunk: &Chunk) -> Result<(), heed::Error> {
+    /// Insert a single, already compressed, chunk into database
+    fn insert_chunk_into_database(
+        tables: &Tables,
+        db: &Env,
+        key: u64,
+        data: &[u8],
+    ) -> Result<(), heed::Error> {
         // Initialize write transaction and open chunks table
         let mut rw_tx = db.write_txn()?;
-        let database = db
-            .open_database::<U64<LE>, Bytes>(&rw_tx, Some("chunks"))?
-            .expect("No table \"chunks\" found. The database should have been initialized");
-
-        // Calculate key
-        let key = hash((chunk.dimension.as_ref().unwrap(), chunk.x_pos, chunk.z_pos));
-
-        let chunk = chunk.clone();
-        let chunk = Handle::current().block_on(async {
-            ZstdCodec::compress_data(chunk)
-                .await
-                .expect("Failed to compress chunk")
-        });
+        let database = tables.open::<U64<LE>, Bytes>(db, &rw_tx, "chunks")?;
 
         // Insert chunk
-        let res = database.put(&mut rw_tx, &key, chunk.as_slice());
+        let res = database.put(&mut rw_tx, &key, data);
         rw_tx.commit()?;
 
         res
@@ -74,14 +76,13 @@ impl Database {
     /// Insert multiple chunks into database
     /// TODO: Find better name/disambiguation
     fn insert_chunks_into_database(
+        tables: &Tables,
         db: &Env,
         chunks: &[SerializedChunk],
     ) -> Result<(), heed::Error> {
         // Initialize write transaction and open chunks table
         let mut rw_tx = db.write_txn()?;
-        let database = db
-            .open_database::<U64<LE>, Bytes>(&rw_tx, Some("chunks"))?
-            .expect("No table \"chunks\" found. The database should have been initialized");
+        let database = tables.open::<U64<LE>, Bytes>(db, &rw_tx, "chunks")?;
 
         // Update page
         for chunk in chunks {
@@ -98,12 +99,14 @@ impl Database {
 
     #[allow(dead_code)]
     async fn load_into_cache(&self, key: u64) -> Result<(), Error> {
-        Database::load_into_cache_standalone(self.db.clone(), self.cache.clone(), key).await
+        let (db, cache) = (self.db.clone(), self.cache.clone());
+        Database::load_into_cache_standalone(self.tables.clone(), db, cache, key).await
     }
 
     async fn load_into_cache_standalone(
+        tables: Tables,
         db: Env,
-        cache: Arc<Cache<u64, Chunk>>,
+        cache: Arc<Cache<u64, Arc<Chunk>>>,
         key: u64,
     ) -> Result<(), Error> {
         // let tsk_db = db.clone();
@@ -116,13 +119,13 @@ impl Database {
                 trace!("Chunk already exists in cache: {:X}", key);
             }
             // If not in cache then search in database
-            else if let Ok(chunk) = Self::get_chunk_from_database(&db, &key).await
+            else if let Ok(chunk) = Self::get_chunk_from_database(&tables, &db, &key).await
                 /*spawn_blocking_db(tsk_db, move || Self::get_chunk_from_database(&db, &key))
                     .await
                     .unwrap()*/
             {
                 if let Some(chunk) = chunk {
-                    cache.insert(key, chunk).await;
+                    cache.insert(key, Arc::new(chunk)).await;
                 } else {
                     warn!(
                         "Chunk does not exist in db, can't load into cache: {:X}",
@@ -161,18 +164,22 @@ impl Database {
         // WARNING: This key wasn't supposed to include value.dimension in the tuple, but it was different from the key used in persistent database most likely a bug.
         let key = hash((value.dimension.as_ref().unwrap(), value.x_pos, value.z_pos));
 
+        // Compress before handing off to the database threadpool, since it has no async runtime
+        let data = Codec::compress_data(value.clone(), self.compression).await?;
+
         // Insert chunk into persistent database
-        let chunk = value.clone();
         let db = self.db.clone();
+        let tables = self.tables.clone();
         let tsk_db = self.db.clone();
         spawn_blocking_db(tsk_db, move || {
-            Self::insert_chunk_into_database(&db, &chunk)
+            Self::insert_chunk_into_database(&tables, &db, key, &data)
         })
         .await
         .unwrap()?;
 
         // Insert into cache
-        self.cache.insert(key, value).await;
+        self.dirty.remove(&key);
+        self.cache.insert(key, Arc::new(value)).await;
         Ok(())
     }
 
@@ -191,7 +198,7 @@ impl Database {
     /// use crate::database::Database;
     /// use crate::utils::error::Error;
     ///
-    /// async fn get_chunk(database: Database, x: i32, z: i32, dimension: String) -> Result<Option<Chunk>, Error> {
+    /// async fn get_chunk(database: Database, x: i32, z: i32, dimension: &str) -> Result<Option<Chunk>, Error> {
     ///   database.get_chunk(x, z, dimension).await
     /// }
     ///
@@ -200,13 +207,42 @@ impl Database {
         &self,
         x: i32,
         z: i32,
-        dimension: String,
+        dimension: &str,
     ) -> Result<Option<Chunk>, Error> {
+        let chunk = self.get_chunk_shared(x, z, dimension).await?;
+        Ok(chunk.map(Arc::unwrap_or_clone))
+    }
+
+    /// [Database::get_chunk] in the configured default dimension.
+    pub async fn get_chunk_default(&self, x: i32, z: i32) -> Result<Option<Chunk>, Error> {
+        self.get_chunk(x, z, &get_global_config().default_dimension).await
+    }
+
+    /// Like [Database::get_chunk], but returns the cached chunk itself instead of a copy.
+    ///
+    /// The chunk is a snapshot. Changes made after it was fetched aren't visible through it.
+    pub async fn get_chunk_shared(
+        &self,
+        x: i32,
+        z: i32,
+        dimension: &str,
+    ) -> Result<Option<Arc<Chunk>>, Error> {
         // Calculate key of this chunk and clone database pointer
         let key = hash((dimension, x, z));
         let db = self.db.clone();
 
-        let res = Self::get_chunk_from_database(&db, &key).await?;
+        // First check unsaved changes, then cache
+        if let Some(chunk) = self.dirty.get(&key) {
+            return Ok(Some(chunk.clone()));
+        }
+        if let Some(chunk) = self.cache.get(&key).await {
+            return Ok(Some(chunk));
+        }
+
+        let res = Self::get_chunk_from_database(&self.tables, &db, &key).await?.map(Arc::new);
+        if let Some(chunk) = &res {
+            self.cache.insert(key, chunk.clone()).await;
+        }
 
         Ok(res)
 
@@ -230,6 +266,63 @@ impl Database {
         }*/
     }
 
+    /// Load every chunk within `radius` of `center` (in chunk coordinates) into the cache.
+    ///
+    /// Returns how many of those chunks exist in the database.
+    pub async fn preload_chunks(
+        &self,
+        center: (i32, i32),
+        radius: i32,
+        dimension: &str,
+    ) -> Result<usize, Error> {
+        let loads = (-radius..=radius)
+            .flat_map(|dx| (-radius..=radius).map(move |dz| (center.0 + dx, center.1 + dz)))
+            .map(|(x, z)| self.get_chunk(x, z, dimension));
+
+        let chunks = futures::future::try_join_all(loads).await?;
+
+        Ok(chunks.iter().filter(|chunk| chunk.is_some()).count())
+    }
+
+    /// Whether a chunk is currently held in the cache, without loading it.
+    pub fn is_chunk_cached(&self, x: i32, z: i32, dimension: &str) -> bool {
+        self.cache.contains_key(&hash((dimension, x, z)))
+    }
+
+    /// [Chunk::to_network_sections] for a chunk from [Database::get_chunk_shared], encoded once and
+    /// reused for every player it's sent to.
+    ///
+    /// Changing a chunk replaces it with a new copy, so the encoding of the old one is never
+    /// returned for it.
+    pub async fn network_sections(&self, chunk: &Arc<Chunk>) -> Result<Arc<Vec<u8>>, Error> {
+        let Some(dimension) = &chunk.dimension else {
+            return Ok(Arc::new(chunk.to_network_sections().await?));
+        };
+        let key = hash((dimension, chunk.x_pos, chunk.z_pos));
+
+        // The weak reference keeps the allocation alive, so the address can't be reused
+        if let Some(cached) = self.net_sections.get(&key) {
+            if Weak::as_ptr(&cached.chunk) == Arc::as_ptr(chunk) {
+                return Ok(cached.encoded.clone());
+            }
+        }
+
+        let encoded = Arc::new(chunk.to_network_sections().await?);
+        self.net_sections.insert(
+            key,
+            NetSections {
+                chunk: Arc::downgrade(chunk),
+                encoded: encoded.clone(),
+            },
+        );
+        Ok(encoded)
+    }
+
+    /// [Database::is_chunk_cached] in the configured default dimension.
+    pub fn is_chunk_cached_default(&self, x: i32, z: i32) -> bool {
+        self.is_chunk_cached(x, z, &get_global_config().default_dimension)
+    }
+
     /// Check if a chunk exists in the database
     /// # Arguments
     /// * `x` - The x position of the chunk
@@ -243,25 +336,25 @@ impl Database {
     /// use crate::database::Database;
     /// use crate::utils::error::Error;
     ///
-    /// async fn chunk_exists(database: Database, x: i32, z: i32, dimension: String) -> Result<bool, Error> {
+    /// async fn chunk_exists(database: Database, x: i32, z: i32, dimension: &str) -> Result<bool, Error> {
     ///  database.chunk_exists(x, z, dimension).await
     /// }
     ///
     /// ```
-    pub async fn chunk_exists(&self, x: i32, z: i32, dimension: String) -> Result<bool, Error> {
+    pub async fn chunk_exists(&self, x: i32, z: i32, dimension: &str) -> Result<bool, Error> {
         // Calculate key and copy database pointer
         let key = hash((dimension, x, z));
         let db = self.db.clone();
 
-        // Check first cache
-        if self.cache.contains_key(&key) {
+        // Check first unsaved changes and cache
+        if self.dirty.contains_key(&key) || self.cache.contains_key(&key) {
             Ok(true)
         // Else check persistent database and load it into cache
         } else {
             /*let res = spawn_blocking_db(tsk_db, move || Self::get_chunk_from_database(&db, &key))
                 .await
                 .unwrap();*/
-            let Some(res) = Self::get_chunk_from_database(&db, &key).await? else {
+            let Some(res) = Self::get_chunk_from_database(&self.tables, &db, &key).await? else {
                 return Ok(false);
             };
 
@@ -269,7 +362,7 @@ impl Database {
             // This has been replaced by directly loading the queried chunk into cache
 
             // Load chunk into cache
-            self.cache.insert(key, res.clone()).await;
+            self.cache.insert(key, Arc::new(res)).await;
             Ok(true)
 
            /* match res {
@@ -285,6 +378,11 @@ impl Database {
         }
     }
 
+    /// [Database::chunk_exists] in the configured default dimension.
+    pub async fn chunk_exists_default(&self, x: i32, z: i32) -> Result<bool, Error> {
+        self.chunk_exists(x, z, &get_global_config().default_dimension).await
+    }
+
     /// Update a chunk in the database <br>
     /// This will also update the chunk in the cache <br>
     /// If the chunk does not exist, it will return an error
@@ -308,18 +406,21 @@ impl Database {
         // WARNING: This key wasn't supposed to include value.dimension in the tuple, but it was different from the key used in persistent database most likely a bug.
         let key = hash((value.dimension.as_ref().unwrap(), value.x_pos, value.z_pos));
 
+        let data = Codec::compress_data(value.clone(), self.compression).await?;
+
         // Insert new chunk state into persistent database
-        let chunk = value.clone();
         let db = self.db.clone();
+        let tables = self.tables.clone();
         let tsk_db = self.db.clone();
         spawn_blocking_db(tsk_db, move || {
-            Self::insert_chunk_into_database(&db, &chunk)
+            Self::insert_chunk_into_database(&tables, &db, key, &data)
         })
         .await
         .unwrap()?;
 
         // Insert new chunk state into cache
-        self.cache.insert(key, value).await;
+        self.dirty.remove(&key);
+        self.cache.insert(key, Arc::new(value)).await;
         Ok(())
     }
 
@@ -344,6 +445,7 @@ impl Database {
     pub async fn batch_insert(&self, values: Vec<SerializedChunk>) -> Result<(), Error> {
         // Clone database pointer
         let db = self.db.clone();
+        let tables = self.tables.clone();
         let tsk_db = self.db.clone();
 
         // Calculate all keys
@@ -375,7 +477,7 @@ impl Database {
         */
         // Then insert into persistent database
         spawn_blocking_db(tsk_db, move || {
-            Self::insert_chunks_into_database(&db, &values)
+            Self::insert_chunks_into_database(&tables, &db, &values)
         })
         .await
         .unwrap()?;
@@ -396,7 +498,7 @@ async fn dump_chunk() {
         .unwrap();
     let chunk = state
         .database
-        .get_chunk(2, 2, "overworld".to_string())
+        .get_chunk(2, 2, "overworld")
         .await
         .unwrap()
         .unwrap();
@@ -3,6 +3,9 @@ use bincode::{Decode, Encode};
 use heed::{BytesDecode, BytesEncode};
 use std::borrow::Cow;
 use std::marker::PhantomData;
+use std::str::FromStr;
+
+use crate::utils::error::Error;
 
 pub struct Zstd<T>(PhantomData<T>);
 
@@ -26,26 +29,177 @@ impl<'a, T: Decode + 'a> BytesDecode<'a> for Zstd<T> {
     }
 }
 
-pub struct ZstdCodec;
+/// Chunk data written with the format byte, before it existed, is a bare zstd frame. Those start
+/// with this magic number, which no format byte collides with.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// The zstd level for "zstd", and "fast" from older configs. Chunks were always written at this
+/// level before the compression could be configured.
+pub const ZSTD_DEFAULT_LEVEL: i32 = 3;
+/// The zstd level for "best" from older configs.
+pub const ZSTD_BEST_LEVEL: i32 = 19;
+
+/// How stored data is compressed. Parsed from `database.compression` in the config.
+///
+/// Compressed data starts with a byte saying which one was used, so data written with different
+/// settings can be read back alongside each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// At the given level. The level isn't needed to read the data back.
+    Zstd(i32),
+    /// Faster to decompress than zstd, but compresses less.
+    Lz4,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression::Zstd(ZSTD_DEFAULT_LEVEL)
+    }
+}
+
+impl Compression {
+    pub const ALL: [Compression; 3] = [
+        Compression::None,
+        Compression::Zstd(ZSTD_DEFAULT_LEVEL),
+        Compression::Lz4,
+    ];
+
+    fn format_byte(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd(_) => 1,
+            Compression::Lz4 => 2,
+        }
+    }
+
+    fn from_format_byte(byte: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.format_byte() == byte)
+    }
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Compression::None),
+            "zstd" => Ok(Compression::Zstd(ZSTD_DEFAULT_LEVEL)),
+            "lz4" => Ok(Compression::Lz4),
+            // From configs written before the algorithm could be chosen, when it was always zstd
+            "fast" => Ok(Compression::Zstd(ZSTD_DEFAULT_LEVEL)),
+            "best" => Ok(Compression::Zstd(ZSTD_BEST_LEVEL)),
+            other => Err(Error::InvalidConfig(
+                "database.compression".to_string(),
+                format!("expected \"none\", \"zstd\" or \"lz4\", got \"{}\"", other),
+            )),
+        }
+    }
+}
+
+pub struct Codec;
 
-impl ZstdCodec {
-    pub async fn compress_data<T: Encode + Send + 'static>(data: T) -> crate::Result<Vec<u8>> {
+impl Codec {
+    pub async fn compress_data<T: Encode + Send + 'static>(
+        data: T,
+        compression: Compression,
+    ) -> crate::Result<Vec<u8>> {
         tokio::task::spawn_blocking(move || {
-            let mut bytes = Vec::new();
-            let mut compressor = zstd::Encoder::new(&mut bytes, 3)?;
-            bincode::encode_into_std_write(&data, &mut compressor, standard())?;
-            compressor.finish()?;
+            let mut bytes = vec![compression.format_byte()];
+            match compression {
+                Compression::None => {
+                    bincode::encode_into_std_write(&data, &mut bytes, standard())?;
+                }
+                Compression::Zstd(level) => {
+                    let mut compressor = zstd::Encoder::new(&mut bytes, level)?;
+                    bincode::encode_into_std_write(&data, &mut compressor, standard())?;
+                    compressor.finish()?;
+                }
+                Compression::Lz4 => {
+                    let encoded = bincode::encode_to_vec(&data, standard())?;
+                    bytes.extend(lz4_flex::compress_prepend_size(&encoded));
+                }
+            }
             Ok(bytes)
         })
         .await?
     }
+
+    /// Decompresses data from [Codec::compress_data], with whichever [Compression] it was
+    /// written with.
     pub async fn decompress_data<T: Decode + Send + 'static>(data: Vec<u8>) -> crate::Result<T> {
         tokio::task::spawn_blocking(move || {
-            let mut decoder = zstd::Decoder::new(data.as_slice())?;
-            // let decoded = bincode::decode_from_slice(data.as_slice(), standard())?;
-            let decoded = bincode::decode_from_std_read(&mut decoder, standard())?;
+            if data.starts_with(&ZSTD_MAGIC) {
+                let mut decoder = zstd::Decoder::new(data.as_slice())?;
+                return Ok(bincode::decode_from_std_read(&mut decoder, standard())?);
+            }
+
+            let (&format, body) = data
+                .split_first()
+                .ok_or_else(|| Error::Generic("Stored data is empty".to_string()))?;
+            let compression = Compression::from_format_byte(format)
+                .ok_or(Error::UnknownChunkCompression(format))?;
+            let decoded = match compression {
+                Compression::None => bincode::decode_from_slice(body, standard())?.0,
+                Compression::Zstd(_) => {
+                    let mut decoder = zstd::Decoder::new(body)?;
+                    bincode::decode_from_std_read(&mut decoder, standard())?
+                }
+                Compression::Lz4 => {
+                    let decompressed = lz4_flex::decompress_size_prepended(body)
+                        .map_err(|e| Error::Generic(format!("Invalid lz4 data: {}", e)))?;
+                    bincode::decode_from_slice(&decompressed, standard())?.0
+                }
+            };
             Ok(decoded)
         })
         .await?
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::test_chunk;
+    use crate::world::chunk_format::Chunk;
+
+    #[tokio::test]
+    async fn test_round_trip_every_compression() {
+        let chunk = test_chunk(3, -7);
+        for compression in Compression::ALL {
+            let data = Codec::compress_data(chunk.clone(), compression).await.unwrap();
+            assert_eq!(data[0], compression.format_byte());
+            let decoded = Codec::decompress_data::<Chunk>(data).await.unwrap();
+            assert_eq!(decoded, chunk, "{:?}", compression);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_data_without_format_byte() {
+        let chunk = test_chunk(0, 0);
+        let mut legacy = Vec::new();
+        let mut compressor = zstd::Encoder::new(&mut legacy, 3).unwrap();
+        bincode::encode_into_std_write(&chunk, &mut compressor, standard()).unwrap();
+        compressor.finish().unwrap();
+
+        let decoded = Codec::decompress_data::<Chunk>(legacy).await.unwrap();
+        assert_eq!(decoded, chunk);
+    }
+
+    #[tokio::test]
+    async fn test_old_config_values_are_zstd() {
+        assert_eq!("fast".parse::<Compression>().unwrap(), Compression::default());
+        let best = "best".parse::<Compression>().unwrap();
+        assert_eq!(best, Compression::Zstd(ZSTD_BEST_LEVEL));
+
+        let chunk = test_chunk(1, 1);
+        let data = Codec::compress_data(chunk.clone(), best).await.unwrap();
+        assert_eq!(Codec::decompress_data::<Chunk>(data).await.unwrap(), chunk);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_format_byte() {
+        let result = Codec::decompress_data::<Chunk>(vec![9, 1, 2, 3]).await;
+        assert!(matches!(result, Err(Error::UnknownChunkCompression(9))));
+    }
+}
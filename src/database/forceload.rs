@@ -0,0 +1,233 @@
+//! Chunks that stay loaded whether or not a player is near them: the spawn chunks, and any added
+//! with `/forceload`, which are stored in the `forceloaded` table.
+//!
+//! The cache never expires these, and entities in them are ticked as if a player was nearby.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use heed::types::Bytes;
+use heed::{Env, RoTxn};
+use moka::Expiry;
+
+use super::{spawn_blocking_db, Tables};
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::utils::hash::hash;
+use crate::world::chunk_format::Chunk;
+
+/// How long other chunks stay in the cache after they were last changed.
+pub(super) const CACHE_TTL: Duration = Duration::from_millis(1000);
+
+/// Why a chunk is kept loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForceLoadReason {
+    /// Within the spawn radius. Not stored, it's worked out again on every start.
+    Spawn,
+    /// Added with `/forceload`.
+    Command,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForcedChunk {
+    pub x: i32,
+    pub z: i32,
+    pub dimension: String,
+    pub reason: ForceLoadReason,
+}
+
+pub(super) type ForceLoaded = Arc<DashMap<u64, ForcedChunk>>;
+
+/// Keeps force-loaded chunks in the cache, and everything else for [CACHE_TTL].
+pub(super) struct ChunkExpiry {
+    pub(super) force_loaded: ForceLoaded,
+}
+
+impl ChunkExpiry {
+    fn ttl(&self, key: &u64) -> Option<Duration> {
+        (!self.force_loaded.contains_key(key)).then_some(CACHE_TTL)
+    }
+}
+
+impl Expiry<u64, Arc<Chunk>> for ChunkExpiry {
+    fn expire_after_create(&self, key: &u64, _: &Arc<Chunk>, _: Instant) -> Option<Duration> {
+        self.ttl(key)
+    }
+
+    fn expire_after_update(
+        &self,
+        key: &u64,
+        _: &Arc<Chunk>,
+        _: Instant,
+        _: Option<Duration>,
+    ) -> Option<Duration> {
+        self.ttl(key)
+    }
+}
+
+fn open_forceloaded(
+    tables: &Tables,
+    db: &Env,
+    tx: &RoTxn,
+) -> Result<heed::Database<Bytes, Bytes>, heed::Error> {
+    tables.open::<Bytes, Bytes>(db, tx, "forceloaded")
+}
+
+/// The stored value of a force-loaded chunk: x and z, then the dimension.
+fn encode(x: i32, z: i32, dimension: &str) -> Vec<u8> {
+    let mut value = Vec::with_capacity(8 + dimension.len());
+    value.extend_from_slice(&x.to_be_bytes());
+    value.extend_from_slice(&z.to_be_bytes());
+    value.extend_from_slice(dimension.as_bytes());
+    value
+}
+
+fn decode(value: &[u8]) -> Option<ForcedChunk> {
+    let x = i32::from_be_bytes(value.get(0..4)?.try_into().ok()?);
+    let z = i32::from_be_bytes(value.get(4..8)?.try_into().ok()?);
+    let dimension = String::from_utf8(value.get(8..)?.to_vec()).ok()?;
+    Some(ForcedChunk {
+        x,
+        z,
+        dimension,
+        reason: ForceLoadReason::Command,
+    })
+}
+
+/// Every chunk added with `/forceload`, read when the database is opened.
+pub(super) fn read_force_loaded(tables: &Tables, db: &Env) -> Result<ForceLoaded, heed::Error> {
+    let ro_tx = db.read_txn()?;
+    let table = open_forceloaded(tables, db, &ro_tx)?;
+    let force_loaded = DashMap::new();
+    for entry in table.iter(&ro_tx)? {
+        let (key, value) = entry?;
+        if let (Ok(key), Some(chunk)) = (<[u8; 8]>::try_from(key), decode(value)) {
+            force_loaded.insert(u64::from_be_bytes(key), chunk);
+        }
+    }
+    Ok(Arc::new(force_loaded))
+}
+
+impl Database {
+    /// Keep a chunk loaded until it's removed with [Database::unforce_load], across restarts.
+    ///
+    /// Returns `false` if it was already force-loaded, spawn chunks included. Those keep their
+    /// reason, so they still can't be removed.
+    pub async fn force_load(&self, x: i32, z: i32, dimension: &str) -> Result<bool, Error> {
+        let key = hash((dimension.to_string(), x, z));
+        if self.force_loaded.contains_key(&key) {
+            return Ok(false);
+        }
+
+        let value = encode(x, z, dimension);
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move || {
+            let mut rw_tx = db.write_txn()?;
+            let table = open_forceloaded(&tables, &db, &rw_tx)?;
+            table.put(&mut rw_tx, &key.to_be_bytes(), &value)?;
+            rw_tx.commit()
+        })
+        .await
+        .unwrap()?;
+
+        self.keep_loaded(key, x, z, dimension, ForceLoadReason::Command)
+            .await?;
+        Ok(true)
+    }
+
+    /// Keep every chunk within `radius` of `center` loaded while the server is running, loading
+    /// them into the cache now.
+    ///
+    /// Returns how many of those chunks exist in the database.
+    pub async fn keep_spawn_chunks_loaded(
+        &self,
+        center: (i32, i32),
+        radius: i32,
+        dimension: &str,
+    ) -> Result<usize, Error> {
+        for x in center.0 - radius..=center.0 + radius {
+            for z in center.1 - radius..=center.1 + radius {
+                let key = hash((dimension.to_string(), x, z));
+                self.force_loaded.entry(key).or_insert_with(|| ForcedChunk {
+                    x,
+                    z,
+                    dimension: dimension.to_string(),
+                    reason: ForceLoadReason::Spawn,
+                });
+                // Loaded again below, without an expiry
+                self.cache.invalidate(&key).await;
+            }
+        }
+        self.preload_chunks(center, radius, dimension).await
+    }
+
+    async fn keep_loaded(
+        &self,
+        key: u64,
+        x: i32,
+        z: i32,
+        dimension: &str,
+        reason: ForceLoadReason,
+    ) -> Result<(), Error> {
+        self.force_loaded.insert(
+            key,
+            ForcedChunk {
+                x,
+                z,
+                dimension: dimension.to_string(),
+                reason,
+            },
+        );
+        // Insert it again if it's already cached, so it loses its expiry
+        if let Some(chunk) = self.get_chunk_shared(x, z, dimension).await? {
+            self.cache.insert(key, chunk).await;
+        }
+        Ok(())
+    }
+
+    /// Stop keeping a chunk added with [Database::force_load] loaded.
+    ///
+    /// Returns `false` if it wasn't force-loaded. Spawn chunks can't be removed.
+    pub async fn unforce_load(&self, x: i32, z: i32, dimension: &str) -> Result<bool, Error> {
+        let key = hash((dimension.to_string(), x, z));
+        let removed = self
+            .force_loaded
+            .remove_if(&key, |_, chunk| chunk.reason == ForceLoadReason::Command)
+            .is_some();
+        if !removed {
+            return Ok(false);
+        }
+
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move || {
+            let mut rw_tx = db.write_txn()?;
+            let table = open_forceloaded(&tables, &db, &rw_tx)?;
+            table.delete(&mut rw_tx, &key.to_be_bytes())?;
+            rw_tx.commit()
+        })
+        .await
+        .unwrap()?;
+
+        // Let it expire like any other chunk
+        self.cache.invalidate(&key).await;
+        Ok(true)
+    }
+
+    pub fn is_force_loaded(&self, x: i32, z: i32, dimension: &str) -> bool {
+        self.force_loaded
+            .contains_key(&hash((dimension.to_string(), x, z)))
+    }
+
+    /// Every force-loaded chunk, spawn chunks included.
+    pub fn force_loaded_chunks(&self) -> Vec<ForcedChunk> {
+        self.force_loaded
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+}
@@ -0,0 +1,62 @@
+//! Gamerules changed from their defaults, stored by name in the `gamerules` table.
+//!
+//! See [crate::world::gamerules] for the rules themselves.
+
+use heed::types::Bytes;
+use heed::{Env, RoTxn};
+
+use super::{spawn_blocking_db, Tables};
+use crate::database::Database;
+use crate::utils::error::Error;
+
+fn open_gamerules(
+    tables: &Tables,
+    db: &Env,
+    tx: &RoTxn,
+) -> Result<heed::Database<Bytes, Bytes>, heed::Error> {
+    tables.open::<Bytes, Bytes>(db, tx, "gamerules")
+}
+
+impl Database {
+    /// Every stored gamerule, by name, with its value as text.
+    pub async fn stored_gamerules(&self) -> Result<Vec<(String, String)>, Error> {
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        let rules = spawn_blocking_db(tsk_db, move || {
+            let ro_tx = db.read_txn()?;
+            let table = open_gamerules(&tables, &db, &ro_tx)?;
+            let mut rules = Vec::new();
+            for entry in table.iter(&ro_tx)? {
+                let (name, value) = entry?;
+                rules.push((
+                    String::from_utf8_lossy(name).into_owned(),
+                    String::from_utf8_lossy(value).into_owned(),
+                ));
+            }
+            Ok(rules)
+        })
+        .await
+        .unwrap()?;
+
+        Ok(rules)
+    }
+
+    /// Store the value of a gamerule, replacing the previous one.
+    pub async fn store_gamerule(&self, name: &str, value: &str) -> Result<(), Error> {
+        let (name, value) = (name.to_string(), value.to_string());
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move || {
+            let mut rw_tx = db.write_txn()?;
+            let table = open_gamerules(&tables, &db, &rw_tx)?;
+            table.put(&mut rw_tx, name.as_bytes(), value.as_bytes())?;
+            rw_tx.commit()
+        })
+        .await
+        .unwrap()?;
+
+        Ok(())
+    }
+}
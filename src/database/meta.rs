@@ -0,0 +1,59 @@
+//! Values describing the world as a whole, like its seed, stored by name in the `meta` table.
+
+use heed::types::Bytes;
+use heed::{Env, RoTxn};
+use sha2::{Digest, Sha256};
+
+use super::{spawn_blocking_db, Tables};
+use crate::database::Database;
+use crate::utils::error::Error;
+
+const SEED_KEY: &[u8] = b"seed";
+
+fn open_meta(
+    tables: &Tables,
+    db: &Env,
+    tx: &RoTxn,
+) -> Result<heed::Database<Bytes, Bytes>, heed::Error> {
+    tables.open::<Bytes, Bytes>(db, tx, "meta")
+}
+
+impl Database {
+    /// The world's seed: `configured` if it's set, otherwise the stored one.
+    ///
+    /// A world without a stored seed gets a random one the first time this is called, which is
+    /// kept from then on.
+    pub async fn world_seed(&self, configured: Option<i64>) -> Result<i64, Error> {
+        if let Some(seed) = configured {
+            return Ok(seed);
+        }
+
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        let seed = spawn_blocking_db(tsk_db, move || {
+            let mut rw_tx = db.write_txn()?;
+            let meta = open_meta(&tables, &db, &rw_tx)?;
+            if let Some(stored) = meta.get(&rw_tx, SEED_KEY)? {
+                if let Ok(stored) = <[u8; 8]>::try_from(stored) {
+                    return Ok(i64::from_be_bytes(stored));
+                }
+            }
+            let seed: i64 = rand::random();
+            meta.put(&mut rw_tx, SEED_KEY, &seed.to_be_bytes())?;
+            rw_tx.commit()?;
+            Ok(seed)
+        })
+        .await
+        .unwrap()?;
+
+        Ok(seed)
+    }
+}
+
+/// The seed as the client sees it in the Login packet: the first 8 bytes of its SHA-256 hash, so
+/// the seed itself isn't shared with every client.
+pub fn hashed_seed(seed: i64) -> i64 {
+    let hash = Sha256::digest(seed.to_le_bytes());
+    i64::from_le_bytes(hash[..8].try_into().unwrap())
+}
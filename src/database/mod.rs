@@ -1,24 +1,39 @@
 use byteorder::LE;
+use dashmap::DashMap;
 use deepsize::DeepSizeOf;
 use futures::FutureExt;
 use heed::types::{Bytes, U64};
-use heed::{Env as LMDBDatabase, Env, EnvFlags, EnvOpenOptions, MdbError};
+use heed::{Env as LMDBDatabase, Env, EnvFlags, EnvOpenOptions, MdbError, RoTxn};
 use moka::notification::{ListenerFuture, RemovalCause};
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::any::Any;
 use std::env;
 use std::future::Future;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::{Arc, LazyLock, Mutex, OnceLock, RwLock};
-use std::time::Duration;
 use tokio::fs;
 use tokio::sync::oneshot;
 use tracing::{debug, info, trace, warn};
 
-use crate::utils::config::get_global_config;
+use crate::utils::config::{get_global_config, Database as DatabaseConfig};
 use crate::utils::error::Error;
 
 use crate::world::chunk_format::Chunk;
+use chunks::NetSections;
+use encoding::Compression;
+use forceload::{read_force_loaded, ChunkExpiry, ForceLoaded};
+pub mod backup;
+#[cfg(test)]
+pub(crate) mod benches;
 pub mod chunks;
+pub mod forceload;
+pub mod gamerules;
+pub mod meta;
+pub mod ops;
+pub mod playerdata;
+pub mod protection;
+pub mod save;
 pub(crate) mod encoding;
 
 const LMDB_MIN_PAGE_SIZE: usize = 1800 * 1024usize.pow(2); // 1800MB
@@ -37,12 +52,34 @@ static LMDB_READER_SYNC: LazyLock<Arc<RwLock<()>>> = LazyLock::new(|| Arc::new(R
 ///
 /// Internally contain a handle to the persistent database and a
 /// cache for all in-memory updates
+///
+/// Cached chunks are shared behind an [Arc] and never changed in place. Changing one replaces it
+/// with an updated copy, so readers always hold a consistent snapshot.
 pub struct Database {
     db: LMDBDatabase,
-    cache: Arc<moka::future::Cache<u64, Chunk>>,
+    /// Handles of the tables in `db`, see [Tables::open].
+    tables: Tables,
+    cache: Arc<moka::future::Cache<u64, Arc<Chunk>>>,
+    /// Chunks changed in memory that haven't been written to `db` yet, see [Database::save_all].
+    dirty: DashMap<u64, Arc<Chunk>>,
+    /// Chunks the cache never expires, see [forceload].
+    force_loaded: ForceLoaded,
+    /// Encoded sections of cached chunks, see [Database::network_sections].
+    net_sections: Arc<DashMap<u64, NetSections>>,
+    /// How chunks are compressed when they're written.
+    compression: Compression,
+    // Declared last so the environment is dropped before its directory is removed
+    _temp_dir: Option<TempDir>,
 }
 
-fn evict_chunk(_key: Arc<u64>, value: Chunk, cause: RemovalCause) -> ListenerFuture {
+fn evict_chunk(
+    net_sections: &DashMap<u64, NetSections>,
+    key: Arc<u64>,
+    value: Arc<Chunk>,
+    cause: RemovalCause,
+) -> ListenerFuture {
+    // Also covers chunks being replaced by a changed copy
+    net_sections.remove(&key);
     async move {
         if cause == RemovalCause::Expired {
             trace!(
@@ -55,83 +92,214 @@ fn evict_chunk(_key: Arc<u64>, value: Chunk, cause: RemovalCause) -> ListenerFut
     .boxed()
 }
 
+/// Where the database keeps its data. Parsed from `database.mode` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseMode {
+    /// Stored on disk under `database.path`, persists between runs.
+    File,
+    /// Stored on disk in a throwaway directory inside the OS temp dir, deleted once the database
+    /// is dropped. Still goes through LMDB like [DatabaseMode::File], nothing is kept only in
+    /// memory. Useful for tests and ephemeral servers.
+    Temp,
+}
+
+impl FromStr for DatabaseMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(DatabaseMode::File),
+            // What it was called in older configs
+            "temp" | "memory" => Ok(DatabaseMode::Temp),
+            other => Err(Error::InvalidConfig(
+                "database.mode".to_string(),
+                format!("expected \"file\" or \"temp\", got \"{}\"", other),
+            )),
+        }
+    }
+}
+
+/// Removes the directory backing a [DatabaseMode::Temp] database once it's dropped.
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.0) {
+            warn!("Failed to remove temporary database at {}: {}", self.0.display(), e);
+        }
+    }
+}
+
 /// Start database
 pub async fn start_database() -> Result<Database, Error> {
-    // Parse root directory from environment variable
-    let root = if env::var("FERRUMC_ROOT").is_ok() {
-        PathBuf::from(env::var("FERRUMC_ROOT").unwrap())
-    } else {
-        PathBuf::from(
-            env::current_exe()
-                .unwrap()
-                .parent()
-                .ok_or(Error::Generic("Failed to get exe directory".to_string()))?,
-        )
-    };
-
-    // Obtain global config to locate which world folder to load
-    let world = get_global_config().world.clone();
-    let world_path = root.join("data").join(world);
-
-    debug!("Opening database at {}", world_path.display());
-
-    if !fs::try_exists(&world_path).await? {
-        fs::create_dir_all(&world_path).await?;
-    }
-
-    // Database Options
-    let mut opts = EnvOpenOptions::new();
-    opts.max_readers(num_cpus::get() as u32)
-        .map_size(LMDB_MIN_PAGE_SIZE)
-        .max_dbs(LMDB_MAX_DBS);
-
-    // Open database (This operation is safe as we assume no other process touched the database)
-    let lmdb = unsafe {
-        opts.flags(EnvFlags::WRITE_MAP | EnvFlags::NO_SYNC)
-            .open(&world_path)
-            .expect("Unable to open LMDB environment located at {world_path:?}")
-    };
-
-    // Start database threadpool
-    LMDB_THREADPOOL.get_or_init(|| {
-        ThreadPoolBuilder::new()
-            .num_threads(num_cpus::get() / 2)
-            .build()
-            .unwrap()
-    });
+    let config = get_global_config();
+    Database::open(&config.database, &config.world).await
+}
+
+/// Handles of the tables of one database by name, see [Tables::open].
+#[derive(Clone, Default)]
+pub(crate) struct Tables {
+    handles: Arc<DashMap<&'static str, Box<dyn Any + Send + Sync>>>,
+    /// How many times a table had to be looked up rather than coming from `handles`.
+    #[cfg(test)]
+    lookups: Arc<std::sync::atomic::AtomicUsize>,
+}
 
-    // Check if database is built. Otherwise, initialize it
-    let mut rw_tx = lmdb.write_txn()?;
-    if lmdb
-        // .open_database::<U64<LE>, Zstd<Chunk>>(&rw_tx, Some("chunks"))
-        .open_database::<U64<LE>, Bytes>(&rw_tx, Some("chunks"))?
-        .is_none()
+impl Tables {
+    /// Opens one of the tables created by [Database::open].
+    ///
+    /// The handle is only looked up the first time, after that the cached one is reused. Handles
+    /// stay valid for as long as the environment is open, which every [Database] opens itself.
+    ///
+    /// A missing table means the database wasn't initialized by us, which is reported as
+    /// [MdbError::NotFound] instead of panicking.
+    pub(crate) fn open<K, V>(
+        &self,
+        db: &Env,
+        tx: &RoTxn,
+        name: &'static str,
+    ) -> Result<heed::Database<K, V>, heed::Error>
+    where
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
     {
-        lmdb.create_database::<U64<LE>, Bytes>(&mut rw_tx, Some("chunks"))
-            .expect("Unable to create database");
+        if let Some(table) = self
+            .handles
+            .get(name)
+            .and_then(|table| table.downcast_ref::<heed::Database<K, V>>().copied())
+        {
+            return Ok(table);
+        }
+
+        #[cfg(test)]
+        self.lookups.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let table = db.open_database::<K, V>(tx, Some(name))?.ok_or_else(|| {
+            warn!("No table \"{}\" found, the database wasn't initialized", name);
+            heed::Error::Mdb(MdbError::NotFound)
+        })?;
+        self.handles.insert(name, Box::new(table));
+        Ok(table)
     }
-    // `entities` table to be added, but needs the type to do so
+}
 
-    rw_tx.commit()?;
+/// Get the root directory of the server.
+///
+/// Uses the `FERRUMC_ROOT` environment variable if set, otherwise the directory of the executable.
+pub(crate) fn get_root_dir() -> Result<PathBuf, Error> {
+    if let Ok(root) = env::var("FERRUMC_ROOT") {
+        return Ok(PathBuf::from(root));
+    }
+    env::current_exe()?
+        .parent()
+        .map(PathBuf::from)
+        .ok_or(Error::Generic("Failed to get exe directory".to_string()))
+}
 
-    info!("Database started");
+impl Database {
+    /// Open the database for `world`, honoring `database.mode` and `database.path`.
+    pub async fn open(config: &DatabaseConfig, world: &str) -> Result<Database, Error> {
+        let mode = config.mode.parse::<DatabaseMode>()?;
+        let compression = config.compression.parse::<Compression>()?;
+
+        let (world_path, temp_dir) = match mode {
+            DatabaseMode::File => {
+                let dir = get_global_config().paths.resolve(&config.path)?;
+                (dir.join(world), None)
+            }
+            DatabaseMode::Temp => {
+                let path = env::temp_dir().join(format!("ferrumc-{}", uuid::Uuid::new_v4()));
+                (path.clone(), Some(TempDir(path)))
+            }
+        };
+
+        debug!("Opening {:?} database at {}", mode, world_path.display());
+
+        if !fs::try_exists(&world_path).await? {
+            fs::create_dir_all(&world_path).await.map_err(|e| {
+                Error::DatabaseError(format!(
+                    "Unable to create the database directory {}: {}",
+                    world_path.display(),
+                    e
+                ))
+            })?;
+        }
 
-    info!("Initializing cache");
+        // Database Options
+        let mut opts = EnvOpenOptions::new();
+        opts.max_readers(num_cpus::get() as u32)
+            .map_size(LMDB_MIN_PAGE_SIZE)
+            .max_dbs(LMDB_MAX_DBS);
+
+        // Open database (This operation is safe as we assume no other process touched the database)
+        let lmdb = unsafe {
+            opts.flags(EnvFlags::WRITE_MAP | EnvFlags::NO_SYNC)
+                .open(&world_path)
+                .map_err(|e| {
+                    Error::DatabaseError(format!(
+                        "Unable to open the database at {}: {}",
+                        world_path.display(),
+                        e
+                    ))
+                })?
+        };
+
+        // Start database threadpool
+        LMDB_THREADPOOL.get_or_init(|| {
+            ThreadPoolBuilder::new()
+                .num_threads(num_cpus::get() / 2)
+                .build()
+                .unwrap()
+        });
 
-    // Initializing moka cache
-    let cache = moka::future::Cache::builder()
-        .async_eviction_listener(evict_chunk)
-        .weigher(|_, v| v.deep_size_of() as u32)
-        .eviction_policy(moka::policy::EvictionPolicy::tiny_lfu())
-        /*.max_capacity(get_global_config().database.cache_size as u64 * 1024)
-        .initial_capacity(1000)*/
-        .time_to_live(Duration::from_millis(1000))
-        .build();
+        // Create the tables that don't exist yet
+        let mut rw_tx = lmdb.write_txn()?;
+        lmdb.create_database::<U64<LE>, Bytes>(&mut rw_tx, Some("chunks"))?;
+        for table in ["ops", "meta", "forceloaded", "playerdata", "protection", "gamerules"] {
+            lmdb.create_database::<Bytes, Bytes>(&mut rw_tx, Some(table))?;
+        }
+        // `entities` table to be added, but needs the type to do so
+
+        rw_tx.commit()?;
+
+        info!("Database started");
+
+        let tables = Tables::default();
+        let force_loaded = read_force_loaded(&tables, &lmdb)?;
+
+        info!("Initializing cache");
+
+        // Initializing moka cache
+        let net_sections = Arc::new(DashMap::new());
+        let evicted_sections = net_sections.clone();
+        let cache = moka::future::Cache::builder()
+            .async_eviction_listener(move |key, value, cause| {
+                evict_chunk(&evicted_sections, key, value, cause)
+            })
+            .weigher(|_, v: &Arc<Chunk>| Chunk::deep_size_of(v) as u32)
+            .eviction_policy(moka::policy::EvictionPolicy::tiny_lfu())
+            /*.max_capacity(get_global_config().database.cache_size as u64 * 1024)
+            .initial_capacity(1000)*/
+            .expire_after(ChunkExpiry {
+                force_loaded: force_loaded.clone(),
+            })
+            .build();
+
+        Ok(Database {
+            db: lmdb,
+            tables,
+            cache: Arc::new(cache),
+            dirty: DashMap::new(),
+            force_loaded,
+            net_sections,
+            compression,
+            _temp_dir: temp_dir,
+        })
+    }
 
-    Ok(Database {
-        db: lmdb,
-        cache: Arc::new(cache),
-    })
+    /// How chunks are compressed when they're written, from `database.compression`.
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
 }
 
 /// LMDB will follow a linear growth as opposed to MDBX which
@@ -200,3 +368,218 @@ where
 
     res
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    pub(crate) fn temp_config() -> DatabaseConfig {
+        DatabaseConfig {
+            cache_size: 1024,
+            compression: "zstd".to_string(),
+            mode: "temp".to_string(),
+            path: String::new(),
+        }
+    }
+
+    pub(crate) fn test_chunk(x: i32, z: i32) -> Chunk {
+        Chunk {
+            dimension: Some("overworld".to_string()),
+            status: "full".to_string(),
+            data_version: 3465,
+            heightmaps: None,
+            is_light_on: None,
+            inhabited_time: None,
+            y_pos: -4,
+            x_pos: x,
+            z_pos: z,
+            structures: None,
+            last_update: None,
+            sections: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_at_unwritable_path_fails() {
+        // Even root can't create a directory inside a regular file
+        let file = env::temp_dir().join(format!("ferrumc-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&file, b"not a directory").unwrap();
+        let config = DatabaseConfig {
+            mode: "file".to_string(),
+            path: file.display().to_string(),
+            ..temp_config()
+        };
+
+        let result = Database::open(&config, "world").await;
+        std::fs::remove_file(&file).unwrap();
+        let Err(Error::DatabaseError(reason)) = result else {
+            panic!("Expected opening the database to fail");
+        };
+        assert!(reason.contains(&file.display().to_string()), "{}", reason);
+    }
+
+    #[tokio::test]
+    async fn test_table_handles_are_reused() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        // Reading the force-loaded chunks at startup opened that table
+        assert_eq!(database.tables.lookups.load(Ordering::Relaxed), 1);
+
+        for round in 0..10 {
+            for dimension in ["overworld", "the_nether"] {
+                let mut chunk = test_chunk(round, 0);
+                chunk.dimension = Some(dimension.to_string());
+                database.insert_chunk(chunk).await.unwrap();
+            }
+        }
+        database.force_load(0, 0, "overworld").await.unwrap();
+        assert_eq!(database.chunk_count().await.unwrap(), 20);
+
+        // Only the chunks table was looked up, once
+        assert_eq!(database.tables.lookups.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_default_wrappers_use_configured_dimension() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        let config = get_global_config();
+        let mut default = test_chunk(0, 0);
+        default.dimension = Some(config.default_dimension.clone());
+        let mut nether = test_chunk(0, 0);
+        nether.dimension = Some("the_nether".to_string());
+        nether.data_version = 1;
+        database.insert_chunk(nether.clone()).await.unwrap();
+
+        assert!(!database.chunk_exists_default(0, 0).await.unwrap());
+        assert_eq!(database.get_chunk_default(0, 0).await.unwrap(), None);
+
+        database.insert_chunk(default.clone()).await.unwrap();
+        assert!(database.chunk_exists_default(0, 0).await.unwrap());
+        assert!(database.is_chunk_cached_default(0, 0));
+        assert_eq!(database.get_chunk_default(0, 0).await.unwrap(), Some(default));
+
+        let changed = database
+            .modify_chunk_default(0, 0, |chunk| chunk.status = "light".to_string())
+            .await
+            .unwrap();
+        assert!(changed);
+        let chunk = database.get_chunk(0, 0, &config.default_dimension).await.unwrap();
+        assert_eq!(chunk.unwrap().status, "light");
+        let chunk = database.get_chunk(0, 0, "the_nether").await.unwrap();
+        assert_eq!(chunk, Some(nether));
+    }
+
+    #[tokio::test]
+    async fn test_temp_database_roundtrip() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        let temp_path = database._temp_dir.as_ref().unwrap().0.clone();
+        assert!(temp_path.starts_with(env::temp_dir()));
+
+        let chunk = test_chunk(1, 2);
+        database.insert_chunk(chunk.clone()).await.unwrap();
+
+        let fetched = database.get_chunk(1, 2, "overworld").await.unwrap();
+        assert_eq!(fetched, Some(chunk));
+
+        drop(database);
+        assert!(!temp_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_preload_fills_cache() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        database.insert_chunk(test_chunk(0, 0)).await.unwrap();
+        database.insert_chunk(test_chunk(1, -1)).await.unwrap();
+        database.insert_chunk(test_chunk(5, 5)).await.unwrap();
+
+        // Start cold, like after a restart
+        database.cache.invalidate_all();
+        database.cache.run_pending_tasks().await;
+        assert!(!database.is_chunk_cached(0, 0, "overworld"));
+
+        crate::world::spawn::preload_spawn_chunks(&database, 1)
+            .await
+            .unwrap();
+
+        assert!(database.is_chunk_cached(0, 0, "overworld"));
+        assert!(database.is_chunk_cached(1, -1, "overworld"));
+        // Outside the radius
+        assert!(!database.is_chunk_cached(5, 5, "overworld"));
+    }
+
+    #[tokio::test]
+    async fn test_force_loaded_chunk_survives_expiry() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        database.insert_chunk(test_chunk(0, 0)).await.unwrap();
+        database.insert_chunk(test_chunk(3, 3)).await.unwrap();
+        assert!(database.force_load(0, 0, "overworld").await.unwrap());
+        assert!(!database.force_load(0, 0, "overworld").await.unwrap());
+
+        tokio::time::sleep(forceload::CACHE_TTL * 2).await;
+        database.cache.run_pending_tasks().await;
+
+        assert!(database.is_chunk_cached(0, 0, "overworld"));
+        assert!(!database.is_chunk_cached(3, 3, "overworld"));
+
+        assert!(database.unforce_load(0, 0, "overworld").await.unwrap());
+        assert!(!database.is_force_loaded(0, 0, "overworld"));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_chunk_stays_force_loaded() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        database
+            .keep_spawn_chunks_loaded((0, 0), 1, "overworld")
+            .await
+            .unwrap();
+
+        // Adding and removing it again as a command doesn't touch the spawn chunk
+        assert!(!database.force_load(1, 1, "overworld").await.unwrap());
+        assert!(!database.unforce_load(1, 1, "overworld").await.unwrap());
+        assert!(database.is_force_loaded(1, 1, "overworld"));
+    }
+
+    #[tokio::test]
+    async fn test_op_levels() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        assert_eq!(database.get_op_level(7).await.unwrap(), 0);
+
+        database.set_op_level(7, 3).await.unwrap();
+        assert_eq!(database.get_op_level(7).await.unwrap(), 3);
+        assert_eq!(database.get_op_level(8).await.unwrap(), 0);
+
+        database.set_op_level(7, 0).await.unwrap();
+        assert_eq!(database.get_op_level(7).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_world_seed_persists() {
+        let path = env::temp_dir().join(format!("ferrumc-{}", uuid::Uuid::new_v4()));
+        let config = DatabaseConfig {
+            mode: "file".to_string(),
+            path: path.to_string_lossy().into_owned(),
+            ..temp_config()
+        };
+
+        let database = Database::open(&config, "world").await.unwrap();
+        let seed = database.world_seed(None).await.unwrap();
+        assert_eq!(database.world_seed(None).await.unwrap(), seed);
+        drop(database);
+
+        let database = Database::open(&config, "world").await.unwrap();
+        assert_eq!(database.world_seed(None).await.unwrap(), seed);
+        // A configured seed wins over the stored one
+        assert_eq!(database.world_seed(Some(42)).await.unwrap(), 42);
+        drop(database);
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_database_mode() {
+        assert_eq!("file".parse::<DatabaseMode>().unwrap(), DatabaseMode::File);
+        assert_eq!("temp".parse::<DatabaseMode>().unwrap(), DatabaseMode::Temp);
+        assert_eq!("memory".parse::<DatabaseMode>().unwrap(), DatabaseMode::Temp);
+        assert!("sled".parse::<DatabaseMode>().is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! Operator permission levels, stored per player UUID in the `ops` table.
+
+use heed::types::Bytes;
+use heed::{Env, RoTxn};
+
+use super::{spawn_blocking_db, Tables};
+use crate::database::Database;
+use crate::utils::error::Error;
+
+/// The highest permission level, which can run every command.
+pub const MAX_OP_LEVEL: u8 = 4;
+
+fn open_ops(
+    tables: &Tables,
+    db: &Env,
+    tx: &RoTxn,
+) -> Result<heed::Database<Bytes, Bytes>, heed::Error> {
+    tables.open::<Bytes, Bytes>(db, tx, "ops")
+}
+
+impl Database {
+    /// The permission level of a player, 0 unless they've been opped.
+    pub async fn get_op_level(&self, uuid: u128) -> Result<u8, Error> {
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        let level = spawn_blocking_db(tsk_db, move || {
+            let ro_tx = db.read_txn()?;
+            let ops = open_ops(&tables, &db, &ro_tx)?;
+            let level = ops.get(&ro_tx, &uuid.to_be_bytes())?;
+            Ok(level.and_then(|level| level.first().copied()))
+        })
+        .await
+        .unwrap()?;
+
+        Ok(level.unwrap_or(0))
+    }
+
+    /// Set the permission level of a player. Level 0 removes them from the ops.
+    pub async fn set_op_level(&self, uuid: u128, level: u8) -> Result<(), Error> {
+        let level = level.min(MAX_OP_LEVEL);
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move || {
+            let mut rw_tx = db.write_txn()?;
+            let ops = open_ops(&tables, &db, &rw_tx)?;
+            if level == 0 {
+                ops.delete(&mut rw_tx, &uuid.to_be_bytes())?;
+            } else {
+                ops.put(&mut rw_tx, &uuid.to_be_bytes(), &[level])?;
+            }
+            rw_tx.commit()
+        })
+        .await
+        .unwrap()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+//! What players had when they last left, stored per player UUID in the `playerdata` table.
+//!
+//! See [crate::net::player_data] for taking and restoring these snapshots.
+
+use bincode::config::standard;
+use bincode::{Decode, Encode};
+use heed::types::Bytes;
+use heed::{Env, RoTxn};
+
+use super::{spawn_blocking_db, Tables};
+use crate::database::Database;
+use crate::utils::encoding::item_stack::ItemStack;
+use crate::utils::error::Error;
+
+/// A snapshot of a player's state.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct PlayerData {
+    /// x, y and z.
+    pub position: (i32, i16, i32),
+    /// Yaw and pitch.
+    pub rotation: (f32, f32),
+    pub health: f32,
+    pub food: i32,
+    pub saturation: f32,
+    pub gamemode: u8,
+    /// Every inventory slot in order, empty ones included.
+    pub inventory: Vec<ItemStack>,
+}
+
+fn open_playerdata(
+    tables: &Tables,
+    db: &Env,
+    tx: &RoTxn,
+) -> Result<heed::Database<Bytes, Bytes>, heed::Error> {
+    tables.open::<Bytes, Bytes>(db, tx, "playerdata")
+}
+
+impl Database {
+    /// A player's stored data, `None` if they've never been saved, e.g. on their first join.
+    pub async fn load_player_data(&self, uuid: u128) -> Result<Option<PlayerData>, Error> {
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        let bytes = spawn_blocking_db(tsk_db, move || {
+            let ro_tx = db.read_txn()?;
+            let table = open_playerdata(&tables, &db, &ro_tx)?;
+            let bytes = table.get(&ro_tx, &uuid.to_be_bytes())?;
+            Ok(bytes.map(|bytes| bytes.to_vec()))
+        })
+        .await
+        .unwrap()?;
+
+        let Some(bytes) = bytes else {
+            return Ok(None);
+        };
+        let (data, _) = bincode::decode_from_slice(&bytes, standard())?;
+        Ok(Some(data))
+    }
+
+    /// Store a player's data, replacing whatever was stored for them before.
+    pub async fn save_player_data(&self, uuid: u128, data: &PlayerData) -> Result<(), Error> {
+        let bytes = bincode::encode_to_vec(data, standard())?;
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move || {
+            let mut rw_tx = db.write_txn()?;
+            let table = open_playerdata(&tables, &db, &rw_tx)?;
+            table.put(&mut rw_tx, &uuid.to_be_bytes(), &bytes)?;
+            rw_tx.commit()
+        })
+        .await
+        .unwrap()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! Protected areas of the world, stored by name in the `protection` table.
+//!
+//! See [crate::world::protection] for how they're enforced.
+
+use bincode::config::standard;
+use bincode::{Decode, Encode};
+use heed::types::Bytes;
+use heed::{Env, RoTxn};
+
+use super::{spawn_blocking_db, Tables};
+use crate::database::Database;
+use crate::utils::error::Error;
+
+/// A cuboid only its members may change blocks in.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ProtectedRegion {
+    pub name: String,
+    /// The lowest corner, inclusive.
+    pub min: (i32, i32, i32),
+    /// The highest corner, inclusive.
+    pub max: (i32, i32, i32),
+    /// UUIDs of the players allowed to change blocks in it.
+    pub members: Vec<u128>,
+}
+
+impl ProtectedRegion {
+    /// The region between two opposite corners, given in any order, without any members.
+    pub fn new(name: &str, a: (i32, i32, i32), b: (i32, i32, i32)) -> Self {
+        Self {
+            name: name.to_string(),
+            min: (a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)),
+            max: (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)),
+            members: Vec::new(),
+        }
+    }
+
+    pub fn contains(&self, (x, y, z): (i32, i32, i32)) -> bool {
+        (self.min.0..=self.max.0).contains(&x)
+            && (self.min.1..=self.max.1).contains(&y)
+            && (self.min.2..=self.max.2).contains(&z)
+    }
+
+    pub fn is_member(&self, uuid: u128) -> bool {
+        self.members.contains(&uuid)
+    }
+}
+
+fn open_protection(
+    tables: &Tables,
+    db: &Env,
+    tx: &RoTxn,
+) -> Result<heed::Database<Bytes, Bytes>, heed::Error> {
+    tables.open::<Bytes, Bytes>(db, tx, "protection")
+}
+
+impl Database {
+    /// The protected region with this name, if there is one.
+    pub async fn get_protected_region(&self, name: &str) -> Result<Option<ProtectedRegion>, Error> {
+        let name = name.to_string();
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        let bytes = spawn_blocking_db(tsk_db, move || {
+            let ro_tx = db.read_txn()?;
+            let table = open_protection(&tables, &db, &ro_tx)?;
+            let bytes = table.get(&ro_tx, name.as_bytes())?;
+            Ok(bytes.map(|bytes| bytes.to_vec()))
+        })
+        .await
+        .unwrap()?;
+
+        let Some(bytes) = bytes else {
+            return Ok(None);
+        };
+        let (region, _) = bincode::decode_from_slice(&bytes, standard())?;
+        Ok(Some(region))
+    }
+
+    /// Every protected region, sorted by name.
+    pub async fn protected_regions(&self) -> Result<Vec<ProtectedRegion>, Error> {
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        let values = spawn_blocking_db(tsk_db, move || {
+            let ro_tx = db.read_txn()?;
+            let table = open_protection(&tables, &db, &ro_tx)?;
+            let mut values = Vec::new();
+            for entry in table.iter(&ro_tx)? {
+                let (_, value) = entry?;
+                values.push(value.to_vec());
+            }
+            Ok(values)
+        })
+        .await
+        .unwrap()?;
+
+        let mut regions = Vec::with_capacity(values.len());
+        for value in values {
+            let (region, _) = bincode::decode_from_slice(&value, standard())?;
+            regions.push(region);
+        }
+        Ok(regions)
+    }
+
+    /// Store a protected region, replacing any with the same name.
+    pub async fn save_protected_region(&self, region: &ProtectedRegion) -> Result<(), Error> {
+        let name = region.name.clone();
+        let bytes = bincode::encode_to_vec(region, standard())?;
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move || {
+            let mut rw_tx = db.write_txn()?;
+            let table = open_protection(&tables, &db, &rw_tx)?;
+            table.put(&mut rw_tx, name.as_bytes(), &bytes)?;
+            rw_tx.commit()
+        })
+        .await
+        .unwrap()?;
+
+        Ok(())
+    }
+
+    /// Stop protecting a region. Returns `false` if there was no region with this name.
+    pub async fn remove_protected_region(&self, name: &str) -> Result<bool, Error> {
+        let name = name.to_string();
+        let db = self.db.clone();
+        let tables = self.tables.clone();
+        let tsk_db = self.db.clone();
+        let removed = spawn_blocking_db(tsk_db, move || {
+            let mut rw_tx = db.write_txn()?;
+            let table = open_protection(&tables, &db, &rw_tx)?;
+            let removed = table.delete(&mut rw_tx, name.as_bytes())?;
+            rw_tx.commit()?;
+            Ok(removed)
+        })
+        .await
+        .unwrap()?;
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::temp_config;
+
+    #[test]
+    fn test_corners_in_any_order() {
+        let region = ProtectedRegion::new("spawn", (10, 80, -5), (-10, 60, 5));
+        assert_eq!(region.min, (-10, 60, -5));
+        assert_eq!(region.max, (10, 80, 5));
+        assert!(region.contains((10, 60, 0)));
+        assert!(!region.contains((11, 60, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_regions_are_stored() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        let mut region = ProtectedRegion::new("spawn", (0, 0, 0), (10, 10, 10));
+        region.members.push(42);
+        database.save_protected_region(&region).await.unwrap();
+
+        assert_eq!(database.get_protected_region("spawn").await.unwrap(), Some(region.clone()));
+        assert_eq!(database.protected_regions().await.unwrap(), vec![region]);
+        assert!(database.remove_protected_region("spawn").await.unwrap());
+        assert!(!database.remove_protected_region("spawn").await.unwrap());
+        assert!(database.protected_regions().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Chunks changed in memory only, and writing them back to disk.
+
+use std::sync::Arc;
+
+use dashmap::mapref::entry::Entry;
+
+use super::spawn_blocking_db;
+use crate::database::encoding::Codec;
+use crate::database::Database;
+use crate::utils::config::get_global_config;
+use crate::utils::error::Error;
+use crate::utils::hash::hash;
+use crate::world::chunk_format::Chunk;
+use crate::world::importing::SerializedChunk;
+
+impl Database {
+    /// Update a chunk in memory only.
+    ///
+    /// It's served from memory until the next [Database::save_all] writes it to the persistent
+    /// database.
+    pub async fn cache_chunk(&self, value: Chunk) {
+        let key = hash((value.dimension.as_ref().unwrap(), value.x_pos, value.z_pos));
+        let value = Arc::new(value);
+        self.dirty.insert(key, value.clone());
+        self.cache.insert(key, value).await;
+    }
+
+    /// Change a chunk in memory with `modify`, e.g. to set blocks in it.
+    ///
+    /// The chunk is only marked as changed, and written by the next [Database::save_all], if
+    /// `modify` actually changed something. Returns whether it did.
+    ///
+    /// `modify` works on a copy, so anyone holding the chunk from [Database::get_chunk_shared]
+    /// keeps seeing it as it was. Changes to the same chunk are applied one after the other, each
+    /// on top of the last.
+    pub async fn modify_chunk<F>(
+        &self,
+        x: i32,
+        z: i32,
+        dimension: &str,
+        modify: F,
+    ) -> Result<bool, Error>
+    where
+        F: FnOnce(&mut Chunk),
+    {
+        let loaded = self
+            .get_chunk_shared(x, z, dimension)
+            .await?
+            .ok_or(Error::ChunkNotFound(x, z))?;
+        let key = hash((dimension, x, z));
+
+        // Holding the entry keeps other changes to the chunk out until this one is in
+        let updated = match self.dirty.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let chunk = entry.get_mut();
+                copy_on_write(chunk, modify).then(|| chunk.clone())
+            }
+            Entry::Vacant(entry) => {
+                let mut chunk = loaded;
+                copy_on_write(&mut chunk, modify).then(|| entry.insert(chunk).clone())
+            }
+        };
+
+        let Some(chunk) = updated else {
+            return Ok(false);
+        };
+        self.cache.insert(key, chunk).await;
+        Ok(true)
+    }
+
+    /// [Database::modify_chunk] in the configured default dimension.
+    pub async fn modify_chunk_default<F>(&self, x: i32, z: i32, modify: F) -> Result<bool, Error>
+    where
+        F: FnOnce(&mut Chunk),
+    {
+        let config = get_global_config();
+        self.modify_chunk(x, z, &config.default_dimension, modify).await
+    }
+
+    /// The number of chunks changed in memory that haven't been saved yet.
+    pub fn dirty_chunk_count(&self) -> usize {
+        self.dirty.len()
+    }
+
+    /// Write every chunk changed in memory to the persistent database, then
+    /// [flush](Database::flush) it. Returns the number of chunks written.
+    ///
+    /// If writing fails, the chunks stay in memory to be saved again later.
+    pub async fn save_all(&self) -> Result<usize, Error> {
+        let keys: Vec<u64> = self.dirty.iter().map(|entry| *entry.key()).collect();
+        let chunks: Vec<(u64, Arc<Chunk>)> = keys
+            .into_iter()
+            .filter_map(|key| self.dirty.remove(&key))
+            .collect();
+
+        let result = async {
+            let mut serialized = Vec::with_capacity(chunks.len());
+            for (key, chunk) in &chunks {
+                // Keep it cached, so it's still served from memory until it's written
+                self.cache.insert(*key, chunk.clone()).await;
+                let data = Codec::compress_data(Chunk::clone(chunk), self.compression).await?;
+                serialized.push(SerializedChunk::new(*key, data));
+            }
+            self.batch_insert(serialized).await?;
+            self.flush().await
+        }
+        .await;
+
+        if let Err(e) = result {
+            for (key, chunk) in chunks {
+                // Don't overwrite anything changed again in the meantime
+                self.dirty.entry(key).or_insert(chunk);
+            }
+            return Err(e);
+        }
+
+        Ok(chunks.len())
+    }
+
+    /// Force everything written so far onto the disk.
+    ///
+    /// The database is opened with `NO_SYNC`, so otherwise writes only reach the disk whenever
+    /// the OS gets around to it.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let db = self.db.clone();
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move || db.force_sync())
+            .await
+            .unwrap()?;
+        Ok(())
+    }
+}
+
+/// Applies `modify` to a copy of `chunk`, and swaps it in if that changed anything.
+///
+/// Returns whether it did.
+fn copy_on_write<F>(chunk: &mut Arc<Chunk>, modify: F) -> bool
+where
+    F: FnOnce(&mut Chunk),
+{
+    let mut updated = Chunk::clone(chunk);
+    modify(&mut updated);
+    if updated == **chunk {
+        return false;
+    }
+    *chunk = Arc::new(updated);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::{temp_config, test_chunk};
+
+    #[tokio::test]
+    async fn test_save_all() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        let mut changed = test_chunk(0, 0);
+        changed.data_version = 1;
+        for x in 0..3 {
+            database.insert_chunk(test_chunk(x, 0)).await.unwrap();
+        }
+
+        database.cache_chunk(changed.clone()).await;
+        database.cache_chunk(test_chunk(5, 5)).await;
+        assert_eq!(database.dirty_chunk_count(), 2);
+        assert_eq!(database.chunk_count().await.unwrap(), 3);
+        let chunk = database.get_chunk(0, 0, "overworld").await.unwrap();
+        assert_eq!(chunk, Some(changed.clone()));
+
+        assert_eq!(database.save_all().await.unwrap(), 2);
+        assert_eq!(database.dirty_chunk_count(), 0);
+        assert_eq!(database.chunk_count().await.unwrap(), 4);
+
+        // Skip the cache to check what was actually written
+        database.cache.invalidate_all();
+        database.cache.run_pending_tasks().await;
+        let chunk = database.get_chunk(0, 0, "overworld").await.unwrap();
+        assert_eq!(chunk, Some(changed));
+    }
+
+    #[tokio::test]
+    async fn test_only_modified_chunks_are_saved() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        database.insert_chunk(test_chunk(0, 0)).await.unwrap();
+        database.insert_chunk(test_chunk(1, 0)).await.unwrap();
+
+        let dimension = "overworld";
+        let modified = database
+            .modify_chunk(0, 0, dimension, |chunk| chunk.status = "light".to_string())
+            .await
+            .unwrap();
+        assert!(modified);
+        // Setting something to what it already was doesn't count as a change
+        let modified = database
+            .modify_chunk(1, 0, dimension, |chunk| chunk.status = "full".to_string())
+            .await
+            .unwrap();
+        assert!(!modified);
+        assert!(database.modify_chunk(9, 9, dimension, |_| {}).await.is_err());
+
+        assert_eq!(database.dirty_chunk_count(), 1);
+        assert_eq!(database.save_all().await.unwrap(), 1);
+        // Saved chunks are clean again
+        assert_eq!(database.save_all().await.unwrap(), 0);
+
+        database.cache.invalidate_all();
+        database.cache.run_pending_tasks().await;
+        let chunk = database.get_chunk(0, 0, dimension).await.unwrap().unwrap();
+        assert_eq!(chunk.status, "light");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_reads_see_whole_chunks() {
+        let database = Arc::new(Database::open(&temp_config(), "world").await.unwrap());
+        database.insert_chunk(test_chunk(0, 0)).await.unwrap();
+        let dimension = "overworld";
+
+        // Every change keeps status and data_version in step with each other
+        let mutator = {
+            let database = database.clone();
+            tokio::spawn(async move {
+                for version in 1..=500 {
+                    database
+                        .modify_chunk(0, 0, dimension, |chunk| {
+                            chunk.data_version = version;
+                            chunk.status = version.to_string();
+                        })
+                        .await
+                        .unwrap();
+                }
+            })
+        };
+        let readers: Vec<_> = (0..3)
+            .map(|_| {
+                let database = database.clone();
+                tokio::spawn(async move {
+                    for _ in 0..500 {
+                        let chunk = database.get_chunk_shared(0, 0, dimension).await.unwrap();
+                        let chunk = chunk.unwrap();
+                        if chunk.data_version != 3465 {
+                            assert_eq!(chunk.status, chunk.data_version.to_string());
+                        }
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+
+        mutator.await.unwrap();
+        for reader in readers {
+            reader.await.unwrap();
+        }
+
+        // The last change is the one that's written
+        assert_eq!(database.save_all().await.unwrap(), 1);
+        database.cache.invalidate_all();
+        database.cache.run_pending_tasks().await;
+        let chunk = database.get_chunk(0, 0, dimension).await.unwrap().unwrap();
+        assert_eq!(chunk.data_version, 500);
+        assert_eq!(chunk.status, "500");
+    }
+}
@@ -0,0 +1,53 @@
+//! Benchmarks for querying many entities. Run them with:
+//! `cargo test --release query_benchmarks -- --ignored --nocapture`
+
+use criterion::{black_box, Criterion};
+use futures::StreamExt;
+use tokio::runtime::Runtime;
+
+use crate::ecs::world::World;
+use crate::utils::encoding::position::Position;
+
+const ENTITIES: i32 = 10_000;
+
+fn bench_query(c: &mut Criterion, runtime: &Runtime) {
+    let world = World::new();
+    runtime.block_on(async {
+        for x in 0..ENTITIES {
+            world.create_entity().await.with(Position { x, y: 64, z: 0 }).build();
+        }
+    });
+
+    // Every result, and so every component lock, is kept until the positions are copied out
+    c.bench_function("copy 10k positions out of an eager query", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let query = world.query::<&Position>();
+                let results: Vec<_> = query.iter().await.collect();
+                let xs: Vec<i32> = results.iter().map(|(_, position)| position.x).collect();
+                black_box(xs);
+            })
+        })
+    });
+    // Only the copies are kept, one component is locked at a time
+    c.bench_function("copy 10k positions out of a query stream", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let query = world.query::<&Position>();
+                let xs: Vec<i32> = query.stream().map(|(_, position)| position.x).collect().await;
+                black_box(xs);
+            })
+        })
+    });
+}
+
+#[test]
+#[ignore]
+fn query_benchmarks() {
+    let runtime = Runtime::new().unwrap();
+    let mut criterion = Criterion::default().sample_size(10);
+
+    bench_query(&mut criterion, &runtime);
+
+    criterion.final_summary();
+}
@@ -5,6 +5,7 @@ use std::marker::PhantomData;
 
 use crate::ecs::error::Error;
 use crate::ecs::helpers::sparse_set::SparseSet;
+use crate::utils::lock_order::{self, HeldLock, LockRank};
 use dashmap::DashMap;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
@@ -21,6 +22,7 @@ pub trait Component: 'static + Send + Sync + Debug {}
 #[derive(Debug)]
 pub struct ComponentRef<'a, T: Component + 'a> {
     read_guard: RwLockReadGuard<'a, Box<dyn Component>>,
+    _held: HeldLock,
     _phantom: PhantomData<T>,
 }
 
@@ -34,6 +36,7 @@ pub struct ComponentRef<'a, T: Component + 'a> {
 #[derive(Debug)]
 pub struct ComponentRefMut<'a, T: Component> {
     write_guard: RwLockWriteGuard<'a, Box<dyn Component>>,
+    _held: HeldLock,
     _phantom: PhantomData<T>,
 }
 
@@ -115,6 +118,7 @@ impl ComponentStorage {
             .ok_or(Error::ComponentNotFound)?;
         let component = storage.get(entity_id).ok_or(Error::ComponentNotFound)?;
 
+        let held = lock_order::acquire(LockRank::Component);
         let read_guard = unsafe {
             std::mem::transmute::<
                 RwLockReadGuard<'_, Box<dyn Component>>,
@@ -124,6 +128,7 @@ impl ComponentStorage {
 
         Ok(ComponentRef {
             read_guard,
+            _held: held,
             _phantom: PhantomData,
         })
     }
@@ -147,6 +152,7 @@ impl ComponentStorage {
             .ok_or(Error::ComponentNotFound)?;
         let component = storage.get(entity_id).ok_or(Error::ComponentNotFound)?;
 
+        let held = lock_order::acquire(LockRank::Component);
         let write = component.write().await;
 
         let write_guard = unsafe {
@@ -158,6 +164,7 @@ impl ComponentStorage {
 
         Ok(ComponentRefMut {
             write_guard,
+            _held: held,
             _phantom: PhantomData,
         })
     }
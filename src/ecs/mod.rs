@@ -1,6 +1,8 @@
 #[cfg(test)]
 use std::sync::OnceLock;
 
+#[cfg(test)]
+mod benches;
 pub mod component;
 pub mod entity;
 pub mod error;
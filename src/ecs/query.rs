@@ -1,5 +1,7 @@
 use std::marker::PhantomData;
 
+use futures::Stream;
+
 use crate::ecs::component::{Component, ComponentRef, ComponentRefMut, ComponentStorage};
 use crate::ecs::entity::EntityManager;
 use crate::utils::prelude::*;
@@ -39,6 +41,23 @@ impl<T: Component> QueryItem for &mut T {
 }
 
 /// Struct for querying components in the ECS.
+///
+/// Every result holds its entity's components locked until it's dropped. To keep that short,
+/// go through the results one at a time with [Query::next] or [Query::stream], copy out just
+/// what's needed and drop each result before fetching the next:
+///
+/// ```
+/// let mut timed_out = Vec::new();
+/// let mut query = world.query::<(&KeepAlive, &ConnectionWrapper)>();
+/// while let Some((_, (keep_alive, conn))) = query.next().await {
+///     if clock.elapsed_since(keep_alive.last_sent) > TIMEOUT {
+///         timed_out.push(conn.handle());
+///     }
+/// }
+/// ```
+///
+/// [Query::iter] fetches everything up front instead, so all the components stay locked until the
+/// last result is dropped.
 #[derive(Clone, Copy)]
 pub struct Query<'a, Q: QueryItem> {
     entity_manager: &'a EntityManager,
@@ -64,7 +83,8 @@ impl<'a, Q: QueryItem> Query<'a, Q> {
         }
     }
 
-    /// Returns an iterator over the query results.
+    /// Returns an iterator over the query results, all fetched up front. See [Query::stream] for
+    /// one that only fetches results as they're consumed.
     ///
     /// # Examples
     ///
@@ -95,6 +115,39 @@ impl<'a, Q: QueryItem> Query<'a, Q> {
         results.into_iter()
     }
 
+    /// Returns the query results as a stream that fetches each one as it's polled, so only the
+    /// results that haven't been dropped yet hold their components. Unlike [Query::next], it
+    /// doesn't move the query along, it always starts at the first entity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let query = Query::<(&Position, &Velocity)>::new(&entity_manager, &component_storage);
+    /// let moving: Vec<usize> = query
+    ///     .stream()
+    ///     .filter_map(|(entity_id, (_, velocity))| async move {
+    ///         velocity.is_moving().then_some(entity_id)
+    ///     })
+    ///     .collect()
+    ///     .await;
+    /// ```
+    pub fn stream(&self) -> impl Stream<Item = (usize, Q::Item<'a>)> + 'a
+    where
+        Q: 'a,
+    {
+        let (entity_manager, component_storage) = (self.entity_manager, self.component_storage);
+        futures::stream::unfold(0, move |mut entity_id| async move {
+            let max_entity_id = entity_manager.len().await;
+            while entity_id <= max_entity_id {
+                if let Ok(item) = Q::fetch(entity_id, component_storage).await {
+                    return Some(((entity_id, item), entity_id + 1));
+                }
+                entity_id += 1;
+            }
+            None
+        })
+    }
+
     /// Returns the next query result.
     ///
     /// # Examples
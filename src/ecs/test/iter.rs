@@ -4,6 +4,7 @@ mod tests {
     use std::time::Duration;
 
     use futures::future::join_all;
+    use futures::StreamExt;
     use tokio::sync::Barrier;
 
     use crate::ecs::component::{Component, ComponentStorage};
@@ -418,4 +419,36 @@ mod tests {
             );
         });
     }
+
+    #[tokio::test]
+    async fn test_stream_releases_consumed_results() {
+        let storage = ComponentStorage::new();
+        let entity_manager = EntityManager::new();
+        for i in 0..10_000 {
+            let entity = entity_manager.create_entity().await;
+            storage.insert(entity, Position { x: i, y: 0, z: 0 });
+        }
+        let query = Query::<&Position>::new(&entity_manager, &storage);
+        let write_first = || {
+            tokio::time::timeout(Duration::from_millis(50), storage.get_mut::<Position>(0usize))
+        };
+
+        // Everything fetched up front stays locked as long as the results are around
+        let results: Vec<_> = query.iter().await.collect();
+        assert!(write_first().await.is_err());
+        drop(results);
+
+        // Halfway through the stream, the results already consumed are free again
+        let mut stream = std::pin::pin!(query.stream());
+        let mut sum = 0;
+        for _ in 0..5_000 {
+            let (_, position) = stream.next().await.unwrap();
+            sum += position.x as i64;
+        }
+        assert!(write_first().await.is_ok());
+        while let Some((_, position)) = stream.next().await {
+            sum += position.x as i64;
+        }
+        assert_eq!(sum, (0..10_000).sum::<i64>());
+    }
 }
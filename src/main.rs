@@ -6,22 +6,33 @@ extern crate macro_rules_attribute;
 
 use std::env;
 use std::process::exit;
-use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
 
-use dashmap::DashMap;
 use tokio::net::TcpListener;
 use tracing::{error, info, trace};
 
 use crate::ecs::world::World;
+use crate::net::systems::health::Heartbeat;
+use crate::utils::clock::SystemClock;
+use crate::world::block_changes::PendingBlockChanges;
+use crate::world::difficulty::CurrentDifficulty;
+use crate::world::gamerules::GameRules;
+use crate::world::ticks::GameTicks;
+use crate::world::time::WorldTime;
+use crate::world::weather::WorldWeather;
+use crate::net::entity_ids::NetworkEntityIds;
+use crate::net::boss_bar::BossBars;
+use crate::net::scoreboard::Scoreboard;
+use crate::net::packets::middleware::MiddlewareChain;
 use crate::net::ConnectionList;
 use crate::state::{GlobalState, ServerState};
 use crate::{
-    net::systems::{kill_all_systems, start_all_systems},
+    net::systems::{kill_all_systems, start_all_systems, SystemRegistry},
     net::Connection,
     utils::{config::get_global_config, prelude::*},
 };
 
+mod commands;
 pub mod ecs;
 pub mod net;
 mod setup;
@@ -59,8 +70,6 @@ async fn entry() -> Result<()> {
 
     start_server().await?;
 
-    tokio::signal::ctrl_c().await?;
-
     info!("Exiting server;");
 
     Ok(())
@@ -71,16 +80,12 @@ async fn entry() -> Result<()> {
 /// The actual management of connections tx/rx is handled by [net::systems::connection_handler]
 async fn start_server() -> Result<()> {
     let config = get_global_config();
+    // Loaded up front so reports for the wrong version stop the server right away
+    let registries = utils::registries::get_registries();
+    info!("Loaded registries for Minecraft {}", registries.version.name);
     trace!("Starting server on {}:{}", config.host, config.port);
 
-    let tcp_addr = format!("{}:{}", config.host, config.port);
-
-    let Ok(listener) = TcpListener::bind(tcp_addr.clone()).await else {
-        error!("Failed to bind to address: {}", &tcp_addr);
-        error!("Perhaps the port {} is already in use?", &config.port);
-
-        return Err(Error::TcpError("Failed to bind to address".to_string()));
-    };
+    let listener = net::bind_listener(&config).await?;
 
     let addr = listener.local_addr()?;
 
@@ -93,27 +98,55 @@ async fn start_server() -> Result<()> {
         exit(0);
     }
 
+    if let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--restore=").map(String::from)) {
+        let force = env::args().any(|arg| arg == "--force");
+        let restored = state.database.import(&path, force).await?;
+        info!("Restored {} chunks from {}", restored, path);
+        exit(0);
+    }
+
+    world::spawn::preload_spawn_chunks(&state.database, config.spawn_preload_radius).await?;
+
     info!("Server started on {}", addr);
 
     // Start all systems (separate task)
-    let all_systems = tokio::task::spawn(start_all_systems(state));
+    let all_systems = tokio::task::spawn(start_all_systems(state.clone()));
 
-    // Wait for all systems to finish
-    all_systems.await??;
+    // Wait for all systems to finish, or for the server to be stopped
+    tokio::select! {
+        result = all_systems => result??,
+        _ = tokio::signal::ctrl_c() => info!("Shutting down..."),
+    }
+
+    if let Err(e) = net::player_data::disconnect_all_players(&state).await {
+        error!("Failed to save players before shutting down: {}", e);
+    }
 
     // Kill all systems since we're done.
-    kill_all_systems().await?;
+    kill_all_systems(state).await?;
 
     Ok(())
 }
 async fn create_state(tcp_listener: TcpListener) -> Result<GlobalState> {
+    let database = database::start_database().await?;
+    let gamerules = GameRules::load(&database).await?;
     Ok(Arc::new(ServerState {
         world: Arc::new(World::new()),
-        connections: ConnectionList {
-            connections: DashMap::new(),
-            connection_count: AtomicU32::new(0),
-        },
-        database: database::start_database().await?,
+        connections: ConnectionList::new(),
+        database,
         server_stream: tcp_listener,
+        heartbeat: Heartbeat::default(),
+        clock: Arc::new(SystemClock),
+        ticks: GameTicks::new(get_global_config().performance.tick_rate),
+        difficulty: CurrentDifficulty::new(get_global_config().difficulty.parse()?),
+        gamerules,
+        time: WorldTime::default(),
+        weather: WorldWeather::default(),
+        block_changes: PendingBlockChanges::default(),
+        entity_ids: NetworkEntityIds::new(),
+        scoreboard: Scoreboard::new(),
+        boss_bars: BossBars::new(),
+        systems: SystemRegistry::default(),
+        middleware: MiddlewareChain::default(),
     }))
 }
@@ -0,0 +1,52 @@
+//! Benchmarks for encoding the chunks sent to a joining player. Run them with:
+//! `cargo test --release chunk_encoding_benchmarks -- --ignored --nocapture`
+
+use criterion::{black_box, Criterion};
+use futures::StreamExt;
+use tokio::runtime::Runtime;
+
+use crate::net::utils::chunk_encoder::tests::net_chunk;
+use crate::net::utils::chunk_encoder::{encode, encode_chunks};
+use crate::world::chunk_format::Chunk;
+
+/// The chunks within a view distance of 8.
+fn join_batch() -> Vec<Chunk> {
+    (-8..=8)
+        .flat_map(|x| (-8..=8).map(move |z| net_chunk(x, z)))
+        .collect()
+}
+
+fn bench_chunk_batch(c: &mut Criterion, runtime: &Runtime) {
+    let chunks = join_batch();
+
+    c.bench_function("encode 17x17 chunks serially", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                for chunk in black_box(chunks.clone()) {
+                    encode(chunk).await.unwrap();
+                }
+            })
+        })
+    });
+    c.bench_function("encode 17x17 chunks in parallel", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let mut encoded = encode_chunks(black_box(chunks.clone()));
+                while let Some(chunk) = encoded.next().await {
+                    chunk.unwrap();
+                }
+            })
+        })
+    });
+}
+
+#[test]
+#[ignore]
+fn chunk_encoding_benchmarks() {
+    let runtime = Runtime::new().unwrap();
+    let mut criterion = Criterion::default().sample_size(10);
+
+    bench_chunk_batch(&mut criterion, &runtime);
+
+    criterion.final_summary();
+}
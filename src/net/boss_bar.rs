@@ -0,0 +1,294 @@
+//! Boss bars shown at the top of the screen, for events and info displays.
+//!
+//! A bar is either shown to everyone, including players joining later (see [send_boss_bars]), or
+//! only to the players added as its viewers. Changes are sent to whoever sees the bar.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use uuid::Uuid;
+
+use crate::net::packets::outgoing::boss_bar::BossBarOut;
+use crate::net::utils::broadcast::{broadcast, broadcast_to};
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BossBarColor {
+    #[default]
+    Pink = 0,
+    Blue = 1,
+    Red = 2,
+    Green = 3,
+    Yellow = 4,
+    Purple = 5,
+    White = 6,
+}
+
+/// How many notches the bar is split into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BossBarDivision {
+    #[default]
+    None = 0,
+    Six = 1,
+    Ten = 2,
+    Twelve = 3,
+    Twenty = 4,
+}
+
+#[derive(Debug, Clone)]
+pub struct BossBar {
+    pub uuid: u128,
+    pub title: String,
+    /// From 0 to 1.
+    pub health: f32,
+    pub color: BossBarColor,
+    pub division: BossBarDivision,
+}
+
+impl BossBar {
+    /// A full pink bar with a new random uuid.
+    pub fn new(title: &str) -> Self {
+        Self {
+            uuid: Uuid::new_v4().as_u128(),
+            title: title.to_string(),
+            health: 1.0,
+            color: BossBarColor::default(),
+            division: BossBarDivision::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Viewers {
+    Everyone,
+    Players(HashSet<u32>),
+}
+
+#[derive(Debug)]
+struct Entry {
+    bar: BossBar,
+    viewers: Viewers,
+}
+
+/// Every boss bar, by uuid.
+#[derive(Debug, Default)]
+pub struct BossBars {
+    bars: Mutex<HashMap<u128, Entry>>,
+}
+
+impl BossBars {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, uuid: u128) -> Option<BossBar> {
+        self.bars.lock().unwrap().get(&uuid).map(|entry| entry.bar.clone())
+    }
+
+    /// Whether `entity_id` sees the bar.
+    pub fn is_viewer(&self, uuid: u128, entity_id: u32) -> bool {
+        self.bars.lock().unwrap().get(&uuid).is_some_and(|entry| match &entry.viewers {
+            Viewers::Everyone => true,
+            Viewers::Players(players) => players.contains(&entity_id),
+        })
+    }
+
+    /// Updates a bar and returns who to tell about it.
+    fn update(&self, uuid: u128, update: impl FnOnce(&mut BossBar)) -> Result<Viewers> {
+        let mut bars = self.bars.lock().unwrap();
+        let entry = bars.get_mut(&uuid).ok_or_else(|| no_such_bar(uuid))?;
+        update(&mut entry.bar);
+        Ok(entry.viewers.clone())
+    }
+}
+
+fn no_such_bar(uuid: u128) -> Error {
+    Error::Generic(format!("No boss bar {}", Uuid::from_u128(uuid)))
+}
+
+async fn send_to(packet: &BossBarOut, viewers: Viewers, state: &GlobalState) -> Result<()> {
+    match viewers {
+        Viewers::Everyone => broadcast(packet, state, None).await,
+        Viewers::Players(players) => broadcast_to(packet, state, players).await,
+    }
+}
+
+/// Adds a bar and returns its uuid. A bar for `everyone` is shown right away, otherwise nobody
+/// sees it until they're added with [add_viewer].
+pub async fn create(bar: BossBar, everyone: bool, state: &GlobalState) -> Result<u128> {
+    let uuid = bar.uuid;
+    let packet = BossBarOut::add(&bar);
+    let viewers = match everyone {
+        true => Viewers::Everyone,
+        false => Viewers::Players(HashSet::new()),
+    };
+    state
+        .boss_bars
+        .bars
+        .lock()
+        .unwrap()
+        .insert(uuid, Entry { bar, viewers });
+
+    if everyone {
+        broadcast(&packet, state, None).await?;
+    }
+    Ok(uuid)
+}
+
+/// Removes a bar from everyone's screen. Returns `false` if there was no such bar.
+pub async fn remove(uuid: u128, state: &GlobalState) -> Result<bool> {
+    let Some(entry) = state.boss_bars.bars.lock().unwrap().remove(&uuid) else {
+        return Ok(false);
+    };
+    send_to(&BossBarOut::remove(uuid), entry.viewers, state).await?;
+    Ok(true)
+}
+
+/// Shows a bar to a player. Returns `false` if they already see it.
+pub async fn add_viewer(uuid: u128, entity_id: u32, state: &GlobalState) -> Result<bool> {
+    let bar = {
+        let mut bars = state.boss_bars.bars.lock().unwrap();
+        let entry = bars.get_mut(&uuid).ok_or_else(|| no_such_bar(uuid))?;
+        match &mut entry.viewers {
+            Viewers::Players(players) => {
+                if !players.insert(entity_id) {
+                    return Ok(false);
+                }
+                entry.bar.clone()
+            }
+            Viewers::Everyone => return Ok(false),
+        }
+    };
+    broadcast_to(&BossBarOut::add(&bar), state, [entity_id]).await?;
+    Ok(true)
+}
+
+/// Hides a bar from a player. Returns `false` if they didn't see it.
+///
+/// Bars shown to everyone can't be hidden from single players.
+pub async fn remove_viewer(uuid: u128, entity_id: u32, state: &GlobalState) -> Result<bool> {
+    {
+        let mut bars = state.boss_bars.bars.lock().unwrap();
+        let entry = bars.get_mut(&uuid).ok_or_else(|| no_such_bar(uuid))?;
+        match &mut entry.viewers {
+            Viewers::Everyone => {
+                return Err(Error::Generic(format!(
+                    "Boss bar {} is shown to everyone",
+                    Uuid::from_u128(uuid)
+                )))
+            }
+            Viewers::Players(players) => {
+                if !players.remove(&entity_id) {
+                    return Ok(false);
+                }
+            }
+        }
+    }
+    broadcast_to(&BossBarOut::remove(uuid), state, [entity_id]).await?;
+    Ok(true)
+}
+
+/// Sets how full a bar is, clamped to 0 to 1.
+pub async fn set_health(uuid: u128, health: f32, state: &GlobalState) -> Result<()> {
+    let health = health.clamp(0.0, 1.0);
+    let viewers = state.boss_bars.update(uuid, |bar| bar.health = health)?;
+    send_to(&BossBarOut::update_health(uuid, health), viewers, state).await
+}
+
+pub async fn set_title(uuid: u128, title: &str, state: &GlobalState) -> Result<()> {
+    let viewers = state.boss_bars.update(uuid, |bar| bar.title = title.to_string())?;
+    send_to(&BossBarOut::update_title(uuid, title), viewers, state).await
+}
+
+pub async fn set_style(
+    uuid: u128,
+    color: BossBarColor,
+    division: BossBarDivision,
+    state: &GlobalState,
+) -> Result<()> {
+    let viewers = state.boss_bars.update(uuid, |bar| {
+        bar.color = color;
+        bar.division = division;
+    })?;
+    send_to(&BossBarOut::update_style(uuid, color, division), viewers, state).await
+}
+
+/// Sends a joining player every bar shown to everyone.
+pub async fn send_boss_bars(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let bars: Vec<BossBar> = {
+        let bars = state.boss_bars.bars.lock().unwrap();
+        bars.values()
+            .filter(|entry| matches!(entry.viewers, Viewers::Everyone))
+            .map(|entry| entry.bar.clone())
+            .collect()
+    };
+    if bars.is_empty() {
+        return Ok(());
+    }
+
+    let mut packet_queue = PacketQueue::new();
+    for bar in &bars {
+        packet_queue.queue(BossBarOut::add(bar)).await?;
+    }
+    let conn = state.connections.get_connection(entity_id)?;
+    let conn = conn.read().await;
+    conn.send_packets(packet_queue).await
+}
+
+/// Stops tracking a player that left as a viewer of any bar.
+pub fn forget_viewer(entity_id: u32, state: &GlobalState) {
+    for entry in state.boss_bars.bars.lock().unwrap().values_mut() {
+        if let Viewers::Players(players) = &mut entry.viewers {
+            players.remove(&entity_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_viewers_get_updates() {
+        let state = test_state().await;
+        let (viewer, mut viewer_client) = add_test_player(&state, "Viewer").await;
+        let (other, mut other_client) = add_test_player(&state, "Other").await;
+
+        let uuid = create(BossBar::new("Raid"), false, &state).await.unwrap();
+        assert!(add_viewer(uuid, viewer, &state).await.unwrap());
+        assert!(!add_viewer(uuid, viewer, &state).await.unwrap());
+        set_health(uuid, 0.5, &state).await.unwrap();
+        assert_eq!(read_packet(&mut viewer_client).await, expected_add(uuid));
+        let (id, body) = read_packet(&mut viewer_client).await;
+        assert_eq!((id, body[16]), (0x0B, 2));
+        assert_eq!(state.boss_bars.get(uuid).unwrap().health, 0.5);
+        assert!(!state.boss_bars.is_viewer(uuid, other));
+
+        // Bars for everyone reach players joining later too
+        let event = create(BossBar::new("Event"), true, &state).await.unwrap();
+        assert_eq!(read_packet(&mut other_client).await.0, 0x0B);
+        let (late, mut late_client) = add_test_player(&state, "Late").await;
+        send_boss_bars(late, &state).await.unwrap();
+        assert_eq!(read_packet(&mut late_client).await.0, 0x0B);
+        assert!(state.boss_bars.is_viewer(event, late));
+
+        forget_viewer(viewer, &state);
+        assert!(!state.boss_bars.is_viewer(uuid, viewer));
+        assert!(remove(uuid, &state).await.unwrap());
+        assert!(state.boss_bars.get(uuid).is_none());
+    }
+
+    fn expected_add(uuid: u128) -> (i32, Vec<u8>) {
+        let mut body = uuid.to_be_bytes().to_vec();
+        body.push(0);
+        let title = r#"{"text":"Raid"}"#;
+        body.push(title.len() as u8);
+        body.extend_from_slice(title.as_bytes());
+        body.extend_from_slice(&1.0f32.to_be_bytes());
+        body.extend_from_slice(&[0, 0, 0]);
+        (0x0B, body)
+    }
+}
@@ -0,0 +1,104 @@
+//! The entity ids clients know entities by, separate from the ids of their ECS entities.
+//!
+//! An ECS id is reused as soon as its entity is deleted, while clients expect every spawned entity
+//! to keep the same id until it's removed on their end too. Network ids are only handed out again
+//! once they've been freed with [NetworkEntityIds::free], which happens when the entity despawns.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct NetworkEntityIds {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The id handed out next once there are no freed ones left.
+    next: i32,
+    free: Vec<i32>,
+    by_entity: HashMap<u32, i32>,
+    by_network_id: HashMap<i32, u32>,
+}
+
+impl NetworkEntityIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The network id of an ECS entity, giving it one if it doesn't have one yet.
+    pub fn allocate(&self, entity: u32) -> i32 {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(id) = inner.by_entity.get(&entity) {
+            return *id;
+        }
+
+        let id = match inner.free.pop() {
+            Some(id) => id,
+            None => {
+                // Vanilla starts counting at 1
+                inner.next += 1;
+                inner.next
+            }
+        };
+        inner.by_entity.insert(entity, id);
+        inner.by_network_id.insert(id, entity);
+        id
+    }
+
+    /// The network id of an ECS entity, if it has one.
+    pub fn network_id(&self, entity: u32) -> Option<i32> {
+        self.inner.lock().unwrap().by_entity.get(&entity).copied()
+    }
+
+    /// The ECS entity a network id belongs to, e.g. the target of an interaction.
+    pub fn entity(&self, network_id: i32) -> Option<u32> {
+        self.inner
+            .lock()
+            .unwrap()
+            .by_network_id
+            .get(&network_id)
+            .copied()
+    }
+
+    /// Releases an entity's network id so it can be handed out again, returning it.
+    pub fn free(&self, entity: u32) -> Option<i32> {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.by_entity.remove(&entity)?;
+        inner.by_network_id.remove(&id);
+        inner.free.push(id);
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_allocate_and_free() {
+        let ids = NetworkEntityIds::new();
+        let allocated: Vec<i32> = (0..100).map(|entity| ids.allocate(entity)).collect();
+        assert_eq!(allocated.iter().collect::<HashSet<_>>().len(), 100);
+        // Allocating again returns the same id
+        assert_eq!(ids.allocate(5), allocated[5]);
+        assert_eq!(ids.entity(allocated[5]), Some(5));
+
+        assert_eq!(ids.free(5), Some(allocated[5]));
+        assert_eq!(ids.free(5), None);
+        assert_eq!(ids.network_id(5), None);
+        assert_eq!(ids.entity(allocated[5]), None);
+
+        // The freed id is reused, without colliding with any id still in use
+        let reused = ids.allocate(1000);
+        assert_eq!(reused, allocated[5]);
+        let in_use: HashSet<i32> = (0..100)
+            .filter(|entity| *entity != 5)
+            .map(|entity| ids.network_id(entity).unwrap())
+            .collect();
+        assert!(!in_use.contains(&reused));
+        assert_eq!(ids.allocate(1001), 101);
+    }
+}
@@ -0,0 +1,475 @@
+//! Makes players visible to each other. Everyone is added to the player list when they join, and
+//! players are spawned for each other while they're within the entity tracking distance.
+
+use std::collections::HashSet;
+
+use crate::net::boss_bar;
+use crate::net::packets::incoming::client_info::ClientInfo;
+use crate::net::packets::outgoing::player_info_remove::PlayerInfoRemove;
+use crate::net::packets::outgoing::player_info_update::PlayerInfoUpdate;
+use crate::net::packets::outgoing::remove_entities::RemoveEntities;
+use crate::net::packets::outgoing::set_entity_metadata::SetEntityMetadata;
+use crate::net::packets::outgoing::spawn_player::SpawnPlayer;
+use crate::net::utils::broadcast::{broadcast, broadcast_to};
+use crate::state::GlobalState;
+use crate::utils::components::last_sent_movement::LastSentMovement;
+use crate::utils::components::metadata::Metadata;
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::components::tracked_entities::TrackedEntities;
+use crate::utils::config::get_global_config;
+use crate::utils::encoding::entity_metadata::EntityMetadata;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Adds a player that just entered play to everyone's player list, and starts tracking the
+/// players around them.
+pub async fn spawn_player(entity_id: u32, state: &GlobalState) -> Result<()> {
+    // Copy everything out first, so no component is borrowed while sending. Players still logging
+    // in have no tracked entities yet, they get everyone in their own list once they spawn.
+    let mut players = Vec::new();
+    let mut query =
+        state.world.query::<(&Player, &Position, &Rotation, Option<&TrackedEntities>)>();
+    while let Some((id, (player, position, rotation, tracked))) = query.next().await {
+        let id = id as u32;
+        if tracked.is_some() || id == entity_id {
+            players.push((id, player.clone(), position.clone(), rotation.clone()));
+        }
+    }
+
+    let Some((_, player, position, rotation)) =
+        players.iter().find(|(id, ..)| *id == entity_id).cloned()
+    else {
+        return Err(crate::ecs::error::Error::EntityNotFound(entity_id as usize).into());
+    };
+
+    {
+        let conn = state.connections.get_connection(entity_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(PlayerInfoUpdate::add_players(
+            players.iter().map(|(_, player, ..)| player),
+        ))
+        .await?;
+    }
+    let others = players.iter().map(|(id, ..)| *id).filter(|id| *id != entity_id);
+    broadcast_to(&PlayerInfoUpdate::add_players([&player]), state, others.collect::<Vec<_>>())
+        .await?;
+
+    // A respawning player is still spawned where it died for the players tracking it
+    untrack(entity_id, state).await?;
+
+    // Anyone spawning the player from here on sees it here, movement is sent as deltas from it
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, LastSentMovement::new(position, rotation))
+        .insert(entity_id, TrackedEntities::default());
+
+    update_tracking(state).await
+}
+
+/// A player that has entered play, copied out of the world.
+struct Tracker {
+    entity_id: u32,
+    uuid: u128,
+    chunk: (i32, i32),
+    distance: i32,
+    tracked: HashSet<u32>,
+    last_sent: LastSentMovement,
+    metadata: EntityMetadata,
+}
+
+impl Tracker {
+    fn can_see(&self, other: &Tracker) -> bool {
+        (self.chunk.0 - other.chunk.0).abs() <= self.distance
+            && (self.chunk.1 - other.chunk.1).abs() <= self.distance
+    }
+}
+
+/// Spawns players for everyone they came within tracking distance of, and removes them for
+/// everyone they went out of it for.
+///
+/// The distance is `entity_tracking_distance` in chunks, but never more than the view distance.
+pub async fn update_tracking(state: &GlobalState) -> Result<()> {
+    update_tracking_budgeted(state, &mut 0, 0).await
+}
+
+/// Like [update_tracking], but only for up to `budget` players, or everyone if it's 0.
+///
+/// Players take turns in entity id order, starting at the first one with an id of at least
+/// `cursor`. The cursor is moved past the last player updated, so calling this every tick with
+/// the same cursor gets to everyone.
+pub async fn update_tracking_budgeted(
+    state: &GlobalState,
+    cursor: &mut u32,
+    budget: usize,
+) -> Result<()> {
+    let config = get_global_config();
+    let server_view_distance = config.view_distance as i32;
+    let tracking_distance = config.entity_tracking_distance as i32;
+
+    let mut trackers = Vec::new();
+    let mut query = state.world.query::<(
+        &Player,
+        &Position,
+        Option<&ClientInfo>,
+        &TrackedEntities,
+        &LastSentMovement,
+        Option<&Metadata>,
+    )>();
+    while let Some((entity_id, (player, position, client_info, tracked, last_sent, metadata))) =
+        query.next().await
+    {
+        let view_distance = client_info.map_or(server_view_distance, |info| {
+            (info.view_distance as i32).min(server_view_distance)
+        });
+        trackers.push(Tracker {
+            entity_id: entity_id as u32,
+            uuid: player.uuid,
+            chunk: (position.x >> 4, position.z >> 4),
+            distance: tracking_distance.min(view_distance),
+            tracked: tracked.0.clone(),
+            last_sent: last_sent.clone(),
+            metadata: metadata.map(|metadata| metadata.all()).unwrap_or_default(),
+        });
+    }
+
+    let mut turn: Vec<&Tracker> = trackers.iter().collect();
+    turn.sort_by_key(|tracker| tracker.entity_id);
+    if budget > 0 && budget < turn.len() {
+        let start = turn
+            .iter()
+            .position(|tracker| tracker.entity_id >= *cursor)
+            .unwrap_or(0);
+        turn.rotate_left(start);
+        turn.truncate(budget);
+        *cursor = turn[budget - 1].entity_id + 1;
+    }
+
+    for tracker in turn {
+        let visible: HashSet<u32> = trackers
+            .iter()
+            .filter(|other| other.entity_id != tracker.entity_id && tracker.can_see(other))
+            .map(|other| other.entity_id)
+            .collect();
+        if visible == tracker.tracked {
+            continue;
+        }
+
+        let conn = state.connections.get_connection(tracker.entity_id)?;
+        let conn = conn.read().await;
+        for other in trackers.iter().filter(|other| {
+            visible.contains(&other.entity_id) && !tracker.tracked.contains(&other.entity_id)
+        }) {
+            let network_id = state.entity_ids.allocate(other.entity_id);
+            let LastSentMovement { position, rotation } = &other.last_sent;
+            conn.send_packet(SpawnPlayer::new(network_id, other.uuid, position, rotation))
+                .await?;
+            if !other.metadata.is_empty() {
+                conn.send_packet(SetEntityMetadata::new(network_id, other.metadata.clone()))
+                    .await?;
+            }
+        }
+
+        let removed: Vec<i32> = tracker
+            .tracked
+            .difference(&visible)
+            .filter_map(|id| state.entity_ids.network_id(*id))
+            .collect();
+        if !removed.is_empty() {
+            conn.send_packet(RemoveEntities::new(&removed)).await?;
+        }
+        drop(conn);
+
+        state
+            .world
+            .get_component_storage()
+            .insert(tracker.entity_id, TrackedEntities(visible));
+    }
+
+    Ok(())
+}
+
+/// Changes an entity's metadata, and sends what changed to the entity itself if it's a player
+/// and to every player tracking it.
+pub async fn update_metadata(
+    entity_id: u32,
+    state: &GlobalState,
+    update: impl FnOnce(&mut Metadata),
+) -> Result<()> {
+    let changes = {
+        if state.world.get_component::<Metadata>(entity_id).await.is_err() {
+            state
+                .world
+                .get_component_storage()
+                .insert(entity_id, Metadata::default());
+        }
+        let mut metadata = state.world.get_component_mut::<Metadata>(entity_id).await?;
+        update(&mut metadata);
+        metadata.take_changes()
+    };
+
+    // Without a network id it was never sent to anyone, it gets everything once it's spawned
+    let Some(network_id) = state.entity_ids.network_id(entity_id) else {
+        return Ok(());
+    };
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let mut recipients = vec![entity_id];
+    let mut query = state.world.query::<&TrackedEntities>();
+    while let Some((tracker, tracked)) = query.next().await {
+        if tracked.contains(entity_id) {
+            recipients.push(tracker as u32);
+        }
+    }
+    broadcast_to(&SetEntityMetadata::new(network_id, changes), state, recipients).await
+}
+
+/// Removes an entity from the world along with everything that refers to it.
+///
+/// It's despawned for everyone tracking it, and a player is also taken off everyone's player
+/// list, out of the name and UUID lookups and out of boss bar viewers. Its network id is freed
+/// last, so it can't be handed out while clients may still know it. The references are removed
+/// even if telling the other players fails.
+pub async fn despawn_entity(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let uuid = state
+        .world
+        .get_component::<Player>(entity_id)
+        .await
+        .map(|player| player.uuid)
+        .ok();
+
+    let mut notified = untrack(entity_id, state).await;
+    if let (Some(uuid), true) = (uuid, notified.is_ok()) {
+        notified = broadcast(&PlayerInfoRemove::new(vec![uuid]), state, Some(entity_id)).await;
+    }
+
+    state.connections.unregister_player(entity_id);
+    boss_bar::forget_viewer(entity_id, state);
+    state.world.delete_entity(entity_id).await?;
+    state.entity_ids.free(entity_id);
+
+    notified
+}
+
+/// Removes an entity for every player tracking it.
+async fn untrack(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let mut trackers = Vec::new();
+    let mut query = state.world.query::<&mut TrackedEntities>();
+    while let Some((id, mut tracked)) = query.next().await {
+        if tracked.0.remove(&entity_id) {
+            trackers.push(id as u32);
+        }
+    }
+
+    match state.entity_ids.network_id(entity_id) {
+        Some(network_id) if !trackers.is_empty() => {
+            broadcast_to(&RemoveEntities::new(&[network_id]), state, trackers).await
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ferrumc_codec::network_types::varint::VarInt;
+    use tokio::net::TcpStream;
+
+    use super::*;
+    use crate::net::drop_conn;
+    use crate::utils::components::metadata::SNEAKING;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_join_spawns_for_existing_players() {
+        let state = test_state().await;
+
+        let (first, mut first_client) = add_test_player(&state, "first").await;
+        spawn_player(first, &state).await.unwrap();
+        // Only itself in the player list, nobody to spawn yet
+        assert_eq!(read_packet(&mut first_client).await.0, 0x3A);
+
+        let (second, mut second_client) = add_test_player(&state, "second").await;
+        spawn_player(second, &state).await.unwrap();
+
+        assert_eq!(read_packet(&mut first_client).await.0, 0x3A);
+        let (packet_id, body) = read_packet(&mut first_client).await;
+        assert_eq!(packet_id, 0x03);
+        let spawned = VarInt::read(&mut Cursor::new(body)).await.unwrap();
+        assert_eq!(Some(spawned.get_val()), state.entity_ids.network_id(second));
+
+        // The new player gets the existing one spawned in too
+        assert_eq!(read_packet(&mut second_client).await.0, 0x3A);
+        let (packet_id, body) = read_packet(&mut second_client).await;
+        assert_eq!(packet_id, 0x03);
+        let spawned = VarInt::read(&mut Cursor::new(body)).await.unwrap();
+        assert_eq!(Some(spawned.get_val()), state.entity_ids.network_id(first));
+    }
+
+    #[tokio::test]
+    async fn test_leave_despawns_for_others() {
+        let state = test_state().await;
+        let (first, mut first_client) = add_test_player(&state, "first").await;
+        let (second, mut second_client) = add_test_player(&state, "second").await;
+        spawn_player(first, &state).await.unwrap();
+        spawn_player(second, &state).await.unwrap();
+        drain_join(&mut first_client, &mut second_client).await;
+        let network_id = state.entity_ids.network_id(second).unwrap();
+
+        drop_conn(second, state.clone()).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut first_client).await;
+        assert_eq!(packet_id, 0x3E);
+        let mut body = Cursor::new(body);
+        assert_eq!(VarInt::read(&mut body).await.unwrap().get_val(), 1);
+        assert_eq!(VarInt::read(&mut body).await.unwrap().get_val(), network_id);
+        assert_eq!(read_packet(&mut first_client).await.0, 0x39);
+
+        assert!(state.connections.get_connection(first).is_ok());
+        // The id is free for the next entity once the client was told to remove it
+        assert_eq!(state.entity_ids.network_id(second), None);
+        let (third, _third_client) = add_test_player(&state, "third").await;
+        assert_eq!(state.entity_ids.network_id(third), Some(network_id));
+    }
+
+    #[tokio::test]
+    async fn test_moving_out_of_range_removes_entity() {
+        let state = test_state().await;
+        let (first, mut first_client) = add_test_player(&state, "first").await;
+        let (second, mut second_client) = add_test_player(&state, "second").await;
+        spawn_player(first, &state).await.unwrap();
+        spawn_player(second, &state).await.unwrap();
+        drain_join(&mut first_client, &mut second_client).await;
+        let network_id = state.entity_ids.network_id(second).unwrap();
+
+        state
+            .world
+            .get_component_storage()
+            .insert(second, Position::new(1000, 64, 1000));
+        update_tracking(&state).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut first_client).await;
+        assert_eq!(packet_id, 0x3E);
+        let mut body = Cursor::new(body);
+        assert_eq!(VarInt::read(&mut body).await.unwrap().get_val(), 1);
+        assert_eq!(VarInt::read(&mut body).await.unwrap().get_val(), network_id);
+        let tracked = state.world.get_component::<TrackedEntities>(first).await.unwrap();
+        assert!(!tracked.contains(second));
+    }
+
+    #[tokio::test]
+    async fn test_budget_spreads_updates_across_ticks() {
+        let state = test_state().await;
+        let mut players = Vec::new();
+        for i in 0..5 {
+            let (player, client) = add_test_player(&state, &format!("player{}", i)).await;
+            state
+                .world
+                .get_component_storage()
+                .insert(
+                    player,
+                    LastSentMovement::new(Position::new(0, 64, 0), Rotation::new(0.0, 0.0)),
+                )
+                .insert(player, TrackedEntities::default());
+            players.push((player, client));
+        }
+        let tracking = |player: u32| {
+            let state = state.clone();
+            async move {
+                !state
+                    .world
+                    .get_component::<TrackedEntities>(player)
+                    .await
+                    .unwrap()
+                    .0
+                    .is_empty()
+            }
+        };
+
+        let mut cursor = 0;
+        for expected in [2, 4, 5, 5] {
+            update_tracking_budgeted(&state, &mut cursor, 2).await.unwrap();
+            let mut updated = 0;
+            for (player, _) in &players {
+                if tracking(*player).await {
+                    updated += 1;
+                }
+            }
+            assert_eq!(updated, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_despawn_removes_every_reference() {
+        let state = test_state().await;
+        let (first, mut first_client) = add_test_player(&state, "first").await;
+        let (second, mut second_client) = add_test_player(&state, "second").await;
+        spawn_player(first, &state).await.unwrap();
+        spawn_player(second, &state).await.unwrap();
+        drain_join(&mut first_client, &mut second_client).await;
+        let network_id = state.entity_ids.network_id(second).unwrap();
+
+        despawn_entity(second, &state).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut first_client).await;
+        assert_eq!(packet_id, 0x3E);
+        let mut body = Cursor::new(body);
+        assert_eq!(VarInt::read(&mut body).await.unwrap().get_val(), 1);
+        assert_eq!(VarInt::read(&mut body).await.unwrap().get_val(), network_id);
+        assert_eq!(read_packet(&mut first_client).await.0, 0x39);
+
+        let tracked = state.world.get_component::<TrackedEntities>(first).await.unwrap();
+        assert!(!tracked.contains(second));
+        drop(tracked);
+        assert!(state.world.get_component::<Player>(second).await.is_err());
+        assert!(state.connections.by_name("second").is_none());
+        assert_eq!(state.entity_ids.network_id(second), None);
+        assert_eq!(state.entity_ids.entity(network_id), None);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_changes_reach_trackers() {
+        let state = test_state().await;
+        let (first, mut first_client) = add_test_player(&state, "first").await;
+        let (second, mut second_client) = add_test_player(&state, "second").await;
+        spawn_player(first, &state).await.unwrap();
+        spawn_player(second, &state).await.unwrap();
+        drain_join(&mut first_client, &mut second_client).await;
+        let network_id = state.entity_ids.network_id(second).unwrap();
+
+        update_metadata(second, &state, |metadata| metadata.set_flag(SNEAKING, true))
+            .await
+            .unwrap();
+
+        for client in [&mut first_client, &mut second_client] {
+            let (packet_id, body) = read_packet(client).await;
+            assert_eq!(packet_id, 0x52);
+            let mut body = Cursor::new(body);
+            assert_eq!(VarInt::read(&mut body).await.unwrap().get_val(), network_id);
+        }
+
+        // Players it's spawned for later get it along with the spawn
+        let (third, mut third_client) = add_test_player(&state, "third").await;
+        spawn_player(third, &state).await.unwrap();
+        assert_eq!(read_packet(&mut third_client).await.0, 0x3A);
+        let mut spawned = Vec::new();
+        for _ in 0..3 {
+            spawned.push(read_packet(&mut third_client).await.0);
+        }
+        spawned.sort();
+        assert_eq!(spawned, vec![0x03, 0x03, 0x52]);
+    }
+
+    /// Reads the player list and spawn packets from two players joining one after the other.
+    async fn drain_join(first: &mut TcpStream, second: &mut TcpStream) {
+        assert_eq!(read_packet(first).await.0, 0x3A);
+        assert_eq!(read_packet(first).await.0, 0x3A);
+        assert_eq!(read_packet(first).await.0, 0x03);
+        assert_eq!(read_packet(second).await.0, 0x3A);
+        assert_eq!(read_packet(second).await.0, 0x03);
+    }
+}
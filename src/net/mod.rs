@@ -1,24 +1,32 @@
 use std::cmp::PartialEq;
 use std::fmt::{Debug, Display};
-use std::io::Cursor;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr};
 use std::sync::atomic::AtomicU32;
 use std::sync::{atomic, Arc};
 use std::time::Duration;
 
 use dashmap::DashMap;
+pub use ferrumc_codec::dec::read_packet_header;
 use ferrumc_codec::enc::NetEncode;
-use ferrumc_codec::network_types::varint::VarInt;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
-use tracing::{debug, error, trace};
+use tokio::io::{AsyncReadExt, BufReader};
+use tokio::sync::{Mutex, MutexGuard, RwLock};
+use tracing::{debug, error, trace, warn, Instrument, Span};
 
 use ferrumc_macros::Component;
 
-use crate::net::packets::handle_packet;
+use crate::ecs::query::QueryItem;
+use crate::ecs::world::World;
+use crate::net::packets::{decode_packet, PacketHandler};
+use crate::net::utils::buffer_pool::ENCODE_POOL;
+use crate::net::utils::packet_dump::{packet_dump, Direction};
+use crate::net::utils::send_queue::{SendQueue, CLOSE_TIMEOUT, SEND_QUEUE_TIMEOUT};
 use crate::state::GlobalState;
 
-use super::utils::config::get_global_config;
+use super::utils::config::{get_global_config, ServerConfig};
+use super::utils::lock_order::{self, LockRank};
 use super::utils::prelude::*;
+use super::utils::protocol::{self, ProtocolData};
 pub mod utils;
 // To allow implementing the `Component` trait for `Connection`. Since we can't implement a trait for a type defined in another crate.
 #[derive(Component)]
@@ -30,15 +38,47 @@ impl Debug for ConnectionWrapper {
     }
 }
 
+impl ConnectionWrapper {
+    /// A handle to the connection that can be kept after the component is released.
+    pub fn handle(&self) -> Arc<RwLock<Connection>> {
+        self.0.clone()
+    }
+
+    /// The connection of every entity that also matches `Q`, e.g. `&Player`.
+    ///
+    /// A component (or query result) must never be held while awaiting a connection, see
+    /// [lock_order], so this copies the handles out and releases everything it looked at.
+    pub async fn handles<Q: QueryItem>(world: &World) -> Vec<(u32, Arc<RwLock<Connection>>)> {
+        let mut handles = Vec::new();
+        let mut query = world.query::<(&ConnectionWrapper, Q)>();
+        while let Some((entity_id, (conn, _))) = query.next().await {
+            handles.push((entity_id as u32, conn.handle()));
+        }
+        handles
+    }
+}
+
 /// Implementing `Send` for `ConnectionWrapper` to allow sending it between threads.
 /// This is safe because `ConnectionWrapper` is just a wrapper around `Arc<RwLock<Connection>>`, which is `Send`.
 unsafe impl Send for ConnectionWrapper {}
 unsafe impl Sync for ConnectionWrapper {}
 
+#[cfg(test)]
+mod benches;
+pub mod boss_bar;
+pub mod entity_ids;
+pub mod entity_tracking;
 pub mod packets;
+pub mod particles;
+pub mod player_data;
+pub mod player_health;
+pub mod player_inventory;
+pub mod scoreboard;
+pub mod sound;
 pub mod systems;
 mod test_ecs;
 pub mod the_dimension_codec;
+pub mod title;
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum State {
@@ -69,15 +109,54 @@ impl State {
 
 /// A list of connections, with a counter for the number of connections.
 ///
+/// Connections of players that finished logging in can also be looked up by name and UUID, see
+/// [ConnectionList::register_player].
+///
 /// In desperate need of reworking.
+#[derive(Default)]
 pub struct ConnectionList {
     // The connections, keyed with random values. The value also contains the connection id for ease of access.
     pub connections: DashMap<u32, Arc<RwLock<Connection>>>,
     // The number of connections.
     pub connection_count: AtomicU32,
+    // Connection ids by lowercased player name.
+    names: DashMap<String, u32>,
+    // Connection ids by player UUID.
+    uuids: DashMap<u128, u32>,
 }
 
 impl ConnectionList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn by_id(&self, conn_id: impl TryInto<u32>) -> Result<Arc<RwLock<Connection>>> {
+        self.get_connection(conn_id)
+    }
+
+    /// The connection of the player with this name, ignoring case like Minecraft does.
+    pub fn by_name(&self, name: &str) -> Option<Arc<RwLock<Connection>>> {
+        let conn_id = *self.names.get(&name.to_lowercase())?;
+        self.get_connection(conn_id).ok()
+    }
+
+    pub fn by_uuid(&self, uuid: u128) -> Option<Arc<RwLock<Connection>>> {
+        let conn_id = *self.uuids.get(&uuid)?;
+        self.get_connection(conn_id).ok()
+    }
+
+    /// Makes the connection findable by its player's name and UUID, once they've logged in.
+    pub fn register_player(&self, conn_id: u32, name: &str, uuid: u128) {
+        self.names.insert(name.to_lowercase(), conn_id);
+        self.uuids.insert(uuid, conn_id);
+    }
+
+    /// Removes the name and UUID lookups of a connection. Called when it is dropped.
+    pub fn unregister_player(&self, conn_id: u32) {
+        self.names.retain(|_, id| *id != conn_id);
+        self.uuids.retain(|_, id| *id != conn_id);
+    }
+
     pub fn get_connection(&self, conn_id: impl TryInto<u32>) -> Result<Arc<RwLock<Connection>>> {
         let conn_id = conn_id.try_into().map_err(|_| Error::ConversionError)?;
         let conn = self
@@ -94,6 +173,7 @@ impl ConnectionList {
 /// - `id`: The numerical ID for the connection. Is also the key for it's [ConnectionList] entry.
 /// - `socket`: The TCP socket for the connection ([tokio::net::TcpStream]).
 /// - `player_uuid`: The UUID of the player, if the connection is authenticated ([uuid::Uuid]).
+/// - `ip`: The client's IP address. IPv4 clients on a dual-stack listener show up as plain IPv4.
 /// - `state`: The current state of the connection ([State]).
 /// - `metadata`: Metadata for the connection ([ConnectionMetadata]).
 /// - `drop`: Whether to drop and clean up the connection after this network tick.
@@ -102,48 +182,125 @@ pub struct Connection {
     // pub socket: tokio::net::TcpStream,
     pub stream: NetStream,
     pub player_uuid: Option<uuid::Uuid>,
+    pub ip: IpAddr,
     pub state: State,
     pub metadata: ConnectionMetadata,
     pub drop: bool,
 }
 
 pub struct NetStream {
-    pub in_stream: Mutex<tokio::net::tcp::OwnedReadHalf>,
-    pub out_stream: Mutex<tokio::net::tcp::OwnedWriteHalf>,
+    pub in_stream: Mutex<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+    /// The capacity `in_stream` was created with, see [crate::utils::config::ServerConfig].
+    pub read_buffer_size: usize,
+    /// Owns the write half of the socket. See [SendQueue].
+    pub out_stream: SendQueue,
 }
 
 #[derive(Debug, Default)]
 pub struct ConnectionMetadata {
     pub protocol_version: i32,
     pub entity: usize,
+    /// The client's brand, e.g. `vanilla` or `fabric`, once it has sent it.
+    /// The client's settings are stored as the [crate::net::packets::incoming::client_info::ClientInfo] component.
+    pub brand: Option<String>,
+}
+
+impl ConnectionMetadata {
+    /// The registries and packet ids for the client's version, `None` if it isn't supported.
+    pub fn protocol_data(&self) -> Option<&'static ProtocolData> {
+        protocol::protocol_data(self.protocol_version)
+    }
 }
 
 pub fn setup_tracer() {
     console_subscriber::init();
 }
 
+/// Binds the server's listener to the configured host and port.
+pub async fn bind_listener(config: &ServerConfig) -> Result<tokio::net::TcpListener> {
+    let addr = config.bind_address()?;
+
+    tokio::net::TcpListener::bind(addr).await.map_err(|e| {
+        error!("Failed to bind to address: {}", addr);
+        error!("Perhaps the port {} is already in use?", addr.port());
+        Error::TcpError(format!("Failed to bind to {}: {}", addr, e))
+    })
+}
+
 /// Handles a connection. This is the main entry point for a connection.
 ///
 /// - `socket`: The TCP socket for the connection ([tokio::net::TcpStream]).
 ///
-/// Creates a new [Connection] and adds it to the [ConnectionList]. Passes the connection to [manage_conn].
+/// Creates a new [Connection] and adds it to the [ConnectionList]. Passes the connection to [manage_conn],
+/// under [supervise_connection].
 pub async fn init_connection(socket: tokio::net::TcpStream, state: GlobalState) -> Result<()> {
     let entity_id = state.world.create_entity().await.build() as u32;
 
-    let (in_stream, out_stream) = socket.into_split();
-
-    let conn = Connection {
-        id: entity_id,
-        stream: NetStream {
-            in_stream: Mutex::new(in_stream),
-            out_stream: Mutex::new(out_stream),
-        },
-        player_uuid: None,
-        state: State::Handshake,
-        metadata: ConnectionMetadata::default(),
-        drop: false,
-    };
+    let config = get_global_config();
+    let conn = Connection::new(
+        entity_id,
+        socket,
+        config.send_queue_depth as usize,
+        config.read_buffer_size as usize,
+    );
+    let conn = add_connection(conn, &state);
+    Span::current().record("conn_id", entity_id);
+
+    let current_amount = state
+        .connections
+        .connection_count
+        .load(atomic::Ordering::Relaxed);
+
+    debug!(
+        "Connection established with id: {}. Current connection count: {}",
+        entity_id, current_amount
+    );
 
+    supervise_connection(entity_id, manage_conn(conn, state.clone()), state).await
+}
+
+/// Runs a connection's task in a task of its own, and cleans the connection up however it ends.
+///
+/// Whether the task returns, fails or panics, the connection's entity and [ConnectionList] entry
+/// are removed, so nothing is left behind for other systems to trip over.
+pub async fn supervise_connection<F>(conn_id: u32, task: F, state: GlobalState) -> Result<()>
+where
+    F: Future<Output = Result<()>> + Send + 'static,
+{
+    match tokio::spawn(task.in_current_span()).await {
+        Ok(Ok(())) => debug!("Connection {} closed", conn_id),
+        Ok(Err(e)) => error!(
+            "Error occurred in {:?}: {:?}, dropping connection",
+            conn_id, e
+        ),
+        Err(e) if e.is_panic() => error!(
+            "Connection {} panicked: {}, dropping connection",
+            conn_id,
+            panic_message(e.into_panic())
+        ),
+        Err(e) => warn!("Connection {} was cancelled: {}", conn_id, e),
+    }
+
+    // The task may have dropped the connection itself already
+    match drop_conn(conn_id, state).await {
+        Ok(()) | Err(Error::ConnectionNotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Registers the connection with the world and the [ConnectionList].
+pub(crate) fn add_connection(conn: Connection, state: &GlobalState) -> Arc<RwLock<Connection>> {
+    let entity_id = conn.id;
     let conn = Arc::new(RwLock::new(conn));
 
     state
@@ -161,38 +318,18 @@ pub async fn init_connection(socket: tokio::net::TcpStream, state: GlobalState)
         .connection_count
         .fetch_add(1, atomic::Ordering::Relaxed);
 
-    let current_amount = state
-        .connections
-        .connection_count
-        .load(atomic::Ordering::Relaxed);
-
-    debug!(
-        "Connection established with id: {}. Current connection count: {}",
-        entity_id, current_amount
-    );
-
-    let res = manage_conn(conn.clone(), state.clone()).await;
-
-    if let Err(e) = res {
-        error!(
-            "Error occurred in {:?}: {:?}, dropping connection",
-            entity_id, e
-        );
-        drop_conn(entity_id, state).await?;
-    }
-
-    Ok(())
+    conn
 }
 
 /// Manages a connection. This is the main loop for a connection.
 ///
 /// - `conn`: The connection to manage ([Arc<RwLock<Connection>>]).
 ///
-/// Reads packets from the connection and passes them to [handle_packet]. The handle_packet function
+/// Reads packets from the connection and passes them to [decode_packet]. The decode_packet function
 /// is generated at compile time by [ferrumc_macros::bake_packet_registry].
 pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> Result<()> {
     {
-        let local_addr = conn.read().await.stream.in_stream.lock().await.peer_addr()?;
+        let local_addr = conn.read().await.stream.in_stream.lock().await.get_ref().peer_addr()?;
         debug!(
             "Starting receiver for the addr: {:?}",
             local_addr
@@ -200,32 +337,27 @@ pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> R
     }
 
     loop {
-        // Get the length of the packet
         let conn_read = conn.read().await;
+        let (conn_id, conn_state) = (conn_read.id, conn_read.state.clone());
 
-        trace!("Reading length buffer");
+        trace!("Reading packet header");
 
-        let (packet_length, buffer) = get_packet_length_and_buffer(&conn_read).await?;
-        let (conn_id, conn_state) = (conn_read.id, conn_read.state.clone());
-        // drop the handle to the write lock. to allow other tasks to write/read
+        let handler = tokio::select! {
+            handler = read_next_packet(&conn_read, &conn_state) => handler?,
+            _ = conn_read.stream.out_stream.stalled() => return Err(Error::SendQueueFull),
+        };
+        // drop the handle to the read lock. to allow other tasks to write/read
         // mainly cuz the packet tries to access ECS component. And some system tries to access connection turns into a deadlock!!
         drop(conn_read);
 
-        trace!("Packet Length: {}", packet_length.get_val());
-
-        let mut cursor = Cursor::new(buffer);
-
-        // Get the packet id
-        let packet_id = VarInt::read(&mut cursor).await?;
-        trace!("Packet ID: {}", packet_id);
-
-        let packet_id = packet_id.get_val() as u8;
-
-        let state_clone = state.clone();
-        tokio::spawn(async move {
-            handle_packet(packet_id, conn_id, &conn_state, &mut cursor, state_clone).await
-        });
-        // handle_packet(packet_id, conn_id, &conn_state, &mut cursor, state.clone()).await?;
+        if let Some(handler) = handler {
+            let state_clone = state.clone();
+            tokio::spawn(lock_order::tracked(async move {
+                if let Err(e) = handler(conn_id, state_clone).await {
+                    warn!("Failed to handle packet for {}: {:?}", conn_id, e);
+                }
+            }));
+        }
 
         drop_conn_if_flagged(conn.clone(), state.clone()).await?;
 
@@ -240,13 +372,35 @@ pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> R
     #[allow(unreachable_code)]
     Ok(())
 }
-async fn get_packet_length_and_buffer(conn: &RwLockReadGuard<'_,Connection>) -> Result<(VarInt, Vec<u8>)> {
-    let mut conn = conn.get_in_stream().await;
-    let packet_length = VarInt::read(&mut *conn).await?;
-    let mut buffer = vec![0u8; packet_length.get_val() as usize];
-    conn.read_exact(&mut buffer).await?;
-    Ok((packet_length, buffer))
+/// Reads the next packet off the connection and decodes it into a handler.
+async fn read_next_packet(
+    conn: &Connection,
+    conn_state: &State,
+) -> Result<Option<PacketHandler>> {
+    let mut in_stream = conn.get_in_stream().await;
+    let (packet_id, mut body) = read_packet_header(&mut *in_stream).await?;
+    trace!("Packet ID: {}", packet_id);
+
+    let handler = match packet_dump() {
+        Some(dump) if dump.includes(packet_id.get_val()) => {
+            let mut bytes = Vec::new();
+            body.read_to_end(&mut bytes).await?;
+            dump.log(Direction::Incoming, conn_state, packet_id.get_val(), &bytes);
+            decode_packet(packet_id.get_val() as u8, conn_state, &mut bytes.as_slice()).await
+        }
+        // Decode straight from the socket, the body is never buffered as a whole
+        _ => decode_packet(packet_id.get_val() as u8, conn_state, &mut body).await,
+    };
+
+    // Skip whatever the decoder didn't read, so the next packet starts at the right place
+    tokio::io::copy(&mut body, &mut tokio::io::sink()).await?;
+
+    Ok(handler.unwrap_or_else(|e| {
+        warn!("Failed to decode packet 0x{:02X}: {}", packet_id.get_val(), e);
+        None
+    }))
 }
+
 async fn drop_conn_if_flagged(conn: Arc<RwLock<Connection>>, state: GlobalState) -> Result<()> {
     let read = conn.read().await;
     let do_drop = read.drop;
@@ -260,6 +414,7 @@ async fn drop_conn_if_flagged(conn: Arc<RwLock<Connection>>, state: GlobalState)
     Ok(())
 }
 pub async fn drop_conn(connection_id: u32, state: GlobalState) -> Result<()> {
+    lock_order::check(LockRank::Connection);
     debug!("Dropping connection with id: {}", connection_id);
     let connection = state.connections.connections.remove(&connection_id);
     let Some((_, conn_arc)) = connection else {
@@ -273,21 +428,86 @@ pub async fn drop_conn(connection_id: u32, state: GlobalState) -> Result<()> {
     {
         let read_lock = conn_arc.read().await;
         let entity_id = read_lock.id;
-        state.world.delete_entity(entity_id).await?;
+        if let Err(e) = player_data::save_player(entity_id, &state).await {
+            warn!("Failed to save player {}: {:?}", entity_id, e);
+        }
+        if let Err(e) = entity_tracking::despawn_entity(entity_id, &state).await {
+            warn!("Failed to despawn player {}: {:?}", entity_id, e);
+        }
     }
 
     // drop the connection in the end, just in case it errors out
-    let conn = conn_arc.read().await;
-    let mut conn = conn.get_out_stream().await;
-    conn.shutdown().await?;
+    conn_arc.read().await.close_gracefully(CLOSE_TIMEOUT).await;
     Ok(())
 }
 
 impl Connection {
+    pub fn new(
+        id: u32,
+        socket: tokio::net::TcpStream,
+        send_queue_depth: usize,
+        read_buffer_size: usize,
+    ) -> Self {
+        // Only fails if the client already disconnected
+        let ip = socket
+            .peer_addr()
+            .map(|addr| addr.ip().to_canonical())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        let (in_stream, out_stream) = socket.into_split();
+
+        Self {
+            id,
+            stream: NetStream {
+                in_stream: Mutex::new(BufReader::with_capacity(read_buffer_size, in_stream)),
+                read_buffer_size,
+                out_stream: SendQueue::new(out_stream, send_queue_depth, SEND_QUEUE_TIMEOUT),
+            },
+            player_uuid: None,
+            ip,
+            state: State::Handshake,
+            metadata: ConnectionMetadata::default(),
+            drop: false,
+        }
+    }
+
+    /// Encodes the packet into a pooled buffer and queues it for the connection's writer task.
+    ///
+    /// Fails with [Error::SendQueueFull] if the client can't keep up, which also gets the
+    /// connection dropped.
+    ///
+    /// Must not be called while holding a component, see [lock_order].
     pub async fn send_packet(&self, packet: impl NetEncode) -> Result<()> {
-        let mut out_stream = self.get_out_stream().await;
-        packet.net_encode(&mut *out_stream).await?;
-        Ok(())
+        lock_order::check(LockRank::Connection);
+        let mut buffer = ENCODE_POOL.get();
+        packet.net_encode(&mut *buffer).await?;
+        if let Some(dump) = packet_dump() {
+            dump.log_encoded(Direction::Outgoing, &self.state, &buffer);
+        }
+
+        self.stream.out_stream.send(buffer).await
+    }
+
+    /// Queues an already encoded packet, e.g. one shared by a [utils::broadcast::broadcast].
+    pub async fn send_encoded(&self, packet: &[u8]) -> Result<()> {
+        lock_order::check(LockRank::Connection);
+        let mut buffer = ENCODE_POOL.get();
+        buffer.extend_from_slice(packet);
+        if let Some(dump) = packet_dump() {
+            dump.log_encoded(Direction::Outgoing, &self.state, &buffer);
+        }
+
+        self.stream.out_stream.send(buffer).await
+    }
+
+    /// Queues an already encoded packet if there's room, see [SendQueue::try_send].
+    pub fn try_send_encoded(&self, packet: &[u8]) -> Result<()> {
+        let mut buffer = ENCODE_POOL.get();
+        buffer.extend_from_slice(packet);
+        if let Some(dump) = packet_dump() {
+            dump.log_encoded(Direction::Outgoing, &self.state, &buffer);
+        }
+
+        self.stream.out_stream.try_send(buffer)
     }
 
     /// Just exists so it doesn't seem weird when sending a packet_queue, since multiple packetS are sent.
@@ -295,15 +515,169 @@ impl Connection {
         self.send_packet(packets).await
     }
 
-    pub async fn get_in_stream<'a>(&'a self) -> MutexGuard<'a, tokio::net::tcp::OwnedReadHalf> {
+    pub async fn get_in_stream<'a>(
+        &'a self,
+    ) -> MutexGuard<'a, BufReader<tokio::net::tcp::OwnedReadHalf>> {
         self.stream.in_stream.lock().await
     }
 
-    pub async fn get_out_stream<'a>(&'a self) -> MutexGuard<'a, tokio::net::tcp::OwnedWriteHalf> {
-        self.stream.out_stream.lock().await
+    /// Writes out the packets queued so far, like a [packets::outgoing::disconnect::Disconnect],
+    /// then shuts the write half of the socket down. Gives up after `timeout`, so a dead socket
+    /// can't hold up whoever is closing it.
+    pub async fn close_gracefully(&self, timeout: Duration) {
+        if !self.stream.out_stream.close_gracefully(timeout).await {
+            debug!(
+                "Connection {} didn't flush within {:?}, closed it anyway",
+                self.id, timeout
+            );
+        }
     }
 
     pub async fn drop_connection(&self, state: GlobalState) -> Result<()> {
         Ok(drop_conn(self.id, state).await?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::packets::outgoing::disconnect::Disconnect;
+    use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+    use crate::utils::components::player::Player;
+    use crate::utils::encoding::position::Position;
+
+    #[tokio::test]
+    async fn test_invalid_bind_address() {
+        let mut config = ServerConfig::default();
+        config.host = "256.0.0.1".to_string();
+        let Err(Error::InvalidConfig(field, reason)) = bind_listener(&config).await else {
+            panic!("Expected an invalid host");
+        };
+        assert_eq!(field, "host");
+        assert!(reason.contains("256.0.0.1"));
+
+        // A valid address that isn't one of ours
+        config.host = "192.0.2.1".to_string();
+        config.port = 25565;
+        let Err(Error::TcpError(reason)) = bind_listener(&config).await else {
+            panic!("Expected binding to fail");
+        };
+        assert!(reason.contains("192.0.2.1:25565"));
+    }
+
+    /// Binds to `host`, connects to it from `client_ip` and returns the IP the connection recorded.
+    async fn accepted_ip(host: &str, client_ip: IpAddr) -> IpAddr {
+        let mut config = ServerConfig::default();
+        config.host = host.to_string();
+        config.port = 0;
+        let listener = bind_listener(&config).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let (client, accepted) = tokio::join!(
+            tokio::net::TcpStream::connect((client_ip, port)),
+            listener.accept()
+        );
+        client.unwrap();
+        let (socket, _) = accepted.unwrap();
+        Connection::new(1, socket, 16, 1024).ip
+    }
+
+    #[tokio::test]
+    async fn test_read_buffer_size() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (client, accepted) =
+            tokio::join!(tokio::net::TcpStream::connect(addr), listener.accept());
+        client.unwrap();
+        let (socket, _) = accepted.unwrap();
+
+        let conn = Connection::new(1, socket, 16, 32 * 1024);
+        assert_eq!(conn.stream.read_buffer_size, 32 * 1024);
+    }
+
+    #[tokio::test]
+    async fn test_ipv6_listener() {
+        let ip = accepted_ip("::1", IpAddr::V6(std::net::Ipv6Addr::LOCALHOST)).await;
+        assert_eq!(ip, IpAddr::V6(std::net::Ipv6Addr::LOCALHOST));
+    }
+
+    #[tokio::test]
+    async fn test_dual_stack_ipv4_client() {
+        let ip = accepted_ip("::", IpAddr::V4(Ipv4Addr::LOCALHOST)).await;
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::LOCALHOST));
+    }
+
+    #[tokio::test]
+    async fn test_registry_lookups() {
+        let state = test_state().await;
+        let (conn_id, _client) = add_test_player(&state, "Notch").await;
+        state.connections.register_player(conn_id, "Notch", 42);
+
+        let id_of = |conn: Option<Arc<RwLock<Connection>>>| async move {
+            conn.expect("Connection not found").read().await.id
+        };
+        assert_eq!(id_of(state.connections.by_id(conn_id).ok()).await, conn_id);
+        assert_eq!(id_of(state.connections.by_name("notch")).await, conn_id);
+        assert_eq!(id_of(state.connections.by_uuid(42)).await, conn_id);
+        assert!(state.connections.by_name("Jeb").is_none());
+
+        drop_conn(conn_id, state.clone()).await.unwrap();
+        assert!(state.connections.by_name("Notch").is_none());
+        assert!(state.connections.by_uuid(42).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_connection_handles() {
+        let state = test_state().await;
+        let (first, mut first_client) = add_test_player(&state, "first").await;
+        let (second, mut second_client) = add_test_player(&state, "second").await;
+        // Has a connection, but isn't a player
+        let (other, _other_client) = add_test_player(&state, "other").await;
+        state
+            .world
+            .get_component_storage()
+            .remove::<Player>(other as usize)
+            .unwrap();
+
+        let handles = ConnectionWrapper::handles::<&Player>(&state.world).await;
+        let ids: Vec<u32> = handles.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![first, second]);
+
+        // Nothing is borrowed anymore, so components can be written while the connections are used
+        tokio::time::timeout(Duration::from_secs(5), async {
+            for (entity_id, conn) in handles {
+                let conn = conn.write().await;
+                state
+                    .world
+                    .get_component_storage()
+                    .insert(entity_id, Position::new(1, 2, 3));
+                conn.send_packet(KeepAlivePacketOut::new_auto(7)).await.unwrap();
+            }
+        })
+        .await
+        .expect("Using the handles shouldn't deadlock");
+
+        assert_eq!(read_packet(&mut first_client).await.0, 0x23);
+        assert_eq!(read_packet(&mut second_client).await.0, 0x23);
+    }
+
+    #[tokio::test]
+    async fn test_queued_disconnect_is_written_before_close() {
+        let state = test_state().await;
+        let (conn_id, mut client) = add_test_player(&state, "Leaving").await;
+        {
+            let conn = state.connections.get_connection(conn_id).unwrap();
+            let conn = conn.read().await;
+            conn.send_packet(Disconnect::new("Bye")).await.unwrap();
+        }
+
+        drop_conn(conn_id, state.clone()).await.unwrap();
+
+        assert_eq!(read_packet(&mut client).await.0, 0x1A);
+        // Then the socket is closed
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+    }
+}
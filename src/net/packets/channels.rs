@@ -0,0 +1,145 @@
+//! Routes plugin messages to a handler for their channel, e.g. `minecraft:brand` or
+//! `bungeecord:main`.
+//!
+//! Messages on channels without a handler are ignored, since clients and proxies send plenty the
+//! server doesn't care about.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use async_trait::async_trait;
+use tracing::{debug, trace};
+
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::impls::packet_impls::NetDecode;
+use crate::utils::prelude::*;
+
+/// The channel clients announce their brand on, right after joining.
+pub const BRAND_CHANNEL: &str = "minecraft:brand";
+
+static CHANNELS: LazyLock<RwLock<HashMap<String, Arc<dyn ChannelHandler>>>> =
+    LazyLock::new(|| {
+        let mut channels: HashMap<String, Arc<dyn ChannelHandler>> = HashMap::new();
+        channels.insert(BRAND_CHANNEL.to_string(), Arc::new(BrandHandler));
+        RwLock::new(channels)
+    });
+
+#[async_trait]
+pub trait ChannelHandler: Send + Sync {
+    /// Handles the data of a message sent by `conn_id` on the channel this was registered for.
+    async fn handle(&self, conn_id: ConnectionId, data: &[u8], state: GlobalState) -> Result<()>;
+}
+
+/// Sends messages on `channel` to `handler`, replacing any handler it had before.
+pub fn register_channel(channel: impl Into<String>, handler: Box<dyn ChannelHandler>) {
+    CHANNELS
+        .write()
+        .unwrap()
+        .insert(channel.into(), Arc::from(handler));
+}
+
+/// Passes a plugin message to the handler for its channel, if there is one.
+pub async fn dispatch(
+    conn_id: ConnectionId,
+    channel: &str,
+    data: &[u8],
+    state: GlobalState,
+) -> Result<()> {
+    let handler = CHANNELS.read().unwrap().get(channel).cloned();
+    let Some(handler) = handler else {
+        trace!("Ignoring plugin message on {}", channel);
+        return Ok(());
+    };
+    handler.handle(conn_id, data, state).await
+}
+
+/// Reads the brand out of a [BRAND_CHANNEL] message, which is a single string.
+pub async fn decode_brand(data: &[u8]) -> Result<String> {
+    Ok(Box::into_inner(String::net_decode(&mut Cursor::new(data)).await?))
+}
+
+/// Stores the client's brand on its connection's metadata.
+struct BrandHandler;
+
+#[async_trait]
+impl ChannelHandler for BrandHandler {
+    async fn handle(&self, conn_id: ConnectionId, data: &[u8], state: GlobalState) -> Result<()> {
+        let brand = decode_brand(data).await?;
+        debug!("Connection {} has client brand {}", conn_id, brand);
+
+        let Some(conn) = state.connections.connections.get(&conn_id) else {
+            return Err(Error::ConnectionNotFound(conn_id));
+        };
+        conn.write().await.metadata.brand = Some(brand);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::net::packets::incoming::plugin_message::PluginMessage;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::helpers::{add_test_player, test_state};
+
+    /// Remembers every message it's given.
+    #[derive(Default)]
+    struct Recorder {
+        received: Arc<Mutex<Vec<(ConnectionId, Vec<u8>)>>>,
+    }
+
+    #[async_trait]
+    impl ChannelHandler for Recorder {
+        async fn handle(
+            &self,
+            conn_id: ConnectionId,
+            data: &[u8],
+            _state: GlobalState,
+        ) -> Result<()> {
+            self.received.lock().unwrap().push((conn_id, data.to_vec()));
+            Ok(())
+        }
+    }
+
+    /// A plugin message packet body.
+    fn message(channel: &str, data: &[u8]) -> Vec<u8> {
+        let mut body = vec![channel.len() as u8];
+        body.extend_from_slice(channel.as_bytes());
+        body.extend_from_slice(data);
+        body
+    }
+
+    #[tokio::test]
+    async fn test_messages_are_routed_by_channel() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Modded").await;
+
+        let recorder = Recorder::default();
+        let received = recorder.received.clone();
+        register_channel("ferrumc:test", Box::new(recorder));
+
+        for body in [message("ferrumc:test", &[1, 2, 3]), message("ferrumc:unknown", &[4])] {
+            let packet = PluginMessage::net_decode(&mut Cursor::new(body)).await.unwrap();
+            packet.handle(player, state.clone()).await.unwrap();
+        }
+
+        assert_eq!(*received.lock().unwrap(), vec![(player, vec![1, 2, 3])]);
+    }
+
+    #[tokio::test]
+    async fn test_brand_is_stored() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Branded").await;
+
+        let body = message(BRAND_CHANNEL, b"\x06fabric");
+        let packet = PluginMessage::net_decode(&mut Cursor::new(body)).await.unwrap();
+        packet.handle(player, state.clone()).await.unwrap();
+
+        let conn = state.connections.get_connection(player).unwrap();
+        assert_eq!(conn.read().await.metadata.brand.as_deref(), Some("fabric"));
+    }
+}
@@ -0,0 +1,29 @@
+use tracing::debug;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::commands;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+
+/// Sent by the client when the player runs a command. The leading `/` is not included.
+///
+/// The message signing fields that follow the timestamp are not decoded, since we run in offline mode.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x04, state = "play")]
+pub struct ChatCommand {
+    pub command: String,
+    pub timestamp: i64,
+}
+
+impl IncomingPacket for ChatCommand {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        debug!("Command from {}: /{}", conn_id, self.command);
+
+        commands::dispatch(&self.command, conn_id, state).await
+    }
+}
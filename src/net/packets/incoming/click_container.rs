@@ -0,0 +1,172 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::debug;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::set_container_content::PLAYER_INVENTORY;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::player_inventory::send_inventory;
+use crate::state::GlobalState;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::encoding::item_stack::{ChangedSlots, ItemStack};
+use crate::utils::prelude::*;
+
+/// The slot index of clicks outside the window.
+const OUTSIDE: i16 = -999;
+
+/// Sent when the player clicks in an open window, along with what the client thinks changed.
+///
+/// The click is redone on the server, and the client is sent the whole inventory again if it
+/// got a different result, so it can't make up items.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x0B, state = "play")]
+pub struct ClickContainer {
+    pub window_id: u8,
+    /// The last state id the client was sent, see [Inventory::state_id].
+    pub state_id: VarInt,
+    pub slot: i16,
+    pub button: i8,
+    /// 0 for normal clicks, 2 for number keys, 4 for dropping. The others aren't supported yet.
+    pub mode: VarInt,
+    pub changed_slots: ChangedSlots,
+    pub carried_item: ItemStack,
+}
+
+impl IncomingPacket for ClickContainer {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        // The player inventory is the only window there is
+        if self.window_id != PLAYER_INVENTORY {
+            debug!("Click in unknown window {} from {}", self.window_id, conn_id);
+            return Ok(());
+        }
+
+        let in_sync = {
+            let mut inventory = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<Inventory>(conn_id, Default::default)
+                .await;
+            let up_to_date = self.state_id.get_val() == inventory.state_id;
+            match self.apply(&mut inventory) {
+                Ok(()) => up_to_date && self.predicted(&inventory),
+                Err(e) => {
+                    debug!("Rejected click from {}: {}", conn_id, e);
+                    false
+                }
+            }
+        };
+
+        if !in_sync {
+            send_inventory(conn_id, &state).await?;
+        }
+        Ok(())
+    }
+}
+
+impl ClickContainer {
+    fn apply(&self, inventory: &mut Inventory) -> Result<()> {
+        let slot = || {
+            usize::try_from(self.slot)
+                .map_err(|_| Error::Generic(format!("No inventory slot {}", self.slot)))
+        };
+        match self.mode.get_val() {
+            0 if self.slot == OUTSIDE => {
+                inventory.drop_carried(self.button == 0);
+                Ok(())
+            }
+            0 => inventory.click(slot()?, self.button),
+            2 => inventory.swap_with_hotbar(slot()?, self.button),
+            4 if self.slot == OUTSIDE => Ok(()),
+            4 => inventory
+                .drop_from_slot(slot()?, self.button == 1)
+                .map(|_| ()),
+            mode => Err(Error::Generic(format!("Unsupported click mode {}", mode))),
+        }
+    }
+
+    /// Whether the client ended up with the same inventory as the server.
+    fn predicted(&self, inventory: &Inventory) -> bool {
+        let slots_match = self.changed_slots.0.iter().all(|(slot, item)| {
+            usize::try_from(*slot).is_ok_and(|slot| same_stack(inventory.get(slot), item))
+        });
+        slots_match && same_stack(inventory.carried(), &self.carried_item)
+    }
+}
+
+fn same_stack(server: Option<&ItemStack>, client: &ItemStack) -> bool {
+    match server {
+        Some(server) => server == client,
+        None => client.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::player_inventory::set_slot;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    fn click(
+        state_id: i32,
+        slot: i16,
+        button: i8,
+        changed: ChangedSlots,
+        carried: ItemStack,
+    ) -> ClickContainer {
+        ClickContainer {
+            window_id: PLAYER_INVENTORY,
+            state_id: VarInt::from(state_id),
+            slot,
+            button,
+            mode: VarInt::from(0),
+            changed_slots: changed,
+            carried_item: carried,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pickup_then_place() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+        set_slot(player, 20, Some(ItemStack::new(1, 8)), &state)
+            .await
+            .unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x12);
+
+        // Pick up the whole stack
+        let pickup = click(
+            1,
+            20,
+            0,
+            ChangedSlots(vec![(20, ItemStack::EMPTY)]),
+            ItemStack::new(1, 8),
+        );
+        pickup.handle(player, state.clone()).await.unwrap();
+        {
+            let inventory = state.world.get_component::<Inventory>(player).await.unwrap();
+            assert_eq!(inventory.get(20), None);
+            assert_eq!(inventory.carried(), Some(&ItemStack::new(1, 8)));
+        }
+
+        // Place a single item, but have the client predict two
+        let place = click(
+            1,
+            30,
+            1,
+            ChangedSlots(vec![(30, ItemStack::new(1, 2))]),
+            ItemStack::new(1, 6),
+        );
+        place.handle(player, state.clone()).await.unwrap();
+        {
+            let inventory = state.world.get_component::<Inventory>(player).await.unwrap();
+            assert_eq!(inventory.get(30), Some(&ItemStack::new(1, 1)));
+            assert_eq!(inventory.carried(), Some(&ItemStack::new(1, 7)));
+        }
+
+        // Only the wrong prediction is corrected, so this is the first packet since
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x12);
+        // Window, state id, slot count, then the empty slots before it
+        assert_eq!(body[3 + 30..3 + 30 + 4], [1, 1, 1, 0]);
+    }
+}
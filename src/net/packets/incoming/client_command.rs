@@ -0,0 +1,74 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::debug;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::player_health;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// Sent when the client clicks respawn on the death screen, or opens its statistics.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x07, state = "play")]
+pub struct ClientCommand {
+    /// 0 to respawn, 1 to request statistics.
+    pub action: VarInt,
+}
+
+impl IncomingPacket for ClientCommand {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        match self.action.get_val() {
+            0 => player_health::respawn(conn_id, &state).await,
+            // Statistics aren't tracked
+            1 => Ok(()),
+            action => {
+                debug!("Unknown client command {} from {}", action, conn_id);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::net::player_health::set_health;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+    use crate::utils::components::health::{Food, Health, MAX_FOOD, MAX_HEALTH};
+    use crate::utils::encoding::position::Position;
+    use crate::world::spawn::spawn_point;
+
+    #[tokio::test]
+    async fn test_respawn_after_death() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+        state
+            .world
+            .get_component_storage()
+            .insert(player, Position::new(250, 12, -80))
+            .insert(player, Food::new(2, 0.0));
+
+        set_health(player, 0.0, &state).await.unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x57);
+        assert_eq!(read_packet(&mut client).await.0, 0x38);
+
+        // Perform respawn
+        let packet = ClientCommand::net_decode(&mut Cursor::new(vec![0]))
+            .await
+            .unwrap();
+        packet.handle(player, state.clone()).await.unwrap();
+
+        assert_eq!(read_packet(&mut client).await.0, 0x41);
+        assert_eq!(read_packet(&mut client).await.0, 0x3C);
+        let (spawn, _) = spawn_point();
+        let position = state.world.get_component::<Position>(player).await.unwrap().clone();
+        assert_eq!((position.x, position.y, position.z), (spawn.x, spawn.y, spawn.z));
+        let health = *state.world.get_component::<Health>(player).await.unwrap();
+        assert_eq!(health.get(), MAX_HEALTH);
+        let food = *state.world.get_component::<Food>(player).await.unwrap();
+        assert_eq!(food.level(), MAX_FOOD);
+    }
+}
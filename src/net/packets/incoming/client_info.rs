@@ -44,3 +44,26 @@ impl IncomingPacket for ClientInfo {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_client_info() {
+        let mut body = vec![5];
+        body.extend_from_slice(b"en_us");
+        // View distance, chat mode (commands only), chat colors, skin parts, main hand (right)
+        body.extend_from_slice(&[12, 1, 1, 0x7F, 1]);
+
+        let info = ClientInfo::net_decode(&mut Cursor::new(body)).await.unwrap();
+        assert_eq!(info.locale, "en_us");
+        assert_eq!(info.view_distance, 12);
+        assert_eq!(info.chat_mode, 1);
+        assert!(info.chat_colors);
+        assert_eq!(info.displayed_skin_parts, 0x7F);
+        assert_eq!(info.main_hand, 1);
+    }
+}
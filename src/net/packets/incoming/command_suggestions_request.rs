@@ -0,0 +1,36 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::debug;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::commands::completion::complete;
+use crate::net::packets::outgoing::command_suggestions_response::CommandSuggestionsResponse;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+
+/// Sent by the client while the player is typing a command, asking how it could be completed.
+///
+/// `text` is everything typed so far, including the leading `/`.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x09, state = "play")]
+pub struct CommandSuggestionsRequest {
+    pub transaction_id: VarInt,
+    pub text: String,
+}
+
+impl IncomingPacket for CommandSuggestionsRequest {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        debug!("Completions requested by {}: {}", conn_id, self.text);
+
+        let completions = complete(&self.text, conn_id, &state).await?;
+        let response = CommandSuggestionsResponse::new(self.transaction_id, completions);
+
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(response).await
+    }
+}
@@ -1,16 +1,19 @@
 use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::packets;
 
-use ferrumc_macros::{packet, NetDecode};
+use ferrumc_macros::packet;
+use tokio::io::AsyncRead;
+use tracing::debug;
 
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::State;
 use crate::state::GlobalState;
+use crate::utils::protocol::is_supported;
 use crate::utils::prelude::*;
 
 /// The first packet sent by the client to the server.
 ///
 /// This packet is used to negotiate the protocol version, server address, server port, and the next state.
-#[derive(NetDecode)]
 #[packet(packet_id = 0x00, state = "handshake")]
 pub struct Handshake {
     pub protocol_version: VarInt,
@@ -19,6 +22,27 @@ pub struct Handshake {
     pub next_state: VarInt,
 }
 
+impl Handshake {
+    /// Decoded by [packets::Handshake::read], which the fuzz targets run on its own.
+    pub async fn net_decode<T>(bytes: &mut T) -> Result<Self>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let packets::Handshake {
+            protocol_version,
+            server_address,
+            server_port,
+            next_state,
+        } = packets::Handshake::read(bytes).await?;
+        Ok(Self {
+            protocol_version,
+            server_address,
+            server_port,
+            next_state,
+        })
+    }
+}
+
 impl IncomingPacket for Handshake {
     async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
         let Some(conn) = state.connections.connections.get(&conn_id) else {
@@ -30,7 +54,15 @@ impl IncomingPacket for Handshake {
         conn.metadata.protocol_version = self.protocol_version.get_val();
         conn.state = match self.next_state.get_val() {
             1 => State::Status,
-            2 => State::Login,
+            2 => {
+                if !is_supported(conn.metadata.protocol_version) {
+                    debug!(
+                        "Client logging in with unsupported protocol {}",
+                        conn.metadata.protocol_version
+                    );
+                }
+                State::Login
+            }
             s => return Err(Error::InvalidState(s)),
         };
 
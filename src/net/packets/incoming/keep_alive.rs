@@ -28,7 +28,7 @@ impl IncomingPacket for KeepAlivePacketIn {
 
         debug!("KeepAlive for player: {:?}", *keep_alive);
 
-        keep_alive.last_received = std::time::Instant::now();
+        keep_alive.last_received = state.clock.now();
 
         Ok(())
     }
@@ -1,4 +1,3 @@
-use std::time::Instant;
 
 use ferrumc_codec::network_types::varint::VarInt;
 #[cfg(not(test))]
@@ -9,22 +8,37 @@ use uuid::Uuid;
 
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::net::packets::outgoing::change_difficulty::ChangeDifficulty;
 use crate::net::packets::outgoing::default_spawn_position::DefaultSpawnPosition;
 use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+use crate::net::packets::outgoing::login_play::LoginPlay;
 use crate::net::packets::outgoing::login_plugin_request::LoginPluginRequest;
 use crate::net::packets::outgoing::login_success::LoginSuccess;
+use crate::net::packets::outgoing::resource_pack::ResourcePackOut;
+use crate::net::packets::outgoing::set_container_content::SetContainerContent;
+use crate::net::packets::outgoing::set_health::SetHealth;
 use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::database::meta::hashed_seed;
+use crate::database::playerdata::PlayerData;
+use crate::net::entity_tracking;
+use crate::net::scoreboard;
+use crate::net::boss_bar;
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::systems::chunk_sender::ChunkSender;
 use crate::net::utils::packet_queue::PacketQueue;
 use crate::net::State::Play;
 use crate::state::GlobalState;
+use crate::utils::components::gamemode::GameMode;
+use crate::utils::components::health::{Food, Health};
+use crate::utils::components::inventory::Inventory;
 use crate::utils::components::keep_alive::KeepAlive;
 use crate::utils::components::player::Player;
 use crate::utils::components::rotation::Rotation;
-use crate::utils::constants::init;
+use crate::utils::config::{get_global_config, ServerConfig};
 use crate::utils::encoding::position::Position;
 use crate::utils::prelude::*;
+use crate::world::spawn::spawn_point;
+use crate::world::weather::Weather;
 use crate::Connection;
 
 /// The login start packet is sent by the client to the server to start the login process.
@@ -58,25 +72,54 @@ impl IncomingPacket for LoginStart {
         // let conn = conn.read().await;
 
         let mut packet_queue = PacketQueue::new();
+        let saved = state
+            .database
+            .load_player_data(self.offline_uuid().as_u128())
+            .await?;
+        let gamemode = saved.as_ref().map_or_else(
+            || default_gamemode(&get_global_config()),
+            |saved| saved.gamemode(),
+        );
 
         self.send_login_success(&mut packet_queue).await?;
-        self.send_login_play(&mut packet_queue).await?;
+        self.send_login_play(conn_id, gamemode, &state, &mut packet_queue)
+            .await?;
         self.send_spawn_position(&mut packet_queue).await?;
+        packet_queue
+            .queue(ChangeDifficulty::new(state.difficulty.get()))
+            .await?;
+        // Clear skies are what the client assumes
+        let weather = state.weather.get(&get_global_config().default_dimension);
+        if weather != Weather::Clear {
+            for packet in weather.packets() {
+                packet_queue.queue(packet).await?;
+            }
+        }
 
         let data: i64 = random();
-        let mut keep_alive = KeepAlive::new(Instant::now(), Instant::now(), data);
+        let now = state.clock.now();
+        let mut keep_alive = KeepAlive::new(now, now, data);
         self.send_keep_alive(&mut packet_queue, &mut keep_alive).await?;
-        self.update_world_state(&*conn.read().await, keep_alive, state.clone())
+        self.update_world_state(&*conn.read().await, keep_alive, saved, state.clone())
             .await?;
 
         self.synchronize_player_position(state.clone(), &*conn.read().await, &mut packet_queue)
             .await?;
 
 
+        self.send_health_and_inventory(conn_id, &state, &mut packet_queue)
+            .await?;
+
         let packet = LoginPluginRequest::server_brand("🦀".repeat(100)).await;
         // conn.send_packet(packet).await?;
         packet_queue.queue(packet).await?;
 
+        if let Some(resource_pack) =
+            ResourcePackOut::from_config(&get_global_config().resource_pack)
+        {
+            packet_queue.queue(resource_pack).await?;
+        }
+
         info!("Player {} has joined the server", self.username);
 
 
@@ -94,20 +137,28 @@ impl IncomingPacket for LoginStart {
 
         ChunkSender::send_chunks_to_player(state.clone(), entity).await?;
 
+        entity_tracking::spawn_player(entity, &state).await?;
+        scoreboard::send_scoreboard(entity, &state).await?;
+        boss_bar::send_boss_bars(entity, &state).await?;
 
         Ok(())
     }
 }
 
 impl LoginStart {
+    /// The UUID the player is known by, derived from their username since there is no authentication.
+    fn offline_uuid(&self) -> Uuid {
+        let namespace_uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, "OfflinePlayer".as_bytes());
+        Uuid::new_v3(&namespace_uuid, self.username.as_bytes())
+    }
+
     async fn send_login_success(&self, packet_queue: &mut PacketQueue) -> Result<()> {
         debug!("LoginStart packet received");
         debug!("Username: {}", self.username);
         let uuid = Uuid::from_u128(self.uuid);
         debug!("UUID: {uuid}");
 
-        let namespace_uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, "OfflinePlayer".as_bytes());
-        let uuid = Uuid::new_v3(&namespace_uuid, self.username.as_bytes());
+        let uuid = self.offline_uuid();
 
         let response = LoginSuccess::new_auto(
             uuid.as_bytes().into(),
@@ -125,29 +176,16 @@ impl LoginStart {
         Ok(())
     }
 
-    async fn send_login_play(&self, packet_queue: &mut PacketQueue) -> Result<()> {
-        let play_packet = crate::net::packets::outgoing::login_play::LoginPlay {
-            packet_id: VarInt::from(0x28),
-            entity_id: 0,
-            hardcore: false,
-            gamemode: 1,
-            previous_gamemode: -1,
-            dimension_length: VarInt::new(1),
-            dimension_names: vec!["minecraft:overworld".to_string()],
-            registry_codec: NBT_CODEC.to_vec(),
-            dimension_type: "minecraft:overworld".to_string(),
-            dimension_name: "minecraft:overworld".to_string(),
-            seed_hash: 0,
-            max_players: VarInt::new(20),
-            view_distance: VarInt::new(10),
-            simulation_distance: VarInt::new(10),
-            reduced_debug_info: false,
-            enable_respawn_screen: true,
-            is_debug: false,
-            is_flat: false,
-            has_death_location: false,
-            portal_cooldown: VarInt::new(0),
-        };
+    async fn send_login_play(
+        &self,
+        entity_id: u32,
+        gamemode: GameMode,
+        state: &GlobalState,
+        packet_queue: &mut PacketQueue,
+    ) -> Result<()> {
+        let config = get_global_config();
+        let seed = state.database.world_seed(config.seed).await?;
+        let play_packet = login_play(state.entity_ids.allocate(entity_id), gamemode, &config, seed);
 
         packet_queue.queue(play_packet).await?;
         /*let mut cursor = std::io::Cursor::new(Vec::new());
@@ -159,12 +197,8 @@ impl LoginStart {
     }
 
     async fn send_spawn_position(&self, packet_queue: &mut PacketQueue) -> Result<()> {
-        let player_position = Position {
-            x: init::DEFAULT_SPAWN_X_POS,
-            y: init::DEFAULT_SPAWN_Y_POS,
-            z: init::DEFAULT_SPAWN_Z_POS,
-        };
-        let spawn_position = DefaultSpawnPosition::new_auto(player_position.clone(), 0.0);
+        let (player_position, _) = spawn_point();
+        let spawn_position = DefaultSpawnPosition::new_auto(player_position, 0.0);
         packet_queue.queue(spawn_position).await?;
         Ok(())
     }
@@ -184,30 +218,59 @@ impl LoginStart {
         &self,
         conn: &Connection,
         keep_alive: KeepAlive,
+        saved: Option<PlayerData>,
         state: GlobalState,
     ) -> Result<()> {
         let entity = conn.id;
 
         let component_storage = state.world.get_component_storage();
 
+        let (position, rotation) = spawn_point();
         component_storage
+            .insert(entity, position)
+            .insert(entity, rotation)
+            .insert(entity, keep_alive)
+            .insert(entity, default_gamemode(&get_global_config()))
+            .insert(entity, Health::default())
+            .insert(entity, Food::default())
+            .insert(entity, Inventory::new())
             .insert(
                 entity,
-                Position::new(
-                    init::DEFAULT_SPAWN_X_POS,
-                    init::DEFAULT_SPAWN_Y_POS,
-                    init::DEFAULT_SPAWN_Z_POS,
-                ),
-            )
-            .insert(
-                entity,
-                Rotation::new(init::DEFAULT_SPAWN_YAW, init::DEFAULT_SPAWN_PITCH),
-            )
-            .insert(entity, keep_alive)
-            .insert(entity, Player::new(self.uuid, self.username.clone()));
+                Player::new(self.offline_uuid().as_u128(), self.username.clone()),
+            );
+        // Returning players pick up where they left off, new ones keep the defaults
+        if let Some(saved) = saved {
+            saved.restore(entity, &state);
+        }
+        state.connections.register_player(
+            entity,
+            &self.username,
+            self.offline_uuid().as_u128(),
+        );
 
         Ok(())
     }
+    async fn send_health_and_inventory(
+        &self,
+        entity: u32,
+        state: &GlobalState,
+        packet_queue: &mut PacketQueue,
+    ) -> Result<()> {
+        let component_storage = state.world.get_component_storage();
+
+        let health = component_storage.get::<Health>(entity).await?;
+        let food = component_storage.get::<Food>(entity).await?;
+        packet_queue.queue(SetHealth::new(&health, &food)).await?;
+        drop((health, food));
+
+        let inventory = component_storage.get::<Inventory>(entity).await?;
+        packet_queue
+            .queue(SetContainerContent::player_inventory(&inventory))
+            .await?;
+
+        Ok(())
+    }
+
     async fn synchronize_player_position(
         &self,
         state: GlobalState,
@@ -227,3 +290,66 @@ impl LoginStart {
         Ok(())
     }
 }
+
+/// The game mode new players join in.
+fn default_gamemode(config: &ServerConfig) -> GameMode {
+    // Checked when the config is loaded
+    config.default_gamemode.parse().unwrap_or_default()
+}
+
+/// The login play packet for a player, with the world settings from `config`.
+fn login_play(entity_id: i32, gamemode: GameMode, config: &ServerConfig, seed: i64) -> LoginPlay {
+    LoginPlay {
+        packet_id: VarInt::from(0x28),
+        // Has to match the id other players see this player spawn with
+        entity_id,
+        hardcore: false,
+        gamemode: gamemode as u8,
+        previous_gamemode: -1,
+        dimension_length: VarInt::new(1),
+        dimension_names: vec!["minecraft:overworld".to_string()],
+        registry_codec: NBT_CODEC.to_vec(),
+        dimension_type: "minecraft:overworld".to_string(),
+        dimension_name: "minecraft:overworld".to_string(),
+        seed_hash: hashed_seed(seed),
+        max_players: VarInt::new(20),
+        view_distance: VarInt::new(config.view_distance as i32),
+        simulation_distance: VarInt::new(config.simulation_distance as i32),
+        reduced_debug_info: false,
+        enable_respawn_screen: true,
+        is_debug: false,
+        is_flat: false,
+        has_death_location: false,
+        portal_cooldown: VarInt::new(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_login_play_uses_configured_distances() {
+        let mut config = ServerConfig::default();
+        config.view_distance = 12;
+        config.simulation_distance = 6;
+        config.default_gamemode = "creative".to_string();
+
+        let packet = login_play(7, default_gamemode(&config), &config, 1234);
+        assert_eq!(packet.entity_id, 7);
+        assert_eq!(packet.view_distance.get_val(), 12);
+        assert_eq!(packet.simulation_distance.get_val(), 6);
+        assert_eq!(packet.seed_hash, hashed_seed(1234));
+        assert_eq!(packet.gamemode, GameMode::Creative as u8);
+
+        // The trailing fields are small enough to find the distances at a fixed offset from the end
+        let mut encoded = Vec::new();
+        packet.net_encode(&mut encoded).await.unwrap();
+        // view distance, simulation distance, 5 bools, portal cooldown
+        let tail = &encoded[encoded.len() - 8..];
+        assert_eq!(tail[0], 12);
+        assert_eq!(tail[1], 6);
+    }
+}
@@ -1,11 +1,19 @@
+pub mod chat_command;
 pub mod chat_message;
+pub mod click_container;
+pub mod client_command;
 pub mod client_info;
+pub mod command_suggestions_request;
 pub mod handshake;
 pub mod keep_alive;
 pub mod login_start;
 pub mod ping;
+pub mod player_action;
+pub mod plugin_message;
+pub mod resource_pack_status;
 pub mod player_abilities;
 pub mod set_player_pos_and_rotate;
 pub mod set_player_position;
 pub mod set_player_rotation;
 pub mod status;
+pub mod use_item_on;
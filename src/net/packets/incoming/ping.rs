@@ -1,7 +1,9 @@
 use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::packets::PingRequest;
+use tokio::io::AsyncRead;
 use tracing::debug;
 
-use ferrumc_macros::{packet, NetDecode};
+use ferrumc_macros::packet;
 
 use crate::net::packets::outgoing::ping::OutgoingPing;
 use crate::net::packets::{ConnectionId, IncomingPacket};
@@ -12,12 +14,22 @@ use crate::utils::prelude::*;
 ///
 /// The payload is a random number that the server should return in the pong.
 /// For some reason, seems to be required for the client to acknowledge the server's status response.
-#[derive(NetDecode)]
 #[packet(packet_id = 0x01, state = "status")]
 pub struct Ping {
     pub payload: i64,
 }
 
+impl Ping {
+    /// Decoded by [PingRequest::read], which the fuzz targets run on its own.
+    pub async fn net_decode<T>(bytes: &mut T) -> Result<Self>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let PingRequest { payload } = PingRequest::read(bytes).await?;
+        Ok(Self { payload })
+    }
+}
+
 impl IncomingPacket for Ping {
     async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
         debug!("Handling ping packet");
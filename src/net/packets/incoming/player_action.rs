@@ -0,0 +1,105 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::acknowledge_block_change::AcknowledgeBlockChange;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::GameMode;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::protection;
+
+const STARTED_DIGGING: i32 = 0;
+const CANCELLED_DIGGING: i32 = 1;
+const FINISHED_DIGGING: i32 = 2;
+
+/// Sent when the player digs at a block, and for a few other actions like dropping items.
+///
+/// Only digging is handled, to undo blocks broken in protected regions.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x1D, state = "play")]
+pub struct PlayerAction {
+    /// 0 to 2 for started, cancelled and finished digging. The others aren't about blocks.
+    pub status: VarInt,
+    pub location: Position,
+    pub face: i8,
+    /// Acknowledged once the dig is handled, see [AcknowledgeBlockChange].
+    pub sequence: VarInt,
+}
+
+impl IncomingPacket for PlayerAction {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        let status = self.status.get_val();
+        if ![STARTED_DIGGING, CANCELLED_DIGGING, FINISHED_DIGGING].contains(&status) {
+            return Ok(());
+        }
+
+        // Blocks break as soon as digging starts in creative
+        let creative = state
+            .world
+            .get_component::<GameMode>(conn_id)
+            .await
+            .is_ok_and(|mode| *mode == GameMode::Creative);
+        let broken = status == FINISHED_DIGGING || (status == STARTED_DIGGING && creative);
+        if broken {
+            let location = &self.location;
+            let position = (location.x, location.y as i32, location.z);
+            protection::check_edit(conn_id, position, &state).await?;
+        }
+
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(AcknowledgeBlockChange::new(self.sequence))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::database::protection::ProtectedRegion;
+    use crate::database::tests::test_chunk;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+    use crate::utils::components::player::Player;
+
+    fn dig(x: i32, y: i16, z: i32, sequence: i32) -> PlayerAction {
+        PlayerAction {
+            status: VarInt::from(FINISHED_DIGGING),
+            location: Position::new(x, y, z),
+            face: 1,
+            sequence: VarInt::from(sequence),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edit_in_protected_region_is_reverted() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Griefer").await;
+        let (member, mut member_client) = add_test_player(&state, "Builder").await;
+        let member_uuid = state.world.get_component::<Player>(member).await.unwrap().uuid;
+
+        let mut chunk = test_chunk(0, 0);
+        chunk.sections = Some(Vec::new());
+        state.database.insert_chunk(chunk).await.unwrap();
+        let mut region = ProtectedRegion::new("spawn", (0, 60, 0), (15, 80, 15));
+        region.members.push(member_uuid);
+        state.database.save_protected_region(&region).await.unwrap();
+
+        // The chunk is sent again before the dig is acknowledged, putting the block back
+        dig(5, 64, 5, 3).handle(player, state.clone()).await.unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x24);
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x06);
+        let sequence = VarInt::read(&mut Cursor::new(body)).await.unwrap();
+        assert_eq!(sequence.get_val(), 3);
+
+        // Members, and anyone outside the region, are only acknowledged
+        dig(5, 64, 5, 4).handle(member, state.clone()).await.unwrap();
+        assert_eq!(read_packet(&mut member_client).await.0, 0x06);
+        dig(5, 81, 5, 5).handle(player, state.clone()).await.unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x06);
+    }
+}
@@ -0,0 +1,24 @@
+use tracing::trace;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::channels;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::remaining_bytes::RemainingBytes;
+use crate::utils::prelude::*;
+
+/// A message on a plugin channel, routed to its handler by [channels::dispatch].
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x0D, state = "play")]
+pub struct PluginMessage {
+    pub channel: String,
+    pub data: RemainingBytes,
+}
+
+impl IncomingPacket for PluginMessage {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("Plugin message from {} on {}", conn_id, self.channel);
+        channels::dispatch(conn_id, &self.channel, &self.data.0, state).await
+    }
+}
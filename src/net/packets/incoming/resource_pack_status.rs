@@ -0,0 +1,47 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::{debug, info, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::drop_conn;
+use crate::net::packets::outgoing::disconnect::Disconnect;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// Shown to players that decline a required resource pack.
+const DECLINED_REASON: &str = "This server requires its resource pack";
+
+/// The client's answer to a [ResourcePackOut](crate::net::packets::outgoing::resource_pack::ResourcePackOut).
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x24, state = "play")]
+pub struct ResourcePackStatus {
+    /// 0 loaded, 1 declined, 2 failed to download, 3 accepted.
+    pub result: VarInt,
+}
+
+impl IncomingPacket for ResourcePackStatus {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        match self.result.get_val() {
+            0 => debug!("Connection {} loaded the resource pack", conn_id),
+            1 => {
+                if !get_global_config().resource_pack.required {
+                    debug!("Connection {} declined the resource pack", conn_id);
+                    return Ok(());
+                }
+                info!("Disconnecting {} for declining the resource pack", conn_id);
+                let conn = state.connections.get_connection(conn_id)?;
+                conn.read()
+                    .await
+                    .send_packet(Disconnect::new(DECLINED_REASON))
+                    .await?;
+                drop_conn(conn_id, state).await?;
+            }
+            2 => warn!("Connection {} failed to download the resource pack", conn_id),
+            3 => debug!("Connection {} accepted the resource pack", conn_id),
+            result => debug!("Unknown resource pack status {} from {}", result, conn_id),
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::systems::chunk_sender::{ChunkSender};
 use crate::state::GlobalState;
+use crate::utils::components::grounded::Grounded;
 use crate::utils::components::rotation::Rotation;
 use crate::utils::encoding::position::Position;
 use crate::utils::prelude::*;
@@ -24,21 +25,28 @@ impl IncomingPacket for SetPlayerPosAndRotate {
 
         let component_storage = state.world.get_component_storage();
 
-        let mut position = component_storage.get_mut::<Position>(my_entity_id).await?;
-        let mut rotation = component_storage.get_mut::<Rotation>(my_entity_id).await?;
-
-        ChunkSender::send_chunks_to_player_if_needed(state.clone(), my_entity_id, (position.x >> 4, position.z >> 4)).await?;
-
-        *position = Position {
-            x: self.x as i32,
-            y: self.y as i16,
-            z: self.z as i32,
+        let (old_chunk, new_chunk) = {
+            let mut position = component_storage.get_mut::<Position>(my_entity_id).await?;
+            let mut rotation = component_storage.get_mut::<Rotation>(my_entity_id).await?;
+
+            let old_chunk = (position.x >> 4, position.z >> 4);
+            *position = Position {
+                x: self.x as i32,
+                y: self.y as i16,
+                z: self.z as i32,
+            };
+
+            *rotation = Rotation {
+                yaw: self.yaw,
+                pitch: self.pitch,
+            };
+            (old_chunk, (position.x >> 4, position.z >> 4))
         };
 
-        *rotation = Rotation {
-            yaw: self.yaw,
-            pitch: self.pitch,
-        };
+        component_storage.insert(my_entity_id, Grounded::new(self.on_ground));
+
+        ChunkSender::update_center_chunk(&state, my_entity_id, old_chunk, new_chunk).await?;
+        ChunkSender::send_chunks_to_player_if_needed(state.clone(), my_entity_id, new_chunk).await?;
 
         trace!("SetPlayerPosAndRotate packet received: {:?}", self);
 
@@ -5,6 +5,7 @@ use ferrumc_macros::{packet, NetDecode};
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::systems::chunk_sender::{ChunkSender};
 use crate::state::GlobalState;
+use crate::utils::components::grounded::Grounded;
 use crate::utils::encoding::position::Position;
 
 /// The set player position packet is sent by the client to the server to update the player's position.
@@ -32,30 +33,45 @@ impl IncomingPacket for SetPlayerPosition {
 
         let component_storage = state.world.get_component_storage();
 
-        let mut position = component_storage.get_mut::<Position>(my_entity_id).await?;
+        let (old_chunk, new_chunk) = {
+            let mut position = component_storage.get_mut::<Position>(my_entity_id).await?;
+            let old_chunk = (position.x >> 4, position.z >> 4);
+            *position = Position {
+                x: self.x as i32,
+                y: self.y as i16,
+                z: self.z as i32,
+            };
+            (old_chunk, (position.x >> 4, position.z >> 4))
+        };
+
+        component_storage.insert(my_entity_id, Grounded::new(self.on_ground));
 
-        ChunkSender::send_chunks_to_player_if_needed(state.clone(), my_entity_id, (position.x >> 4, position.z >> 4)).await?;
+        ChunkSender::update_center_chunk(&state, my_entity_id, old_chunk, new_chunk).await?;
+        ChunkSender::send_chunks_to_player_if_needed(state.clone(), my_entity_id, new_chunk).await?;
 
-        /*let old_chunk_pos = (position.x >> 4, position.z >> 4);
-        let new_chunk_pos = (self.x as i32 >> 4, self.z as i32 >> 4);
+        Ok(())
+    }
+}
 
-        if old_chunk_pos != new_chunk_pos {
-            let state_clone = state.clone();
-            tokio::spawn(
-                async move {
-                    ChunkSender::send_chunks_to_player(state_clone, my_entity_id).await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
 
-                    Ok::<(), Error>(())
-                }
-            );
-        }*/
+    #[tokio::test]
+    async fn test_crossing_chunk_boundary_sets_center_chunk() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
 
-        *position = Position {
-            x: self.x as i32,
-            y: self.y as i16,
-            z: self.z as i32,
+        let moved = SetPlayerPosition {
+            x: 20.5,
+            y: 64.0,
+            z: 3.5,
+            on_ground: true,
         };
+        moved.handle(player, state.clone()).await.unwrap();
 
-        Ok(())
+        // From chunk (0, 0) into (1, 0)
+        assert_eq!(read_packet(&mut client).await, (0x4E, vec![1, 0]));
     }
 }
@@ -2,6 +2,7 @@ use ferrumc_macros::{packet, NetDecode};
 
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::state::GlobalState;
+use crate::utils::components::grounded::Grounded;
 use crate::utils::components::rotation::Rotation;
 
 #[derive(NetDecode)]
@@ -29,6 +30,8 @@ impl IncomingPacket for SetPlayerRotation {
         rotation.yaw = self.yaw;
         rotation.pitch = self.pitch;
 
+        component_storage.insert(my_entity_id, Grounded::new(self.on_ground));
+
         Ok(())
     }
 }
@@ -4,7 +4,7 @@ use rand::prelude::IndexedRandom;
 use serde::Serialize;
 use tokio::io::{AsyncReadExt};
 use tokio::sync::OnceCell;
-use tracing::debug;
+use tracing::{debug, warn};
 
 
 use ferrumc_macros::{packet, NetDecode};
@@ -12,9 +12,13 @@ use ferrumc_macros::{packet, NetDecode};
 use crate::net::packets::outgoing::status::OutgoingStatusResponse;
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::state::GlobalState;
-use crate::utils::config;
+use crate::utils::components::player::online_players;
+use crate::utils::config::{self, ServerConfig};
 use crate::utils::prelude::*;
 
+/// How many online players are listed when hovering over the player count, like vanilla.
+const SAMPLE_SIZE: usize = 12;
+
 /// The status packet is sent by the client to the server to request the server's status.
 ///
 /// Usually sent after handshaking is completed.
@@ -29,7 +33,9 @@ struct JsonResponse {
     version: Version,
     players: Players,
     description: Description,
-    favicon: &'static String,
+    /// Left out rather than empty when there's no icon, some clients choke on an empty one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    favicon: Option<&'static str>,
 }
 
 #[derive(Serialize)]
@@ -65,6 +71,7 @@ impl IncomingPacket for Status {
         let conn = conn.read().await;
 
         let random_motd = config.motd.choose(&mut rand::thread_rng()).unwrap().clone();
+        let players = online_players(&state.world).await;
 
         let response = OutgoingStatusResponse {
             packet_id: VarInt::new(0x00),
@@ -76,20 +83,18 @@ impl IncomingPacket for Status {
                 },
                 players: Players {
                     max: config.max_players,
-                    online: 2,
-                    sample: vec![
-                        Sample {
-                            name: "Recore_".to_string(),
-                            id: "2b3414ed-468a-45c2-b113-6c5f47430edc".to_string(),
-                        },
-                        Sample {
-                            name: "sweattypalms".to_string(),
-                            id: "26d88d10-f052-430f-9406-e6c3089792c4".to_string(),
-                        },
-                    ],
+                    online: players.len() as u32,
+                    sample: players
+                        .iter()
+                        .take(SAMPLE_SIZE)
+                        .map(|player| Sample {
+                            name: player.username.clone(),
+                            id: uuid::Uuid::from_u128(player.uuid).to_string(),
+                        })
+                        .collect(),
                 },
                 description: Description { text: random_motd },
-                favicon: get_encoded_favicon().await,
+                favicon: get_encoded_favicon(&config).await,
             })
             .unwrap(),
         };
@@ -107,20 +112,97 @@ impl IncomingPacket for Status {
     }
 }
 
-/// Get the favicon as a base64 encoded string.
+/// Get the favicon as a base64 encoded string, `None` if it's disabled or can't be served.
 ///
-/// This is cached in a `OnceCell` to avoid reading the file every time.
-async fn get_encoded_favicon() -> &'static String {
-    static FAVICON: OnceCell<String> = OnceCell::const_new();
+/// This is cached in a `OnceCell` to avoid reading the file every time. With
+/// `enable_favicon` off, the file isn't read at all.
+async fn get_encoded_favicon(config: &ServerConfig) -> Option<&'static str> {
+    static FAVICON: OnceCell<Option<String>> = OnceCell::const_new();
+    if !config.enable_favicon {
+        return None;
+    }
     FAVICON
         .get_or_init(|| async {
             let mut data = Vec::new();
-            let Ok(mut image) = tokio::fs::File::open("icon-64.png").await else {
-                return String::new();
-            };
+            let path = config.paths.favicon().ok()?;
+            let mut image = tokio::fs::File::open(path).await.ok()?;
             image.read_to_end(&mut data).await.unwrap_or_default();
+            if let Err(e) = validate_favicon(&data) {
+                warn!("Not serving the server icon: {}", e);
+                return None;
+            }
             let data = base64::engine::general_purpose::STANDARD.encode(&data);
-            format!("data:image/png;base64,{}", data)
+            Some(format!("data:image/png;base64,{}", data))
         })
         .await
+        .as_deref()
+}
+
+/// Check that `data` is a 64x64 PNG, the only icon clients accept.
+///
+/// Only the signature and the IHDR header are checked, the image data itself isn't decoded.
+fn validate_favicon(data: &[u8]) -> Result<()> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if data.len() < 24 || &data[..8] != SIGNATURE || &data[12..16] != b"IHDR" {
+        return Err(Error::Generic("the icon is not a PNG image".to_string()));
+    }
+    let width = u32::from_be_bytes([data[16], data[17], data[18], data[19]]);
+    let height = u32::from_be_bytes([data[20], data[21], data[22], data[23]]);
+    if (width, height) != (64, 64) {
+        return Err(Error::Generic(format!(
+            "the icon must be 64x64 pixels, but is {}x{}",
+            width, height
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_favicon() {
+        assert!(validate_favicon(include_bytes!("../../../../icon-64.png")).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_size_favicon_is_rejected() {
+        let Err(Error::Generic(reason)) = validate_favicon(include_bytes!("../../../../icon.png"))
+        else {
+            panic!("a 1024x1024 icon should be rejected");
+        };
+        assert!(reason.contains("1024x1024"), "unexpected reason: {}", reason);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_favicon_is_left_out() {
+        let mut config = ServerConfig::default();
+        config.enable_favicon = false;
+        let response = JsonResponse {
+            version: Version {
+                name: "1.20.6".to_string(),
+                protocol: 763,
+            },
+            players: Players {
+                max: 20,
+                online: 0,
+                sample: Vec::new(),
+            },
+            description: Description {
+                text: "A server".to_string(),
+            },
+            favicon: get_encoded_favicon(&config).await,
+        };
+
+        let json: serde_json::Value = serde_json::to_value(&response).unwrap();
+        assert!(json.get("favicon").is_none());
+        assert_eq!(json["description"]["text"], "A server");
+    }
+
+    #[test]
+    fn test_non_png_favicon_is_rejected() {
+        assert!(validate_favicon(b"GIF89a not a png at all").is_err());
+        assert!(validate_favicon(&[]).is_err());
+    }
 }
@@ -0,0 +1,60 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::acknowledge_block_change::AcknowledgeBlockChange;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::protection;
+
+/// Sent when the player right clicks a block, which places the held block next to it.
+///
+/// Only handled to undo blocks placed in protected regions.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x31, state = "play")]
+pub struct UseItemOn {
+    /// 0 for the main hand, 1 for the offhand.
+    pub hand: VarInt,
+    /// The block that was clicked.
+    pub location: Position,
+    /// The side of it that was clicked: bottom, top, north, south, west, then east.
+    pub face: VarInt,
+    pub cursor_x: f32,
+    pub cursor_y: f32,
+    pub cursor_z: f32,
+    pub inside_block: bool,
+    /// Acknowledged once the click is handled, see [AcknowledgeBlockChange].
+    pub sequence: VarInt,
+}
+
+impl IncomingPacket for UseItemOn {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        if let Some(placed) = self.placed_at() {
+            protection::check_edit(conn_id, placed, &state).await?;
+        }
+
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(AcknowledgeBlockChange::new(self.sequence))
+            .await
+    }
+}
+
+impl UseItemOn {
+    /// Where a block placed by this click ends up, next to the clicked face.
+    fn placed_at(&self) -> Option<(i32, i32, i32)> {
+        let Position { x, y, z } = self.location;
+        let y = y as i32;
+        match self.face.get_val() {
+            0 => Some((x, y - 1, z)),
+            1 => Some((x, y + 1, z)),
+            2 => Some((x, y, z - 1)),
+            3 => Some((x, y, z + 1)),
+            4 => Some((x - 1, y, z)),
+            5 => Some((x + 1, y, z)),
+            _ => None,
+        }
+    }
+}
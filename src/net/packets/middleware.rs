@@ -0,0 +1,139 @@
+//! Hooks that see every incoming packet before it's handled, e.g. for anti-cheat or logging.
+//!
+//! Middleware runs in the order it was registered. Each one can inspect the packet, change it
+//! through [InterceptedPacket::downcast_mut], or veto it so no later middleware or handler sees it.
+
+use std::any::Any;
+use std::sync::RwLock;
+
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+
+/// The middleware added with [register_middleware], kept per server in [GlobalState].
+#[derive(Default)]
+pub struct MiddlewareChain(RwLock<Vec<Box<dyn PacketMiddleware>>>);
+
+/// What should happen to a packet after a middleware has seen it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Pass the packet on to the next middleware, and then its handler.
+    Allow,
+    /// Drop the packet without handling it.
+    Veto,
+}
+
+pub trait PacketMiddleware: Send + Sync {
+    fn intercept(&self, packet: &mut InterceptedPacket) -> Verdict;
+}
+
+/// A decoded packet about to be handled.
+pub struct InterceptedPacket<'a> {
+    pub conn_id: ConnectionId,
+    pub packet_id: u8,
+    /// The connection state the packet was decoded in, e.g. "play".
+    pub conn_state: &'static str,
+    packet: &'a mut dyn Any,
+}
+
+impl InterceptedPacket<'_> {
+    /// The packet as its concrete type, e.g. `ChatMessage`, or `None` if it's another packet.
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.packet.downcast_ref()
+    }
+
+    pub fn downcast_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.packet.downcast_mut()
+    }
+}
+
+/// Adds a middleware to the end of `state`'s chain.
+pub fn register_middleware(state: &GlobalState, middleware: Box<dyn PacketMiddleware>) {
+    state.middleware.0.write().unwrap().push(middleware);
+}
+
+/// Runs `packet` through every middleware registered with `state`, stopping at the first veto.
+///
+/// Called by the generated packet handlers before [IncomingPacket::handle](super::IncomingPacket).
+pub fn intercept<T: 'static>(
+    state: &GlobalState,
+    conn_id: ConnectionId,
+    packet_id: u8,
+    conn_state: &'static str,
+    packet: &mut T,
+) -> Verdict {
+    let middleware = state.middleware.0.read().unwrap();
+    if middleware.is_empty() {
+        return Verdict::Allow;
+    }
+
+    let mut intercepted = InterceptedPacket {
+        conn_id,
+        packet_id,
+        conn_state,
+        packet,
+    };
+    for middleware in middleware.iter() {
+        if middleware.intercept(&mut intercepted) == Verdict::Veto {
+            return Verdict::Veto;
+        }
+    }
+    Verdict::Allow
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::net::packets::decode_packet;
+    use crate::net::packets::incoming::ping::Ping;
+    use crate::net::State;
+    use crate::tests::helpers::{add_test_player, test_state};
+
+    /// Vetoes pings from one connection.
+    struct PingFilter {
+        blocked: ConnectionId,
+    }
+
+    impl PacketMiddleware for PingFilter {
+        fn intercept(&self, packet: &mut InterceptedPacket) -> Verdict {
+            if packet.conn_id == self.blocked && packet.downcast_ref::<Ping>().is_some() {
+                return Verdict::Veto;
+            }
+            Verdict::Allow
+        }
+    }
+
+    async fn send_ping(conn_id: ConnectionId, state: &crate::state::GlobalState) {
+        let mut body = Cursor::new(42i64.to_be_bytes().to_vec());
+        let handler = decode_packet(0x01, &State::Status, &mut body)
+            .await
+            .unwrap()
+            .unwrap();
+        handler(conn_id, state.clone()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_veto_stops_handler() {
+        let state = test_state().await;
+        let (blocked, _blocked_client) = add_test_player(&state, "Blocked").await;
+        let (allowed, _allowed_client) = add_test_player(&state, "Allowed").await;
+        register_middleware(&state, Box::new(PingFilter { blocked }));
+
+        // Handling a ping flags the connection to be dropped
+        send_ping(blocked, &state).await;
+        let conn = state.connections.get_connection(blocked).unwrap();
+        assert!(!conn.read().await.drop);
+
+        send_ping(allowed, &state).await;
+        let conn = state.connections.get_connection(allowed).unwrap();
+        assert!(conn.read().await.drop);
+
+        // Other servers don't see it
+        let other = test_state().await;
+        let (blocked, _blocked_client) = add_test_player(&other, "Blocked").await;
+        send_ping(blocked, &other).await;
+        let conn = other.connections.get_connection(blocked).unwrap();
+        assert!(conn.read().await.drop);
+    }
+}
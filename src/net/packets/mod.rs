@@ -4,6 +4,8 @@ use crate::state::GlobalState;
 use crate::utils::prelude::*;
 
 pub mod incoming;
+pub mod channels;
+pub mod middleware;
 pub mod outgoing;
 
 pub type ConnectionId = u32;
@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Tells the client the server has handled its block changes up to `sequence`, so it can stop
+/// predicting them and show the blocks the server sent instead.
+#[derive(NetEncode)]
+pub struct AcknowledgeBlockChange {
+    #[encode(default = VarInt::from(0x06))]
+    pub packet_id: VarInt,
+    pub sequence: VarInt,
+}
+
+impl AcknowledgeBlockChange {
+    pub fn new(sequence: VarInt) -> Self {
+        Self::new_auto(sequence)
+    }
+}
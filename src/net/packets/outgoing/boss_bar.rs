@@ -0,0 +1,115 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::json;
+
+use crate::net::boss_bar::{BossBar, BossBarColor, BossBarDivision};
+
+const ADD: i32 = 0;
+const REMOVE: i32 = 1;
+const UPDATE_HEALTH: i32 = 2;
+const UPDATE_TITLE: i32 = 3;
+const UPDATE_STYLE: i32 = 4;
+
+/// Shows, changes or hides a boss bar. Which of the optional fields are sent depends on the
+/// action, adding one sends them all.
+#[derive(NetEncode)]
+pub struct BossBarOut {
+    #[encode(default = VarInt::from(0x0B))]
+    pub packet_id: VarInt,
+    pub uuid: u128,
+    pub action: VarInt,
+    /// A JSON text component.
+    pub title: Option<String>,
+    /// From 0 to 1.
+    pub health: Option<f32>,
+    pub color: Option<VarInt>,
+    pub division: Option<VarInt>,
+    /// 0x01 darkens the sky, 0x02 plays the end music, 0x04 creates fog.
+    pub flags: Option<u8>,
+}
+
+impl BossBarOut {
+    pub fn add(bar: &BossBar) -> Self {
+        Self::new_auto(
+            bar.uuid,
+            VarInt::from(ADD),
+            Some(text(&bar.title)),
+            Some(bar.health),
+            Some(VarInt::from(bar.color as i32)),
+            Some(VarInt::from(bar.division as i32)),
+            Some(0),
+        )
+    }
+
+    pub fn remove(uuid: u128) -> Self {
+        Self::new_auto(uuid, VarInt::from(REMOVE), None, None, None, None, None)
+    }
+
+    pub fn update_health(uuid: u128, health: f32) -> Self {
+        Self::new_auto(uuid, VarInt::from(UPDATE_HEALTH), None, Some(health), None, None, None)
+    }
+
+    pub fn update_title(uuid: u128, title: &str) -> Self {
+        Self::new_auto(uuid, VarInt::from(UPDATE_TITLE), Some(text(title)), None, None, None, None)
+    }
+
+    pub fn update_style(uuid: u128, color: BossBarColor, division: BossBarDivision) -> Self {
+        Self::new_auto(
+            uuid,
+            VarInt::from(UPDATE_STYLE),
+            None,
+            None,
+            Some(VarInt::from(color as i32)),
+            Some(VarInt::from(division as i32)),
+            None,
+        )
+    }
+}
+
+fn text(text: &str) -> String {
+    json!({ "text": text }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    async fn encode(packet: BossBarOut) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        packet.net_encode(&mut encoded).await.unwrap();
+        encoded
+    }
+
+    #[tokio::test]
+    async fn test_encode_add() {
+        let mut bar = BossBar::new("Raid");
+        bar.health = 0.5;
+        bar.color = BossBarColor::Red;
+        let encoded = encode(BossBarOut::add(&bar)).await;
+
+        let title = r#"{"text":"Raid"}"#;
+        let mut body = vec![0x0B];
+        body.extend_from_slice(&bar.uuid.to_be_bytes());
+        body.extend_from_slice(&[ADD as u8, title.len() as u8]);
+        body.extend_from_slice(title.as_bytes());
+        body.extend_from_slice(&0.5f32.to_be_bytes());
+        // Red, no notches, no flags
+        body.extend_from_slice(&[2, 0, 0]);
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+
+    #[tokio::test]
+    async fn test_encode_update_health() {
+        let encoded = encode(BossBarOut::update_health(7, 0.25)).await;
+
+        let mut body = vec![0x0B];
+        body.extend_from_slice(&7u128.to_be_bytes());
+        body.push(UPDATE_HEALTH as u8);
+        body.extend_from_slice(&0.25f32.to_be_bytes());
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+}
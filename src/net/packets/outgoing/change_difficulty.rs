@@ -0,0 +1,41 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::world::difficulty::Difficulty;
+
+/// Tells the client which difficulty to show in its settings.
+#[derive(NetEncode)]
+pub struct ChangeDifficulty {
+    #[encode(default = VarInt::from(0x0C))]
+    pub packet_id: VarInt,
+    pub difficulty: u8,
+    /// Locked difficulties can't be changed from the client's settings screen.
+    pub locked: bool,
+}
+
+impl ChangeDifficulty {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self::new_auto(difficulty as u8, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+    use crate::utils::config::ServerConfig;
+
+    #[tokio::test]
+    async fn test_encode_configured_difficulty() {
+        let mut config = ServerConfig::default();
+        config.difficulty = "hard".to_string();
+
+        let packet = ChangeDifficulty::new(config.difficulty.parse().unwrap());
+        let mut encoded = Vec::new();
+        packet.net_encode(&mut encoded).await.unwrap();
+
+        // Length, packet id, difficulty, locked
+        assert_eq!(encoded, vec![3, 0x0C, 3, 0]);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::state::GlobalState;
 use crate::utils::encoding::bitset::BitSet;
 use crate::utils::error::Error;
-use crate::world::chunk_format::{Heightmaps};
+use crate::utils::config::get_global_config;
+use crate::world::chunk_format::{Chunk, Heightmaps};
+use crate::world::lighting;
 use crate::Result;
 use ferrumc_codec::enc::NetEncode;
 use ferrumc_codec::network_types::varint::VarInt;
@@ -52,25 +54,25 @@ impl ChunkDataAndUpdateLight {
     pub async fn new(state: GlobalState, chunk_x: i32, chunk_z: i32) -> Result<Self> {
         let chunk = state
             .database
-            .get_chunk(chunk_x, chunk_z, "overworld".to_string())
+            .get_chunk_default(chunk_x, chunk_z)
             .await?
             .ok_or(Error::ChunkNotFound(chunk_x, chunk_z))?;
+        Self::from_chunk(chunk).await
+    }
 
+    /// Builds the packet for a chunk already loaded. Packing the sections and computing light
+    /// is CPU heavy, see [crate::net::utils::chunk_encoder] for doing it off the async runtime.
+    pub async fn from_chunk(chunk: Chunk) -> Result<Self> {
+        let sections = chunk.to_network_sections().await?;
+        Self::with_sections(chunk, sections)
+    }
 
-        // Serialize the chunk data
-        let mut data = Cursor::new(Vec::new());
-
-        if let Some(sections) = &chunk.sections {
-            for section in sections {
-                section.net_encode(&mut data).await?;
-                serialize_biomes().await?.net_encode(&mut data).await?;
-            }
-        } else {
-            return Err(Error::InvalidChunk(
-                chunk.x_pos,
-                chunk.z_pos,
-                "Chunk is missing sections".to_string(),
-            ));
+    /// Like [ChunkDataAndUpdateLight::from_chunk], with the sections already encoded by
+    /// [Chunk::to_network_sections].
+    pub fn with_sections(mut chunk: Chunk, sections: Vec<u8>) -> Result<Self> {
+        let (chunk_x, chunk_z) = (chunk.x_pos, chunk.z_pos);
+        if get_global_config().compute_light {
+            lighting::compute_light(&mut chunk)?;
         }
 
         // 24 is the number of sections in a chunk
@@ -141,7 +143,7 @@ impl ChunkDataAndUpdateLight {
             chunk_x,
             chunk_z,
             heightmaps,
-            data: data.into_inner(),
+            data: sections,
             block_entities_count: VarInt::from(0),
             block_entities: Vec::new(),
             sky_light_mask,
@@ -156,6 +158,28 @@ impl ChunkDataAndUpdateLight {
         Ok(res)
     }
 }
+
+impl Chunk {
+    /// Encodes the sections the way the packet carries them. The chunk must be in network mode,
+    /// see [Chunk::convert_to_net_mode].
+    pub async fn to_network_sections(&self) -> Result<Vec<u8>> {
+        let Some(sections) = &self.sections else {
+            return Err(Error::InvalidChunk(
+                self.x_pos,
+                self.z_pos,
+                "Chunk is missing sections".to_string(),
+            ));
+        };
+
+        let mut data = Cursor::new(Vec::new());
+        for section in sections {
+            section.net_encode(&mut data).await?;
+            serialize_biomes().await?.net_encode(&mut data).await?;
+        }
+        Ok(data.into_inner())
+    }
+}
+
 /*
 async fn serialize_block_states(block_states: &BlockStates) -> Result<Vec<u8>> {
     let mut data = Vec::new();
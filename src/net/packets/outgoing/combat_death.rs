@@ -0,0 +1,23 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::json;
+
+/// Shows the death screen, with `message` as the cause of death.
+#[derive(NetEncode)]
+pub struct CombatDeath {
+    #[encode(default = VarInt::from(0x38))]
+    pub packet_id: VarInt,
+    /// The network id of the player that died, who this is sent to.
+    pub player_id: VarInt,
+    /// A JSON text component.
+    pub message: String,
+}
+
+impl CombatDeath {
+    pub fn new(player_id: i32, message: impl Into<String>) -> Self {
+        Self::new_auto(
+            VarInt::from(player_id),
+            json!({ "text": message.into() }).to_string(),
+        )
+    }
+}
@@ -0,0 +1,46 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::commands::completion::Completions;
+
+/// The answer to a [crate::net::packets::incoming::command_suggestions_request::CommandSuggestionsRequest].
+///
+/// `start` and `length` are the part of the typed text that the matches replace.
+#[derive(NetEncode)]
+pub struct CommandSuggestionsResponse {
+    #[encode(default = VarInt::from(0x0F))]
+    pub packet_id: VarInt,
+    pub transaction_id: VarInt,
+    pub start: VarInt,
+    pub length: VarInt,
+    pub count: VarInt,
+    pub matches: Vec<Suggestion>,
+}
+
+#[derive(NetEncode)]
+pub struct Suggestion {
+    pub text: String,
+    // No tooltips for now
+    pub has_tooltip: bool,
+}
+
+impl CommandSuggestionsResponse {
+    pub fn new(transaction_id: VarInt, completions: Completions) -> Self {
+        let matches: Vec<Suggestion> = completions
+            .matches
+            .into_iter()
+            .map(|text| Suggestion {
+                text,
+                has_tooltip: false,
+            })
+            .collect();
+
+        Self::new_auto(
+            transaction_id,
+            VarInt::from(completions.start as i32),
+            VarInt::from(completions.length as i32),
+            VarInt::from(matches.len() as i32),
+            matches,
+        )
+    }
+}
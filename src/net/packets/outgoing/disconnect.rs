@@ -0,0 +1,20 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::json;
+
+/// Disconnects a player that is in the play state, showing them `reason`.
+///
+/// `reason` is a JSON text component.
+#[derive(NetEncode)]
+pub struct Disconnect {
+    #[encode(default = VarInt::from(0x1A))]
+    pub packet_id: VarInt,
+    pub reason: String,
+}
+
+impl Disconnect {
+    /// Disconnect with a plain text reason.
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self::new_auto(json!({ "text": reason.into() }).to_string())
+    }
+}
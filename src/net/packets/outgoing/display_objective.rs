@@ -0,0 +1,26 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+/// Where on the screen an objective is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i8)]
+pub enum DisplaySlot {
+    List = 0,
+    Sidebar = 1,
+    BelowName = 2,
+}
+
+/// Shows an objective in a display slot, or clears the slot if the name is empty.
+#[derive(NetEncode)]
+pub struct DisplayObjective {
+    #[encode(default = VarInt::from(0x51))]
+    pub packet_id: VarInt,
+    pub position: i8,
+    pub score_name: String,
+}
+
+impl DisplayObjective {
+    pub fn new(slot: DisplaySlot, objective_name: &str) -> Self {
+        Self::new_auto(slot as i8, objective_name.to_string())
+    }
+}
@@ -0,0 +1,64 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::gamemode::GameMode;
+
+/// The Game Event event that starts rain.
+pub const BEGIN_RAINING: u8 = 1;
+/// The Game Event event that stops rain.
+pub const END_RAINING: u8 = 2;
+/// The Game Event event that changes the client's game mode.
+pub const CHANGE_GAME_MODE: u8 = 3;
+/// The Game Event event that sets how heavy the rain is, from 0 to 1.
+pub const RAIN_LEVEL_CHANGE: u8 = 7;
+/// The Game Event event that sets how stormy it is, from 0 to 1.
+pub const THUNDER_LEVEL_CHANGE: u8 = 8;
+
+/// Tells the client about a change to the game, see the `event` constants like [CHANGE_GAME_MODE].
+#[derive(NetEncode)]
+pub struct GameEvent {
+    #[encode(default = VarInt::from(0x1F))]
+    pub packet_id: VarInt,
+    pub event: u8,
+    pub value: f32,
+}
+
+impl GameEvent {
+    pub fn change_game_mode(mode: GameMode) -> Self {
+        Self::new_auto(CHANGE_GAME_MODE, mode as u8 as f32)
+    }
+
+    /// Starts or stops the rain. The rain level is still needed to actually see it.
+    pub fn raining(raining: bool) -> Self {
+        let event = if raining { BEGIN_RAINING } else { END_RAINING };
+        Self::new_auto(event, 0.0)
+    }
+
+    pub fn rain_level(level: f32) -> Self {
+        Self::new_auto(RAIN_LEVEL_CHANGE, level.clamp(0.0, 1.0))
+    }
+
+    pub fn thunder_level(level: f32) -> Self {
+        Self::new_auto(THUNDER_LEVEL_CHANGE, level.clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_change_game_mode() {
+        let mut encoded = Vec::new();
+        GameEvent::change_game_mode(GameMode::Adventure)
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+
+        let mut expected = vec![6, 0x1F, CHANGE_GAME_MODE];
+        expected.extend_from_slice(&2.0f32.to_be_bytes());
+        assert_eq!(encoded, expected);
+    }
+}
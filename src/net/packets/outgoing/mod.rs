@@ -9,3 +9,39 @@ pub mod set_center_chunk;
 pub mod status;
 pub mod synchronize_player_position;
 pub mod login_plugin_request;
+pub mod system_chat_message;
+pub mod spawn_player;
+pub mod remove_entities;
+pub mod player_info_update;
+pub mod player_info_remove;
+pub mod update_entity_position;
+pub mod update_entity_position_and_rotation;
+pub mod update_entity_rotation;
+pub mod teleport_entity;
+pub mod set_head_rotation;
+pub mod set_entity_metadata;
+pub mod disconnect;
+pub mod command_suggestions_response;
+pub mod plugin_message;
+pub mod resource_pack;
+pub mod change_difficulty;
+pub mod game_event;
+pub mod set_health;
+pub mod combat_death;
+pub mod respawn;
+pub mod set_container_content;
+pub mod acknowledge_block_change;
+pub mod display_objective;
+pub mod update_objectives;
+pub mod update_score;
+pub mod update_teams;
+pub mod boss_bar;
+pub mod set_title_text;
+pub mod set_subtitle_text;
+pub mod set_action_bar_text;
+pub mod set_title_animation_times;
+pub mod sound_effect;
+pub mod entity_sound_effect;
+pub mod particle;
+pub mod update_time;
+pub mod update_section_blocks;
@@ -0,0 +1,73 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::particle::ParticleType;
+
+/// Spawns particles around a position.
+#[derive(NetEncode)]
+pub struct Particle {
+    #[encode(default = VarInt::from(0x26))]
+    pub packet_id: VarInt,
+    pub particle: VarInt,
+    /// Shows the particles from up to 512 blocks away instead of 32.
+    pub long_distance: bool,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// How far the particles are spread out on each axis.
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub offset_z: f32,
+    pub speed: f32,
+    pub count: i32,
+}
+
+impl Particle {
+    pub fn new(
+        particle: ParticleType,
+        (x, y, z): (f64, f64, f64),
+        count: i32,
+        (offset_x, offset_y, offset_z): (f32, f32, f32),
+        speed: f32,
+    ) -> Self {
+        Self::new_auto(
+            VarInt::from(particle.0),
+            false,
+            x,
+            y,
+            z,
+            offset_x,
+            offset_y,
+            offset_z,
+            speed,
+            count,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_particle() {
+        let mut encoded = Vec::new();
+        Particle::new(ParticleType(28), (0.5, 65.0, -3.5), 10, (0.2, 0.0, 0.2), 0.01)
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+
+        let mut body = vec![0x26, 28, 0];
+        for coordinate in [0.5f64, 65.0, -3.5] {
+            body.extend_from_slice(&coordinate.to_be_bytes());
+        }
+        for value in [0.2f32, 0.0, 0.2, 0.01] {
+            body.extend_from_slice(&value.to_be_bytes());
+        }
+        body.extend_from_slice(&10i32.to_be_bytes());
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+}
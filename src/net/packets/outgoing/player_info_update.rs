@@ -0,0 +1,49 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::player::Player;
+
+/// The "Add Player" action, name and (empty) properties.
+const ACTION_ADD_PLAYER: u8 = 0x01;
+/// The "Update Listed" action, whether the player shows up in the tab list.
+const ACTION_UPDATE_LISTED: u8 = 0x08;
+
+/// Adds players to the client's player list, which it needs before it can spawn them.
+#[derive(NetEncode)]
+pub struct PlayerInfoUpdate {
+    #[encode(default = VarInt::from(0x3A))]
+    pub packet_id: VarInt,
+    pub actions: u8,
+    pub player_count: VarInt,
+    pub players: Vec<PlayerInfoEntry>,
+}
+
+#[derive(NetEncode)]
+pub struct PlayerInfoEntry {
+    pub uuid: u128,
+    pub username: String,
+    // No skins for now
+    pub property_count: VarInt,
+    pub listed: bool,
+}
+
+impl PlayerInfoUpdate {
+    pub fn add_players<'a>(players: impl IntoIterator<Item = &'a Player>) -> Self {
+        let players: Vec<PlayerInfoEntry> = players
+            .into_iter()
+            .map(|player| PlayerInfoEntry {
+                uuid: player.uuid,
+                username: player.username.clone(),
+                property_count: VarInt::from(0),
+                listed: true,
+            })
+            .collect();
+
+        Self::new_auto(
+            ACTION_ADD_PLAYER | ACTION_UPDATE_LISTED,
+            VarInt::from(players.len() as i32),
+            players,
+        )
+    }
+}
@@ -0,0 +1,17 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+/// A message to the client on a plugin channel. `data` is sent as is, without a length prefix.
+#[derive(NetEncode)]
+pub struct PluginMessageOut {
+    #[encode(default = VarInt::from(0x17))]
+    pub packet_id: VarInt,
+    pub channel: String,
+    pub data: Vec<u8>,
+}
+
+impl PluginMessageOut {
+    pub fn new(channel: impl Into<String>, data: Vec<u8>) -> Self {
+        Self::new_auto(channel.into(), data)
+    }
+}
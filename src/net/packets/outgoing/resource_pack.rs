@@ -0,0 +1,71 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::json;
+
+use crate::utils::config::ResourcePack;
+
+/// Asks the client to download a resource pack. The client answers with
+/// [ResourcePackStatus](crate::net::packets::incoming::resource_pack_status::ResourcePackStatus).
+#[derive(NetEncode)]
+pub struct ResourcePackOut {
+    #[encode(default = VarInt::from(0x40))]
+    pub packet_id: VarInt,
+    pub url: String,
+    pub hash: String,
+    pub forced: bool,
+    pub has_prompt: bool,
+    /// A JSON text component, only sent if `has_prompt` is set.
+    pub prompt: Option<String>,
+}
+
+impl ResourcePackOut {
+    /// The pack from the config, or `None` if no pack is configured.
+    pub fn from_config(config: &ResourcePack) -> Option<Self> {
+        if !config.is_enabled() {
+            return None;
+        }
+        let prompt = (!config.prompt.is_empty())
+            .then(|| json!({ "text": config.prompt }).to_string());
+        Some(Self::new_auto(
+            config.url.clone(),
+            config.hash.to_lowercase(),
+            config.required,
+            prompt.is_some(),
+            prompt,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_configured_pack() {
+        let config = ResourcePack {
+            url: "https://example.com/pack.zip".to_string(),
+            hash: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            required: true,
+            prompt: String::new(),
+        };
+        let packet = ResourcePackOut::from_config(&config).unwrap();
+        let mut encoded = Vec::new();
+        packet.net_encode(&mut encoded).await.unwrap();
+
+        let mut body = vec![0x40, config.url.len() as u8];
+        body.extend_from_slice(config.url.as_bytes());
+        body.push(40);
+        body.extend_from_slice(config.hash.as_bytes());
+        // Forced, without a prompt
+        body.extend_from_slice(&[1, 0]);
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+
+    #[test]
+    fn test_no_pack_configured() {
+        assert!(ResourcePackOut::from_config(&ResourcePack::default()).is_none());
+    }
+}
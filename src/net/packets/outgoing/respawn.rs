@@ -0,0 +1,40 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::gamemode::GameMode;
+
+/// Puts the client back in the world after dying, or in another dimension.
+#[derive(NetEncode)]
+pub struct Respawn {
+    #[encode(default = VarInt::from(0x41))]
+    pub packet_id: VarInt,
+    pub dimension_type: String,
+    pub dimension_name: String,
+    pub seed_hash: i64,
+    pub gamemode: u8,
+    pub previous_gamemode: i8,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    pub has_death_location: bool,
+    pub portal_cooldown: VarInt,
+    /// Which data the client keeps, 0 resets its attributes and entity metadata.
+    pub data_kept: u8,
+}
+
+impl Respawn {
+    /// Respawns in the overworld, which is the only dimension there is.
+    pub fn overworld(seed_hash: i64, gamemode: GameMode) -> Self {
+        Self::new_auto(
+            "minecraft:overworld".to_string(),
+            "minecraft:overworld".to_string(),
+            seed_hash,
+            gamemode as u8,
+            -1,
+            false,
+            false,
+            false,
+            VarInt::new(0),
+            0,
+        )
+    }
+}
@@ -0,0 +1,42 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::{json, Value};
+
+/// Text shown above the hotbar for a few seconds.
+#[derive(NetEncode)]
+pub struct SetActionBarText {
+    #[encode(default = VarInt::from(0x46))]
+    pub packet_id: VarInt,
+    /// A JSON text component.
+    pub text: String,
+}
+
+impl SetActionBarText {
+    pub fn new(component: &Value) -> Self {
+        Self::new_auto(component.to_string())
+    }
+
+    /// Plain, unformatted text.
+    pub fn plain(text: &str) -> Self {
+        Self::new(&json!({ "text": text }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_action_bar() {
+        let mut encoded = Vec::new();
+        SetActionBarText::plain("Hi").net_encode(&mut encoded).await.unwrap();
+
+        let text = r#"{"text":"Hi"}"#;
+        let mut body = vec![0x46, text.len() as u8];
+        body.extend_from_slice(text.as_bytes());
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+}
@@ -0,0 +1,67 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::inventory::Inventory;
+use crate::utils::encoding::item_stack::ItemStack;
+
+/// The window id of the player inventory, which is always open.
+pub const PLAYER_INVENTORY: u8 = 0;
+
+/// Replaces every slot of a window, e.g. the whole player inventory.
+#[derive(NetEncode)]
+pub struct SetContainerContent {
+    #[encode(default = VarInt::from(0x12))]
+    pub packet_id: VarInt,
+    pub window_id: u8,
+    pub state_id: VarInt,
+    pub count: VarInt,
+    pub slots: Vec<ItemStack>,
+    /// The item held by the cursor.
+    pub carried_item: ItemStack,
+}
+
+impl SetContainerContent {
+    pub fn player_inventory(inventory: &Inventory) -> Self {
+        let slots = inventory.slots();
+        Self::new_auto(
+            PLAYER_INVENTORY,
+            VarInt::from(inventory.state_id),
+            VarInt::from(slots.len() as i32),
+            slots,
+            inventory.carried().cloned().unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+    use crate::utils::components::inventory::layout;
+
+    #[tokio::test]
+    async fn test_encode_item_in_slot() {
+        let mut inventory = Inventory::new();
+        let slot = Inventory::hotbar_slot(0).unwrap();
+        inventory.set(slot, Some(ItemStack::new(5, 3))).unwrap();
+
+        let mut encoded = Vec::new();
+        SetContainerContent::player_inventory(&inventory)
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+
+        // Length, packet id, window, state id, slot count
+        assert_eq!(encoded[1..5], [0x12, PLAYER_INVENTORY, 1, layout::SIZE as u8]);
+        // Every empty slot is a single byte, so the item starts at its slot index
+        let slots = &encoded[5..];
+        assert!(slots[..slot].iter().all(|present| *present == 0));
+        assert_eq!(slots[slot..slot + 4], [1, 5, 3, 0]);
+        let rest = &slots[slot + 4..];
+        // The slots after it, then the carried item
+        assert_eq!(rest.len(), layout::SIZE - slot - 1 + 1);
+        assert!(rest.iter().all(|present| *present == 0));
+        assert_eq!(encoded[0] as usize, encoded.len() - 1);
+    }
+}
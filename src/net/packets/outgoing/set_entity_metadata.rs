@@ -0,0 +1,50 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::entity_metadata::EntityMetadata;
+
+/// Updates some of an entity's metadata, see [crate::utils::components::metadata::Metadata].
+#[derive(NetEncode)]
+pub struct SetEntityMetadata {
+    #[encode(default = VarInt::from(0x52))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub metadata: EntityMetadata,
+}
+
+impl SetEntityMetadata {
+    pub fn new(entity_id: i32, metadata: EntityMetadata) -> Self {
+        Self::new_auto(VarInt::from(entity_id), metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::components::metadata::{Metadata, SNEAKING};
+
+    #[tokio::test]
+    async fn test_encode_metadata_update() {
+        let mut metadata = Metadata::default();
+        metadata.set_flag(SNEAKING, true);
+        metadata.set_custom_name(Some("Bob"));
+
+        let mut encoded = Vec::new();
+        SetEntityMetadata::new(5, metadata.take_changes())
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+
+        let name = json!({ "text": "Bob" }).to_string();
+        // Packet id and entity id, then index 0 as a byte, index 2 as optional text
+        let mut body = vec![0x52, 5, 0, 0, SNEAKING, 2, 6, 1, name.len() as u8];
+        body.extend_from_slice(name.as_bytes());
+        body.push(0xFF);
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+}
@@ -0,0 +1,12 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Turns an entity's head. Body rotation packets don't, so this is sent alongside them.
+#[derive(NetEncode, Debug)]
+pub struct SetHeadRotation {
+    #[encode(default = VarInt::from(0x42))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub head_yaw: u8,
+}
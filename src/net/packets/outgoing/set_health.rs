@@ -0,0 +1,43 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::health::{Food, Health};
+
+/// Updates the health and hunger bars. A health of 0 makes the client show the death screen.
+#[derive(NetEncode)]
+pub struct SetHealth {
+    #[encode(default = VarInt::from(0x57))]
+    pub packet_id: VarInt,
+    pub health: f32,
+    pub food: VarInt,
+    pub food_saturation: f32,
+}
+
+impl SetHealth {
+    pub fn new(health: &Health, food: &Food) -> Self {
+        Self::new_auto(health.get(), VarInt::from(food.level()), food.saturation())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_set_health() {
+        let mut encoded = Vec::new();
+        SetHealth::new(&Health::new(12.5), &Food::new(30, 4.0))
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+
+        // Length, packet id, health, food (clamped to 20), saturation
+        let mut expected = vec![10, 0x57];
+        expected.extend_from_slice(&12.5f32.to_be_bytes());
+        expected.push(20);
+        expected.extend_from_slice(&4.0f32.to_be_bytes());
+        assert_eq!(encoded, expected);
+    }
+}
@@ -0,0 +1,23 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::{json, Value};
+
+/// The smaller text under the title, shown with the next [super::set_title_text::SetTitleText].
+#[derive(NetEncode)]
+pub struct SetSubtitleText {
+    #[encode(default = VarInt::from(0x5D))]
+    pub packet_id: VarInt,
+    /// A JSON text component.
+    pub text: String,
+}
+
+impl SetSubtitleText {
+    pub fn new(component: &Value) -> Self {
+        Self::new_auto(component.to_string())
+    }
+
+    /// Plain, unformatted text.
+    pub fn plain(text: &str) -> Self {
+        Self::new(&json!({ "text": text }))
+    }
+}
@@ -0,0 +1,24 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::{json, Value};
+
+/// The big text in the middle of the screen. It's only shown once the client gets it, so
+/// send the subtitle and [super::set_title_animation_times::SetTitleAnimationTimes] first.
+#[derive(NetEncode)]
+pub struct SetTitleText {
+    #[encode(default = VarInt::from(0x5F))]
+    pub packet_id: VarInt,
+    /// A JSON text component.
+    pub text: String,
+}
+
+impl SetTitleText {
+    pub fn new(component: &Value) -> Self {
+        Self::new_auto(component.to_string())
+    }
+
+    /// Plain, unformatted text.
+    pub fn plain(text: &str) -> Self {
+        Self::new(&json!({ "text": text }))
+    }
+}
@@ -0,0 +1,78 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::sound_event::{SoundCategory, SoundEvent};
+
+/// Plays a sound at a position.
+#[derive(NetEncode)]
+pub struct SoundEffect {
+    #[encode(default = VarInt::from(0x62))]
+    pub packet_id: VarInt,
+    pub sound: SoundEvent,
+    pub category: VarInt,
+    /// Coordinates in 1/8ths of a block.
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub volume: f32,
+    pub pitch: f32,
+    /// Picks between the variants of a sound.
+    pub seed: i64,
+}
+
+impl SoundEffect {
+    pub fn new(
+        sound: SoundEvent,
+        category: SoundCategory,
+        (x, y, z): (f64, f64, f64),
+        volume: f32,
+        pitch: f32,
+        seed: i64,
+    ) -> Self {
+        let fixed = |coordinate: f64| (coordinate * 8.0) as i32;
+        Self::new_auto(
+            sound,
+            VarInt::from(category as i32),
+            fixed(x),
+            fixed(y),
+            fixed(z),
+            volume,
+            pitch,
+            seed,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_positioned_sound() {
+        let mut encoded = Vec::new();
+        SoundEffect::new(
+            SoundEvent::Registered(40),
+            SoundCategory::Blocks,
+            (1.5, 64.0, -2.25),
+            1.0,
+            0.5,
+            9,
+        )
+        .net_encode(&mut encoded)
+        .await
+        .unwrap();
+
+        // Packet id, sound id + 1, category, fixed point position, volume, pitch, seed
+        let mut body = vec![0x62, 41, 4];
+        body.extend_from_slice(&12i32.to_be_bytes());
+        body.extend_from_slice(&512i32.to_be_bytes());
+        body.extend_from_slice(&(-18i32).to_be_bytes());
+        body.extend_from_slice(&1.0f32.to_be_bytes());
+        body.extend_from_slice(&0.5f32.to_be_bytes());
+        body.extend_from_slice(&9i64.to_be_bytes());
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+}
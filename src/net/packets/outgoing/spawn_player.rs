@@ -0,0 +1,37 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::rotation::{to_angle, Rotation};
+use crate::utils::encoding::position::Position;
+
+/// Spawns another player's entity for the client.
+///
+/// The player has to be in the client's player list (see
+/// [crate::net::packets::outgoing::player_info_update::PlayerInfoUpdate]) first, or the client ignores it.
+#[derive(NetEncode)]
+pub struct SpawnPlayer {
+    #[encode(default = VarInt::from(0x03))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub uuid: u128,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: u8,
+    pub pitch: u8,
+}
+
+impl SpawnPlayer {
+    pub fn new(entity_id: i32, uuid: u128, position: &Position, rotation: &Rotation) -> Self {
+        Self::new_auto(
+            VarInt::from(entity_id),
+            uuid,
+            position.x as f64,
+            position.y as f64,
+            position.z as f64,
+            to_angle(rotation.yaw),
+            to_angle(rotation.pitch),
+        )
+    }
+}
@@ -0,0 +1,22 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::json;
+
+/// A chat message sent by the server (not another player), e.g. command feedback.
+///
+/// `content` is a JSON text component.
+/// If `overlay` is true, the message is shown above the hotbar instead of in the chat.
+#[derive(NetEncode)]
+pub struct SystemChatMessage {
+    #[encode(default = VarInt::from(0x64))]
+    pub packet_id: VarInt,
+    pub content: String,
+    pub overlay: bool,
+}
+
+impl SystemChatMessage {
+    /// A plain text message shown in the chat.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self::new_auto(json!({ "text": text.into() }).to_string(), false)
+    }
+}
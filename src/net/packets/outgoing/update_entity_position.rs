@@ -0,0 +1,15 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Moves an entity by a small amount. Deltas are in 1/4096ths of a block.
+#[derive(NetEncode, Debug)]
+pub struct UpdateEntityPosition {
+    #[encode(default = VarInt::from(0x2B))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub on_ground: bool,
+}
@@ -0,0 +1,17 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Moves and rotates an entity. Deltas are in 1/4096ths of a block.
+#[derive(NetEncode, Debug)]
+pub struct UpdateEntityPositionAndRotation {
+    #[encode(default = VarInt::from(0x2C))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
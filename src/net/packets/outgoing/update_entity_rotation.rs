@@ -0,0 +1,14 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Rotates an entity's body without moving it.
+#[derive(NetEncode, Debug)]
+pub struct UpdateEntityRotation {
+    #[encode(default = VarInt::from(0x2D))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
@@ -0,0 +1,81 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::json;
+
+const CREATE: i8 = 0;
+const REMOVE: i8 = 1;
+const UPDATE: i8 = 2;
+/// Scores are shown as numbers, rather than hearts.
+const INTEGER: i32 = 0;
+
+/// Creates, renames or removes a scoreboard objective.
+#[derive(NetEncode)]
+pub struct UpdateObjectives {
+    #[encode(default = VarInt::from(0x58))]
+    pub packet_id: VarInt,
+    pub objective_name: String,
+    pub mode: i8,
+    /// A JSON text component, not sent when removing.
+    pub display_name: Option<String>,
+    /// Not sent when removing.
+    pub render_type: Option<VarInt>,
+}
+
+impl UpdateObjectives {
+    pub fn create(name: &str, display_name: &str) -> Self {
+        Self::with_display_name(name, CREATE, display_name)
+    }
+
+    pub fn update(name: &str, display_name: &str) -> Self {
+        Self::with_display_name(name, UPDATE, display_name)
+    }
+
+    pub fn remove(name: &str) -> Self {
+        Self::new_auto(name.to_string(), REMOVE, None, None)
+    }
+
+    fn with_display_name(name: &str, mode: i8, display_name: &str) -> Self {
+        Self::new_auto(
+            name.to_string(),
+            mode,
+            Some(json!({ "text": display_name }).to_string()),
+            Some(VarInt::from(INTEGER)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_create() {
+        let mut encoded = Vec::new();
+        UpdateObjectives::create("kills", "Kills")
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+
+        let display_name = r#"{"text":"Kills"}"#;
+        let mut body = vec![0x58, 5];
+        body.extend_from_slice(b"kills");
+        body.push(CREATE as u8);
+        body.push(display_name.len() as u8);
+        body.extend_from_slice(display_name.as_bytes());
+        body.push(INTEGER as u8);
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+
+    #[tokio::test]
+    async fn test_encode_remove() {
+        let mut encoded = Vec::new();
+        UpdateObjectives::remove("kills")
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+        assert_eq!(encoded, [8, 0x58, 5, b'k', b'i', b'l', b'l', b's', REMOVE as u8]);
+    }
+}
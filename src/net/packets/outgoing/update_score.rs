@@ -0,0 +1,63 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+const SET: i32 = 0;
+const REMOVE: i32 = 1;
+
+/// Sets or removes the score of an entry, usually a player name, in an objective.
+#[derive(NetEncode)]
+pub struct UpdateScore {
+    #[encode(default = VarInt::from(0x5B))]
+    pub packet_id: VarInt,
+    pub entity_name: String,
+    pub action: VarInt,
+    pub objective_name: String,
+    /// Not sent when removing.
+    pub value: Option<VarInt>,
+}
+
+impl UpdateScore {
+    pub fn set(entity_name: &str, objective_name: &str, value: i32) -> Self {
+        Self::new_auto(
+            entity_name.to_string(),
+            VarInt::from(SET),
+            objective_name.to_string(),
+            Some(VarInt::from(value)),
+        )
+    }
+
+    pub fn remove(entity_name: &str, objective_name: &str) -> Self {
+        Self::new_auto(
+            entity_name.to_string(),
+            VarInt::from(REMOVE),
+            objective_name.to_string(),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_set() {
+        let mut encoded = Vec::new();
+        UpdateScore::set("Notch", "kills", 300)
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+
+        let mut body = vec![0x5B, 5];
+        body.extend_from_slice(b"Notch");
+        body.push(SET as u8);
+        body.push(5);
+        body.extend_from_slice(b"kills");
+        // 300 as a VarInt
+        body.extend_from_slice(&[0xAC, 0x02]);
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+}
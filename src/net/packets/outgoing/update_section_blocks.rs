@@ -0,0 +1,47 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+
+use ferrumc_macros::NetEncode;
+
+/// Changes several blocks in one chunk section, without resending the chunk.
+#[derive(NetEncode)]
+pub struct UpdateSectionBlocks {
+    #[encode(default = VarInt::from(0x43))]
+    pub packet_id: VarInt,
+    /// The section's x, z and y in sections, packed into 22, 22 and 20 bits.
+    pub section: i64,
+    pub count: VarInt,
+    /// Each block's state id, followed by its x, z and y within the section in 4 bits each.
+    pub blocks: Vec<Varlong>,
+}
+
+impl UpdateSectionBlocks {
+    /// `blocks` are the positions within the section, from 0 to 15, with their block state ids.
+    pub fn new(section: (i32, i32, i32), blocks: &[((i32, i32, i32), i32)]) -> Self {
+        let (x, y, z) = section;
+        let section =
+            ((x as i64 & 0x3FFFFF) << 42) | ((z as i64 & 0x3FFFFF) << 20) | (y as i64 & 0xFFFFF);
+        let blocks: Vec<Varlong> = blocks
+            .iter()
+            .map(|&((x, y, z), state_id)| {
+                let position = (x << 8) | (z << 4) | y;
+                Varlong::from(((state_id as i64) << 12) | position as i64)
+            })
+            .collect();
+        Self::new_auto(section, VarInt::from(blocks.len() as i32), blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_are_packed() {
+        let packet = UpdateSectionBlocks::new((-1, 4, 2), &[((3, 15, 7), 1)]);
+        assert_eq!(packet.section >> 42, -1);
+        assert_eq!((packet.section >> 20) & 0x3FFFFF, 2);
+        assert_eq!(packet.section & 0xFFFFF, 4);
+        assert_eq!(packet.blocks, vec![Varlong::from((1 << 12) | 0x37F)]);
+    }
+}
@@ -0,0 +1,111 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use serde_json::json;
+
+const CREATE: i8 = 0;
+const REMOVE: i8 = 1;
+const UPDATE: i8 = 2;
+const ADD_ENTITIES: i8 = 3;
+const REMOVE_ENTITIES: i8 = 4;
+
+/// How a team looks. Names are JSON text components.
+#[derive(NetEncode, Clone)]
+pub struct TeamInfo {
+    pub display_name: String,
+    /// 0x01 allows friendly fire, 0x02 shows invisible teammates.
+    pub friendly_flags: i8,
+    /// `always`, `hideForOtherTeams`, `hideForOwnTeam` or `never`.
+    pub name_tag_visibility: String,
+    /// `always`, `pushOtherTeams`, `pushOwnTeam` or `never`.
+    pub collision_rule: String,
+    /// A chat color id, 21 for none.
+    pub color: VarInt,
+    pub prefix: String,
+    pub suffix: String,
+}
+
+impl TeamInfo {
+    /// A team with a plain display name and the vanilla defaults for everything else.
+    pub fn named(display_name: &str) -> Self {
+        let text = |text: &str| json!({ "text": text }).to_string();
+        Self {
+            display_name: text(display_name),
+            friendly_flags: 0x01,
+            name_tag_visibility: "always".to_string(),
+            collision_rule: "always".to_string(),
+            color: VarInt::from(21),
+            prefix: text(""),
+            suffix: text(""),
+        }
+    }
+}
+
+/// Creates, changes or removes a team, or changes who's on it. Entities are player names, or
+/// UUIDs for other entities.
+#[derive(NetEncode)]
+pub struct UpdateTeams {
+    #[encode(default = VarInt::from(0x5A))]
+    pub packet_id: VarInt,
+    pub team_name: String,
+    pub mode: i8,
+    /// Only sent when creating or updating.
+    pub info: Option<TeamInfo>,
+    /// Only sent when creating the team or changing who's on it.
+    pub entity_count: Option<VarInt>,
+    pub entities: Option<Vec<String>>,
+}
+
+impl UpdateTeams {
+    pub fn create(name: &str, info: TeamInfo, entities: Vec<String>) -> Self {
+        Self::with_entities(name, CREATE, Some(info), entities)
+    }
+
+    pub fn update(name: &str, info: TeamInfo) -> Self {
+        Self::new_auto(name.to_string(), UPDATE, Some(info), None, None)
+    }
+
+    pub fn remove(name: &str) -> Self {
+        Self::new_auto(name.to_string(), REMOVE, None, None, None)
+    }
+
+    pub fn add_entities(name: &str, entities: Vec<String>) -> Self {
+        Self::with_entities(name, ADD_ENTITIES, None, entities)
+    }
+
+    pub fn remove_entities(name: &str, entities: Vec<String>) -> Self {
+        Self::with_entities(name, REMOVE_ENTITIES, None, entities)
+    }
+
+    fn with_entities(name: &str, mode: i8, info: Option<TeamInfo>, entities: Vec<String>) -> Self {
+        Self::new_auto(
+            name.to_string(),
+            mode,
+            info,
+            Some(VarInt::from(entities.len() as i32)),
+            Some(entities),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_add_entities() {
+        let mut encoded = Vec::new();
+        UpdateTeams::add_entities("red", vec!["Notch".to_string()])
+            .net_encode(&mut encoded)
+            .await
+            .unwrap();
+
+        let mut body = vec![0x5A, 3];
+        body.extend_from_slice(b"red");
+        body.extend_from_slice(&[ADD_ENTITIES as u8, 1, 5]);
+        body.extend_from_slice(b"Notch");
+        assert_eq!(encoded[0] as usize, body.len());
+        assert_eq!(&encoded[1..], body);
+    }
+}
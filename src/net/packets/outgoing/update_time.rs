@@ -0,0 +1,27 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+use crate::world::time::WorldTime;
+
+/// Sets the world's age and time of day. Clients keep advancing the time between updates, unless
+/// it's sent negative.
+#[derive(NetEncode)]
+pub struct UpdateTime {
+    #[encode(default = VarInt::from(0x5E))]
+    pub packet_id: VarInt,
+    pub world_age: i64,
+    pub time_of_day: i64,
+}
+
+impl UpdateTime {
+    /// The current time, frozen on the client unless `daylight_cycle` is on.
+    pub fn new(time: &WorldTime, daylight_cycle: bool) -> Self {
+        let time_of_day = match (daylight_cycle, time.time_of_day()) {
+            (true, time_of_day) => time_of_day,
+            // -0 would still advance
+            (false, 0) => -1,
+            (false, time_of_day) => -time_of_day,
+        };
+        Self::new_auto(time.age(), time_of_day)
+    }
+}
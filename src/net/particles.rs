@@ -0,0 +1,64 @@
+//! Spawning particles for the players that can see them.
+
+use crate::net::packets::incoming::client_info::ClientInfo;
+use crate::net::packets::outgoing::particle::Particle;
+use crate::net::utils::broadcast::broadcast_lossy_to;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::encoding::particle::ParticleType;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Spawns `count` particles of a type without extra data, e.g. `"flame"`, spread by `offset`
+/// around `position`. Sent to every player that has the chunk loaded.
+pub async fn spawn_particle(
+    particle: &str,
+    position: (f64, f64, f64),
+    count: i32,
+    offset: (f32, f32, f32),
+    speed: f32,
+    state: &GlobalState,
+) -> Result<()> {
+    let particle = ParticleType::from_name(particle)?;
+    let chunk = ((position.0 as i32) >> 4, (position.2 as i32) >> 4);
+    let server_view_distance = get_global_config().view_distance as i32;
+
+    let mut viewers = Vec::new();
+    let mut query = state
+        .world
+        .query::<(&Player, &Position, Option<&ClientInfo>)>();
+    while let Some((entity_id, (_, at, client_info))) = query.next().await {
+        let view_distance = client_info.map_or(server_view_distance, |info| {
+            (info.view_distance as i32).min(server_view_distance)
+        });
+        let (dx, dz) = ((at.x >> 4) - chunk.0, (at.z >> 4) - chunk.1);
+        if dx.abs() <= view_distance && dz.abs() <= view_distance {
+            viewers.push(entity_id as u32);
+        }
+    }
+
+    let packet = Particle::new(particle, position, count, offset, speed);
+    broadcast_lossy_to(&packet, state, viewers).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_spawn_particle() {
+        let state = test_state().await;
+        let (_, mut client) = add_test_player(&state, "Player").await;
+
+        spawn_particle("heart", (1.5, 65.0, 1.5), 3, (0.5, 0.5, 0.5), 0.0, &state)
+            .await
+            .unwrap();
+        let (id, body) = read_packet(&mut client).await;
+        assert_eq!((id, body[0]), (0x26, 38));
+        assert!(spawn_particle("dust", (0.0, 0.0, 0.0), 1, (0.0, 0.0, 0.0), 0.0, &state)
+            .await
+            .is_err());
+    }
+}
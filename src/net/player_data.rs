@@ -0,0 +1,197 @@
+//! Saves players' position, inventory, health and game mode when they leave, on autosave and on
+//! shutdown, and puts them back when they join again.
+
+use tracing::{info, warn};
+
+use crate::database::playerdata::PlayerData;
+use crate::net::drop_conn;
+use crate::net::packets::outgoing::disconnect::Disconnect;
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::GameMode;
+use crate::utils::components::health::{Food, Health};
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+impl PlayerData {
+    /// The current state of a player entity, with defaults for anything it doesn't have.
+    pub async fn capture(entity_id: u32, state: &GlobalState) -> Result<Self> {
+        let world = &state.world;
+        let position = world.get_component::<Position>(entity_id).await?.clone();
+        let rotation = world.get_component::<Rotation>(entity_id).await?.clone();
+        let health = world
+            .get_component::<Health>(entity_id)
+            .await
+            .map_or_else(|_| Health::default(), |health| *health);
+        let food = world
+            .get_component::<Food>(entity_id)
+            .await
+            .map_or_else(|_| Food::default(), |food| *food);
+        let gamemode = world
+            .get_component::<GameMode>(entity_id)
+            .await
+            .map_or_else(|_| GameMode::default(), |mode| *mode);
+        let inventory = world
+            .get_component::<Inventory>(entity_id)
+            .await
+            .map_or_else(|_| Vec::new(), |inventory| inventory.slots());
+
+        Ok(Self {
+            position: (position.x, position.y, position.z),
+            rotation: (rotation.yaw, rotation.pitch),
+            health: health.get(),
+            food: food.level(),
+            saturation: food.saturation(),
+            gamemode: gamemode as u8,
+            inventory,
+        })
+    }
+
+    /// Gives a player entity the saved state, replacing the defaults it joined with.
+    pub fn restore(&self, entity_id: u32, state: &GlobalState) {
+        let (x, y, z) = self.position;
+        let (yaw, pitch) = self.rotation;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Position::new(x, y, z))
+            .insert(entity_id, Rotation::new(yaw, pitch))
+            .insert(entity_id, Health::new(self.health))
+            .insert(entity_id, Food::new(self.food, self.saturation))
+            .insert(entity_id, self.gamemode())
+            .insert(entity_id, Inventory::from_slots(self.inventory.clone()));
+    }
+
+    /// The saved game mode, or survival if it's not a valid one.
+    pub fn gamemode(&self) -> GameMode {
+        GameMode::from_id(self.gamemode).unwrap_or_default()
+    }
+}
+
+/// Saves a player's current state. Does nothing if the entity isn't a player.
+pub async fn save_player(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let Ok(player) = state.world.get_component::<Player>(entity_id).await else {
+        return Ok(());
+    };
+    let uuid = player.uuid;
+    drop(player);
+
+    let data = PlayerData::capture(entity_id, state).await?;
+    state.database.save_player_data(uuid, &data).await
+}
+
+/// Saves every online player, returning how many were saved.
+pub async fn save_online_players(state: &GlobalState) -> usize {
+    let mut players = Vec::new();
+    let mut query = state.world.query::<&Player>();
+    while let Some((id, _)) = query.next().await {
+        players.push(id as u32);
+    }
+
+    let mut saved = 0;
+    for player in players {
+        match save_player(player, state).await {
+            Ok(()) => saved += 1,
+            Err(e) => warn!("Failed to save player {}: {}", player, e),
+        }
+    }
+    saved
+}
+
+/// What players are shown when the server stops.
+const SHUTDOWN_REASON: &str = "Server closed";
+
+/// Saves and disconnects every online player when the server stops, then flushes the database so
+/// their data is on disk before the process exits.
+pub async fn disconnect_all_players(state: &GlobalState) -> Result<()> {
+    let mut players = Vec::new();
+    let mut query = state.world.query::<&Player>();
+    while let Some((id, _)) = query.next().await {
+        players.push(id as u32);
+    }
+
+    for &player in &players {
+        if let Ok(conn) = state.connections.get_connection(player) {
+            let conn = conn.read().await;
+            if let Err(e) = conn.send_packet(Disconnect::new(SHUTDOWN_REASON)).await {
+                warn!("Failed to tell player {} about the shutdown: {}", player, e);
+            }
+        }
+        // Saves the player before the socket is closed
+        if let Err(e) = drop_conn(player, state.clone()).await {
+            warn!("Failed to disconnect player {}: {}", player, e);
+        }
+    }
+
+    state.database.flush().await?;
+    info!("Saved and disconnected {} players", players.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::drop_conn;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+    use crate::utils::encoding::item_stack::ItemStack;
+
+    #[tokio::test]
+    async fn test_player_persists_across_reconnect() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Player").await;
+        let uuid = state.world.get_component::<Player>(player).await.unwrap().uuid;
+        let mut inventory = Inventory::new();
+        inventory.set(36, Some(ItemStack::new(1, 32))).unwrap();
+        state
+            .world
+            .get_component_storage()
+            .insert(player, Position::new(120, 70, -45))
+            .insert(player, inventory)
+            .insert(player, Health::new(7.0));
+
+        drop_conn(player, state.clone()).await.unwrap();
+
+        // Joining again gets a new entity
+        let data = state.database.load_player_data(uuid).await.unwrap().unwrap();
+        let (rejoined, _client) = add_test_player(&state, "Player").await;
+        data.restore(rejoined, &state);
+
+        let position = state.world.get_component::<Position>(rejoined).await.unwrap();
+        assert_eq!((position.x, position.y, position.z), (120, 70, -45));
+        drop(position);
+        let inventory = state.world.get_component::<Inventory>(rejoined).await.unwrap();
+        assert_eq!(inventory.get(36), Some(&ItemStack::new(1, 32)));
+        drop(inventory);
+        let health = *state.world.get_component::<Health>(rejoined).await.unwrap();
+        assert_eq!(health.get(), 7.0);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_saves_online_players() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+        let uuid = state.world.get_component::<Player>(player).await.unwrap().uuid;
+        state
+            .world
+            .get_component_storage()
+            .insert(player, Position::new(-8, 80, 16));
+
+        disconnect_all_players(&state).await.unwrap();
+
+        assert_eq!(read_packet(&mut client).await.0, 0x1A);
+        assert!(state.connections.get_connection(player).is_err());
+        let data = state.database.load_player_data(uuid).await.unwrap().unwrap();
+        let (rejoined, _client) = add_test_player(&state, "Player").await;
+        data.restore(rejoined, &state);
+        let position = state.world.get_component::<Position>(rejoined).await.unwrap();
+        assert_eq!((position.x, position.y, position.z), (-8, 80, 16));
+    }
+
+    #[tokio::test]
+    async fn test_first_join_has_no_data() {
+        let state = test_state().await;
+        assert!(state.database.load_player_data(42).await.unwrap().is_none());
+    }
+}
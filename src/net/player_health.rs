@@ -0,0 +1,193 @@
+//! Changes to players' health and hunger, and what happens when they die: the death screen, then
+//! a respawn at the world spawn once the client asks for it.
+
+use tracing::{error, info};
+
+use crate::database::meta::hashed_seed;
+use crate::net::entity_tracking;
+use crate::net::packets::outgoing::combat_death::CombatDeath;
+use crate::net::packets::outgoing::respawn::Respawn;
+use crate::net::packets::outgoing::set_health::SetHealth;
+use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::net::systems::chunk_sender::ChunkSender;
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::GameMode;
+use crate::utils::components::health::{Food, Health};
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+use crate::world::gamerules::SHOW_DEATH_MESSAGES;
+use crate::world::spawn::spawn_point;
+
+/// Shown on the death screen, there's no damage source to name yet.
+const DEATH_MESSAGE: &str = "You died";
+
+/// Sets a player's health, clamped to the valid range, and sends it to them.
+///
+/// Dropping to 0 kills the player, see [die].
+pub async fn set_health(entity_id: u32, health: f32, state: &GlobalState) -> Result<()> {
+    let health = Health::new(health);
+    let was_dead = state
+        .world
+        .get_component::<Health>(entity_id)
+        .await
+        .is_ok_and(|previous| previous.is_dead());
+
+    state.world.get_component_storage().insert(entity_id, health);
+    send_health(entity_id, state).await?;
+
+    if health.is_dead() && !was_dead {
+        die(entity_id, state).await?;
+    }
+    Ok(())
+}
+
+/// Sets a player's food level and saturation, clamped to the valid ranges, and sends them.
+pub async fn set_food(
+    entity_id: u32,
+    level: i32,
+    saturation: f32,
+    state: &GlobalState,
+) -> Result<()> {
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, Food::new(level, saturation));
+    send_health(entity_id, state).await
+}
+
+/// Sends a player their current health and food, assuming full bars for whatever isn't set.
+pub async fn send_health(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let health = state
+        .world
+        .get_component::<Health>(entity_id)
+        .await
+        .map_or_else(|_| Health::default(), |health| *health);
+    let food = state
+        .world
+        .get_component::<Food>(entity_id)
+        .await
+        .map_or_else(|_| Food::default(), |food| *food);
+
+    let conn = state.connections.get_connection(entity_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(SetHealth::new(&health, &food)).await
+}
+
+/// Shows the death screen. The player stays dead until their client asks to respawn.
+async fn die(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let username = state
+        .world
+        .get_component::<Player>(entity_id)
+        .await?
+        .username
+        .clone();
+    info!("{} died", username);
+
+    let message = match state.gamerules.is_enabled(SHOW_DEATH_MESSAGES) {
+        true => DEATH_MESSAGE,
+        false => "",
+    };
+    let network_id = state.entity_ids.allocate(entity_id);
+    let conn = state.connections.get_connection(entity_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(CombatDeath::new(network_id, message)).await
+}
+
+/// Brings a dead player back at the world spawn with full health and food. Does nothing if the
+/// player is alive, so a client can't use it to teleport.
+pub async fn respawn(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let is_dead = state
+        .world
+        .get_component::<Health>(entity_id)
+        .await
+        .is_ok_and(|health| health.is_dead());
+    if !is_dead {
+        return Ok(());
+    }
+
+    let (position, rotation) = spawn_point();
+    let gamemode = state
+        .world
+        .get_component::<GameMode>(entity_id)
+        .await
+        .map_or_else(|_| GameMode::default(), |mode| *mode);
+    let seed = state.database.world_seed(get_global_config().seed).await?;
+
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, Health::default())
+        .insert(entity_id, Food::default())
+        .insert(entity_id, position.clone())
+        .insert(entity_id, rotation.clone());
+
+    {
+        let conn = state.connections.get_connection(entity_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(Respawn::overworld(hashed_seed(seed), gamemode))
+            .await?;
+        conn.send_packet(SynchronizePlayerPosition::new(&position, &rotation))
+            .await?;
+        conn.send_packet(SetHealth::new(&Health::default(), &Food::default()))
+            .await?;
+    }
+
+    // The client forgets its chunks and entities on respawn
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = ChunkSender::send_chunks_to_player(state_clone, entity_id).await {
+            error!("Failed to send chunks after respawning: {}", e);
+        }
+    });
+    entity_tracking::spawn_player(entity_id, state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+    use crate::utils::components::health::MAX_HEALTH;
+    use crate::utils::encoding::position::Position;
+
+    #[tokio::test]
+    async fn test_health_below_zero_kills() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+
+        set_health(player, -5.0, &state).await.unwrap();
+
+        let health = *state.world.get_component::<Health>(player).await.unwrap();
+        assert_eq!(health.get(), 0.0);
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x57);
+        assert_eq!(body[..4], 0.0f32.to_be_bytes());
+        let (packet_id, _) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x38);
+
+        respawn(player, &state).await.unwrap();
+
+        let health = *state.world.get_component::<Health>(player).await.unwrap();
+        assert_eq!(health.get(), MAX_HEALTH);
+        assert_eq!(read_packet(&mut client).await.0, 0x41);
+        assert_eq!(read_packet(&mut client).await.0, 0x3C);
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x57);
+        assert_eq!(body[..4], MAX_HEALTH.to_be_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_respawn_needs_death() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Player").await;
+        state
+            .world
+            .get_component_storage()
+            .insert(player, Position::new(100, 70, 100));
+
+        respawn(player, &state).await.unwrap();
+
+        let position = state.world.get_component::<Position>(player).await.unwrap();
+        assert_eq!(position.x, 100);
+    }
+}
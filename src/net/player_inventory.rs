@@ -0,0 +1,63 @@
+//! Changes to players' inventories, sent to their client as they happen.
+
+use crate::net::packets::outgoing::set_container_content::SetContainerContent;
+use crate::state::GlobalState;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::encoding::item_stack::ItemStack;
+use crate::utils::prelude::*;
+
+/// Puts `item` in a slot of a player's inventory, or empties it, and sends the inventory.
+///
+/// Returns whatever was in the slot before.
+pub async fn set_slot(
+    entity_id: u32,
+    slot: usize,
+    item: Option<ItemStack>,
+    state: &GlobalState,
+) -> Result<Option<ItemStack>> {
+    let previous = state
+        .world
+        .get_component_storage()
+        .get_mut_or_insert_with::<Inventory>(entity_id, Default::default)
+        .await
+        .set(slot, item)?;
+    send_inventory(entity_id, state).await?;
+    Ok(previous)
+}
+
+/// Sends a player their whole inventory.
+pub async fn send_inventory(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let packet = match state.world.get_component::<Inventory>(entity_id).await {
+        Ok(inventory) => SetContainerContent::player_inventory(&inventory),
+        Err(_) => SetContainerContent::player_inventory(&Inventory::new()),
+    };
+
+    let conn = state.connections.get_connection(entity_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(packet).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_set_slot_sends_inventory() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+        let slot = Inventory::hotbar_slot(4).unwrap();
+
+        set_slot(player, slot, Some(ItemStack::new(7, 1)), &state)
+            .await
+            .unwrap();
+
+        let inventory = state.world.get_component::<Inventory>(player).await.unwrap();
+        assert_eq!(inventory.get(slot), Some(&ItemStack::new(7, 1)));
+        drop(inventory);
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x12);
+        // Window, state id, slot count, then the empty slots before it
+        assert_eq!(body[3 + slot..3 + slot + 4], [1, 7, 1, 0]);
+    }
+}
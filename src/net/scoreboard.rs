@@ -0,0 +1,188 @@
+//! Scoreboard objectives everyone sees, with a score per player name.
+//!
+//! Every change is broadcast to the online players, and players joining later are sent the whole
+//! scoreboard, see [send_scoreboard].
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::net::packets::outgoing::display_objective::{DisplayObjective, DisplaySlot};
+use crate::net::packets::outgoing::update_objectives::UpdateObjectives;
+use crate::net::packets::outgoing::update_score::UpdateScore;
+use crate::net::utils::broadcast::broadcast;
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+#[derive(Debug, Clone, Default)]
+struct Objective {
+    display_name: String,
+    scores: BTreeMap<String, i32>,
+}
+
+/// The objectives and which one is in the sidebar.
+#[derive(Debug, Default)]
+pub struct Scoreboard {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    objectives: BTreeMap<String, Objective>,
+    sidebar: Option<String>,
+}
+
+impl Scoreboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn score(&self, objective: &str, entry: &str) -> Option<i32> {
+        let inner = self.inner.lock().unwrap();
+        inner.objectives.get(objective)?.scores.get(entry).copied()
+    }
+
+    pub fn has_objective(&self, objective: &str) -> bool {
+        self.inner.lock().unwrap().objectives.contains_key(objective)
+    }
+}
+
+/// Creates an objective, or changes the display name of an existing one.
+pub async fn set_objective(name: &str, display_name: &str, state: &GlobalState) -> Result<()> {
+    let created = {
+        let mut inner = state.scoreboard.inner.lock().unwrap();
+        let created = !inner.objectives.contains_key(name);
+        inner.objectives.entry(name.to_string()).or_default().display_name =
+            display_name.to_string();
+        created
+    };
+
+    let packet = match created {
+        true => UpdateObjectives::create(name, display_name),
+        false => UpdateObjectives::update(name, display_name),
+    };
+    broadcast(&packet, state, None).await
+}
+
+/// Removes an objective and its scores. Returns `false` if there was no such objective.
+pub async fn remove_objective(name: &str, state: &GlobalState) -> Result<bool> {
+    {
+        let mut inner = state.scoreboard.inner.lock().unwrap();
+        if inner.objectives.remove(name).is_none() {
+            return Ok(false);
+        }
+        if inner.sidebar.as_deref() == Some(name) {
+            inner.sidebar = None;
+        }
+    }
+    broadcast(&UpdateObjectives::remove(name), state, None).await?;
+    Ok(true)
+}
+
+/// Shows an objective in the sidebar, or clears the sidebar with `None`.
+pub async fn show_in_sidebar(name: Option<&str>, state: &GlobalState) -> Result<()> {
+    {
+        let mut inner = state.scoreboard.inner.lock().unwrap();
+        if let Some(name) = name {
+            if !inner.objectives.contains_key(name) {
+                return Err(Error::Generic(format!("No objective named {}", name)));
+            }
+        }
+        inner.sidebar = name.map(str::to_string);
+    }
+    let packet = DisplayObjective::new(DisplaySlot::Sidebar, name.unwrap_or_default());
+    broadcast(&packet, state, None).await
+}
+
+/// Sets the score of an entry, usually a player name, in an objective.
+pub async fn set_score(
+    objective: &str,
+    entry: &str,
+    value: i32,
+    state: &GlobalState,
+) -> Result<()> {
+    {
+        let mut inner = state.scoreboard.inner.lock().unwrap();
+        let Some(scores) = inner.objectives.get_mut(objective).map(|o| &mut o.scores) else {
+            return Err(Error::Generic(format!("No objective named {}", objective)));
+        };
+        scores.insert(entry.to_string(), value);
+    }
+    broadcast(&UpdateScore::set(entry, objective, value), state, None).await
+}
+
+/// Removes the score of an entry from an objective. Returns `false` if it had none.
+pub async fn reset_score(objective: &str, entry: &str, state: &GlobalState) -> Result<bool> {
+    let removed = {
+        let mut inner = state.scoreboard.inner.lock().unwrap();
+        inner
+            .objectives
+            .get_mut(objective)
+            .is_some_and(|objective| objective.scores.remove(entry).is_some())
+    };
+    if removed {
+        broadcast(&UpdateScore::remove(entry, objective), state, None).await?;
+    }
+    Ok(removed)
+}
+
+/// Sends a joining player every objective and score, and what's in the sidebar.
+pub async fn send_scoreboard(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let (objectives, sidebar) = {
+        let inner = state.scoreboard.inner.lock().unwrap();
+        (inner.objectives.clone(), inner.sidebar.clone())
+    };
+    if objectives.is_empty() {
+        return Ok(());
+    }
+
+    let mut packet_queue = PacketQueue::new();
+    for (name, objective) in &objectives {
+        packet_queue
+            .queue(UpdateObjectives::create(name, &objective.display_name))
+            .await?;
+        for (entry, value) in &objective.scores {
+            packet_queue.queue(UpdateScore::set(entry, name, *value)).await?;
+        }
+    }
+    if let Some(sidebar) = sidebar {
+        packet_queue
+            .queue(DisplayObjective::new(DisplaySlot::Sidebar, &sidebar))
+            .await?;
+    }
+
+    let conn = state.connections.get_connection(entity_id)?;
+    let conn = conn.read().await;
+    conn.send_packets(packet_queue).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_scores_are_broadcast() {
+        let state = test_state().await;
+        let (_, mut client) = add_test_player(&state, "Player").await;
+
+        set_objective("kills", "Kills", &state).await.unwrap();
+        set_score("kills", "Player", 3, &state).await.unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x58);
+        assert_eq!(read_packet(&mut client).await.0, 0x5B);
+        assert_eq!(state.scoreboard.score("kills", "Player"), Some(3));
+        assert!(set_score("deaths", "Player", 1, &state).await.is_err());
+
+        // Joining later gets everything at once
+        let (late, mut late_client) = add_test_player(&state, "Late").await;
+        show_in_sidebar(Some("kills"), &state).await.unwrap();
+        assert_eq!(read_packet(&mut late_client).await.0, 0x51);
+        send_scoreboard(late, &state).await.unwrap();
+        assert_eq!(read_packet(&mut late_client).await.0, 0x58);
+        assert_eq!(read_packet(&mut late_client).await.0, 0x5B);
+        assert_eq!(read_packet(&mut late_client).await.0, 0x51);
+
+        assert!(remove_objective("kills", &state).await.unwrap());
+        assert!(!state.scoreboard.has_objective("kills"));
+    }
+}
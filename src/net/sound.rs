@@ -0,0 +1,95 @@
+//! Playing sounds to the players close enough to hear them.
+
+use crate::net::packets::outgoing::entity_sound_effect::EntitySoundEffect;
+use crate::net::packets::outgoing::sound_effect::SoundEffect;
+use crate::net::utils::broadcast::broadcast_lossy_to;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::components::tracked_entities::TrackedEntities;
+use crate::utils::encoding::position::Position;
+use crate::utils::encoding::sound_event::{SoundCategory, SoundEvent};
+use crate::utils::prelude::*;
+
+/// How far a sound at full volume can be heard, in blocks. Louder sounds carry further.
+const HEARING_DISTANCE: f64 = 16.0;
+
+/// Plays a named sound at a position to every player within hearing distance of it.
+pub async fn play_sound(
+    name: &str,
+    category: SoundCategory,
+    position: (f64, f64, f64),
+    volume: f32,
+    pitch: f32,
+    state: &GlobalState,
+) -> Result<()> {
+    let distance = HEARING_DISTANCE * (volume as f64).max(1.0);
+    let (x, y, z) = position;
+
+    let mut listeners = Vec::new();
+    let mut query = state.world.query::<(&Player, &Position)>();
+    while let Some((entity_id, (_, at))) = query.next().await {
+        let (dx, dy, dz) = (at.x as f64 - x, at.y as f64 - y, at.z as f64 - z);
+        if dx * dx + dy * dy + dz * dz <= distance * distance {
+            listeners.push(entity_id as u32);
+        }
+    }
+
+    let sound = SoundEvent::from_name(name);
+    let packet = SoundEffect::new(sound, category, position, volume, pitch, rand::random());
+    broadcast_lossy_to(&packet, state, listeners).await
+}
+
+/// Plays a named sound following an entity, to the entity itself if it's a player and to every
+/// player tracking it.
+pub async fn play_entity_sound(
+    name: &str,
+    category: SoundCategory,
+    entity_id: u32,
+    volume: f32,
+    pitch: f32,
+    state: &GlobalState,
+) -> Result<()> {
+    let Some(network_id) = state.entity_ids.network_id(entity_id) else {
+        return Err(Error::Generic(format!("Entity {} isn't spawned", entity_id)));
+    };
+
+    let mut listeners = vec![entity_id];
+    let mut query = state.world.query::<&TrackedEntities>();
+    while let Some((listener, tracked)) = query.next().await {
+        if tracked.contains(entity_id) {
+            listeners.push(listener as u32);
+        }
+    }
+
+    let sound = SoundEvent::from_name(name);
+    let packet = EntitySoundEffect::new(sound, category, network_id, volume, pitch, rand::random());
+    broadcast_lossy_to(&packet, state, listeners).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_only_nearby_players_hear_sounds() {
+        let state = test_state().await;
+        let (_, mut near_client) = add_test_player(&state, "Near").await;
+        let (far, mut far_client) = add_test_player(&state, "Far").await;
+        state
+            .world
+            .get_component_storage()
+            .insert(far, Position::new(100, 64, 0));
+
+        play_sound("block.bell.use", SoundCategory::Blocks, (0.0, 64.0, 0.0), 1.0, 1.0, &state)
+            .await
+            .unwrap();
+        assert_eq!(read_packet(&mut near_client).await.0, 0x62);
+
+        // A louder sound carries far enough
+        play_sound("block.bell.use", SoundCategory::Blocks, (0.0, 64.0, 0.0), 8.0, 1.0, &state)
+            .await
+            .unwrap();
+        assert_eq!(read_packet(&mut far_client).await.0, 0x62);
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, info};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::player_data::save_online_players;
+use crate::net::systems::System;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+/// Saves changed chunks and online players every `autosave_interval_secs`, and once more when the
+/// server shuts down.
+#[derive(AutoGenName)]
+pub struct AutosaveSystem;
+
+#[async_trait]
+impl System for AutosaveSystem {
+    async fn run(&self, state: GlobalState) {
+        let interval = get_global_config().autosave_interval_secs;
+        if interval == 0 {
+            debug!("Autosaving is disabled");
+            return;
+        }
+
+        autosave(state, Duration::from_secs(interval)).await;
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+
+    async fn kill(&self, state: GlobalState) {
+        info!("Saving the world before shutting down...");
+        save(&state).await;
+    }
+}
+
+/// Saves every `interval`, forever. The first save happens one `interval` from now.
+pub async fn autosave(state: GlobalState, interval: Duration) {
+    let mut ticks = tokio::time::interval(interval);
+    // A slow save shouldn't cause a burst of saves to catch up
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately
+    ticks.tick().await;
+
+    loop {
+        ticks.tick().await;
+        save(&state).await;
+    }
+}
+
+async fn save(state: &GlobalState) {
+    let start = Instant::now();
+    let players = save_online_players(state).await;
+    if players > 0 {
+        debug!("Saved {} players", players);
+    }
+    match state.database.save_all().await {
+        Ok(0) => debug!("Nothing to save"),
+        Ok(chunks) => info!("Saved {} chunks in {:?}", chunks, start.elapsed()),
+        Err(e) => error!("Failed to save the world: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::test_chunk;
+    use crate::tests::helpers::test_state;
+
+    const INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Lets the autosave task run until `chunks` chunks have been written, without moving the
+    /// clock.
+    async fn wait_for_saved(state: &GlobalState, chunks: u64) {
+        while state.database.chunk_count().await.unwrap() < chunks {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Lets the autosave task run for a while, without moving the clock.
+    async fn settle() {
+        for _ in 0..100 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_saves_at_interval() {
+        let state = test_state().await;
+        let task = tokio::spawn(autosave(state.clone(), INTERVAL));
+        settle().await;
+
+        for x in 0..3 {
+            state.database.cache_chunk(test_chunk(x, 0)).await;
+
+            tokio::time::advance(INTERVAL - Duration::from_secs(1)).await;
+            settle().await;
+            assert_eq!(state.database.dirty_chunk_count(), 1);
+
+            tokio::time::advance(Duration::from_secs(1)).await;
+            wait_for_saved(&state, x as u64 + 1).await;
+            assert_eq!(state.database.dirty_chunk_count(), 0);
+        }
+
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_saves_on_shutdown() {
+        let state = test_state().await;
+        state.database.cache_chunk(test_chunk(0, 0)).await;
+
+        AutosaveSystem.kill(state.clone()).await;
+
+        assert_eq!(state.database.dirty_chunk_count(), 0);
+        assert_eq!(state.database.chunk_count().await.unwrap(), 1);
+    }
+}
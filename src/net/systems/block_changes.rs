@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::packets::incoming::client_info::ClientInfo;
+use crate::net::systems::System;
+use crate::net::utils::broadcast::broadcast_to;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Sends the blocks changed during a tick to the players that have their chunks, see
+/// [crate::world::block_changes].
+#[derive(AutoGenName)]
+pub struct BlockChangeSystem;
+
+#[async_trait]
+impl System for BlockChangeSystem {
+    async fn run(&self, state: GlobalState) {
+        let mut ticks = state.ticks.subscribe();
+        while ticks.changed().await.is_ok() {
+            if let Err(e) = flush_block_changes(&state).await {
+                warn!("Failed to send block changes: {}", e);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// Sends every pending block change to the players within view distance of its chunk.
+pub async fn flush_block_changes(state: &GlobalState) -> Result<()> {
+    if state.block_changes.is_empty() {
+        return Ok(());
+    }
+
+    let server_view_distance = get_global_config().view_distance as i32;
+    let mut players = Vec::new();
+    let mut query = state.world.query::<(&Player, &Position, Option<&ClientInfo>)>();
+    while let Some((id, (_, position, client_info))) = query.next().await {
+        let view_distance = client_info.map_or(server_view_distance, |info| {
+            (info.view_distance as i32).min(server_view_distance)
+        });
+        players.push((id as u32, (position.x >> 4, position.z >> 4), view_distance));
+    }
+
+    for (chunk, packet) in state.block_changes.take() {
+        let recipients: Vec<u32> = players
+            .iter()
+            .filter(|(_, center, distance)| {
+                (center.0 - chunk.0).abs() <= *distance && (center.1 - chunk.1).abs() <= *distance
+            })
+            .map(|(id, ..)| *id)
+            .collect();
+        broadcast_to(&packet, state, recipients).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    use ferrumc_codec::network_types::varint::VarInt;
+
+    use super::*;
+    use crate::net::utils::chunk_encoder::tests::net_chunk;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+    use crate::world::block_changes::set_block;
+    use crate::world::chunk_format::Palette;
+
+    #[tokio::test]
+    async fn test_changes_in_one_section_are_sent_together() {
+        let state = test_state().await;
+        state.database.insert_chunk(net_chunk(0, 0)).await.unwrap();
+        let (_, mut client) = add_test_player(&state, "Builder").await;
+        let (far, _far_client) = add_test_player(&state, "Far").await;
+        state
+            .world
+            .get_component_storage()
+            .insert(far, Position::new(10_000, 64, 10_000));
+
+        let stone = Palette {
+            name: "minecraft:stone".to_string(),
+            properties: None,
+        };
+        for x in 0..3 {
+            set_block(&state, x, 64, 0, stone.clone()).await.unwrap();
+        }
+        flush_block_changes(&state).await.unwrap();
+
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x43);
+        let count = VarInt::read(&mut Cursor::new(&body[8..])).await.unwrap();
+        assert_eq!(count.get_val(), 3);
+
+        // Nothing else, in particular no chunk, follows
+        let next = tokio::time::timeout(Duration::from_millis(100), read_packet(&mut client));
+        assert!(next.await.is_err());
+        assert!(state.block_changes.is_empty());
+    }
+}
@@ -1,23 +1,23 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use ferrumc_codec::enc::NetEncode;
+use futures::StreamExt;
 use tokio::sync::RwLock;
 use tracing::{debug, error, warn};
 
 use crate::net::packets::incoming::client_info::ClientInfo;
-use crate::net::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLight;
 use crate::net::packets::outgoing::set_center_chunk::SetCenterChunk;
 use crate::net::systems::System;
+use crate::net::utils::chunk_encoder;
 use crate::net::{Connection, ConnectionWrapper};
 use crate::state::GlobalState;
 use crate::utils::components::last_chunk_tx_pos::LastChunkTxPos;
 use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
 use crate::utils::encoding::position::Position;
 use crate::utils::prelude::*;
 use ferrumc_macros::AutoGenName;
 
-pub const DEFAULT_CHUNK_RADIUS: i8 = 16;
 const CHUNK_TX_INTERVAL_MS: u64 = 50000;
 
 #[derive(AutoGenName)]
@@ -31,13 +31,16 @@ impl System for ChunkSender {
             interval.tick().await;
 
             // Get all the Players, instead of all the *entities*. The player is just a filter.
+            // Only the ids and names are kept, each player is released as soon as it's read
             let query = state.world.query::<&Player>();
-            let send_to = query.iter().await
-                .collect::<Vec<_>>();
-
-            send_to.into_iter().for_each(|(entity_id, player)| {
-                debug!("Sending chunk to player: {}", player.get_username());
-                drop(player);
+            let send_to: Vec<(usize, String)> = query
+                .stream()
+                .map(|(entity_id, player)| (entity_id, player.get_username().to_string()))
+                .collect()
+                .await;
+
+            send_to.into_iter().for_each(|(entity_id, username)| {
+                debug!("Sending chunk to player: {}", username);
                 let state = state.clone();
                 tokio::spawn(async move {
                     if let Err(e) = ChunkSender::send_chunks_to_player(state, entity_id).await {
@@ -67,14 +70,15 @@ impl ChunkSender {
             .get_mut_or_insert_with::<LastChunkTxPos>(entity_id, Default::default)
             .await;
 
-        let client_info = state
+        let view_distance = state
             .world
             .get_component::<ClientInfo>(entity_id)
-            .await?;
+            .await
+            .map_or(get_global_config().view_distance as i8, |info| info.view_distance);
 
         let distance = last_chunk_tx_pos.distance_to(current_pos.0, current_pos.1);
 
-        if distance < (client_info.view_distance as f64 / 5f64) {
+        if distance < (view_distance as f64 / 5f64) {
             return Ok(());
         }
 
@@ -92,6 +96,22 @@ impl ChunkSender {
 
         Ok(())
     }
+    /// Tells a player's client which chunk it's in now, if it moved from `old` into another one.
+    /// Sent before the chunks around the new one.
+    pub async fn update_center_chunk(
+        state: &GlobalState,
+        entity_id: u32,
+        old: (i32, i32),
+        new: (i32, i32),
+    ) -> Result<()> {
+        if old == new {
+            return Ok(());
+        }
+        let conn = state.connections.get_connection(entity_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(SetCenterChunk::new(new.0, new.1)).await
+    }
+
     pub async fn send_chunks_to_player(
         state: GlobalState,
         entity_id: impl TryInto<usize>,
@@ -110,11 +130,16 @@ impl ChunkSender {
 
 
         let pos = c_pos.clone();
-        let view_distance: i8 = c_info.as_ref().map_or(DEFAULT_CHUNK_RADIUS, |c| c.view_distance);
-        let conn = c_conn.0.clone();
+        // The client's view distance, but never more than the server allows
+        let server_view_distance = get_global_config().view_distance as i8;
+        let view_distance: i8 = c_info
+            .as_ref()
+            .map_or(server_view_distance, |c| c.view_distance.min(server_view_distance));
+        let conn = c_conn.handle();
 
         drop(c_pos);
         drop(c_conn);
+        drop(c_info);
 
         debug!(
             "Sending chunks to player: {} @ {:?}",
@@ -125,55 +150,76 @@ impl ChunkSender {
         drop(player);
 
         ChunkSender::send_set_center_chunk(&pos, conn.clone()).await?;
-        ChunkSender::send_chunk_data_to_player(state.clone(), &pos, view_distance, conn.clone()).await?;
+        let budget = get_global_config().chunks_per_tick as usize;
+        ChunkSender::send_chunk_data_to_player(state.clone(), &pos, view_distance, budget, conn)
+            .await?;
 
         Ok(())
     }
 
+    /// Sends the chunks within `player_view_distance` in [spiral] order, `budget` per tick so slow
+    /// clients aren't flooded. A budget of 0 sends them all at once.
     async fn send_chunk_data_to_player(
         state: GlobalState,
         pos: &Position,
         player_view_distance: i8,
+        budget: usize,
         conn: Arc<RwLock<Connection>>,
     ) -> Result<()> {
         let start = std::time::Instant::now();
 
-        let pos_x = pos.x;
-        let pos_z = pos.z;
-
+        let (center_x, center_z) = (pos.x >> 4, pos.z >> 4);
         let chunk_radius = player_view_distance as i32;
 
-        'x: for x in -chunk_radius..=chunk_radius {
-            for z in -chunk_radius..=chunk_radius {
-                let Ok(packet) = ChunkDataAndUpdateLight::new(
-                    state.clone(),
-                    (pos_x >> 4) + x,
-                    (pos_z >> 4) + z,
-                ).await else {
-                    continue;
+        let coordinates = spiral(chunk_radius);
+        let budget = if budget == 0 { coordinates.len().max(1) } else { budget };
+
+        let config = get_global_config();
+        let dimension = config.default_dimension.as_str();
+        let mut chunk_count = 0;
+        let mut sent_bytes = 0;
+        let mut ticks = state.ticks.subscribe();
+        for (batch, coordinates) in coordinates.chunks(budget).enumerate() {
+            if batch > 0 && ticks.changed().await.is_err() {
+                return Ok(());
+            }
+
+            let mut chunks = Vec::new();
+            for (x, z) in coordinates {
+                let (x, z) = (center_x + x, center_z + z);
+                match state.database.get_chunk_shared(x, z, dimension).await {
+                    Ok(chunk) => chunks.extend(chunk),
+                    Err(e) => warn!("Failed to load chunk at ({}, {}): {}", x, z, e),
+                }
+            }
+
+            // Encoded in parallel off the runtime, sent in order as they're done
+            let mut encoded = chunk_encoder::encode_cached_chunks(state.clone(), chunks);
+            while let Some(packet) = encoded.next().await {
+                let packet = match packet {
+                    Ok(packet) => packet,
+                    Err(e) => {
+                        debug!("Skipping chunk that can't be encoded: {}", e);
+                        continue;
+                    }
                 };
+                chunk_count += 1;
+                sent_bytes += packet.len();
                 let conn_read = conn.read().await;
-                if let Err(e) = conn_read.send_packet(packet).await {
+                if let Err(e) = conn_read.send_encoded(&packet).await {
                     warn!("Failed to send chunk to player: {} ; Cancelling.", e);
-                    break 'x;
+                    return Ok(());
                 }
             }
         }
 
-        // check the size of a single chunk and multiply it by the number of chunks sent
-        let sample_chunk = ChunkDataAndUpdateLight::new(state.clone(), pos_x >> 4, pos_z >> 4).await?;
-        let mut vec = vec![];
-        sample_chunk.net_encode(&mut vec).await?;
-        let chunk_rad_axis = chunk_radius * 2 + 1;
         debug!(
-                "Send {}({}x{}) chunks to player in {:?}. Approximately {} kb of data (~{} kb per chunk)",
-                chunk_rad_axis * chunk_rad_axis,
-                chunk_rad_axis,
-                chunk_rad_axis,
-                start.elapsed(),
-                vec.len() as i32 * chunk_rad_axis * chunk_rad_axis / 1024,
-                vec.len() as i32 / 1024
-            );
+            "Sent {} chunks to player in {:?}. {} kb of data (~{} kb per chunk)",
+            chunk_count,
+            start.elapsed(),
+            sent_bytes / 1024,
+            sent_bytes / 1024 / chunk_count.max(1)
+        );
 
         Ok(())
     }
@@ -187,3 +233,100 @@ impl ChunkSender {
         Ok(())
     }
 }
+
+/// Offsets from a center chunk out to `radius`, ring by ring. Each ring starts at its lowest x
+/// and z corner and goes around through increasing x first.
+pub fn spiral(radius: i32) -> Vec<(i32, i32)> {
+    let mut offsets = vec![(0, 0)];
+    for ring in 1..=radius.max(0) {
+        offsets.extend((-ring..=ring).map(|x| (x, -ring)));
+        offsets.extend((-ring + 1..=ring).map(|z| (ring, z)));
+        offsets.extend((-ring..ring).rev().map(|x| (x, ring)));
+        offsets.extend((-ring + 1..ring).rev().map(|z| (-ring, z)));
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::net::read_packet_header;
+    use crate::net::utils::chunk_encoder::tests::net_chunk;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state, test_state_with_clock};
+    use crate::utils::clock::FakeClock;
+
+    #[tokio::test]
+    async fn test_chunks_are_sent_within_budget() {
+        let state = test_state().await;
+        for x in -1..=1 {
+            for z in -1..=1 {
+                state.database.insert_chunk(net_chunk(x, z)).await.unwrap();
+            }
+        }
+        let (player, mut client) = add_test_player(&state, "Player").await;
+        let conn = state.connections.get_connection(player).unwrap();
+
+        let task = tokio::spawn({
+            let state = state.clone();
+            async move {
+                let pos = Position::new(0, 64, 0);
+                ChunkSender::send_chunk_data_to_player(state, &pos, 1, 4, conn).await
+            }
+        });
+
+        // 9 chunks at 4 a tick take three ticks
+        for expected in [4, 4, 1] {
+            for _ in 0..expected {
+                assert_eq!(read_packet(&mut client).await.0, 0x24);
+            }
+            let next = tokio::time::timeout(
+                Duration::from_millis(200),
+                read_packet_header(&mut client),
+            );
+            assert!(next.await.is_err(), "More than the budget was sent in one tick");
+            state.ticks.advance();
+        }
+        task.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_spiral_rings() {
+        let ring = [(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)];
+        assert_eq!(spiral(0), vec![(0, 0)]);
+        assert_eq!(spiral(1)[1..], ring);
+
+        let offsets = spiral(3);
+        assert_eq!(offsets.len(), 7 * 7);
+        let unique: std::collections::HashSet<_> = offsets.iter().collect();
+        assert_eq!(unique.len(), offsets.len());
+        assert_eq!(offsets[9], (-2, -2));
+    }
+
+    #[tokio::test]
+    async fn test_chunks_are_sent_in_spiral_order() {
+        let state = test_state_with_clock(Arc::new(FakeClock::new())).await;
+        for x in 4..=6 {
+            for z in -1..=1 {
+                state.database.insert_chunk(net_chunk(x, z)).await.unwrap();
+            }
+        }
+        let (player, mut client) = add_test_player(&state, "Player").await;
+        let conn = state.connections.get_connection(player).unwrap();
+
+        // Standing in chunk (5, 0)
+        let pos = Position::new(5 * 16 + 3, 64, 7);
+        ChunkSender::send_chunk_data_to_player(state.clone(), &pos, 1, 0, conn)
+            .await
+            .unwrap();
+
+        for (x, z) in spiral(1) {
+            let (id, body) = read_packet(&mut client).await;
+            assert_eq!(id, 0x24);
+            let chunk_x = i32::from_be_bytes(body[0..4].try_into().unwrap());
+            let chunk_z = i32::from_be_bytes(body[4..8].try_into().unwrap());
+            assert_eq!((chunk_x, chunk_z), (5 + x, z));
+        }
+    }
+}
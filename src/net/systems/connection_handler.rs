@@ -1,9 +1,12 @@
+use std::future::Future;
+
 use crate::net::systems::System;
 use crate::state::GlobalState;
 use crate::utils::prelude::*;
 use async_trait::async_trait;
 use ferrumc_macros::AutoGenName;
-use tracing::{debug, error, info_span, Instrument};
+use tokio::net::TcpStream;
+use tracing::{debug, error, info_span, warn, Instrument};
 
 #[derive(AutoGenName)]
 pub struct ConnectionHandler;
@@ -13,9 +16,10 @@ impl System for ConnectionHandler {
     async fn run(&self, state: GlobalState) {
         debug!("ConnectionHandler is starting up");
 
-        if let Err(e) = Self::handle_connections(state).await {
-            error!("There was an error in the ConnectionHandler: {:?}", e);
-        }
+        Self::accept_connections(state, |state, stream| {
+            crate::net::init_connection(stream, state)
+        })
+        .await;
     }
 
     fn name(&self) -> &'static str {
@@ -24,20 +28,88 @@ impl System for ConnectionHandler {
 }
 
 impl ConnectionHandler {
-    async fn handle_connections(state: GlobalState) -> Result<()> {
+    /// Accepts connections forever, handing each one to `handler` in a task of its own.
+    ///
+    /// Failing to accept a single connection, or a handler failing or panicking, never stops the loop.
+    async fn accept_connections<H, F>(state: GlobalState, handler: H)
+    where
+        H: Fn(GlobalState, TcpStream) -> F,
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
         loop {
-            let (stream, _) = state.server_stream.accept().await?;
-            debug!("Accepted connection from {:?}", stream.peer_addr()?);
-            let addy = stream.peer_addr()?;
+            let (stream, addy) = match state.server_stream.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept connection: {:?}", e);
+                    continue;
+                }
+            };
+            debug!("Accepted connection from {:?}", addy);
+
+            let connection = handler(state.clone(), stream);
             tokio::task::spawn(
-                Self::handle_connection(state.clone(), stream)
-                    .instrument(info_span!("conn", %addy).or_current()),
+                async move {
+                    if let Err(e) = connection.await {
+                        error!("There was an error handling the connection: {:?}", e);
+                    }
+                }
+                .instrument(info_span!("conn", %addy, conn_id = tracing::field::Empty).or_current()),
             );
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::net::{add_connection, supervise_connection, Connection, ConnectionWrapper};
+    use crate::tests::helpers::test_state;
+
+    async fn blow_up() -> Result<()> {
+        panic!("connection handler blew up")
+    }
+
+    #[tokio::test]
+    async fn test_panicking_connection_is_cleaned_up() {
+        let state = test_state().await;
+        let addr = state.server_stream.local_addr().unwrap();
+
+        let (ended, mut ended_rx) = mpsc::unbounded_channel();
+        let accept_loop = tokio::spawn(ConnectionHandler::accept_connections(
+            state.clone(),
+            move |state, stream| {
+                let ended = ended.clone();
+                async move {
+                    let conn_id = state.world.create_entity().await.build() as u32;
+                    add_connection(Connection::new(conn_id, stream, 16, 1024), &state);
+
+                    supervise_connection(conn_id, blow_up(), state).await?;
+                    ended.send(conn_id).unwrap();
+                    Ok(())
+                }
+            },
+        ));
+
+        for _ in 0..2 {
+            let _client = TcpStream::connect(addr).await.unwrap();
+            let conn_id = tokio::time::timeout(Duration::from_secs(5), ended_rx.recv())
+                .await
+                .expect("Connection was never cleaned up")
+                .unwrap();
+
+            assert!(state.connections.get_connection(conn_id).is_err());
+            assert!(state
+                .world
+                .get_component::<ConnectionWrapper>(conn_id as usize)
+                .await
+                .is_err());
+        }
 
-    async fn handle_connection(state: GlobalState, stream: tokio::net::TcpStream) -> Result<()> {
-        crate::net::init_connection(stream, state).await?;
-        Ok(())
+        assert!(!accept_loop.is_finished());
+        accept_loop.abort();
     }
 }
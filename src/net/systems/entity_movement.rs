@@ -0,0 +1,289 @@
+use async_trait::async_trait;
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::entity_tracking::update_tracking_budgeted;
+use crate::net::packets::outgoing::set_head_rotation::SetHeadRotation;
+use crate::net::packets::outgoing::teleport_entity::TeleportEntity;
+use crate::net::packets::outgoing::update_entity_position::UpdateEntityPosition;
+use crate::net::packets::outgoing::update_entity_position_and_rotation::UpdateEntityPositionAndRotation;
+use crate::net::packets::outgoing::update_entity_rotation::UpdateEntityRotation;
+use crate::net::systems::System;
+use crate::net::utils::broadcast::{broadcast_lossy_to, broadcast_to};
+use crate::state::GlobalState;
+use crate::utils::components::grounded::Grounded;
+use crate::utils::components::last_sent_movement::LastSentMovement;
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::{to_angle, Rotation};
+use crate::utils::components::tracked_entities::TrackedEntities;
+use crate::utils::config::get_global_config;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// A position delta is sent in 1/4096ths of a block.
+const DELTA_SCALE: i64 = 4096;
+
+/// Sends every entity's movement since the last tick to the players tracking it, then updates
+/// who tracks what. Runs once per game tick, so not while the game is frozen.
+#[derive(AutoGenName)]
+pub struct EntityMovementSystem;
+
+#[async_trait]
+impl System for EntityMovementSystem {
+    async fn run(&self, state: GlobalState) {
+        let mut ticks = state.ticks.subscribe();
+        // The first player to update who they see next tick, see [update_tracking_budgeted]
+        let mut tracking_cursor = 0;
+        while ticks.changed().await.is_ok() {
+            if let Err(e) = Self::broadcast_movement(&state, &mut tracking_cursor).await {
+                warn!("Failed to broadcast entity movement: {:?}", e);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// The packet that brings other players up to date with an entity's movement.
+#[derive(Debug)]
+pub enum EntityMovement {
+    Position(UpdateEntityPosition),
+    PositionAndRotation(UpdateEntityPositionAndRotation),
+    Rotation(UpdateEntityRotation),
+    Teleport(TeleportEntity),
+}
+
+impl EntityMovement {
+    /// Picks the packet for moving an entity from `old` to `new`, or `None` if nothing changed.
+    ///
+    /// Deltas that don't fit in the relative move packets (8 blocks or more) fall back to a teleport.
+    pub fn between(
+        network_id: i32,
+        old: (&Position, &Rotation),
+        new: (&Position, &Rotation),
+        on_ground: bool,
+    ) -> Option<Self> {
+        let (old_position, old_rotation) = old;
+        let (position, rotation) = new;
+        let entity_id = VarInt::from(network_id);
+
+        let (yaw, pitch) = (to_angle(rotation.yaw), to_angle(rotation.pitch));
+        let rotated = yaw != to_angle(old_rotation.yaw) || pitch != to_angle(old_rotation.pitch);
+
+        let delta = |new: i64, old: i64| i16::try_from((new - old) * DELTA_SCALE).ok();
+        let deltas = (
+            delta(position.x as i64, old_position.x as i64),
+            delta(position.y as i64, old_position.y as i64),
+            delta(position.z as i64, old_position.z as i64),
+        );
+
+        let movement = match deltas {
+            (Some(0), Some(0), Some(0)) if !rotated => return None,
+            (Some(0), Some(0), Some(0)) => EntityMovement::Rotation(
+                UpdateEntityRotation::new_auto(entity_id, yaw, pitch, on_ground),
+            ),
+            (Some(dx), Some(dy), Some(dz)) if !rotated => EntityMovement::Position(
+                UpdateEntityPosition::new_auto(entity_id, dx, dy, dz, on_ground),
+            ),
+            (Some(dx), Some(dy), Some(dz)) => EntityMovement::PositionAndRotation(
+                UpdateEntityPositionAndRotation::new_auto(
+                    entity_id, dx, dy, dz, yaw, pitch, on_ground,
+                ),
+            ),
+            _ => EntityMovement::Teleport(TeleportEntity::new_auto(
+                entity_id,
+                position.x as f64,
+                position.y as f64,
+                position.z as f64,
+                yaw,
+                pitch,
+                on_ground,
+            )),
+        };
+
+        Some(movement)
+    }
+
+    async fn send_to(&self, state: &GlobalState, recipients: &[u32]) -> Result<()> {
+        let recipients = recipients.iter().copied();
+        match self {
+            EntityMovement::Position(packet) => broadcast_to(packet, state, recipients).await,
+            EntityMovement::PositionAndRotation(packet) => {
+                broadcast_to(packet, state, recipients).await
+            }
+            EntityMovement::Rotation(packet) => broadcast_to(packet, state, recipients).await,
+            EntityMovement::Teleport(packet) => broadcast_to(packet, state, recipients).await,
+        }
+    }
+}
+
+/// A player, and the entities its client has spawned.
+struct Viewer {
+    entity_id: u32,
+    chunk: (i32, i32),
+    tracked: Option<TrackedEntities>,
+}
+
+impl Viewer {
+    fn is_within(&self, chunk: (i32, i32), distance: i32) -> bool {
+        (self.chunk.0 - chunk.0).abs() <= distance && (self.chunk.1 - chunk.1).abs() <= distance
+    }
+}
+
+impl EntityMovementSystem {
+    async fn broadcast_movement(state: &GlobalState, tracking_cursor: &mut u32) -> Result<()> {
+        let config = get_global_config();
+
+        let mut viewers = Vec::new();
+        let mut query = state
+            .world
+            .query::<(&Player, &Position, Option<&TrackedEntities>)>();
+        while let Some((entity_id, (_, position, tracked))) = query.next().await {
+            viewers.push(Viewer {
+                entity_id: entity_id as u32,
+                chunk: (position.x >> 4, position.z >> 4),
+                tracked: tracked.map(|tracked| tracked.clone()),
+            });
+        }
+
+        // Entities only move while they're within simulation distance of some player, or in a
+        // force-loaded chunk
+        let simulation_distance = config.simulation_distance as i32;
+        let is_simulated = |chunk: (i32, i32)| {
+            viewers
+                .iter()
+                .any(|viewer| viewer.is_within(chunk, simulation_distance))
+                || state.database.is_force_loaded(chunk.0, chunk.1, &config.default_dimension)
+        };
+
+        // Work out all the movements first, so no component is held while sending
+        let mut movements = Vec::new();
+        let mut query = state.world.query::<(
+            &Position,
+            &Rotation,
+            Option<&Grounded>,
+            &mut LastSentMovement,
+        )>();
+        while let Some((entity_id, (position, rotation, grounded, mut last_sent))) =
+            query.next().await
+        {
+            let entity_id = entity_id as u32;
+            let chunk = (position.x >> 4, position.z >> 4);
+            if !is_simulated(chunk) {
+                continue;
+            }
+
+            // Never spawned for anyone, so there's no one to send its movement to
+            let Some(network_id) = state.entity_ids.network_id(entity_id) else {
+                continue;
+            };
+
+            let on_ground = grounded.map_or(false, |grounded| grounded.is_grounded);
+            let Some(movement) = EntityMovement::between(
+                network_id,
+                (&last_sent.position, &last_sent.rotation),
+                (&*position, &*rotation),
+                on_ground,
+            ) else {
+                continue;
+            };
+
+            let yaw = to_angle(rotation.yaw);
+            let head_rotation = (yaw != to_angle(last_sent.rotation.yaw))
+                .then(|| SetHeadRotation::new_auto(VarInt::from(network_id), yaw));
+
+            *last_sent = LastSentMovement::new(position.clone(), rotation.clone());
+            movements.push((entity_id, movement, head_rotation));
+        }
+
+        for (entity_id, movement, head_rotation) in movements {
+            let recipients: Vec<u32> = viewers
+                .iter()
+                .filter(|viewer| {
+                    viewer
+                        .tracked
+                        .as_ref()
+                        .is_some_and(|tracked| tracked.contains(entity_id))
+                })
+                .map(|viewer| viewer.entity_id)
+                .collect();
+            if recipients.is_empty() {
+                continue;
+            }
+
+            movement.send_to(state, &recipients).await?;
+            if let Some(head_rotation) = head_rotation {
+                broadcast_lossy_to(&head_rotation, state, recipients).await?;
+            }
+        }
+
+        // Players only start or stop tracking each other once everyone has their movement
+        let budget = config.entity_updates_per_tick as usize;
+        update_tracking_budgeted(state, tracking_cursor, budget).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn between(old: (i32, i16, i32, f32), new: (i32, i16, i32, f32)) -> Option<EntityMovement> {
+        EntityMovement::between(
+            1,
+            (&Position::new(old.0, old.1, old.2), &Rotation::new(old.3, 0.0)),
+            (&Position::new(new.0, new.1, new.2), &Rotation::new(new.3, 0.0)),
+            true,
+        )
+    }
+
+    #[test]
+    fn test_no_movement() {
+        assert!(between((0, 64, 0, 0.0), (0, 64, 0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_small_movement_is_relative() {
+        let Some(EntityMovement::Position(packet)) = between((0, 64, 0, 0.0), (1, 63, -7, 0.0))
+        else {
+            panic!("Expected a relative position update");
+        };
+        assert_eq!(packet.delta_x, 4096);
+        assert_eq!(packet.delta_y, -4096);
+        assert_eq!(packet.delta_z, -7 * 4096);
+    }
+
+    #[test]
+    fn test_small_movement_with_rotation() {
+        assert!(matches!(
+            between((0, 64, 0, 0.0), (1, 64, 0, 90.0)),
+            Some(EntityMovement::PositionAndRotation(_))
+        ));
+    }
+
+    #[test]
+    fn test_rotation_only() {
+        let Some(EntityMovement::Rotation(packet)) = between((0, 64, 0, 0.0), (0, 64, 0, 90.0))
+        else {
+            panic!("Expected a rotation update");
+        };
+        assert_eq!(packet.yaw, 64);
+    }
+
+    #[test]
+    fn test_large_movement_teleports() {
+        let Some(EntityMovement::Teleport(packet)) = between((0, 64, 0, 0.0), (8, 64, 0, 0.0))
+        else {
+            panic!("Expected a teleport");
+        };
+        assert_eq!(packet.x, 8.0);
+
+        assert!(matches!(
+            between((0, 64, 0, 0.0), (0, -64, 0, 0.0)),
+            Some(EntityMovement::Teleport(_))
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::ecs::world::World;
+use crate::net::systems::System;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::components::velocity::{AffectedByGravity, Velocity};
+use crate::utils::config::{get_global_config, Physics};
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// One step of per-tick entity logic, e.g. physics or AI.
+///
+/// Add new ones to [ENTITY_TICKERS], they run in that order every tick.
+#[async_trait]
+pub trait EntityTicker: Send + Sync {
+    async fn tick(&self, world: &World) -> Result<()>;
+    fn name(&self) -> &'static str;
+}
+
+pub static ENTITY_TICKERS: &[&dyn EntityTicker] = &[&ApplyGravity, &ApplyVelocity];
+
+/// Runs every [EntityTicker] once per game tick.
+#[derive(AutoGenName)]
+pub struct EntityTickSystem;
+
+#[async_trait]
+impl System for EntityTickSystem {
+    async fn run(&self, state: GlobalState) {
+        let mut ticks = state.ticks.subscribe();
+        while ticks.changed().await.is_ok() {
+            tick_entities(&state.world).await;
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// Runs a single tick of every [EntityTicker] against `world`.
+pub async fn tick_entities(world: &World) {
+    for ticker in ENTITY_TICKERS {
+        if let Err(e) = ticker.tick(world).await {
+            warn!("Entity ticker {} failed: {:?}", ticker.name(), e);
+        }
+    }
+}
+
+/// Accelerates every entity [AffectedByGravity] downwards and slows everything with a [Velocity]
+/// down by the configured drag.
+///
+/// Players are skipped, their movement comes from the client.
+#[derive(AutoGenName)]
+pub struct ApplyGravity;
+
+#[async_trait]
+impl EntityTicker for ApplyGravity {
+    async fn tick(&self, world: &World) -> Result<()> {
+        let config = get_global_config();
+        let physics = &config.physics;
+        let mut query =
+            world.query::<(&mut Velocity, Option<&AffectedByGravity>, Option<&Player>)>();
+        while let Some((_, (mut velocity, gravity, player))) = query.next().await {
+            if player.is_some() {
+                continue;
+            }
+            apply_gravity(&mut velocity, gravity.is_some(), physics);
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// One tick of gravity (if `falls`) and drag, clamped to the terminal velocity.
+pub fn apply_gravity(velocity: &mut Velocity, falls: bool, physics: &Physics) {
+    if falls {
+        velocity.y -= physics.gravity;
+    }
+
+    let keep = 1.0 - physics.drag;
+    velocity.x *= keep;
+    velocity.y *= keep;
+    velocity.z *= keep;
+
+    velocity.y = velocity.y.max(-physics.terminal_velocity);
+}
+
+/// Moves every entity by its [Velocity].
+///
+/// Players are skipped, their position comes from the client.
+#[derive(AutoGenName)]
+pub struct ApplyVelocity;
+
+#[async_trait]
+impl EntityTicker for ApplyVelocity {
+    async fn tick(&self, world: &World) -> Result<()> {
+        let mut query = world.query::<(&mut Position, &mut Velocity, Option<&Player>)>();
+        while let Some((_, (mut position, mut velocity, player))) = query.next().await {
+            if player.is_some() {
+                continue;
+            }
+            apply_velocity(&mut position, &mut velocity);
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// Moves `position` by one tick of `velocity`, carrying over any partial blocks.
+pub fn apply_velocity(position: &mut Position, velocity: &mut Velocity) {
+    fn step(remainder: &mut f64, speed: f64) -> i64 {
+        *remainder += speed;
+        let whole = remainder.trunc();
+        *remainder -= whole;
+        whole as i64
+    }
+
+    let (x, y, z) = (velocity.x, velocity.y, velocity.z);
+    let remainder = &mut velocity.remainder;
+    position.x += step(&mut remainder.0, x) as i32;
+    position.y += step(&mut remainder.1, y) as i16;
+    position.z += step(&mut remainder.2, z) as i32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_velocity_moves_entity() {
+        let world = World::new();
+        let entity = world
+            .create_entity()
+            .await
+            .with(Position::new(0, 64, 0))
+            .with(Velocity::new(0.5, -1.0, -0.25))
+            .build();
+
+        for _ in 0..4 {
+            ApplyVelocity.tick(&world).await.unwrap();
+        }
+
+        let position = world.get_component::<Position>(entity).await.unwrap();
+        assert_eq!((position.x, position.y, position.z), (2, 60, -1));
+    }
+
+    #[test]
+    fn test_falling_reaches_terminal_velocity() {
+        let physics = Physics::default();
+        let mut position = Position::new(0, 0, 0);
+        let mut velocity = Velocity::new(0.0, 0.0, 0.0);
+
+        let mut last_speed = 0.0;
+        let mut fallen = 0.0;
+        for _ in 0..300 {
+            apply_gravity(&mut velocity, true, &physics);
+            apply_velocity(&mut position, &mut velocity);
+            fallen -= velocity.y;
+
+            // Always falling faster, but never past terminal velocity
+            assert!(-velocity.y >= last_speed);
+            assert!(-velocity.y <= physics.terminal_velocity);
+            last_speed = -velocity.y;
+        }
+
+        // Drag alone settles at gravity * (1 - drag) / drag, just under terminal velocity
+        let settled = physics.gravity * (1.0 - physics.drag) / physics.drag;
+        assert!((-velocity.y - settled.min(physics.terminal_velocity)).abs() < 0.05);
+        // Whole blocks moved plus the carried remainder add up to the distance fallen
+        let moved = -(position.y as f64) - velocity.remainder.1;
+        assert!((moved - fallen).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_no_gravity_only_drag() {
+        let physics = Physics::default();
+        let mut velocity = Velocity::new(1.0, 0.0, 0.0);
+        apply_gravity(&mut velocity, false, &physics);
+        assert_eq!(velocity.y, 0.0);
+        assert!((velocity.x - 0.98).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_players_are_not_moved() {
+        let world = World::new();
+        let entity = world
+            .create_entity()
+            .await
+            .with(Position::new(0, 64, 0))
+            .with(Velocity::new(1.0, 0.0, 0.0))
+            .with(Player::new(0, "player".to_string()))
+            .build();
+
+        ApplyVelocity.tick(&world).await.unwrap();
+
+        let position = world.get_component::<Position>(entity).await.unwrap();
+        assert_eq!(position.x, 0);
+    }
+}
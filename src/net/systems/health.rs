@@ -0,0 +1,165 @@
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error, info, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::System;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+/// How long to wait for a probe's request before answering anyway, so plain TCP probes that
+/// never send anything still get a response.
+const REQUEST_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// When the game tick last completed.
+#[derive(Default)]
+pub struct Heartbeat {
+    last_beat: Mutex<Option<Instant>>,
+}
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        *self.last_beat.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// How long ago the last beat was, `None` if there hasn't been one yet.
+    pub fn since_last_beat(&self) -> Option<Duration> {
+        self.last_beat.lock().unwrap().map(|beat| beat.elapsed())
+    }
+
+    pub fn is_alive(&self, max_age: Duration) -> bool {
+        self.since_last_beat().is_some_and(|age| age <= max_age)
+    }
+}
+
+/// Answers liveness probes on `health.port`, if `health.enabled` is set.
+///
+/// Responds with an HTTP 200 while the [Heartbeat] is recent enough, and a 503 once the tick
+/// loop has stalled.
+#[derive(AutoGenName)]
+pub struct HealthCheckSystem;
+
+#[async_trait]
+impl System for HealthCheckSystem {
+    async fn run(&self, state: GlobalState) {
+        let config = get_global_config();
+        if !config.health.enabled {
+            return;
+        }
+
+        let addr = match config.bind_address() {
+            Ok(addr) => SocketAddr::new(addr.ip(), config.health.port as u16),
+            Err(e) => {
+                error!("Invalid health check address: {}", e);
+                return;
+            }
+        };
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind health check to {}: {}", addr, e);
+                return;
+            }
+        };
+        info!("Health check listening on {}", addr);
+
+        let max_tick_age = Duration::from_millis(config.health.max_tick_age_ms);
+        serve_health(listener, state, max_tick_age).await;
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// Answers every connection to `listener` with the server's health.
+pub async fn serve_health(listener: TcpListener, state: GlobalState, max_tick_age: Duration) {
+    loop {
+        let (mut stream, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("Failed to accept health check: {}", e);
+                continue;
+            }
+        };
+
+        let alive = state.heartbeat.is_alive(max_tick_age);
+        if !alive {
+            warn!(
+                "Health check failed, last tick was {:?} ago",
+                state.heartbeat.since_last_beat()
+            );
+        }
+
+        tokio::spawn(async move {
+            if let Err(e) = respond(&mut stream, alive).await {
+                debug!("Failed to answer health check: {}", e);
+            }
+        });
+    }
+}
+
+async fn respond(stream: &mut TcpStream, alive: bool) -> std::io::Result<()> {
+    // The request itself doesn't matter, but reading it keeps HTTP clients happy
+    let mut request = [0; 1024];
+    let _ = tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut request)).await;
+
+    let (status, body) = if alive {
+        ("200 OK", "OK")
+    } else {
+        ("503 Service Unavailable", "STALLED")
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::test_state;
+
+    async fn probe(addr: SocketAddr) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_health_follows_ticks() {
+        let state = test_state().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_health(listener, state.clone(), Duration::from_millis(200)));
+
+        // No tick has run yet
+        assert!(probe(addr).await.starts_with("HTTP/1.1 503"));
+
+        // Ticks advancing
+        for _ in 0..3 {
+            state.heartbeat.beat();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert!(probe(addr).await.starts_with("HTTP/1.1 200 OK"));
+        }
+
+        // Ticks stalled
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let response = probe(addr).await;
+        assert!(response.starts_with("HTTP/1.1 503"));
+        assert!(response.ends_with("STALLED"));
+    }
+}
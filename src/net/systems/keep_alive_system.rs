@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
 use tokio::sync::RwLockReadGuard;
 use tracing::{trace, warn};
@@ -8,9 +10,16 @@ use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
 use crate::net::systems::System;
 use crate::net::{Connection, ConnectionWrapper};
 use crate::state::GlobalState;
-use crate::utils::components::keep_alive::KeepAlive;
+use crate::utils::components::keep_alive::{KeepAlive, NoIdleKick};
 use crate::utils::components::player::Player;
 
+/// How often keep alive packets are sent.
+const SEND_INTERVAL: Duration = Duration::from_secs(15);
+/// How often connections are checked for timeouts.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How long a connection may go without a keep alive before it's dropped.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(AutoGenName)]
 pub struct KeepAliveSystem;
 
@@ -28,17 +37,27 @@ impl System for KeepAliveSystem {
 }
 impl KeepAliveSystem {
     async fn sender(state: GlobalState) {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(15));
         let mut query = state
             .world
-            .query::<(&Player, &mut KeepAlive, &ConnectionWrapper)>();
+            .query::<(&Player, &mut KeepAlive, &ConnectionWrapper, Option<&NoIdleKick>)>();
 
         loop {
-            interval.tick().await;
+            // Update the keep alives first, the connections are only used once they're released
+            let mut due = Vec::new();
+            while let Some((_, (_, mut keep_alive, conn, exempt))) = query.next().await {
+                if exempt.is_none() && state.clock.elapsed_since(keep_alive.last_sent) > TIMEOUT {
+                    due.push((conn.handle(), None));
+                    continue;
+                }
 
-            while let Some((_, (player, mut keep_alive, conn))) = query.next().await {
-                if keep_alive.last_sent.elapsed().as_secs() > 30 {
-                    let conn = conn.0.read().await;
+                keep_alive.data += 1;
+                keep_alive.last_sent = state.clock.now();
+                due.push((conn.handle(), Some(keep_alive.data)));
+            }
+
+            for (conn, data) in due {
+                let conn = conn.read().await;
+                let Some(data) = data else {
                     warn!("Dropping connection {} due to inactivity", conn.id);
                     if let Err(err) = conn.drop_connection(state.clone()).await {
                         warn!(
@@ -47,43 +66,44 @@ impl KeepAliveSystem {
                         );
                     }
                     continue;
-                }
+                };
 
-                keep_alive.data += 1;
-                keep_alive.last_sent = std::time::Instant::now();
-
-                let keep_alive_out = KeepAlivePacketOut::new_auto(keep_alive.data);
-                let conn = conn.0.write().await;
-
-                trace!("Sending keep alive packet to player: {:?}", player);
-                if let Err(e) = conn.send_packet(keep_alive_out).await {
+                trace!("Sending keep alive packet to connection {}", conn.id);
+                if let Err(e) = conn.send_packet(KeepAlivePacketOut::new_auto(data)).await {
                     warn!("Error sending keep alive packet: {:?}", e);
                 }
             }
+
+            state.clock.sleep(SEND_INTERVAL).await;
         }
     }
     async fn receiver(state: GlobalState) {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
-        let mut query = state.world.query::<(&KeepAlive, &ConnectionWrapper)>();
+        let mut query = state
+            .world
+            .query::<(&KeepAlive, &ConnectionWrapper, Option<&NoIdleKick>)>();
 
         loop {
-            interval.tick().await;
-
-            while let Some((_, (keep_alive, conn_wrapper))) = query.next().await {
-                if keep_alive.last_sent.elapsed().as_secs() <= 30 {
-                    continue;
+            let mut timed_out = Vec::new();
+            while let Some((_, (keep_alive, conn_wrapper, exempt))) = query.next().await {
+                if exempt.is_none() && state.clock.elapsed_since(keep_alive.last_sent) > TIMEOUT {
+                    timed_out.push(conn_wrapper.handle());
                 }
+            }
 
-                let conn = conn_wrapper.0.read().await;
+            for conn in timed_out {
+                let conn = conn.read().await;
                 let player = state.world.get_component::<Player>(conn.id).await;
 
                 let username = player
                     .as_ref()
-                    .map(|p| p.username.as_str())
-                    .unwrap_or("Unknown<!>Player");
+                    .map(|p| p.username.clone())
+                    .unwrap_or_else(|_| "Unknown<!>Player".to_string());
+                drop(player);
 
-                Self::drop_connection(conn, username, state.clone()).await;
+                Self::drop_connection(conn, &username, state.clone()).await;
             }
+
+            state.clock.sleep(CHECK_INTERVAL).await;
         }
     }
 
@@ -104,3 +124,65 @@ impl KeepAliveSystem {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::tests::helpers::{add_test_player, test_state_with_clock};
+    use crate::utils::clock::{Clock, FakeClock};
+
+    #[tokio::test]
+    async fn test_inactive_connections_time_out() {
+        let clock = Arc::new(FakeClock::new());
+        let state = test_state_with_clock(clock.clone()).await;
+        let (player, _client) = add_test_player(&state, "Sleepy").await;
+        let now = clock.now();
+        state
+            .world
+            .get_component_storage()
+            .insert(player, KeepAlive::new(now, now, 0));
+
+        let receiver = tokio::spawn(KeepAliveSystem::receiver(state.clone()));
+
+        // Still within the timeout
+        clock.advance(Duration::from_secs(20));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(state.connections.get_connection(player).is_ok());
+
+        clock.advance(Duration::from_secs(11));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while state.connections.get_connection(player).is_ok() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Connection should be dropped once the timeout passed");
+
+        receiver.abort();
+    }
+
+    #[tokio::test]
+    async fn test_exempt_connections_never_time_out() {
+        let clock = Arc::new(FakeClock::new());
+        let state = test_state_with_clock(clock.clone()).await;
+        let (bot, _client) = add_test_player(&state, "Bot").await;
+        let now = clock.now();
+        state
+            .world
+            .get_component_storage()
+            .insert(bot, KeepAlive::new(now, now, 0))
+            .insert(bot, NoIdleKick);
+
+        let receiver = tokio::spawn(KeepAliveSystem::receiver(state.clone()));
+
+        for _ in 0..10 {
+            clock.advance(TIMEOUT);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(state.connections.get_connection(bot).is_ok());
+
+        receiver.abort();
+    }
+}
@@ -1,39 +1,89 @@
+use std::sync::Mutex;
+
 use async_trait::async_trait;
 use futures::stream::FuturesUnordered;
 use tracing::{debug_span, info, Instrument};
 
 use crate::state::GlobalState;
+use crate::utils::lock_order;
 use crate::utils::prelude::*;
 
+pub mod autosave;
+pub mod block_changes;
 pub mod chunk_sender;
 pub mod connection_handler;
+pub mod entity_movement;
+pub mod entity_tick;
+pub mod health;
 pub mod keep_alive_system;
 pub mod tick_system;
+pub mod time;
+pub mod weather;
 
 #[async_trait]
 pub trait System: Send + Sync {
     async fn run(&self, state: GlobalState);
     fn name(&self) -> &'static str;
-    async fn kill(&self) {}
+    /// Called once when the server shuts down, e.g. to save what the system was working on.
+    async fn kill(&self, _state: GlobalState) {}
 }
 
 pub static ALL_SYSTEMS: &[&dyn System] = &[
     &tick_system::TickSystem,
+    &autosave::AutosaveSystem,
+    &block_changes::BlockChangeSystem,
     &keep_alive_system::KeepAliveSystem,
     &chunk_sender::ChunkSender,
     &connection_handler::ConnectionHandler,
+    &entity_movement::EntityMovementSystem,
+    &entity_tick::EntityTickSystem,
+    &health::HealthCheckSystem,
+    &time::TimeSystem,
+    &weather::WeatherSystem,
 ];
 
+/// Systems added at runtime with [register_system], kept per server in [GlobalState].
+#[derive(Default)]
+pub struct SystemRegistry(Mutex<Vec<&'static dyn System>>);
+
+impl SystemRegistry {
+    fn registered(&self) -> Vec<&'static dyn System> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Adds a system to run alongside the built in [ALL_SYSTEMS], so crates embedding the server can
+/// plug in their own behaviour.
+///
+/// Only systems registered before [start_all_systems] is called are started.
+pub fn register_system(state: &GlobalState, system: Box<dyn System>) {
+    state.systems.0.lock().unwrap().push(Box::leak(system));
+}
+
+/// The built in systems followed by every one registered with `state`.
+fn all_systems(state: &GlobalState) -> Vec<&'static dyn System> {
+    ALL_SYSTEMS
+        .iter()
+        .copied()
+        .chain(state.systems.registered())
+        .collect()
+}
+
 pub async fn start_all_systems(state: GlobalState) -> Result<()> {
+    let systems = all_systems(&state);
+    start_systems(state, systems).await
+}
+
+async fn start_systems(state: GlobalState, systems: Vec<&'static dyn System>) -> Result<()> {
     let handles = FuturesUnordered::new();
-    for system in ALL_SYSTEMS {
+    for system in systems {
         let name = system.name();
 
-        let handle = tokio::spawn(
+        let handle = tokio::spawn(lock_order::tracked(
             system
                 .run(state.clone())
                 .instrument(debug_span!("sys", %name)),
-        );
+        ));
         handles.push(handle);
     }
 
@@ -42,10 +92,57 @@ pub async fn start_all_systems(state: GlobalState) -> Result<()> {
     Ok(())
 }
 
-pub async fn kill_all_systems() -> Result<()> {
+pub async fn kill_all_systems(state: GlobalState) -> Result<()> {
     info!("Killing all systems...");
-    for system in ALL_SYSTEMS {
-        system.kill().await;
+    for system in all_systems(&state) {
+        system.kill(state.clone()).await;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::tests::helpers::test_state;
+
+    struct CustomSystem {
+        ran: mpsc::UnboundedSender<()>,
+    }
+
+    #[async_trait]
+    impl System for CustomSystem {
+        async fn run(&self, _state: GlobalState) {
+            self.ran.send(()).unwrap();
+        }
+
+        fn name(&self) -> &'static str {
+            "CustomSystem"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registered_system_runs() {
+        let (ran, mut ran_rx) = mpsc::unbounded_channel();
+        let state = test_state().await;
+        register_system(&state, Box::new(CustomSystem { ran }));
+        assert!(all_systems(&state)
+            .iter()
+            .any(|system| system.name() == "CustomSystem"));
+        // Only registered with that server
+        assert!(!all_systems(&test_state().await)
+            .iter()
+            .any(|system| system.name() == "CustomSystem"));
+
+        // Without the built in systems, which need a config file
+        let systems = tokio::spawn(start_systems(state.clone(), state.systems.registered()));
+        tokio::time::timeout(Duration::from_secs(5), ran_rx.recv())
+            .await
+            .expect("Registered system never ran")
+            .unwrap();
+        systems.abort();
+    }
+}
@@ -8,19 +8,27 @@ use crate::utils::components::player::Player;
 use ferrumc_macros::AutoGenName;
 use tracing::{warn};
 
+/// How many ticks the crab in the server brand waits before moving on.
+const BRAND_TICKS: u64 = 2;
+
+/// Runs the game tick at the rate in [crate::world::ticks::GameTicks], unless it's frozen.
 #[derive(AutoGenName)]
 pub struct TickSystem;
 
 #[async_trait]
 impl System for TickSystem {
     async fn run(&self, state: GlobalState) {
-        let mut query = state.world.query::<(&ConnectionWrapper, &Player)>();
-
         let width = 40;
         let total_width = width * 2;
         let mut offset = 0;
 
         loop {
+            state.clock.sleep(state.ticks.interval()).await;
+            state.heartbeat.beat();
+            if !state.ticks.advance() || state.ticks.count() % BRAND_TICKS != 0 {
+                continue;
+            }
+
             let mut crab_wave = vec![" "; total_width];
 
             for x in 0..total_width {
@@ -38,9 +46,9 @@ impl System for TickSystem {
                 .cloned()
                 .collect();
 
-            while let Some((_, (conn, _))) = query.next().await {
+            for (_, conn) in ConnectionWrapper::handles::<&Player>(&state.world).await {
                 let packet = LoginPluginRequest::server_brand(&visible_wave).await;
-                let conn = conn.0.read().await;
+                let conn = conn.read().await;
                 if let Err(e) = conn.send_packet(packet).await {
                     warn!("Failed to send packet: {}", e);
                     continue;
@@ -49,8 +57,6 @@ impl System for TickSystem {
             }
 
             offset = (offset + 1) % total_width;
-
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
     }
 
@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::packets::outgoing::update_time::UpdateTime;
+use crate::net::systems::System;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+use crate::world::gamerules::DO_DAYLIGHT_CYCLE;
+
+/// How often players are told the time, in ticks. They advance it themselves in between.
+const UPDATE_INTERVAL_TICKS: i64 = 20;
+
+/// Advances the world time every game tick, see [crate::world::time].
+#[derive(AutoGenName)]
+pub struct TimeSystem;
+
+#[async_trait]
+impl System for TimeSystem {
+    async fn run(&self, state: GlobalState) {
+        let mut ticks = state.ticks.subscribe();
+        while ticks.changed().await.is_ok() {
+            tick_time(&state).await;
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// One tick of world time. The time of day only moves while `doDaylightCycle` is on.
+pub async fn tick_time(state: &GlobalState) {
+    let daylight_cycle = state.gamerules.is_enabled(DO_DAYLIGHT_CYCLE);
+    state.time.tick(daylight_cycle);
+
+    if state.time.age() % UPDATE_INTERVAL_TICKS == 0 {
+        let packet = UpdateTime::new(&state.time, daylight_cycle);
+        if let Err(e) = broadcast(&packet, state, None).await {
+            warn!("Failed to send the time: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_disabling_daylight_cycle_freezes_time() {
+        let state = test_state().await;
+        let (_, mut client) = add_test_player(&state, "Sleeper").await;
+
+        for _ in 0..UPDATE_INTERVAL_TICKS {
+            tick_time(&state).await;
+        }
+        assert_eq!(state.time.time_of_day(), 20);
+        let (packet_id, body) = read_packet(&mut client).await;
+        assert_eq!(packet_id, 0x5E);
+        assert_eq!(body[8..], 20i64.to_be_bytes());
+
+        let database = &state.database;
+        state
+            .gamerules
+            .set(database, DO_DAYLIGHT_CYCLE, "false")
+            .await
+            .unwrap();
+        for _ in 0..UPDATE_INTERVAL_TICKS {
+            tick_time(&state).await;
+        }
+        assert_eq!(state.time.time_of_day(), 20);
+        assert_eq!(state.time.age(), 40);
+
+        // Sent negative, so the client doesn't advance it either
+        let (_, body) = read_packet(&mut client).await;
+        assert_eq!(body[..8], 40i64.to_be_bytes());
+        assert_eq!(body[8..], (-20i64).to_be_bytes());
+    }
+}
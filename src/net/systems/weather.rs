@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::System;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+use crate::world::gamerules::DO_WEATHER_CYCLE;
+use crate::world::weather::Weather;
+
+/// Changes the weather over time while `doWeatherCycle` is on, see [crate::world::weather].
+#[derive(AutoGenName)]
+pub struct WeatherSystem;
+
+#[async_trait]
+impl System for WeatherSystem {
+    async fn run(&self, state: GlobalState) {
+        let mut ticks = state.ticks.subscribe();
+        while ticks.changed().await.is_ok() {
+            tick_weather(&state).await;
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// One tick of weather in the default dimension, the only one players can be in.
+async fn tick_weather(state: &GlobalState) {
+    if !state.gamerules.is_enabled(DO_WEATHER_CYCLE) {
+        return;
+    }
+
+    let config = get_global_config();
+    if let Some(weather) = state.weather.tick(&config.default_dimension) {
+        info!("The weather changed to {}", weather);
+        if let Err(e) = broadcast_weather(state, weather).await {
+            warn!("Failed to send the weather: {}", e);
+        }
+    }
+}
+
+/// Shows `weather` to every player.
+pub async fn broadcast_weather(state: &GlobalState, weather: Weather) -> Result<()> {
+    for packet in weather.packets() {
+        broadcast(&packet, state, None).await?;
+    }
+    Ok(())
+}
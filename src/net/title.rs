@@ -0,0 +1,106 @@
+//! Titles in the middle of the screen and text above the hotbar.
+
+use serde_json::Value;
+
+use crate::net::packets::outgoing::set_action_bar_text::SetActionBarText;
+use crate::net::packets::outgoing::set_subtitle_text::SetSubtitleText;
+use crate::net::packets::outgoing::set_title_animation_times::SetTitleAnimationTimes;
+use crate::net::packets::outgoing::set_title_text::SetTitleText;
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// How long a title fades in, stays and fades out, in ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TitleTimes {
+    pub fade_in: i32,
+    pub stay: i32,
+    pub fade_out: i32,
+}
+
+impl Default for TitleTimes {
+    /// The client's own defaults.
+    fn default() -> Self {
+        Self {
+            fade_in: 10,
+            stay: 70,
+            fade_out: 20,
+        }
+    }
+}
+
+/// Shows a title, and optionally a subtitle, to a player. Texts are JSON text components.
+///
+/// Without `times` the ones sent last time are used.
+pub async fn send_title(
+    player: u32,
+    title: &Value,
+    subtitle: Option<&Value>,
+    times: Option<TitleTimes>,
+    state: &GlobalState,
+) -> Result<()> {
+    // The title is what makes the client show everything, so it goes last
+    let mut packet_queue = PacketQueue::new();
+    if let Some(times) = times {
+        packet_queue
+            .queue(SetTitleAnimationTimes::new(times.fade_in, times.stay, times.fade_out))
+            .await?;
+    }
+    if let Some(subtitle) = subtitle {
+        packet_queue.queue(SetSubtitleText::new(subtitle)).await?;
+    }
+    packet_queue.queue(SetTitleText::new(title)).await?;
+
+    let conn = state.connections.get_connection(player)?;
+    let conn = conn.read().await;
+    conn.send_packets(packet_queue).await
+}
+
+/// Shows a JSON text component above a player's hotbar.
+pub async fn send_action_bar(player: u32, text: &Value, state: &GlobalState) -> Result<()> {
+    let conn = state.connections.get_connection(player)?;
+    let conn = conn.read().await;
+    conn.send_packet(SetActionBarText::new(text)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_full_title() {
+        let state = test_state().await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+
+        let title = json!({ "text": "Victory", "color": "gold", "bold": true });
+        let subtitle = json!({ "text": "Red team wins" });
+        let times = TitleTimes {
+            fade_in: 5,
+            stay: 40,
+            fade_out: 300,
+        };
+        send_title(player, &title, Some(&subtitle), Some(times), &state)
+            .await
+            .unwrap();
+
+        let mut expected_times = 5i32.to_be_bytes().to_vec();
+        expected_times.extend_from_slice(&40i32.to_be_bytes());
+        expected_times.extend_from_slice(&300i32.to_be_bytes());
+        assert_eq!(read_packet(&mut client).await, (0x60, expected_times));
+        assert_eq!(read_packet(&mut client).await, (0x5D, text_body(&subtitle)));
+        assert_eq!(read_packet(&mut client).await, (0x5F, text_body(&title)));
+
+        send_action_bar(player, &json!({ "text": "+1" }), &state).await.unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x46);
+    }
+
+    fn text_body(component: &Value) -> Vec<u8> {
+        let text = component.to_string();
+        let mut body = vec![text.len() as u8];
+        body.extend_from_slice(text.as_bytes());
+        body
+    }
+}
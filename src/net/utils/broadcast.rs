@@ -0,0 +1,148 @@
+use ferrumc_codec::enc::NetEncode;
+use tracing::{debug, warn};
+
+use crate::net::utils::buffer_pool::ENCODE_POOL;
+use crate::net::State;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// What happens to a recipient whose send queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnFull {
+    /// The recipient misses the packet, for packets that are superseded soon anyway.
+    Skip,
+    /// The recipient is disconnected, for packets it can't do without.
+    Disconnect,
+}
+
+/// Sends a packet to every connection in the play state, except `except`.
+///
+/// The packet is only encoded once, and never waits for a recipient: clients whose send queue is
+/// full are disconnected instead of holding up everyone else. Failing to send to one connection
+/// doesn't stop the broadcast, it's only logged.
+pub async fn broadcast(
+    packet: &impl NetEncode,
+    state: &GlobalState,
+    except: Option<u32>,
+) -> Result<()> {
+    // Collect first, so no DashMap shard stays locked while waiting on a connection
+    let recipients: Vec<u32> = state
+        .connections
+        .connections
+        .iter()
+        .map(|entry| *entry.key())
+        .filter(|id| Some(*id) != except)
+        .collect();
+
+    broadcast_to(packet, state, recipients).await
+}
+
+/// Sends a packet to each of `recipients` that is in the play state, the same way as [broadcast].
+pub async fn broadcast_to(
+    packet: &impl NetEncode,
+    state: &GlobalState,
+    recipients: impl IntoIterator<Item = u32>,
+) -> Result<()> {
+    send_to_all(packet, state, recipients, OnFull::Disconnect).await
+}
+
+/// Like [broadcast_to], but recipients that are lagging behind just miss the packet.
+///
+/// For packets that don't matter much on their own, like sounds, particles or head rotations.
+pub async fn broadcast_lossy_to(
+    packet: &impl NetEncode,
+    state: &GlobalState,
+    recipients: impl IntoIterator<Item = u32>,
+) -> Result<()> {
+    send_to_all(packet, state, recipients, OnFull::Skip).await
+}
+
+async fn send_to_all(
+    packet: &impl NetEncode,
+    state: &GlobalState,
+    recipients: impl IntoIterator<Item = u32>,
+    on_full: OnFull,
+) -> Result<()> {
+    let mut encoded = ENCODE_POOL.get();
+    packet.net_encode(&mut *encoded).await?;
+
+    for id in recipients {
+        // Might have disconnected in the meantime
+        let Ok(conn) = state.connections.get_connection(id) else {
+            continue;
+        };
+        let conn = conn.read().await;
+        if conn.state != State::Play {
+            continue;
+        }
+        match conn.try_send_encoded(&encoded) {
+            Ok(()) => {}
+            Err(Error::SendQueueFull) if on_full == OnFull::Skip => {
+                debug!("Skipped broadcast to {}, its send queue is full", conn.id);
+            }
+            Err(Error::SendQueueFull) => {
+                warn!("Send queue of {} is full, disconnecting it", conn.id);
+                conn.stream.out_stream.stall();
+            }
+            Err(e) => warn!("Failed to broadcast packet to {}: {:?}", conn.id, e),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+    use crate::net::utils::send_queue::SendQueue;
+    use crate::tests::helpers::{add_test_player, read_packet, test_state};
+
+    #[tokio::test]
+    async fn test_full_queue_does_not_block_others() {
+        let state = test_state().await;
+        let (lagging, _lagging_client) = add_test_player(&state, "Lagging").await;
+        let (player, mut client) = add_test_player(&state, "Player").await;
+
+        // Nobody reads the other end of the pipe, so the queue fills up and stays full
+        let (stream, _unread) = tokio::io::duplex(8);
+        let conn = state.connections.get_connection(lagging).unwrap();
+        conn.write().await.stream.out_stream = SendQueue::new(stream, 1, Duration::from_secs(60));
+        {
+            let conn = conn.read().await;
+            for _ in 0..100 {
+                if conn.try_send_encoded(&[0; 8]).is_err() {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+            assert!(matches!(conn.try_send_encoded(&[0]), Err(Error::SendQueueFull)));
+        }
+        let stalled = || async {
+            let conn = conn.read().await;
+            tokio::time::timeout(Duration::from_millis(100), conn.stream.out_stream.stalled())
+                .await
+                .is_ok()
+        };
+
+        let packet = KeepAlivePacketOut::new_auto(1);
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            broadcast_lossy_to(&packet, &state, [lagging, player]),
+        )
+        .await
+        .expect("the broadcast waited for the lagging client")
+        .unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x23);
+        assert!(!stalled().await, "skipping a packet shouldn't disconnect");
+
+        tokio::time::timeout(Duration::from_secs(1), broadcast(&packet, &state, None))
+            .await
+            .expect("the broadcast waited for the lagging client")
+            .unwrap();
+        assert_eq!(read_packet(&mut client).await.0, 0x23);
+        assert!(stalled().await, "the lagging client should be disconnected");
+    }
+}
@@ -0,0 +1,139 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+/// How many idle buffers the global encode pool holds on to.
+const ENCODE_POOL_SIZE: usize = 64;
+/// Buffers that grew beyond this (e.g. after encoding a chunk) are freed instead of pooled,
+/// so one huge packet doesn't pin its memory forever.
+const ENCODE_POOL_MAX_CAPACITY: usize = 1024 * 1024;
+
+/// The pool used for encoding outgoing packets. See [crate::net::Connection::send_packet].
+pub static ENCODE_POOL: LazyLock<BufferPool> =
+    LazyLock::new(|| BufferPool::new(ENCODE_POOL_SIZE, ENCODE_POOL_MAX_CAPACITY));
+
+/// A pool of reusable byte buffers, to avoid allocating a fresh `Vec` for every packet sent.
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    max_buffers: usize,
+    max_capacity: usize,
+    allocations: AtomicUsize,
+}
+
+impl BufferPool {
+    pub fn new(max_buffers: usize, max_capacity: usize) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::with_capacity(max_buffers)),
+            max_buffers,
+            max_capacity,
+            allocations: AtomicUsize::new(0),
+        }
+    }
+
+    /// Take an empty buffer out of the pool, allocating a new one if the pool is empty.
+    ///
+    /// The buffer goes back into the pool once the returned [PooledBuffer] is dropped.
+    pub fn get(&self) -> PooledBuffer<'_> {
+        let buffer = self
+            .buffers
+            .lock()
+            .expect("Buffer pool mutex poisoned")
+            .pop()
+            .unwrap_or_else(|| {
+                self.allocations.fetch_add(1, Ordering::Relaxed);
+                Vec::new()
+            });
+
+        PooledBuffer {
+            buffer,
+            pool: self,
+        }
+    }
+
+    /// How many buffers this pool had to allocate so far.
+    pub fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    fn put_back(&self, mut buffer: Vec<u8>) {
+        if buffer.capacity() > self.max_capacity {
+            return;
+        }
+        buffer.clear();
+
+        let mut buffers = self.buffers.lock().expect("Buffer pool mutex poisoned");
+        if buffers.len() < self.max_buffers {
+            buffers.push(buffer);
+        }
+    }
+}
+
+/// A buffer borrowed from a [BufferPool]. Derefs to the underlying `Vec<u8>`.
+pub struct PooledBuffer<'a> {
+    buffer: Vec<u8>,
+    pool: &'a BufferPool,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        self.pool.put_back(std::mem::take(&mut self.buffer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+    use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+
+    #[tokio::test]
+    async fn test_repeated_sends_reuse_buffer() {
+        let pool = BufferPool::new(4, 1024);
+
+        for i in 0..100 {
+            let mut buffer = pool.get();
+            KeepAlivePacketOut::new_auto(i)
+                .net_encode(&mut *buffer)
+                .await
+                .unwrap();
+            assert!(!buffer.is_empty());
+        }
+
+        assert_eq!(pool.allocations(), 1);
+    }
+
+    #[test]
+    fn test_buffers_come_back_empty() {
+        let pool = BufferPool::new(4, 1024);
+        pool.get().extend_from_slice(&[1, 2, 3]);
+
+        let buffer = pool.get();
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 3);
+        assert_eq!(pool.allocations(), 1);
+    }
+
+    #[test]
+    fn test_oversized_buffers_are_dropped() {
+        let pool = BufferPool::new(4, 16);
+        pool.get().extend_from_slice(&[0; 64]);
+        drop(pool.get());
+
+        assert_eq!(pool.allocations(), 2);
+    }
+}
@@ -0,0 +1,141 @@
+//! Encodes chunk packets on tokio's blocking thread pool, so packing sections and computing
+//! light doesn't hold up the async runtime.
+//!
+//! At most [max_parallel_encodes] chunks are encoded at once across the whole server, leaving a
+//! core for everything else.
+
+use std::sync::Arc;
+
+use futures::{Stream, StreamExt};
+use lazy_static::lazy_static;
+use tokio::sync::Semaphore;
+
+use crate::net::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLight;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+use crate::world::chunk_format::Chunk;
+use ferrumc_codec::enc::NetEncode;
+
+lazy_static! {
+    static ref ENCODE_PERMITS: Semaphore = Semaphore::new(max_parallel_encodes());
+}
+
+/// One less than the number of cores, but at least one.
+pub fn max_parallel_encodes() -> usize {
+    std::thread::available_parallelism()
+        .map_or(1, |cores| cores.get() - 1)
+        .max(1)
+}
+
+/// Encodes the Chunk Data and Update Light packet for a chunk, ready for
+/// [crate::net::Connection::send_encoded].
+pub async fn encode_chunk(chunk: Chunk) -> Result<Vec<u8>> {
+    let _permit = ENCODE_PERMITS
+        .acquire()
+        .await
+        .expect("The semaphore is never closed");
+    tokio::task::spawn_blocking(move || futures::executor::block_on(encode(chunk))).await?
+}
+
+/// Encodes chunks in parallel, yielding them in the order they were given.
+pub fn encode_chunks(
+    chunks: impl IntoIterator<Item = Chunk>,
+) -> impl Stream<Item = Result<Vec<u8>>> {
+    futures::stream::iter(chunks)
+        .map(encode_chunk)
+        .buffered(max_parallel_encodes())
+}
+
+/// Like [encode_chunk], for a chunk from [crate::database::Database::get_chunk_shared]. Its
+/// sections are only encoded the first time, see [crate::database::Database::network_sections].
+pub async fn encode_cached_chunk(state: GlobalState, chunk: Arc<Chunk>) -> Result<Vec<u8>> {
+    let _permit = ENCODE_PERMITS
+        .acquire()
+        .await
+        .expect("The semaphore is never closed");
+    tokio::task::spawn_blocking(move || {
+        futures::executor::block_on(async {
+            let sections = state.database.network_sections(&chunk).await?;
+            let chunk = Arc::unwrap_or_clone(chunk);
+            let packet = ChunkDataAndUpdateLight::with_sections(chunk, sections.to_vec())?;
+            let mut encoded = Vec::new();
+            packet.net_encode(&mut encoded).await?;
+            Ok(encoded)
+        })
+    })
+    .await?
+}
+
+/// Like [encode_chunks], reusing encoded sections, see [encode_cached_chunk].
+pub fn encode_cached_chunks(
+    state: GlobalState,
+    chunks: impl IntoIterator<Item = Arc<Chunk>>,
+) -> impl Stream<Item = Result<Vec<u8>>> {
+    futures::stream::iter(chunks)
+        .map(move |chunk| encode_cached_chunk(state.clone(), chunk))
+        .buffered(max_parallel_encodes())
+}
+
+/// Encodes a chunk on the current thread.
+pub(crate) async fn encode(chunk: Chunk) -> Result<Vec<u8>> {
+    let packet = ChunkDataAndUpdateLight::from_chunk(chunk).await?;
+    let mut encoded = Vec::new();
+    packet.net_encode(&mut encoded).await?;
+    Ok(encoded)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::database::benches::representative_chunk;
+    use crate::tests::helpers::test_state;
+
+    /// A chunk in the network format, like the ones in the database.
+    pub(crate) fn net_chunk(x: i32, z: i32) -> Chunk {
+        let mut chunk = representative_chunk(x, z);
+        chunk.convert_to_net_mode().unwrap();
+        chunk
+    }
+
+    #[tokio::test]
+    async fn test_parallel_encoding_keeps_order() {
+        let chunks: Vec<Chunk> = (0..4).map(|x| net_chunk(x, 0)).collect();
+        let mut expected = Vec::new();
+        for chunk in chunks.clone() {
+            expected.push(encode(chunk).await.unwrap());
+        }
+
+        let encoded: Vec<Vec<u8>> = encode_chunks(chunks)
+            .map(|encoded| encoded.unwrap())
+            .collect()
+            .await;
+        assert_eq!(encoded, expected);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_chunk_reuses_encoded_sections() {
+        let state = test_state().await;
+        let database = &state.database;
+        database.insert_chunk(net_chunk(0, 0)).await.unwrap();
+        let get = || database.get_chunk_shared(0, 0, "overworld");
+
+        let first = database.network_sections(&get().await.unwrap().unwrap()).await.unwrap();
+        let chunk = get().await.unwrap().unwrap();
+        let second = database.network_sections(&chunk).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // Same packet as encoding from scratch
+        let expected = encode(Chunk::clone(&chunk)).await.unwrap();
+        assert_eq!(encode_cached_chunk(state.clone(), chunk).await.unwrap(), expected);
+
+        database
+            .modify_chunk(0, 0, "overworld", |chunk| {
+                chunk.sections.as_mut().unwrap().pop();
+            })
+            .await
+            .unwrap();
+        let changed = database.network_sections(&get().await.unwrap().unwrap()).await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &changed));
+        assert!(changed.len() < first.len());
+    }
+}
@@ -1 +1,6 @@
-pub mod packet_queue;
\ No newline at end of file
+pub mod broadcast;
+pub mod buffer_pool;
+pub mod chunk_encoder;
+pub mod packet_dump;
+pub mod packet_queue;
+pub mod send_queue;
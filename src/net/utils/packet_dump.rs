@@ -0,0 +1,164 @@
+//! Logs the raw bytes of packets at trace level for protocol debugging, when `debug.packet_dump`
+//! is on. When it's off, a packet costs one check of an already initialized [OnceLock].
+
+use std::fmt::{Display, Formatter, Write};
+use std::sync::OnceLock;
+
+use tracing::{trace, Level};
+
+use crate::net::State;
+use crate::utils::config::{get_global_config, Debugging};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Direction::Incoming => "incoming",
+            Direction::Outgoing => "outgoing",
+        })
+    }
+}
+
+/// Which packets are dumped.
+#[derive(Debug)]
+pub struct PacketDump {
+    allow: Vec<u8>,
+    deny: Vec<u8>,
+}
+
+impl PacketDump {
+    /// `None` if dumping is off.
+    pub fn from_config(config: &Debugging) -> Option<Self> {
+        config.packet_dump.then(|| Self {
+            allow: config.packet_dump_allow.clone(),
+            deny: config.packet_dump_deny.clone(),
+        })
+    }
+
+    pub fn includes(&self, packet_id: i32) -> bool {
+        let Ok(id) = u8::try_from(packet_id) else {
+            return self.allow.is_empty();
+        };
+        (self.allow.is_empty() || self.allow.contains(&id)) && !self.deny.contains(&id)
+    }
+
+    /// Logs a packet's body, everything after its id.
+    pub fn log(&self, direction: Direction, state: &State, packet_id: i32, body: &[u8]) {
+        if !self.includes(packet_id) || !tracing::enabled!(Level::TRACE) {
+            return;
+        }
+        let mut hex = String::with_capacity(body.len() * 2);
+        for byte in body {
+            let _ = write!(hex, "{:02x}", byte);
+        }
+        trace!(
+            "{} {} packet 0x{:02X} ({} bytes): {}",
+            direction,
+            state.as_str(),
+            packet_id,
+            body.len(),
+            hex
+        );
+    }
+
+    /// Logs every packet in `bytes`, which holds whole length prefixed packets like a send
+    /// buffer.
+    pub fn log_encoded(&self, direction: Direction, state: &State, mut bytes: &[u8]) {
+        while let Some(length) = read_varint(&mut bytes) {
+            let Some((mut packet, rest)) = bytes.split_at_checked(length.max(0) as usize) else {
+                return;
+            };
+            bytes = rest;
+            if let Some(packet_id) = read_varint(&mut packet) {
+                self.log(direction, state, packet_id, packet);
+            }
+        }
+    }
+}
+
+/// The dump set up from the config on first use, `None` if `debug.packet_dump` is off.
+pub fn packet_dump() -> Option<&'static PacketDump> {
+    static DUMP: OnceLock<Option<PacketDump>> = OnceLock::new();
+    DUMP.get_or_init(|| PacketDump::from_config(&get_global_config().debug)).as_ref()
+}
+
+/// Reads a VarInt off the front of `bytes`.
+fn read_varint(bytes: &mut &[u8]) -> Option<i32> {
+    let mut value = 0;
+    for i in 0..5 {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= ((byte & 0x7F) as i32) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::*;
+    use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+    use crate::net::utils::buffer_pool::ENCODE_POOL;
+
+    fn dump(allow: Vec<u8>, deny: Vec<u8>) -> PacketDump {
+        PacketDump::from_config(&Debugging {
+            packet_dump: true,
+            packet_dump_allow: allow,
+            packet_dump_deny: deny,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_allow_and_deny() {
+        assert!(PacketDump::from_config(&Debugging::default()).is_none());
+        assert!(dump(vec![], vec![]).includes(0x23));
+        assert!(!dump(vec![0x12], vec![]).includes(0x23));
+        assert!(!dump(vec![], vec![0x23]).includes(0x23));
+    }
+
+    #[tokio::test]
+    async fn test_logs_id_and_direction() {
+        let mut buffer = ENCODE_POOL.get();
+        KeepAlivePacketOut::new_auto(7).net_encode(&mut *buffer).await.unwrap();
+
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::TRACE)
+            .with_writer(move || Captured(writer.clone()))
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            dump(vec![], vec![]).log_encoded(Direction::Outgoing, &State::Play, &buffer);
+            dump(vec![], vec![0x23]).log(Direction::Incoming, &State::Play, 0x23, &[1]);
+        });
+
+        let output = String::from_utf8(captured.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("outgoing play packet 0x23 (8 bytes): 0000000000000007"));
+        assert!(!output.contains("incoming"));
+    }
+
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
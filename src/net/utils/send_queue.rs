@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc::error::{SendTimeoutError, TrySendError};
+use tokio::sync::{mpsc, watch, Notify};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::net::utils::buffer_pool::PooledBuffer;
+use crate::utils::error::Error;
+
+/// How long a packet may wait for room in a full queue before the client is considered too slow.
+pub const SEND_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a dropped connection gets to write out what's still queued, see
+/// [SendQueue::close_gracefully].
+pub const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// `None` asks the writer to shut the stream down once everything queued before it is written.
+type QueuedPacket = Option<PooledBuffer<'static>>;
+
+/// A bounded queue of encoded packets, written out to the client by a background task.
+///
+/// Senders never touch the socket themselves, so a slow client only ever holds up its own writer.
+/// If the queue stays full for longer than the send timeout, [SendQueue::send] fails with
+/// [Error::SendQueueFull] and [SendQueue::stalled] resolves, so the connection can be dropped
+/// instead of buffering without limit.
+pub struct SendQueue {
+    sender: mpsc::Sender<QueuedPacket>,
+    writer: JoinHandle<()>,
+    /// Never sent to, it only closes once the writer task is gone.
+    writer_done: watch::Receiver<()>,
+    send_timeout: Duration,
+    stalled: Notify,
+}
+
+impl SendQueue {
+    /// Spawn the writer task for `stream`, holding at most `depth` packets at once.
+    pub fn new<W>(stream: W, depth: usize, send_timeout: Duration) -> Self
+    where
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(depth.max(1));
+        let (done, writer_done) = watch::channel(());
+        let writer = tokio::spawn(write_queued(stream, receiver, done));
+
+        Self {
+            sender,
+            writer,
+            writer_done,
+            send_timeout,
+            stalled: Notify::new(),
+        }
+    }
+
+    /// Queue an encoded packet, waiting up to the send timeout for room in the queue.
+    pub async fn send(&self, buffer: PooledBuffer<'static>) -> Result<(), Error> {
+        match self.sender.send_timeout(Some(buffer), self.send_timeout).await {
+            Ok(()) => Ok(()),
+            Err(SendTimeoutError::Timeout(_)) => {
+                self.stalled.notify_one();
+                Err(Error::SendQueueFull)
+            }
+            Err(SendTimeoutError::Closed(_)) => Err(Error::SendQueueClosed),
+        }
+    }
+
+    /// Queue an encoded packet only if there's room right now, without waiting.
+    ///
+    /// Unlike [SendQueue::send], a full queue doesn't count as stalled, see [SendQueue::stall].
+    pub fn try_send(&self, buffer: PooledBuffer<'static>) -> Result<(), Error> {
+        match self.sender.try_send(Some(buffer)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(Error::SendQueueFull),
+            Err(TrySendError::Closed(_)) => Err(Error::SendQueueClosed),
+        }
+    }
+
+    /// Report the client as too slow, as if a send had timed out.
+    pub fn stall(&self) {
+        self.stalled.notify_one();
+    }
+
+    /// Resolves once a send timed out because the queue was full, or [SendQueue::stall] was called.
+    pub async fn stalled(&self) {
+        self.stalled.notified().await
+    }
+
+    /// Shut the stream down after the packets already queued have been written.
+    ///
+    /// If the queue is full the client is not keeping up anyway, so the writer is aborted instead.
+    pub fn close(&self) {
+        if self.sender.try_send(None).is_err() {
+            self.writer.abort();
+        }
+    }
+
+    /// Like [SendQueue::close], but waits until the packets already queued have been written and
+    /// the stream is shut down. If that takes longer than `timeout`, e.g. because the client
+    /// stopped reading, the writer is aborted instead. Returns whether everything was written.
+    pub async fn close_gracefully(&self, timeout: Duration) -> bool {
+        let mut writer_done = self.writer_done.clone();
+        let flushed = tokio::time::timeout(timeout, async {
+            // Fails if the writer is already gone, which is what's waited for anyway
+            let _ = self.sender.send(None).await;
+            while writer_done.changed().await.is_ok() {}
+        })
+        .await
+        .is_ok();
+
+        if !flushed {
+            self.writer.abort();
+        }
+        flushed
+    }
+}
+
+/// Writes packets until the queue is closed. `_done` is dropped along with the task, however it
+/// ends.
+async fn write_queued<W>(
+    mut stream: W,
+    mut receiver: mpsc::Receiver<QueuedPacket>,
+    _done: watch::Sender<()>,
+) where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(Some(buffer)) = receiver.recv().await {
+        if let Err(e) = stream.write_all(&buffer).await {
+            debug!("Failed to write queued packet: {}", e);
+            return;
+        }
+    }
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+    use crate::net::utils::buffer_pool::ENCODE_POOL;
+
+    fn buffer_of(bytes: &[u8]) -> PooledBuffer<'static> {
+        let mut buffer = ENCODE_POOL.get();
+        buffer.extend_from_slice(bytes);
+        buffer
+    }
+
+    #[tokio::test]
+    async fn test_packets_are_written_in_order() {
+        let (client, mut server) = tokio::io::duplex(64);
+        let queue = SendQueue::new(client, 4, SEND_QUEUE_TIMEOUT);
+
+        queue.send(buffer_of(&[1, 2])).await.unwrap();
+        queue.send(buffer_of(&[3])).await.unwrap();
+        queue.close();
+
+        let mut received = Vec::new();
+        server.read_to_end(&mut received).await.unwrap();
+        assert_eq!(received, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_writer_fills_queue() {
+        // Nobody ever reads the other end, so the writer blocks once the pipe is full
+        let (client, _server) = tokio::io::duplex(8);
+        let queue = SendQueue::new(client, 2, Duration::from_millis(50));
+
+        let mut result = Ok(());
+        for _ in 0..8 {
+            result = queue.send(buffer_of(&[0; 8])).await;
+            if result.is_err() {
+                break;
+            }
+        }
+
+        assert!(matches!(result, Err(Error::SendQueueFull)));
+        tokio::time::timeout(Duration::from_secs(1), queue.stalled())
+            .await
+            .expect("Stalled queue was not reported");
+    }
+
+    #[tokio::test]
+    async fn test_close_gracefully_writes_queued_packets() {
+        let (client, mut server) = tokio::io::duplex(64);
+        let queue = SendQueue::new(client, 4, SEND_QUEUE_TIMEOUT);
+
+        queue.send(buffer_of(&[1, 2, 3])).await.unwrap();
+        assert!(queue.close_gracefully(Duration::from_secs(1)).await);
+
+        // Everything is already written, and the stream ends after it
+        let mut received = Vec::new();
+        server.read_to_end(&mut received).await.unwrap();
+        assert_eq!(received, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_close_gracefully_gives_up_on_dead_stream() {
+        // Nobody reads the other end, so the packet never gets through
+        let (client, _server) = tokio::io::duplex(8);
+        let queue = SendQueue::new(client, 4, SEND_QUEUE_TIMEOUT);
+        queue.send(buffer_of(&[0; 64])).await.unwrap();
+
+        let closing = queue.close_gracefully(Duration::from_millis(50));
+        let flushed = tokio::time::timeout(Duration::from_secs(1), closing)
+            .await
+            .expect("Closing a dead stream hung");
+        assert!(!flushed);
+    }
+}
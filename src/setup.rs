@@ -1,6 +1,7 @@
 use std::env;
 use std::env::current_exe;
 
+use crate::utils::config::ServerConfig;
 use crate::utils::error::Error;
 use tokio::fs;
 use tracing::{error, info};
@@ -56,7 +57,7 @@ pub(crate) async fn setup() -> Result<(), Error> {
     info!("Creating files...");
     let exe = current_exe()?;
     let dir = exe.parent().unwrap();
-    fs::write(dir.join("config.toml"), BASE_CONFIG.as_bytes()).await?;
+    ServerConfig::write_default(dir.join("config.toml"))?;
     fs::create_dir(dir.join("logs")).await?;
     fs::create_dir(dir.join("plugins")).await?;
     fs::write(
@@ -68,29 +69,3 @@ pub(crate) async fn setup() -> Result<(), Error> {
     info!("Files setup successfully!");
     Ok(())
 }
-
-/// The default configuration file
-/// Not using ServerConfig::default(), since it doesn't have documentation on the usage of each field.
-static BASE_CONFIG: &str = r#"
-# The network address to bind to. Usually just 0.0.0.0 or 127.0.0.1 if you don't want to expose the server to the internet.
-host = "0.0.0.0"
-# The port to bind to. Default is 25565.
-port = 25565
-# The message displayed in the server list.
-motd = ["A FerrumC server; Absolute precision, power, and perfection."]
-# The maximum number of players that can be connected at once.
-max_players = 20
-# How many network updates to process per second per user. 0 means no limit.
-# This is the number of times per second the server will send updates to the client.
-# Having this too low will cause noticable lag for clients but may improve server performance.
-network_tick_rate = 0
-# The default world name. You can switch between mutliple worlds by changing this value.
-world = "world"
-
-[database]
-# The cache size in KB. We recommend leaving this at the default value.
-cache_size = 1024
-# The compression algorithm to use. "fast" is recommended for most use cases.
-# "best" is slower but may provide better compression ratio.
-compression = "fast"
-"#;
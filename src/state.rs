@@ -1,6 +1,19 @@
 use crate::database::Database;
 use crate::ecs::world::World;
+use crate::net::boss_bar::BossBars;
+use crate::net::systems::health::Heartbeat;
+use crate::net::systems::SystemRegistry;
+use crate::net::entity_ids::NetworkEntityIds;
+use crate::net::scoreboard::Scoreboard;
+use crate::net::packets::middleware::MiddlewareChain;
 use crate::net::ConnectionList;
+use crate::utils::clock::Clock;
+use crate::world::block_changes::PendingBlockChanges;
+use crate::world::difficulty::CurrentDifficulty;
+use crate::world::gamerules::GameRules;
+use crate::world::ticks::GameTicks;
+use crate::world::time::WorldTime;
+use crate::world::weather::WorldWeather;
 use std::sync::Arc;
 
 pub struct ServerState {
@@ -8,6 +21,29 @@ pub struct ServerState {
     pub connections: ConnectionList,
     pub database: Database,
     pub server_stream: tokio::net::TcpListener,
+    /// Beats every run of the tick loop, also while frozen, see [crate::net::systems::health].
+    pub heartbeat: Heartbeat,
+    /// Time source for interval and timeout based systems, see [crate::utils::clock].
+    pub clock: Arc<dyn Clock>,
+    /// The tick rate, and whether the game is frozen, see [crate::world::ticks].
+    pub ticks: GameTicks,
+    pub difficulty: CurrentDifficulty,
+    /// See [crate::world::gamerules].
+    pub gamerules: GameRules,
+    pub time: WorldTime,
+    pub weather: WorldWeather,
+    /// Block changes not sent to players yet, see [crate::world::block_changes].
+    pub block_changes: PendingBlockChanges,
+    /// The ids entities are sent to clients with, see [crate::net::entity_ids].
+    pub entity_ids: NetworkEntityIds,
+    /// Objectives and scores shown to everyone, see [crate::net::scoreboard].
+    pub scoreboard: Scoreboard,
+    /// Boss bars and who sees them, see [crate::net::boss_bar].
+    pub boss_bars: BossBars,
+    /// Systems added at runtime, see [crate::net::systems::register_system].
+    pub systems: SystemRegistry,
+    /// Hooks run on every incoming packet, see [crate::net::packets::middleware].
+    pub middleware: MiddlewareChain,
 }
 
 pub type GlobalState = Arc<ServerState>;
@@ -1,6 +1,8 @@
 mod chunk_stuff;
+pub(crate) mod helpers;
 mod nbt_de;
 mod nbt_ser;
+mod packet_decoding;
 pub mod query;
 
 #[cfg(test)]
@@ -30,6 +32,44 @@ mod tests {
         assert_eq!(handshake.server_port, 25565);
         assert_eq!(handshake.next_state, VarInt::new(1));
     }
+    #[tokio::test]
+    async fn test_decode_split_across_reads() {
+        use tokio::io::AsyncWriteExt;
+
+        use crate::net::read_packet_header;
+
+        #[derive(NetDecode)]
+        struct Handshake {
+            protocol_version: VarInt,
+            server_address: String,
+            server_port: u16,
+            next_state: VarInt,
+        }
+        // Length, packet id, then the handshake body
+        let packet = vec![
+            0x10, 0x00, 0xFB, 0x05, 0x09, 0x31, 0x32, 0x37, 0x2E, 0x30, 0x2E, 0x30, 0x2E, 0x31,
+            0x63, 0xDD, 0x01,
+        ];
+
+        let (mut client, mut server) = tokio::io::duplex(64);
+        let writer = tokio::spawn(async move {
+            client.write_all(&packet[..6]).await.unwrap();
+            client.flush().await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            client.write_all(&packet[6..]).await.unwrap();
+        });
+
+        let (packet_id, mut body) = read_packet_header(&mut server).await.unwrap();
+        assert_eq!(packet_id, VarInt::new(0x00));
+        let handshake = Handshake::net_decode(&mut body).await.unwrap();
+        assert_eq!(handshake.protocol_version, VarInt::new(763));
+        assert_eq!(handshake.server_address, "127.0.0.1".to_string());
+        assert_eq!(handshake.server_port, 25565);
+        assert_eq!(handshake.next_state, VarInt::new(1));
+        assert_eq!(body.limit(), 0);
+
+        writer.await.unwrap();
+    }
     /*
     #[tokio::test]
     async fn test_nbt_decode() {
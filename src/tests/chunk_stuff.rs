@@ -12,7 +12,7 @@ pub async fn dump_heightmaps() -> Result<(), Box<dyn std::error::Error>> {
 
     let chunk = state
         .database
-        .get_chunk(0, 0, "overworld".to_string())
+        .get_chunk(0, 0, "overworld")
         .await
         .unwrap()
         .unwrap();
@@ -0,0 +1,102 @@
+//! Setup shared by tests that need a server state and connected players.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::database::tests::temp_config;
+use crate::database::Database;
+use crate::ecs::world::World;
+use crate::net::entity_ids::NetworkEntityIds;
+use crate::net::boss_bar::BossBars;
+use crate::net::scoreboard::Scoreboard;
+use crate::net::systems::health::Heartbeat;
+use crate::net::systems::SystemRegistry;
+use crate::net::packets::middleware::MiddlewareChain;
+use crate::net::{add_connection, read_packet_header, Connection, ConnectionList, State};
+use crate::state::{GlobalState, ServerState};
+use crate::utils::clock::{Clock, SystemClock};
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::encoding::position::Position;
+use crate::world::block_changes::PendingBlockChanges;
+use crate::world::difficulty::{CurrentDifficulty, Difficulty};
+use crate::world::gamerules::GameRules;
+use crate::world::ticks::GameTicks;
+use crate::world::time::WorldTime;
+use crate::world::weather::WorldWeather;
+
+/// A server state with a temporary database, listening on a random local port.
+pub async fn test_state() -> GlobalState {
+    test_state_with_clock(Arc::new(SystemClock)).await
+}
+
+/// Like [test_state], but with time coming from `clock`, e.g. a [crate::utils::clock::FakeClock].
+pub async fn test_state_with_clock(clock: Arc<dyn Clock>) -> GlobalState {
+    Arc::new(ServerState {
+        world: Arc::new(World::new()),
+        connections: ConnectionList::new(),
+        database: Database::open(&temp_config(), "world").await.unwrap(),
+        server_stream: TcpListener::bind("127.0.0.1:0").await.unwrap(),
+        heartbeat: Heartbeat::default(),
+        clock,
+        ticks: GameTicks::default(),
+        difficulty: CurrentDifficulty::new(Difficulty::default()),
+        gamerules: GameRules::default(),
+        time: WorldTime::default(),
+        weather: WorldWeather::default(),
+        block_changes: PendingBlockChanges::default(),
+        entity_ids: NetworkEntityIds::new(),
+        scoreboard: Scoreboard::new(),
+        boss_bars: BossBars::new(),
+        systems: SystemRegistry::default(),
+        middleware: MiddlewareChain::default(),
+    })
+}
+
+/// Connects a client to the state's listener and registers the server side of it as a player
+/// in the play state, like a finished login would.
+///
+/// Returns the player's entity id and the client's end of the socket.
+pub async fn add_test_player(state: &GlobalState, username: &str) -> (u32, TcpStream) {
+    let addr = state.server_stream.local_addr().unwrap();
+    let (client, accepted) = tokio::join!(TcpStream::connect(addr), state.server_stream.accept());
+    let (socket, _) = accepted.unwrap();
+
+    let entity_id = state.world.create_entity().await.build() as u32;
+    let conn = add_connection(Connection::new(entity_id, socket, 64, 8192), state);
+    conn.write().await.state = State::Play;
+    state.entity_ids.allocate(entity_id);
+
+    let uuid = uuid::Uuid::new_v4().as_u128();
+    state.connections.register_player(entity_id, username, uuid);
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, Player::new(uuid, username.to_string()))
+        .insert(entity_id, Position::new(0, 64, 0))
+        .insert(entity_id, Rotation::new(0.0, 0.0));
+
+    (entity_id, client.unwrap())
+}
+
+/// Reads the next packet a client received, returning its id and body.
+pub async fn read_packet(client: &mut TcpStream) -> (i32, Vec<u8>) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        let (packet_id, mut body) = read_packet_header(&mut *client).await.unwrap();
+        let mut data = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut body, &mut data)
+            .await
+            .unwrap();
+        (packet_id.get_val(), data)
+    })
+    .await
+    .expect("Timed out waiting for a packet")
+}
+
+/// Gives a test player a permission level, like `/op` would.
+pub async fn set_op_level(state: &GlobalState, player: u32, level: u8) {
+    let uuid = state.world.get_component::<Player>(player).await.unwrap().uuid;
+    state.database.set_op_level(uuid, level).await.unwrap();
+}
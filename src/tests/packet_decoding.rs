@@ -0,0 +1,74 @@
+//! Feeds arbitrary bytes to every incoming packet decoder, making sure malformed packets are
+//! rejected with an error instead of panicking (or allocating whatever a client asks for).
+//!
+//! The primitive decoders in `ferrumc_codec` and the handshake and status packets it decodes for
+//! the server are fuzzed by the `cargo fuzz` targets in `fuzz/`. The server is a binary crate with
+//! no library for a fuzz target to link against, so this runs the same check over random inputs
+//! to the rest of its packets, and the inputs that broke decoding before.
+
+use std::io::Cursor;
+use std::panic::AssertUnwindSafe;
+
+use futures::FutureExt;
+
+use crate::net::packets::decode_packet;
+use crate::net::State;
+
+const STATES: [State; 4] = [State::Handshake, State::Status, State::Login, State::Play];
+
+/// Decodes `body` as every packet id in every state, panicking with the input if anything panics.
+async fn decode_everything(body: &[u8]) {
+    for state in &STATES {
+        for packet_id in 0..=0x7F {
+            let mut reader = Cursor::new(body);
+            let decoded = AssertUnwindSafe(decode_packet(packet_id, state, &mut reader))
+                .catch_unwind()
+                .await;
+            assert!(
+                decoded.is_ok(),
+                "Decoding packet 0x{:02X} in state {} panicked on {:02X?}",
+                packet_id,
+                state,
+                body
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_packets_dont_panic() {
+    let inputs: &[&[u8]] = &[
+        &[],
+        // String length of -1
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F],
+        // String length of i32::MAX, with nothing after it
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0x07],
+        // Handshake with a huge server address length
+        &[0xFB, 0x05, 0xFF, 0xFF, 0xFF, 0xFF, 0x07, 0x63, 0xDD, 0x01],
+        // VarInt that never ends
+        &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80],
+        // Invalid UTF-8 in a string
+        &[0x02, 0xC3, 0x28],
+    ];
+    for input in inputs {
+        decode_everything(input).await;
+    }
+}
+
+#[tokio::test]
+async fn test_random_packets_dont_panic() {
+    // xorshift, so failures can be reproduced
+    let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    for _ in 0..200 {
+        let length = (next() % 64) as usize;
+        let body: Vec<u8> = (0..length).map(|_| next() as u8).collect();
+        decode_everything(&body).await;
+    }
+}
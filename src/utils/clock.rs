@@ -0,0 +1,114 @@
+//! Abstracts time so systems can be tested without waiting on the real clock.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tokio::sync::Notify;
+
+/// A source of time for systems that run on intervals or track timeouts.
+pub trait Clock: Send + Sync {
+    /// The current time.
+    fn now(&self) -> Instant;
+
+    /// Waits until `duration` has passed on this clock, counting from when this is called rather
+    /// than when the returned future is first polled.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()>;
+
+    /// How much time has passed on this clock since `earlier`.
+    fn elapsed_since(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+}
+
+/// The real clock, backed by [Instant] and tokio's timer.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()> {
+        tokio::time::sleep(duration).boxed()
+    }
+}
+
+/// A clock that only moves when [FakeClock::advance] is called.
+pub struct FakeClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+    advanced: Notify,
+}
+
+impl FakeClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+            advanced: Notify::new(),
+        }
+    }
+
+    /// Moves the clock forward, waking every sleeper whose deadline has passed.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+        self.advanced.notify_waiters();
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()> {
+        self.sleep_until(self.now() + duration).boxed()
+    }
+}
+
+impl FakeClock {
+    async fn sleep_until(&self, deadline: Instant) {
+        loop {
+            // Register before checking, so an advance in between isn't missed
+            let advanced = self.advanced.notified();
+            tokio::pin!(advanced);
+            advanced.as_mut().enable();
+            if self.now() >= deadline {
+                return;
+            }
+            advanced.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fake_clock_sleep_waits_for_advance() {
+        let clock = FakeClock::new();
+        let start = clock.now();
+
+        let sleep = clock.sleep(Duration::from_secs(10));
+        tokio::pin!(sleep);
+
+        clock.advance(Duration::from_secs(5));
+        assert!(sleep.as_mut().now_or_never().is_none());
+
+        clock.advance(Duration::from_secs(5));
+        tokio::time::timeout(Duration::from_secs(5), sleep)
+            .await
+            .expect("Sleeper should wake once the deadline passed");
+        assert_eq!(clock.elapsed_since(start), Duration::from_secs(10));
+    }
+}
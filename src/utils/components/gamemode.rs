@@ -0,0 +1,58 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use ferrumc_macros::Component;
+
+use crate::utils::prelude::*;
+
+/// A player's game mode. New players start out in the configured `default_gamemode`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum GameMode {
+    #[default]
+    Survival = 0,
+    Creative = 1,
+    Adventure = 2,
+    Spectator = 3,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Survival,
+        GameMode::Creative,
+        GameMode::Adventure,
+        GameMode::Spectator,
+    ];
+
+    /// The game mode with the given protocol id.
+    pub fn from_id(id: u8) -> Option<GameMode> {
+        Self::ALL.into_iter().find(|mode| *mode as u8 == id)
+    }
+
+    /// The name used in the config and in commands.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Adventure => "adventure",
+            GameMode::Spectator => "spectator",
+        }
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or_else(|| Error::Generic(format!("Unknown game mode: {}", s)))
+    }
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
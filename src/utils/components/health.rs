@@ -0,0 +1,93 @@
+use ferrumc_macros::Component;
+
+/// The most health a player can have, ten hearts.
+pub const MAX_HEALTH: f32 = 20.0;
+/// The most food a player can have, and the cap for saturation.
+pub const MAX_FOOD: i32 = 20;
+/// How much saturation players join and respawn with.
+pub const DEFAULT_SATURATION: f32 = 5.0;
+
+/// A player's health, between 0 (dead) and [MAX_HEALTH].
+///
+/// Changed through [crate::net::player_health::set_health], which keeps the client in sync.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Health(f32);
+
+impl Health {
+    /// Clamps `value` to the valid range.
+    pub fn new(value: f32) -> Self {
+        // NaN would otherwise slip through clamp
+        if value.is_nan() {
+            return Self(0.0);
+        }
+        Self(value.clamp(0.0, MAX_HEALTH))
+    }
+
+    pub fn get(&self) -> f32 {
+        self.0
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.0 <= 0.0
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self(MAX_HEALTH)
+    }
+}
+
+/// A player's hunger bar and the saturation hidden behind it.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Food {
+    level: i32,
+    saturation: f32,
+}
+
+impl Food {
+    /// Clamps `level` to 0..=[MAX_FOOD], and `saturation` to 0..=`level` like vanilla does.
+    pub fn new(level: i32, saturation: f32) -> Self {
+        let level = level.clamp(0, MAX_FOOD);
+        let saturation = if saturation.is_nan() {
+            0.0
+        } else {
+            saturation.clamp(0.0, level as f32)
+        };
+        Self { level, saturation }
+    }
+
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    pub fn saturation(&self) -> f32 {
+        self.saturation
+    }
+}
+
+impl Default for Food {
+    fn default() -> Self {
+        Self::new(MAX_FOOD, DEFAULT_SATURATION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_are_clamped() {
+        assert_eq!(Health::new(-3.0).get(), 0.0);
+        assert!(Health::new(-3.0).is_dead());
+        assert_eq!(Health::new(25.0).get(), MAX_HEALTH);
+        assert_eq!(Health::new(f32::NAN).get(), 0.0);
+
+        let food = Food::new(30, 40.0);
+        assert_eq!(food.level(), MAX_FOOD);
+        assert_eq!(food.saturation(), MAX_FOOD as f32);
+        let food = Food::new(3, 10.0);
+        assert_eq!(food.saturation(), 3.0);
+        assert_eq!(Food::new(-1, -1.0), Food::new(0, 0.0));
+    }
+}
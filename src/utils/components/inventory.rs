@@ -0,0 +1,294 @@
+use ferrumc_macros::Component;
+
+use crate::utils::encoding::item_stack::ItemStack;
+use crate::utils::prelude::*;
+
+/// Where everything is in the player inventory window, by slot index.
+pub mod layout {
+    use std::ops::Range;
+
+    pub const CRAFTING_OUTPUT: usize = 0;
+    /// The 2x2 crafting grid, row by row.
+    pub const CRAFTING_GRID: Range<usize> = 1..5;
+    /// Head, chest, legs, then feet.
+    pub const ARMOR: Range<usize> = 5..9;
+    /// The three rows above the hotbar.
+    pub const MAIN: Range<usize> = 9..36;
+    pub const HOTBAR: Range<usize> = 36..45;
+    pub const OFFHAND: usize = 45;
+    pub const SIZE: usize = 46;
+}
+
+/// The most items a slot holds. Items that stack to 16 or not at all aren't told apart yet.
+pub const MAX_STACK_SIZE: i8 = 64;
+
+/// The items a player is carrying, laid out like the player inventory window, see [layout].
+///
+/// Changed through [crate::net::player_inventory::set_slot], which keeps the client in sync, or
+/// by the player clicking around in it.
+#[derive(Component, Debug, Clone)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    /// The stack held by the cursor while the inventory is open.
+    carried: Option<ItemStack>,
+    /// Goes up with every change the server makes, so the client can tell when it's out of date.
+    pub state_id: i32,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self {
+            slots: vec![None; layout::SIZE],
+            carried: None,
+            state_id: 0,
+        }
+    }
+
+    /// An inventory holding `slots` in order, e.g. from [Inventory::slots]. Missing slots are left
+    /// empty, and extra ones are dropped.
+    pub fn from_slots(slots: Vec<ItemStack>) -> Self {
+        let mut slots: Vec<Option<ItemStack>> = slots
+            .into_iter()
+            .map(|item| Some(item).filter(|item| !item.is_empty()))
+            .collect();
+        slots.resize(layout::SIZE, None);
+        Self {
+            slots,
+            ..Self::new()
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&ItemStack> {
+        self.slots.get(slot)?.as_ref()
+    }
+
+    pub fn carried(&self) -> Option<&ItemStack> {
+        self.carried.as_ref()
+    }
+
+    /// Puts `item` in `slot`, or empties it, returning whatever was there before.
+    pub fn set(&mut self, slot: usize, item: Option<ItemStack>) -> Result<Option<ItemStack>> {
+        let Some(current) = self.slots.get_mut(slot) else {
+            return Err(Error::Generic(format!("No inventory slot {}", slot)));
+        };
+        self.state_id = self.state_id.wrapping_add(1);
+        Ok(std::mem::replace(current, item.filter(|item| !item.is_empty())))
+    }
+
+    /// The slot index of a hotbar slot, from 0 (leftmost) to 8.
+    pub fn hotbar_slot(index: usize) -> Option<usize> {
+        (index < layout::HOTBAR.len()).then(|| layout::HOTBAR.start + index)
+    }
+
+    /// Every slot in order, as they're sent to the client.
+    pub fn slots(&self) -> Vec<ItemStack> {
+        self.slots
+            .iter()
+            .map(|item| item.clone().unwrap_or_default())
+            .collect()
+    }
+
+    /// A normal left (`button` 0) or right (1) click on a slot.
+    ///
+    /// Left clicks pick up or put down whole stacks, right clicks pick up half a stack or put down
+    /// a single item. Clicking a different item than the carried one swaps them.
+    pub fn click(&mut self, slot: usize, button: i8) -> Result<()> {
+        let right = match button {
+            0 => false,
+            1 => true,
+            _ => return Err(Error::Generic(format!("Unknown mouse button {}", button))),
+        };
+        if slot == layout::CRAFTING_OUTPUT {
+            return Err(Error::Generic("Crafting isn't supported".to_string()));
+        }
+        let Some(target) = self.slots.get_mut(slot) else {
+            return Err(Error::Generic(format!("No inventory slot {}", slot)));
+        };
+
+        match (target.take(), self.carried.take()) {
+            (None, None) => {}
+            (Some(mut item), None) => {
+                if right {
+                    let taken = item.count - item.count / 2;
+                    item.count -= taken;
+                    self.carried = Some(ItemStack {
+                        count: taken,
+                        ..item.clone()
+                    });
+                    *target = Some(item).filter(|item| !item.is_empty());
+                } else {
+                    self.carried = Some(item);
+                }
+            }
+            (None, Some(mut carried)) => {
+                if right {
+                    carried.count -= 1;
+                    *target = Some(ItemStack {
+                        count: 1,
+                        ..carried.clone()
+                    });
+                    self.carried = Some(carried).filter(|item| !item.is_empty());
+                } else {
+                    *target = Some(carried);
+                }
+            }
+            (Some(mut item), Some(mut carried)) if stacks_with(&item, &carried) => {
+                let room = (MAX_STACK_SIZE - item.count).max(0);
+                let moved = if right { room.min(1) } else { room.min(carried.count) };
+                item.count += moved;
+                carried.count -= moved;
+                *target = Some(item);
+                self.carried = Some(carried).filter(|item| !item.is_empty());
+            }
+            (Some(item), Some(carried)) => {
+                *target = Some(carried);
+                self.carried = Some(item);
+            }
+        }
+        Ok(())
+    }
+
+    /// Swaps a slot with a hotbar slot (`hotbar` 0 to 8), or with the offhand for 40, like the
+    /// number keys and F do while hovering a slot.
+    pub fn swap_with_hotbar(&mut self, slot: usize, hotbar: i8) -> Result<()> {
+        let other = match hotbar {
+            40 => Some(layout::OFFHAND),
+            hotbar => usize::try_from(hotbar).ok().and_then(Self::hotbar_slot),
+        };
+        let Some(other) = other else {
+            return Err(Error::Generic(format!("No hotbar slot {}", hotbar)));
+        };
+        if slot >= layout::SIZE || slot == layout::CRAFTING_OUTPUT {
+            return Err(Error::Generic(format!("Can't swap slot {}", slot)));
+        }
+        self.slots.swap(slot, other);
+        Ok(())
+    }
+
+    /// Throws away one item from a slot, or the whole stack. Returns what was thrown away.
+    pub fn drop_from_slot(&mut self, slot: usize, whole_stack: bool) -> Result<Option<ItemStack>> {
+        let Some(target) = self.slots.get_mut(slot) else {
+            return Err(Error::Generic(format!("No inventory slot {}", slot)));
+        };
+        Ok(take(target, whole_stack))
+    }
+
+    /// Throws away the carried stack, or one item of it, like clicking outside the window.
+    pub fn drop_carried(&mut self, whole_stack: bool) -> Option<ItemStack> {
+        take(&mut self.carried, whole_stack)
+    }
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether two stacks can be merged into one.
+fn stacks_with(a: &ItemStack, b: &ItemStack) -> bool {
+    a.id == b.id && a.nbt == b.nbt
+}
+
+/// Takes the whole stack out of a slot, or just one item.
+fn take(slot: &mut Option<ItemStack>, whole_stack: bool) -> Option<ItemStack> {
+    if whole_stack {
+        return slot.take();
+    }
+    let item = slot.as_mut()?;
+    item.count -= 1;
+    let taken = ItemStack {
+        count: 1,
+        ..item.clone()
+    };
+    if item.is_empty() {
+        *slot = None;
+    }
+    Some(taken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_slot() {
+        let mut inventory = Inventory::new();
+        let slot = Inventory::hotbar_slot(2).unwrap();
+        assert_eq!(slot, 38);
+        assert!(Inventory::hotbar_slot(9).is_none());
+
+        let previous = inventory.set(slot, Some(ItemStack::new(1, 10))).unwrap();
+        assert!(previous.is_none());
+        assert_eq!(inventory.get(slot), Some(&ItemStack::new(1, 10)));
+        assert_eq!(inventory.state_id, 1);
+        assert!(inventory.set(layout::SIZE, None).is_err());
+
+        // Empty stacks leave the slot empty
+        inventory.set(slot, Some(ItemStack::new(1, 0))).unwrap();
+        assert!(inventory.get(slot).is_none());
+    }
+
+    #[test]
+    fn test_pickup_and_place() {
+        let mut inventory = Inventory::new();
+        inventory.set(10, Some(ItemStack::new(1, 10))).unwrap();
+
+        // Left click picks up the whole stack
+        inventory.click(10, 0).unwrap();
+        assert_eq!(inventory.get(10), None);
+        assert_eq!(inventory.carried(), Some(&ItemStack::new(1, 10)));
+
+        // Right click puts one down
+        inventory.click(20, 1).unwrap();
+        assert_eq!(inventory.get(20), Some(&ItemStack::new(1, 1)));
+        assert_eq!(inventory.carried(), Some(&ItemStack::new(1, 9)));
+
+        // Left click on the same item merges the rest into it
+        inventory.click(20, 0).unwrap();
+        assert_eq!(inventory.get(20), Some(&ItemStack::new(1, 10)));
+        assert_eq!(inventory.carried(), None);
+
+        // Right click picks up half, rounding up
+        inventory.set(21, Some(ItemStack::new(1, 5))).unwrap();
+        inventory.click(21, 1).unwrap();
+        assert_eq!(inventory.get(21), Some(&ItemStack::new(1, 2)));
+        assert_eq!(inventory.carried(), Some(&ItemStack::new(1, 3)));
+
+        // A different item swaps with the carried one
+        inventory.set(22, Some(ItemStack::new(2, 1))).unwrap();
+        inventory.click(22, 0).unwrap();
+        assert_eq!(inventory.get(22), Some(&ItemStack::new(1, 3)));
+        assert_eq!(inventory.carried(), Some(&ItemStack::new(2, 1)));
+
+        assert!(inventory.click(layout::CRAFTING_OUTPUT, 0).is_err());
+    }
+
+    #[test]
+    fn test_merging_stops_at_max_stack_size() {
+        let mut inventory = Inventory::new();
+        inventory.set(10, Some(ItemStack::new(1, 60))).unwrap();
+        inventory.set(11, Some(ItemStack::new(1, 10))).unwrap();
+
+        inventory.click(11, 0).unwrap();
+        inventory.click(10, 0).unwrap();
+        assert_eq!(inventory.get(10), Some(&ItemStack::new(1, MAX_STACK_SIZE)));
+        assert_eq!(inventory.carried(), Some(&ItemStack::new(1, 6)));
+    }
+
+    #[test]
+    fn test_swap_and_drop() {
+        let mut inventory = Inventory::new();
+        inventory.set(10, Some(ItemStack::new(1, 3))).unwrap();
+
+        inventory.swap_with_hotbar(10, 0).unwrap();
+        assert_eq!(inventory.get(10), None);
+        assert_eq!(inventory.get(layout::HOTBAR.start), Some(&ItemStack::new(1, 3)));
+
+        let dropped = inventory.drop_from_slot(layout::HOTBAR.start, false).unwrap();
+        assert_eq!(dropped, Some(ItemStack::new(1, 1)));
+        assert_eq!(inventory.get(layout::HOTBAR.start), Some(&ItemStack::new(1, 2)));
+        inventory.drop_from_slot(layout::HOTBAR.start, true).unwrap();
+        assert_eq!(inventory.get(layout::HOTBAR.start), None);
+    }
+}
@@ -6,3 +6,10 @@ pub struct KeepAlive {
     pub last_sent: std::time::Instant,
     pub data: i64,
 }
+
+/// Exempts a connection from being dropped when it stops answering keep alives, e.g. for bots
+/// or monitoring clients.
+///
+/// See [crate::net::systems::keep_alive_system::KeepAliveSystem].
+#[derive(Debug, Component, Clone, Copy)]
+pub struct NoIdleKick;
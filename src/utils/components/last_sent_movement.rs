@@ -0,0 +1,13 @@
+use ferrumc_macros::{Component, Constructor};
+
+use crate::utils::components::rotation::Rotation;
+use crate::utils::encoding::position::Position;
+
+/// Where other players last saw this entity, so only the difference has to be sent to them.
+///
+/// See [crate::net::systems::entity_movement::EntityMovementSystem].
+#[derive(Debug, Component, Constructor, Clone)]
+pub struct LastSentMovement {
+    pub position: Position,
+    pub rotation: Rotation,
+}
@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ferrumc_macros::Component;
+use serde_json::json;
+
+use crate::utils::encoding::entity_metadata::{EntityMetadata, MetadataValue};
+
+/// The index of the base entity flags, a byte of the bits below.
+pub const FLAGS: u8 = 0;
+/// The index of the custom name, optional text.
+pub const CUSTOM_NAME: u8 = 2;
+/// The index of whether the custom name is always shown, a boolean.
+pub const CUSTOM_NAME_VISIBLE: u8 = 3;
+
+pub const ON_FIRE: u8 = 0x01;
+pub const SNEAKING: u8 = 0x02;
+pub const SPRINTING: u8 = 0x08;
+pub const SWIMMING: u8 = 0x10;
+pub const INVISIBLE: u8 = 0x20;
+pub const GLOWING: u8 = 0x40;
+pub const FLYING_WITH_ELYTRA: u8 = 0x80;
+
+/// An entity's synced metadata, by index. Indexes that aren't set are left at the client's
+/// defaults.
+///
+/// Changed through [crate::net::entity_tracking::update_metadata], which sends the changes to
+/// the players tracking the entity.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    values: BTreeMap<u8, MetadataValue>,
+    /// Indexes changed since [Metadata::take_changes].
+    changed: BTreeSet<u8>,
+}
+
+impl Metadata {
+    pub fn get(&self, index: u8) -> Option<&MetadataValue> {
+        self.values.get(&index)
+    }
+
+    /// Sets a value, marking it changed unless it already had it.
+    pub fn set(&mut self, index: u8, value: MetadataValue) {
+        if self.values.get(&index) != Some(&value) {
+            self.values.insert(index, value);
+            self.changed.insert(index);
+        }
+    }
+
+    pub fn flags(&self) -> u8 {
+        match self.get(FLAGS) {
+            Some(MetadataValue::Byte(flags)) => *flags,
+            _ => 0,
+        }
+    }
+
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags() & flag != 0
+    }
+
+    pub fn set_flag(&mut self, flag: u8, on: bool) {
+        let flags = match on {
+            true => self.flags() | flag,
+            false => self.flags() & !flag,
+        };
+        self.set(FLAGS, MetadataValue::Byte(flags));
+    }
+
+    /// Sets the name shown above the entity, as plain text.
+    pub fn set_custom_name(&mut self, name: Option<&str>) {
+        let name = name.map(|name| json!({ "text": name }).to_string());
+        self.set(CUSTOM_NAME, MetadataValue::OptionalText(name));
+    }
+
+    /// Whether the custom name is shown without looking at the entity.
+    pub fn set_custom_name_visible(&mut self, visible: bool) {
+        self.set(CUSTOM_NAME_VISIBLE, MetadataValue::Boolean(visible));
+    }
+
+    /// Every value that's set, for players the entity is spawned for.
+    pub fn all(&self) -> EntityMetadata {
+        EntityMetadata(self.values.iter().map(|(i, v)| (*i, v.clone())).collect())
+    }
+
+    /// The values changed since the last call.
+    pub fn take_changes(&mut self) -> EntityMetadata {
+        let changed = std::mem::take(&mut self.changed);
+        EntityMetadata(
+            changed
+                .into_iter()
+                .filter_map(|index| Some((index, self.values.get(&index)?.clone())))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_changes_are_taken() {
+        let mut metadata = Metadata::default();
+        metadata.set_flag(SNEAKING, true);
+        metadata.set_flag(GLOWING, true);
+        assert!(metadata.has_flag(SNEAKING));
+        assert_eq!(
+            metadata.take_changes().0,
+            vec![(FLAGS, MetadataValue::Byte(SNEAKING | GLOWING))]
+        );
+
+        // Setting what it already is isn't a change
+        metadata.set_flag(SNEAKING, true);
+        assert!(metadata.take_changes().is_empty());
+
+        metadata.set_flag(SNEAKING, false);
+        metadata.set_custom_name_visible(true);
+        assert_eq!(metadata.take_changes().0.len(), 2);
+        assert_eq!(metadata.all().0.len(), 2);
+        assert!(!metadata.has_flag(SNEAKING));
+    }
+}
@@ -1,5 +1,12 @@
 pub mod grounded;
+pub mod gamemode;
+pub mod health;
+pub mod inventory;
 pub mod keep_alive;
+pub mod last_sent_movement;
+pub mod metadata;
 pub mod player;
 pub mod rotation;
+pub mod tracked_entities;
+pub mod velocity;
 pub mod last_chunk_tx_pos;
\ No newline at end of file
@@ -1,6 +1,8 @@
 use ferrumc_macros::{Component, Constructor};
 
-#[derive(Component, Constructor, Debug)]
+use crate::ecs::world::World;
+
+#[derive(Component, Constructor, Debug, Clone)]
 pub struct Player {
     pub uuid: u128,
     pub username: String,
@@ -15,3 +17,13 @@ impl Player {
         &self.username
     }
 }
+
+/// Every player that has finished logging in.
+pub async fn online_players(world: &World) -> Vec<Player> {
+    let mut players = Vec::new();
+    let mut query = world.query::<&Player>();
+    while let Some((_, player)) = query.next().await {
+        players.push((*player).clone());
+    }
+    players
+}
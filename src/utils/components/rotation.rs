@@ -15,3 +15,8 @@ impl Rotation {
         self.pitch += pitch;
     }
 }
+
+/// Converts degrees into the protocol's angle type, which counts in 1/256ths of a full turn.
+pub fn to_angle(degrees: f32) -> u8 {
+    (degrees.rem_euclid(360.0) / 360.0 * 256.0) as u8
+}
@@ -0,0 +1,15 @@
+use std::collections::HashSet;
+
+use ferrumc_macros::Component;
+
+/// The entities a player's client has spawned, so only those are sent updates about.
+///
+/// Kept up to date with the entities near the player by [crate::net::entity_tracking].
+#[derive(Debug, Component, Clone, Default)]
+pub struct TrackedEntities(pub HashSet<u32>);
+
+impl TrackedEntities {
+    pub fn contains(&self, entity_id: u32) -> bool {
+        self.0.contains(&entity_id)
+    }
+}
@@ -0,0 +1,30 @@
+use ferrumc_macros::Component;
+
+/// How far an entity moves each tick, in blocks.
+///
+/// See [crate::net::systems::entity_tick::ApplyVelocity].
+#[derive(Debug, Component, Clone, Default)]
+pub struct Velocity {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// Movement that hasn't added up to a whole block yet, since positions are in whole blocks.
+    pub remainder: (f64, f64, f64),
+}
+
+impl Velocity {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            remainder: (0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// Marks an entity as pulled down by gravity each tick.
+///
+/// See [crate::net::systems::entity_tick::ApplyGravity].
+#[derive(Debug, Component, Clone, Copy)]
+pub struct AffectedByGravity;
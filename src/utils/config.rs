@@ -1,31 +1,207 @@
-use std::io::ErrorKind::NotFound;
+use std::fs::OpenOptions;
+use std::io::ErrorKind::{AlreadyExists, NotFound};
 use std::io::Write;
-use std::sync::OnceLock;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
 
 use crate::utils::constants::{
     DEFAULT_CONFIG_FILE, DEFAULT_MAX_PLAYERS, DEFAULT_MOTD, DEFAULT_SERVER_HOST,
     DEFAULT_SERVER_PORT,
 };
+use crate::utils::components::gamemode::GameMode;
 use crate::utils::error::Error;
+use crate::world::difficulty::Difficulty;
 use config::{Config, ConfigError};
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
+use tracing_subscriber::filter::Directive;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerConfig {
+    /// The IP address of the interface to listen on, see [ServerConfig::bind_address].
     pub host: String,
     pub port: u32,
     pub motd: Vec<String>,
+    /// Show the icon at [Paths::favicon] in the server list. When off, the file is never read.
+    #[serde(default = "defaults::enable_favicon")]
+    pub enable_favicon: bool,
     pub max_players: u32,
+    /// Names of players that always have the highest permission level, on top of the ops stored in
+    /// the database.
+    #[serde(default = "defaults::ops")]
+    pub ops: Vec<String>,
     pub network_tick_rate: u32,
+    /// How many encoded packets may wait to be sent to a single client.
+    #[serde(default = "defaults::send_queue_depth")]
+    pub send_queue_depth: u32,
+    /// How many bytes are read from a client's socket at once, at least [MIN_READ_BUFFER_SIZE].
+    #[serde(default = "defaults::read_buffer_size")]
+    pub read_buffer_size: u32,
+    /// How many chunks around a player are sent to them.
+    #[serde(default = "defaults::view_distance")]
+    pub view_distance: u32,
+    /// How many chunks around a player are ticked. Can't be more than the view distance.
+    #[serde(default = "defaults::simulation_distance")]
+    pub simulation_distance: u32,
+    /// How many chunks away from a player other entities are still sent to them, capped by the
+    /// player's own view distance. Can't be more than the view distance.
+    #[serde(default = "defaults::entity_tracking_distance")]
+    pub entity_tracking_distance: u32,
+    /// How many chunks around spawn are loaded into the cache at startup. 0 disables preloading.
+    #[serde(default = "defaults::spawn_preload_radius")]
+    pub spawn_preload_radius: u32,
+    /// How many chunks are sent to a player per tick, nearest first. 0 sends them all at once.
+    #[serde(default = "defaults::chunks_per_tick")]
+    pub chunks_per_tick: u32,
+    /// How many players have who they can see updated per tick, taking turns. 0 updates everyone
+    /// every tick.
+    #[serde(default = "defaults::entity_updates_per_tick")]
+    pub entity_updates_per_tick: u32,
     pub database: Database,
+    #[serde(default)]
+    pub physics: Physics,
+    #[serde(default)]
+    pub performance: Performance,
+    #[serde(default)]
+    pub health: Health,
+    #[serde(default)]
+    pub logging: Logging,
+    #[serde(default)]
+    pub resource_pack: ResourcePack,
+    #[serde(default)]
+    pub paths: Paths,
+    #[serde(default)]
+    pub debug: Debugging,
     pub world: String,
+    /// The dimension chunks are looked up in when none is given, see
+    /// [crate::database::Database::get_chunk_default].
+    #[serde(default = "defaults::default_dimension")]
+    pub default_dimension: String,
+    /// The world seed. When unset, a random seed is generated once and stored with the world,
+    /// see [crate::database::Database::world_seed].
+    #[serde(default)]
+    pub seed: Option<i64>,
+    /// The difficulty the server starts at, see [crate::world::difficulty::Difficulty].
+    #[serde(default = "defaults::difficulty")]
+    pub difficulty: String,
+    /// The game mode players join in, see [crate::utils::components::gamemode::GameMode].
+    #[serde(default = "defaults::default_gamemode")]
+    pub default_gamemode: String,
+    /// How often changed chunks are saved, in seconds. 0 disables autosaving.
+    #[serde(default = "defaults::autosave_interval_secs")]
+    pub autosave_interval_secs: u64,
+    /// The format of the region files imported worlds are read from, see [crate::world::region::RegionFormat].
+    #[serde(default = "defaults::region_format")]
+    pub region_format: String,
+    /// Compute sky and block light for chunks as they're sent, instead of using the light stored
+    /// with them, see [crate::world::lighting].
+    #[serde(default)]
+    pub compute_light: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Database {
     pub cache_size: u32,
+    /// "none", "zstd" or "lz4", or "fast" and "best" from older configs. See
+    /// [crate::database::encoding::Compression].
     pub compression: String,
+    /// Either "file" or "temp", or "memory" from older configs. See
+    /// [crate::database::DatabaseMode].
+    #[serde(default = "defaults::database_mode")]
+    pub mode: String,
+    /// The directory worlds are stored in when running in "file" mode.
+    /// Relative paths are resolved against the server root.
+    #[serde(default = "defaults::database_path")]
+    pub path: String,
+}
+
+/// Physics constants for non-player entities, in blocks per tick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Physics {
+    /// Subtracted from the vertical velocity every tick.
+    pub gravity: f64,
+    /// The fraction of velocity lost every tick.
+    pub drag: f64,
+    /// The fastest an entity can fall.
+    pub terminal_velocity: f64,
+}
+
+/// How fast the game runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Performance {
+    /// Game ticks per second, up to [MAX_TICK_RATE]. Can be changed in game with `/tick rate`.
+    pub tick_rate: u32,
+}
+
+/// An endpoint for liveness probes, separate from the game port.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Health {
+    pub enabled: bool,
+    pub port: u32,
+    /// How long the game tick may go without completing before the server counts as hung.
+    pub max_tick_age_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Logging {
+    /// "pretty" for human readable logs, or "json" for one JSON object per line.
+    pub format: String,
+    /// Extra `RUST_LOG` style directives, e.g. "ferrumc::net=debug,info". Empty to only use the
+    /// log level.
+    pub filter: String,
+}
+
+/// Where the server reads and writes its files, see [Paths::resolve].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Paths {
+    /// The directory every other path is relative to. Relative to the server root, see
+    /// [crate::database::get_root_dir].
+    pub data_dir: String,
+    /// The 64x64 PNG shown in the server list.
+    pub favicon: String,
+    /// The world that `--import` reads region files from.
+    pub import: String,
+    /// Where `/backup` writes its exports.
+    pub backups: String,
+}
+
+/// Tools for debugging the server, all off by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Debugging {
+    /// Log the bytes of every packet sent and received at trace level, see
+    /// [crate::net::utils::packet_dump].
+    pub packet_dump: bool,
+    /// Only dump packets with these ids. Empty to dump all of them.
+    pub packet_dump_allow: Vec<u8>,
+    /// Never dump packets with these ids.
+    pub packet_dump_deny: Vec<u8>,
+}
+
+/// A resource pack offered to players when they join.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourcePack {
+    /// Where the client downloads the pack from. Empty to not offer one.
+    pub url: String,
+    /// The SHA-1 hash of the pack as 40 hex digits, or empty to skip checking it.
+    pub hash: String,
+    /// Disconnect players that decline the pack.
+    pub required: bool,
+    /// Shown to the player when they're asked to accept the pack. Empty for the default prompt.
+    pub prompt: String,
+}
+
+impl ResourcePack {
+    pub fn is_enabled(&self) -> bool {
+        !self.url.is_empty()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,17 +212,12 @@ pub struct Server {
 impl ServerConfig {
     /// Load the server configuration from the config file
     pub fn new() -> Result<Self, Error> {
-        let settings = Config::builder()
-            .add_source(config::File::with_name("config"))
-            .build()
+        let settings = build_settings()
             .or_else(|err| {
                 if is_not_found(&err) {
                     info!("Config file wasn't found, creating a new one.");
-                    create_config_file()?;
-                    return Config::builder()
-                        .add_source(config::File::with_name("config"))
-                        .build()
-                        .map_err(Error::from);
+                    ServerConfig::write_default(DEFAULT_CONFIG_FILE)?;
+                    return build_settings().map_err(Error::from);
                 }
                 Err(Error::from(err))
             })?;
@@ -61,9 +232,7 @@ impl ServerConfig {
                 return if input.trim() == "y" {
                     info!("Creating new config file...");
                     create_config_file()?;
-                    Config::builder()
-                        .add_source(config::File::with_name("config"))
-                        .build()
+                    build_settings()
                         .map_err(Error::from)
                         .and_then(|settings| settings.try_deserialize().map_err(Error::from))
                 } else {
@@ -74,8 +243,324 @@ impl ServerConfig {
             Err(Error::from(e))
         })?;
 
+        de_settings.validate()?;
+
         Ok(de_settings)
     }
+
+    /// The address to listen on, from `host` and `port`.
+    ///
+    /// `host` can be an IP address or a hostname, which is resolved preferring IPv4 addresses.
+    pub fn bind_address(&self) -> Result<SocketAddr, Error> {
+        let host = self.host.trim();
+        let addrs: Vec<SocketAddr> = (host, self.port as u16)
+            .to_socket_addrs()
+            .map_err(|e| {
+                invalid(
+                    "host",
+                    format!("couldn't resolve \"{}\" to an address: {}", self.host, e),
+                )
+            })?
+            .collect();
+        addrs
+            .iter()
+            .find(|addr| addr.is_ipv4())
+            .or(addrs.first())
+            .copied()
+            .ok_or_else(|| invalid("host", format!("\"{}\" has no addresses", self.host)))
+    }
+
+    /// Validate the values of the config, since serde only checks that the types line up.
+    ///
+    /// Returns [Error::InvalidConfig] pointing at the first offending field.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.host.trim().is_empty() {
+            return Err(invalid("host", "must not be empty"));
+        }
+        if self.port == 0 || self.port > u16::MAX as u32 {
+            return Err(invalid(
+                "port",
+                format!("must be between 1 and {}, got {}", u16::MAX, self.port),
+            ));
+        }
+        self.bind_address()?;
+        if self.motd.is_empty() {
+            return Err(invalid("motd", "must contain at least one message"));
+        }
+        if self.max_players == 0 {
+            return Err(invalid("max_players", "must be greater than 0"));
+        }
+        if self.network_tick_rate > 1000 {
+            return Err(invalid(
+                "network_tick_rate",
+                format!("must be between 0 and 1000, got {}", self.network_tick_rate),
+            ));
+        }
+        if self.send_queue_depth == 0 {
+            return Err(invalid("send_queue_depth", "must be greater than 0"));
+        }
+        if self.read_buffer_size < MIN_READ_BUFFER_SIZE {
+            return Err(invalid(
+                "read_buffer_size",
+                format!(
+                    "must be at least {}, got {}",
+                    MIN_READ_BUFFER_SIZE, self.read_buffer_size
+                ),
+            ));
+        }
+        if !(MIN_VIEW_DISTANCE..=MAX_VIEW_DISTANCE).contains(&self.view_distance) {
+            return Err(invalid(
+                "view_distance",
+                format!(
+                    "must be between {} and {}, got {}",
+                    MIN_VIEW_DISTANCE, MAX_VIEW_DISTANCE, self.view_distance
+                ),
+            ));
+        }
+        if self.simulation_distance < MIN_VIEW_DISTANCE
+            || self.simulation_distance > self.view_distance
+        {
+            return Err(invalid(
+                "simulation_distance",
+                format!(
+                    "must be between {} and the view distance ({}), got {}",
+                    MIN_VIEW_DISTANCE, self.view_distance, self.simulation_distance
+                ),
+            ));
+        }
+        if self.entity_tracking_distance < MIN_VIEW_DISTANCE
+            || self.entity_tracking_distance > self.view_distance
+        {
+            return Err(invalid(
+                "entity_tracking_distance",
+                format!(
+                    "must be between {} and the view distance ({}), got {}",
+                    MIN_VIEW_DISTANCE, self.view_distance, self.entity_tracking_distance
+                ),
+            ));
+        }
+        if self.spawn_preload_radius > MAX_VIEW_DISTANCE {
+            return Err(invalid(
+                "spawn_preload_radius",
+                format!(
+                    "must be between 0 and {}, got {}",
+                    MAX_VIEW_DISTANCE, self.spawn_preload_radius
+                ),
+            ));
+        }
+        if !self.physics.gravity.is_finite() || self.physics.gravity < 0.0 {
+            return Err(invalid("physics.gravity", "must be a positive number"));
+        }
+        if !(0.0..1.0).contains(&self.physics.drag) {
+            return Err(invalid(
+                "physics.drag",
+                format!("must be at least 0 and less than 1, got {}", self.physics.drag),
+            ));
+        }
+        if !self.physics.terminal_velocity.is_finite() || self.physics.terminal_velocity <= 0.0 {
+            return Err(invalid("physics.terminal_velocity", "must be greater than 0"));
+        }
+        if !(1..=MAX_TICK_RATE).contains(&self.performance.tick_rate) {
+            return Err(invalid(
+                "performance.tick_rate",
+                format!(
+                    "must be between 1 and {}, got {}",
+                    MAX_TICK_RATE, self.performance.tick_rate
+                ),
+            ));
+        }
+        if self.health.enabled {
+            if self.health.port == 0 || self.health.port > u16::MAX as u32 {
+                return Err(invalid(
+                    "health.port",
+                    format!("must be between 1 and {}, got {}", u16::MAX, self.health.port),
+                ));
+            }
+            if self.health.port == self.port {
+                return Err(invalid("health.port", "must be different from port"));
+            }
+            if self.health.max_tick_age_ms == 0 {
+                return Err(invalid("health.max_tick_age_ms", "must be greater than 0"));
+            }
+        }
+        if let Some(id) = self
+            .debug
+            .packet_dump_deny
+            .iter()
+            .find(|id| self.debug.packet_dump_allow.contains(id))
+        {
+            return Err(invalid(
+                "debug.packet_dump_deny",
+                format!("packet 0x{:02X} is also in packet_dump_allow", id),
+            ));
+        }
+        if !VALID_LOG_FORMATS.contains(&self.logging.format.as_str()) {
+            return Err(invalid(
+                "logging.format",
+                format!(
+                    "expected one of {:?}, got \"{}\"",
+                    VALID_LOG_FORMATS, self.logging.format
+                ),
+            ));
+        }
+        if let Some(directive) = log_directives(&self.logging.filter)
+            .find(|directive| directive.parse::<Directive>().is_err())
+        {
+            return Err(invalid(
+                "logging.filter",
+                format!("invalid directive \"{}\"", directive),
+            ));
+        }
+        if self.resource_pack.is_enabled()
+            && !["http://", "https://"]
+                .iter()
+                .any(|scheme| self.resource_pack.url.starts_with(scheme))
+        {
+            return Err(invalid(
+                "resource_pack.url",
+                format!("expected an http(s) URL, got \"{}\"", self.resource_pack.url),
+            ));
+        }
+        if !self.resource_pack.hash.is_empty()
+            && (self.resource_pack.hash.len() != 40
+                || !self.resource_pack.hash.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(invalid(
+                "resource_pack.hash",
+                format!(
+                    "expected a SHA-1 hash of 40 hex digits, got \"{}\"",
+                    self.resource_pack.hash
+                ),
+            ));
+        }
+        if self.world.trim().is_empty() {
+            return Err(invalid("world", "must not be empty"));
+        }
+        if self
+            .world
+            .contains(|c: char| matches!(c, '/' | '\\' | ':' | '.'))
+        {
+            return Err(invalid(
+                "world",
+                format!("\"{}\" must be a plain folder name", self.world),
+            ));
+        }
+        if self.default_dimension.trim().is_empty() {
+            return Err(invalid("default_dimension", "must not be empty"));
+        }
+        if self.difficulty.parse::<Difficulty>().is_err() {
+            return Err(invalid(
+                "difficulty",
+                format!(
+                    "expected one of peaceful, easy, normal or hard, got \"{}\"",
+                    self.difficulty
+                ),
+            ));
+        }
+        if self.default_gamemode.parse::<GameMode>().is_err() {
+            return Err(invalid(
+                "default_gamemode",
+                format!(
+                    "expected one of survival, creative, adventure or spectator, got \"{}\"",
+                    self.default_gamemode
+                ),
+            ));
+        }
+        if !VALID_REGION_FORMATS.contains(&self.region_format.as_str()) {
+            return Err(invalid(
+                "region_format",
+                format!(
+                    "expected one of {:?}, got \"{}\"",
+                    VALID_REGION_FORMATS, self.region_format
+                ),
+            ));
+        }
+        if !VALID_COMPRESSION.contains(&self.database.compression.as_str()) {
+            return Err(invalid(
+                "database.compression",
+                format!(
+                    "expected one of {:?}, got \"{}\"",
+                    VALID_COMPRESSION, self.database.compression
+                ),
+            ));
+        }
+
+        if !VALID_DATABASE_MODES.contains(&self.database.mode.as_str()) {
+            return Err(invalid(
+                "database.mode",
+                format!(
+                    "expected one of {:?}, got \"{}\"",
+                    VALID_DATABASE_MODES, self.database.mode
+                ),
+            ));
+        }
+        if self.database.mode == "file" && self.database.path.trim().is_empty() {
+            return Err(invalid("database.path", "must not be empty in \"file\" mode"));
+        }
+
+        Ok(())
+    }
+}
+
+/// The prefix for environment variables overriding config values. See [env_overrides].
+const ENV_PREFIX: &str = "FERRUMC";
+
+/// Smaller read buffers would need several reads for most packets.
+pub const MIN_READ_BUFFER_SIZE: u32 = 1024;
+
+/// The fastest the game can tick, a tick every millisecond.
+pub const MAX_TICK_RATE: u32 = 1000;
+
+/// The range the client accepts for view and simulation distance
+const MIN_VIEW_DISTANCE: u32 = 2;
+const MAX_VIEW_DISTANCE: u32 = 32;
+
+/// The accepted values for `database.compression`. "fast" and "best" are from older configs, see
+/// [crate::database::encoding::Compression].
+const VALID_COMPRESSION: &[&str] = &["none", "zstd", "lz4", "fast", "best"];
+
+/// The accepted values for `logging.format`
+pub(crate) const VALID_LOG_FORMATS: &[&str] = &["pretty", "json"];
+
+/// The accepted values for `region_format`
+const VALID_REGION_FORMATS: &[&str] = &["anvil", "linear"];
+
+/// The accepted values for `database.mode`
+const VALID_DATABASE_MODES: &[&str] = &["file", "temp", "memory"];
+
+fn invalid(field: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidConfig(field.to_string(), reason.into())
+}
+
+/// Build the layered settings: the config file first, then any environment variable overrides.
+fn build_settings() -> Result<Config, ConfigError> {
+    Config::builder()
+        .add_source(config::File::with_name("config"))
+        .add_source(env_overrides())
+        .build()
+}
+
+/// Environment variables that override values from the config file.
+///
+/// Variables are named `FERRUMC_<FIELD>`, with nested fields separated by a double underscore,
+/// e.g. `FERRUMC_PORT=25566` or `FERRUMC_DATABASE__CACHE_SIZE=2048`.
+/// Values are parsed into the type of the field they override, and `FERRUMC_MOTD` and `FERRUMC_OPS`
+/// take a comma separated list.
+fn env_overrides() -> config::Environment {
+    env_overrides_from(None)
+}
+
+/// [env_overrides] read from `vars` instead of the process environment, or from the process
+/// environment if it's `None`.
+fn env_overrides_from(vars: Option<config::Map<String, String>>) -> config::Environment {
+    config::Environment::with_prefix(ENV_PREFIX)
+        .source(vars)
+        .prefix_separator("_")
+        .separator("__")
+        .try_parsing(true)
+        .list_separator(",")
+        .with_list_parse_key("motd")
+        .with_list_parse_key("ops")
 }
 
 /// Check if the error is a not found error
@@ -103,40 +588,799 @@ fn missing_field(err: &ConfigError) -> Option<String> {
     None
 }
 
-/// Create a new config file
+/// Replace the config file with a freshly generated default one
 fn create_config_file() -> Result<(), Error> {
-    let path = std::path::Path::new(DEFAULT_CONFIG_FILE);
+    let path = Path::new(DEFAULT_CONFIG_FILE);
     if path.exists() {
         std::fs::remove_file(path)?;
     }
-    let mut file = std::fs::File::create(path)?;
-    let contents = toml::to_string(&ServerConfig::default())?;
-    file.write_all(contents.as_bytes())?;
-
-    info!("Path: {}", path.display());
+    ServerConfig::write_default(path)?;
 
     Ok(())
 }
 
+/// The default configuration file, written on first run.
+/// Not using ServerConfig::default(), since it doesn't have documentation on the usage of each field.
+pub const DEFAULT_CONFIG: &str = r#"
+# Any value in here can be overridden with an environment variable named FERRUMC_<FIELD>.
+# Nested values use a double underscore, e.g. FERRUMC_DATABASE__CACHE_SIZE=2048.
+# The network address to bind to. Usually just 0.0.0.0 or 127.0.0.1 if you don't want to expose the server to the internet.
+# Has to be the IP address of one of the host's interfaces. Use "::" to listen on both IPv6 and IPv4.
+host = "0.0.0.0"
+# The port to bind to. Default is 25565.
+port = 25565
+# The message displayed in the server list.
+motd = ["A FerrumC server; Absolute precision, power, and perfection."]
+# Show the icon from paths.favicon in the server list. Turn it off to never read the file.
+enable_favicon = true
+# The maximum number of players that can be connected at once.
+max_players = 20
+# Names of players that can run every command. More can be added in game with /op.
+ops = []
+# How many network updates to process per second per user. 0 means no limit.
+# This is the number of times per second the server will send updates to the client.
+# Having this too low will cause noticable lag for clients but may improve server performance.
+network_tick_rate = 0
+# How many packets can be waiting to be sent to a single client. Clients that can't keep up
+# for long enough to fill this are disconnected instead of buffering packets forever.
+send_queue_depth = 1024
+# How many bytes are read from a client's socket at once, at least 1024. Bigger buffers need
+# fewer syscalls for busy clients, but cost memory for every connection.
+read_buffer_size = 8192
+# How many chunks around a player are sent to them, between 2 and 32.
+view_distance = 10
+# How many chunks around a player are ticked (entities move, etc). Can't be more than view_distance.
+simulation_distance = 10
+# How many chunks away other players are still visible. Can't be more than view_distance.
+entity_tracking_distance = 8
+# How many chunks around spawn to load into memory at startup, so the first player to join doesn't
+# have to wait for them. 0 disables preloading.
+spawn_preload_radius = 4
+# How many chunks are sent to a player per tick, nearest first, so slow connections aren't flooded.
+# 0 sends every chunk in view at once.
+chunks_per_tick = 16
+# How many players get other players spawned and removed around them per tick, taking turns.
+# Spreads the work out on busy servers, at the cost of players appearing a few ticks late.
+# 0 updates everyone every tick.
+entity_updates_per_tick = 64
+# The default world name. You can switch between mutliple worlds by changing this value.
+world = "world"
+# The dimension chunks are read from and written to when a command or system doesn't name one.
+default_dimension = "overworld"
+# The world seed. Leave commented out to generate a random one the first time the world is opened.
+# seed = 0
+# "peaceful", "easy", "normal" or "hard". Can be changed in game with /difficulty.
+difficulty = "normal"
+# The game mode players join in: "survival", "creative", "adventure" or "spectator".
+default_gamemode = "creative"
+# How often to save changed chunks to disk, in seconds. They're always saved on shutdown.
+# 0 disables autosaving, leaving only /save-all and shutdown.
+autosave_interval_secs = 300
+# The format of the region files in the import folder. "anvil" for vanilla .mca files, or
+# "linear" for .linear files.
+region_format = "anvil"
+# Compute the light of chunks as they're sent, instead of using the light saved with them.
+# Costs some CPU per chunk, useful for worlds that were imported or edited without lighting.
+compute_light = false
+
+[database]
+# The cache size in KB. We recommend leaving this at the default value.
+cache_size = 1024
+# How chunks are compressed when they're saved: "zstd", "lz4" or "none". lz4 is faster to load
+# but takes more space. Chunks saved with a different setting can still be read.
+compression = "zstd"
+# Where the world is stored. "file" keeps it on disk under `path`, "temp" keeps it in a
+# throwaway directory in the OS temp dir that is deleted when the server stops.
+mode = "file"
+# The directory worlds are stored in, relative to paths.data_dir.
+path = "data"
+
+[physics]
+# How much falling entities speed up each tick, in blocks per tick. Players aren't affected,
+# their movement comes from the client.
+gravity = 0.08
+# The fraction of an entity's velocity lost to air resistance each tick.
+drag = 0.02
+# The fastest an entity can fall, in blocks per tick.
+terminal_velocity = 3.92
+
+[performance]
+# Game ticks per second, 20 like vanilla. Lower it to slow the game down for testing, the
+# /tick command can also change it, freeze the game and step through it one tick at a time.
+tick_rate = 20
+
+[health]
+# Answer liveness probes (plain TCP or HTTP GET) on a separate port. Responds 200 OK while the
+# game tick is running, and 503 once it hasn't completed for max_tick_age_ms.
+enabled = false
+port = 25580
+max_tick_age_ms = 5000
+
+[logging]
+# "pretty" for human readable logs, or "json" for one JSON object per line (with the fields of
+# the spans it was logged in), for log aggregators.
+format = "pretty"
+# Per module log levels, in the same format as RUST_LOG, e.g. "ferrumc::net=debug,info".
+# A --log=<level> argument still takes precedence over a plain level here.
+filter = ""
+
+[resource_pack]
+# A resource pack players are asked to download when they join. Leave empty to not send one.
+url = ""
+# The SHA-1 hash of the pack (40 hex digits), so clients can cache it. Optional.
+hash = ""
+# Disconnect players that decline the pack.
+required = false
+# A message shown on the download prompt. Empty for the client's default.
+prompt = ""
+
+[paths]
+# The directory the server keeps its files in, created if missing. Relative to FERRUMC_ROOT if
+# set, otherwise to the directory of the executable.
+data_dir = "."
+# Overrides for single files and directories. Relative paths are resolved against data_dir.
+favicon = "icon-64.png"
+import = "import"
+backups = "backups"
+
+[debug]
+# Log the id, direction and bytes of every packet at trace level, e.g. with --log=trace.
+packet_dump = false
+# Packet ids to dump, e.g. [0x14, 0x15]. Empty to dump every packet.
+packet_dump_allow = []
+# Packet ids to never dump, e.g. keep alives.
+packet_dump_deny = []
+"#;
+
+impl ServerConfig {
+    /// Write the commented default config to `path`.
+    ///
+    /// Never overwrites an existing file. Returns `true` if the file was created and `false` if
+    /// something already existed at `path`.
+    pub fn write_default(path: impl AsRef<Path>) -> Result<bool, Error> {
+        let path = path.as_ref();
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == AlreadyExists => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        file.write_all(DEFAULT_CONFIG.trim_start().as_bytes())?;
+
+        info!("Created default config at {}", path.display());
+
+        Ok(true)
+    }
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
             host: DEFAULT_SERVER_HOST.to_string(),
             port: DEFAULT_SERVER_PORT,
             motd: vec![DEFAULT_MOTD.to_string()],
+            enable_favicon: defaults::enable_favicon(),
             max_players: DEFAULT_MAX_PLAYERS,
+            ops: defaults::ops(),
             network_tick_rate: 0,
+            send_queue_depth: defaults::send_queue_depth(),
+            read_buffer_size: defaults::read_buffer_size(),
+            view_distance: defaults::view_distance(),
+            simulation_distance: defaults::simulation_distance(),
+            entity_tracking_distance: defaults::entity_tracking_distance(),
+            spawn_preload_radius: defaults::spawn_preload_radius(),
+            chunks_per_tick: defaults::chunks_per_tick(),
+            entity_updates_per_tick: defaults::entity_updates_per_tick(),
             world: "world".to_string(),
+            default_dimension: defaults::default_dimension(),
+            seed: None,
+            difficulty: defaults::difficulty(),
+            default_gamemode: defaults::default_gamemode(),
+            autosave_interval_secs: defaults::autosave_interval_secs(),
+            region_format: defaults::region_format(),
+            compute_light: false,
             database: Database {
                 cache_size: 1024,
-                compression: "fast".to_string(),
+                compression: "zstd".to_string(),
+                mode: defaults::database_mode(),
+                path: defaults::database_path(),
             },
+            physics: Physics::default(),
+            performance: Performance::default(),
+            health: Health::default(),
+            logging: Logging::default(),
+            resource_pack: ResourcePack::default(),
+            paths: Paths::default(),
+            debug: Debugging::default(),
+        }
+    }
+}
+
+/// The defaults for settings added after the first release, so older config files still load.
+/// Also used by [ServerConfig::default], so the two can't disagree.
+mod defaults {
+    pub fn enable_favicon() -> bool {
+        true
+    }
+
+    pub fn ops() -> Vec<String> {
+        vec![]
+    }
+
+    pub fn send_queue_depth() -> u32 {
+        1024
+    }
+
+    pub fn read_buffer_size() -> u32 {
+        8192
+    }
+
+    pub fn view_distance() -> u32 {
+        10
+    }
+
+    pub fn simulation_distance() -> u32 {
+        10
+    }
+
+    pub fn entity_tracking_distance() -> u32 {
+        8
+    }
+
+    pub fn spawn_preload_radius() -> u32 {
+        4
+    }
+
+    pub fn chunks_per_tick() -> u32 {
+        16
+    }
+
+    pub fn entity_updates_per_tick() -> u32 {
+        64
+    }
+
+    pub fn default_dimension() -> String {
+        "overworld".to_string()
+    }
+
+    pub fn difficulty() -> String {
+        "normal".to_string()
+    }
+
+    pub fn default_gamemode() -> String {
+        "creative".to_string()
+    }
+
+    pub fn autosave_interval_secs() -> u64 {
+        300
+    }
+
+    pub fn region_format() -> String {
+        "anvil".to_string()
+    }
+
+    pub fn database_mode() -> String {
+        "file".to_string()
+    }
+
+    pub fn database_path() -> String {
+        "data".to_string()
+    }
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Self {
+            data_dir: ".".to_string(),
+            favicon: "icon-64.png".to_string(),
+            import: "import".to_string(),
+            backups: "backups".to_string(),
+        }
+    }
+}
+
+impl Paths {
+    /// The data directory, created if it doesn't exist yet.
+    pub fn data_dir(&self) -> Result<PathBuf, Error> {
+        let dir = crate::database::get_root_dir()?.join(&self.data_dir);
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Resolve `path` against the data directory. Absolute paths are returned as is.
+    pub fn resolve(&self, path: impl AsRef<Path>) -> Result<PathBuf, Error> {
+        Ok(self.data_dir()?.join(path))
+    }
+
+    pub fn favicon(&self) -> Result<PathBuf, Error> {
+        self.resolve(&self.favicon)
+    }
+
+    pub fn import(&self) -> Result<PathBuf, Error> {
+        self.resolve(&self.import)
+    }
+
+    pub fn backups(&self) -> Result<PathBuf, Error> {
+        self.resolve(&self.backups)
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 25580,
+            max_tick_age_ms: 5000,
         }
     }
 }
 
-/// Get the global server configuration
-pub fn get_global_config() -> &'static ServerConfig {
-    static CONFIG: OnceLock<ServerConfig> = OnceLock::new();
-    CONFIG.get_or_init(|| ServerConfig::new().expect("Failed to load config"))
+impl Default for Logging {
+    fn default() -> Self {
+        Self {
+            format: "pretty".to_string(),
+            filter: String::new(),
+        }
+    }
+}
+
+impl Default for Physics {
+    /// Vanilla's values for falling items
+    fn default() -> Self {
+        Self {
+            gravity: 0.08,
+            drag: 0.02,
+            terminal_velocity: 3.92,
+        }
+    }
+}
+
+impl Default for Performance {
+    fn default() -> Self {
+        Self { tick_rate: 20 }
+    }
+}
+
+/// The configured `[logging]` table, read on its own so the logger can be set up before the rest
+/// of the config is loaded. `None` if there's no config yet.
+pub(crate) fn configured_logging() -> Option<Logging> {
+    build_settings().ok()?.get("logging").ok()
+}
+
+/// Splits a `logging.filter` into its comma separated directives.
+pub(crate) fn log_directives(filter: &str) -> impl Iterator<Item = &str> {
+    filter
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+}
+
+/// A config that can be swapped out while it's being read.
+///
+/// Readers get an `Arc` snapshot, so a reload never changes a config halfway through its use and
+/// holding on to one doesn't block the next reload.
+pub struct SharedConfig(RwLock<Arc<ServerConfig>>);
+
+impl SharedConfig {
+    pub fn new(config: ServerConfig) -> Self {
+        Self(RwLock::new(Arc::new(config)))
+    }
+
+    /// The current config.
+    pub fn load(&self) -> Arc<ServerConfig> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Replace the config. Snapshots loaded before keep seeing the old one.
+    pub fn store(&self, config: ServerConfig) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(config);
+    }
+}
+
+fn global_config() -> &'static SharedConfig {
+    static CONFIG: OnceLock<SharedConfig> = OnceLock::new();
+    CONFIG.get_or_init(|| SharedConfig::new(ServerConfig::new().expect("Failed to load config")))
+}
+
+/// Get the global server configuration.
+///
+/// This is a snapshot, call it again to see a config reloaded with [set_global_config].
+pub fn get_global_config() -> Arc<ServerConfig> {
+    global_config().load()
+}
+
+/// Replace the global server configuration, e.g. after reloading the config file.
+pub fn set_global_config(config: ServerConfig) {
+    global_config().store(config);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The config file written by the first release, before any of the later settings existed.
+    const BASELINE_CONFIG: &str = r#"
+host = "0.0.0.0"
+port = 25565
+motd = ["A FerrumC server; Absolute precision, power, and perfection."]
+max_players = 20
+network_tick_rate = 0
+world = "world"
+
+[database]
+cache_size = 1024
+compression = "fast"
+"#;
+
+    fn baseline_settings() -> Config {
+        Config::builder()
+            .add_source(config::File::from_str(BASELINE_CONFIG, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+    }
+
+    fn assert_invalid(config: ServerConfig, expected_field: &str) {
+        let Err(Error::InvalidConfig(field, reason)) = config.validate() else {
+            panic!("Expected config to be invalid for field {}", expected_field);
+        };
+        assert_eq!(field, expected_field, "unexpected error: {}", reason);
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(ServerConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_reload_is_observed_atomically() {
+        fn config(distance: u32) -> ServerConfig {
+            ServerConfig {
+                view_distance: distance,
+                simulation_distance: distance,
+                ..ServerConfig::default()
+            }
+        }
+
+        let shared = SharedConfig::new(config(2));
+        let before = shared.load();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for distance in (2..=32).cycle().take(10_000) {
+                    shared.store(config(distance));
+                }
+                shared.store(config(32));
+            });
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..10_000 {
+                        let config = shared.load();
+                        // A torn read would mix fields of two different configs
+                        assert_eq!(config.view_distance, config.simulation_distance);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(before.view_distance, 2, "old snapshots must not change");
+        assert_eq!(shared.load().view_distance, 32);
+    }
+
+    #[test]
+    fn test_paths_are_resolved_under_data_dir() {
+        let data_dir = std::env::temp_dir().join(format!("ferrumc-{}", uuid::Uuid::new_v4()));
+        let paths = Paths {
+            data_dir: data_dir.display().to_string(),
+            backups: "/var/backups/ferrumc".to_string(),
+            ..Paths::default()
+        };
+
+        assert_eq!(paths.favicon().unwrap(), data_dir.join("icon-64.png"));
+        assert_eq!(paths.import().unwrap(), data_dir.join("import"));
+        assert_eq!(paths.resolve("data").unwrap(), data_dir.join("data"));
+        assert!(data_dir.is_dir(), "the data dir should be created");
+        // Overrides may point outside the data dir
+        assert_eq!(paths.backups().unwrap(), PathBuf::from("/var/backups/ferrumc"));
+
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_port() {
+        let mut config = ServerConfig::default();
+        config.port = 0;
+        assert_invalid(config, "port");
+
+        let mut config = ServerConfig::default();
+        config.port = 70000;
+        assert_invalid(config, "port");
+    }
+
+    #[test]
+    fn test_bind_address() {
+        let mut config = ServerConfig::default();
+        config.host = "127.0.0.1".to_string();
+        config.port = 25566;
+        assert_eq!(config.bind_address().unwrap().to_string(), "127.0.0.1:25566");
+
+        config.host = "localhost".to_string();
+        assert_eq!(config.bind_address().unwrap().to_string(), "127.0.0.1:25566");
+
+        config.host = "::1".to_string();
+        assert_eq!(config.bind_address().unwrap().to_string(), "[::1]:25566");
+
+        config.host = "not an address".to_string();
+        assert_invalid(config, "host");
+    }
+
+    #[test]
+    fn test_invalid_send_queue_depth() {
+        let mut config = ServerConfig::default();
+        config.send_queue_depth = 0;
+        assert_invalid(config, "send_queue_depth");
+    }
+
+    #[test]
+    fn test_invalid_read_buffer_size() {
+        let mut config = ServerConfig::default();
+        config.read_buffer_size = MIN_READ_BUFFER_SIZE - 1;
+        assert_invalid(config, "read_buffer_size");
+    }
+
+    #[test]
+    fn test_entity_tracking_distance_within_view_distance() {
+        let mut config = ServerConfig::default();
+        config.view_distance = 6;
+        config.simulation_distance = 6;
+        config.entity_tracking_distance = 8;
+        assert_invalid(config, "entity_tracking_distance");
+
+        let mut config = ServerConfig::default();
+        config.entity_tracking_distance = 1;
+        assert_invalid(config, "entity_tracking_distance");
+    }
+
+    #[test]
+    fn test_simulation_distance_within_view_distance() {
+        let mut config = ServerConfig::default();
+        config.view_distance = 8;
+        config.simulation_distance = 12;
+        assert_invalid(config, "simulation_distance");
+
+        let mut config = ServerConfig::default();
+        config.view_distance = 12;
+        config.simulation_distance = 8;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_physics() {
+        let mut config = ServerConfig::default();
+        config.physics.drag = 1.0;
+        assert_invalid(config, "physics.drag");
+
+        let mut config = ServerConfig::default();
+        config.physics.terminal_velocity = 0.0;
+        assert_invalid(config, "physics.terminal_velocity");
+    }
+
+    #[test]
+    fn test_packet_dump_allowed_and_denied() {
+        let mut config = ServerConfig::default();
+        config.debug.packet_dump_allow = vec![0x14, 0x15];
+        config.debug.packet_dump_deny = vec![0x12, 0x15];
+        assert_invalid(config, "debug.packet_dump_deny");
+    }
+
+    #[test]
+    fn test_invalid_tick_rate() {
+        let mut config = ServerConfig::default();
+        config.performance.tick_rate = 0;
+        assert_invalid(config, "performance.tick_rate");
+
+        let mut config = ServerConfig::default();
+        config.performance.tick_rate = MAX_TICK_RATE + 1;
+        assert_invalid(config, "performance.tick_rate");
+    }
+
+    #[test]
+    fn test_invalid_health_port() {
+        let mut config = ServerConfig::default();
+        config.health.enabled = true;
+        config.health.port = config.port;
+        assert_invalid(config, "health.port");
+    }
+
+    #[test]
+    fn test_invalid_log_format() {
+        let mut config = ServerConfig::default();
+        config.logging.format = "xml".to_string();
+        assert_invalid(config, "logging.format");
+    }
+
+    #[test]
+    fn test_invalid_log_filter() {
+        let mut config = ServerConfig::default();
+        config.logging.filter = "ferrumc::net=debug, info".to_string();
+        assert!(config.validate().is_ok());
+
+        config.logging.filter = "ferrumc::net=loud".to_string();
+        assert_invalid(config, "logging.filter");
+    }
+
+    #[test]
+    fn test_invalid_resource_pack() {
+        let mut config = ServerConfig::default();
+        config.resource_pack.url = "ftp://example.com/pack.zip".to_string();
+        assert_invalid(config, "resource_pack.url");
+
+        let mut config = ServerConfig::default();
+        config.resource_pack.url = "https://example.com/pack.zip".to_string();
+        config.resource_pack.hash = "not a hash".to_string();
+        assert_invalid(config, "resource_pack.hash");
+
+        let mut config = ServerConfig::default();
+        config.resource_pack.url = "https://example.com/pack.zip".to_string();
+        config.resource_pack.hash = "a".repeat(40);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_difficulty() {
+        let mut config = ServerConfig::default();
+        config.difficulty = "nightmare".to_string();
+        assert_invalid(config, "difficulty");
+    }
+
+    #[test]
+    fn test_invalid_default_gamemode() {
+        let mut config = ServerConfig::default();
+        config.default_gamemode = "hardcore".to_string();
+        assert_invalid(config, "default_gamemode");
+    }
+
+    #[test]
+    fn test_invalid_region_format() {
+        let mut config = ServerConfig::default();
+        config.region_format = "mcr".to_string();
+        assert_invalid(config, "region_format");
+    }
+
+    #[test]
+    fn test_baseline_config_loads() {
+        let config: ServerConfig = baseline_settings().try_deserialize().unwrap();
+        config.validate().unwrap();
+
+        let defaults = ServerConfig::default();
+        assert_eq!(config.view_distance, defaults.view_distance);
+        assert_eq!(config.difficulty, defaults.difficulty);
+        assert_eq!(config.database.mode, defaults.database.mode);
+        assert_eq!(config.logging.format, defaults.logging.format);
+        assert!(config.enable_favicon);
+    }
+
+    #[test]
+    fn test_partial_sections_load() {
+        let partial = r#"
+[physics]
+gravity = 0.1
+
+[health]
+enabled = true
+
+[logging]
+format = "json"
+
+[resource_pack]
+url = "https://example.com/pack.zip"
+"#;
+        let config: ServerConfig = Config::builder()
+            .add_source(config::File::from_str(BASELINE_CONFIG, config::FileFormat::Toml))
+            .add_source(config::File::from_str(partial, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        config.validate().unwrap();
+
+        let defaults = ServerConfig::default();
+        assert_eq!(config.physics.gravity, 0.1);
+        assert_eq!(config.physics.drag, defaults.physics.drag);
+        assert!(config.health.enabled);
+        assert_eq!(config.health.port, defaults.health.port);
+        assert_eq!(config.logging.format, "json");
+        assert_eq!(config.logging.filter, defaults.logging.filter);
+        assert!(config.resource_pack.is_enabled());
+        assert!(!config.resource_pack.required);
+    }
+
+    #[test]
+    fn test_baseline_compression_is_valid() {
+        let mut config = ServerConfig::default();
+        config.database.compression =
+            baseline_settings().get_string("database.compression").unwrap();
+        assert!(config.validate().is_ok());
+        config.database.compression = "best".to_string();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_compression() {
+        let mut config = ServerConfig::default();
+        config.database.compression = "fastest".to_string();
+        assert_invalid(config, "database.compression");
+    }
+
+    #[test]
+    fn test_invalid_database_mode() {
+        let mut config = ServerConfig::default();
+        config.database.mode = "disk".to_string();
+        assert_invalid(config, "database.mode");
+    }
+
+    #[test]
+    fn test_invalid_world_name() {
+        let mut config = ServerConfig::default();
+        config.world = "../world".to_string();
+        assert_invalid(config, "world");
+    }
+
+    #[test]
+    fn test_empty_default_dimension() {
+        let mut config = ServerConfig::default();
+        config.default_dimension = " ".to_string();
+        assert_invalid(config, "default_dimension");
+    }
+
+    #[test]
+    fn test_env_override() {
+        // Not through the process environment, which every test shares
+        let mut vars = config::Map::new();
+        vars.insert("FERRUMC_DATABASE__CACHE_SIZE".to_string(), "4096".to_string());
+        let config: ServerConfig = Config::builder()
+            .add_source(config::File::from_str(
+                &toml::to_string(&ServerConfig::default()).unwrap(),
+                config::FileFormat::Toml,
+            ))
+            .add_source(env_overrides_from(Some(vars)))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        assert_eq!(config.database.cache_size, 4096);
+        assert_eq!(config.port, DEFAULT_SERVER_PORT);
+    }
+
+    #[test]
+    fn test_write_default_config() {
+        let dir = std::env::temp_dir().join(format!("ferrumc-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(DEFAULT_CONFIG_FILE);
+
+        assert!(ServerConfig::write_default(&path).unwrap());
+        let config: ServerConfig = Config::builder()
+            .add_source(config::File::from(path.as_path()))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert!(config.validate().is_ok());
+
+        // An existing file is left untouched
+        std::fs::write(&path, "port = 1").unwrap();
+        assert!(!ServerConfig::write_default(&path).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "port = 1");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_error_message_mentions_field() {
+        let mut config = ServerConfig::default();
+        config.motd = vec![];
+        let err = config.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid config value for \"motd\": must contain at least one message"
+        );
+    }
 }
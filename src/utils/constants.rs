@@ -13,3 +13,14 @@ pub mod init {
     pub const DEFAULT_SPAWN_YAW: f32 = 0.0;
     pub const DEFAULT_SPAWN_PITCH: f32 = 0.0;
 }
+
+/// The limits of where an entity can be in the overworld.
+pub mod world_bounds {
+    /// The furthest an entity can go from 0 along x or z, like vanilla.
+    pub const MAX_HORIZONTAL: i32 = 30_000_000;
+    pub const MIN_Y: i16 = -64;
+    pub const MAX_Y: i16 = 319;
+}
+
+/// The protocol version the server speaks, Minecraft 1.20.1.
+pub const PROTOCOL_VERSION: i32 = 763;
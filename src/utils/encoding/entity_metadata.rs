@@ -0,0 +1,76 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+/// Marks the end of an entity's metadata, in place of an index.
+const END: u8 = 0xFF;
+
+/// A metadata value, encoded as its type id followed by the value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    Byte(u8),
+    VarInt(i32),
+    Float(f32),
+    String(String),
+    /// A JSON text component, if any.
+    OptionalText(Option<String>),
+    Boolean(bool),
+}
+
+impl MetadataValue {
+    fn type_id(&self) -> i32 {
+        match self {
+            Self::Byte(_) => 0,
+            Self::VarInt(_) => 1,
+            Self::Float(_) => 3,
+            Self::String(_) => 4,
+            Self::OptionalText(_) => 6,
+            Self::Boolean(_) => 8,
+        }
+    }
+}
+
+impl NetEncode for MetadataValue {
+    async fn net_encode<T>(&self, bytes: &mut T) -> Result<(), ferrumc_codec::CodecError>
+    where
+        T: AsyncWrite + Unpin,
+    {
+        VarInt::from(self.type_id()).net_encode(bytes).await?;
+        match self {
+            Self::Byte(value) => value.net_encode(bytes).await,
+            Self::VarInt(value) => VarInt::from(*value).net_encode(bytes).await,
+            Self::Float(value) => value.net_encode(bytes).await,
+            Self::String(value) => value.net_encode(bytes).await,
+            Self::OptionalText(None) => false.net_encode(bytes).await,
+            Self::OptionalText(Some(text)) => {
+                true.net_encode(bytes).await?;
+                text.net_encode(bytes).await
+            }
+            Self::Boolean(value) => value.net_encode(bytes).await,
+        }
+    }
+}
+
+/// Metadata entries by index, as sent in Set Entity Metadata: each index and value, then an end
+/// marker.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityMetadata(pub Vec<(u8, MetadataValue)>);
+
+impl EntityMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl NetEncode for EntityMetadata {
+    async fn net_encode<T>(&self, bytes: &mut T) -> Result<(), ferrumc_codec::CodecError>
+    where
+        T: AsyncWrite + Unpin,
+    {
+        for (index, value) in &self.0 {
+            index.net_encode(bytes).await?;
+            value.net_encode(bytes).await?;
+        }
+        END.net_encode(bytes).await
+    }
+}
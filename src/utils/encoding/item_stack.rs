@@ -0,0 +1,326 @@
+use bincode::{Decode, Encode};
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::utils::error::Error;
+
+/// The largest NBT compound accepted inline, like vanilla's limit for network NBT.
+const MAX_NBT_BYTES: usize = 2 * 1024 * 1024;
+/// How deeply lists and compounds can be nested.
+const MAX_NBT_DEPTH: usize = 512;
+
+const TAG_END: u8 = 0;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+
+/// A stack of items, e.g. in an inventory slot.
+///
+/// On the network a stack is a present flag, then the item id, count and NBT if it's present.
+/// Empty stacks (see [ItemStack::is_empty]) are sent as absent.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ItemStack {
+    /// The item's registry id.
+    pub id: i32,
+    pub count: i8,
+    /// Extra data like enchantments or a custom name, as an encoded NBT compound.
+    pub nbt: Option<Vec<u8>>,
+}
+
+impl ItemStack {
+    /// Nothing, like an empty slot.
+    pub const EMPTY: ItemStack = ItemStack {
+        id: 0,
+        count: 0,
+        nbt: None,
+    };
+
+    pub fn new(id: i32, count: i8) -> Self {
+        Self {
+            id,
+            count,
+            nbt: None,
+        }
+    }
+
+    pub fn with_nbt(mut self, nbt: Vec<u8>) -> Self {
+        self.nbt = Some(nbt);
+        self
+    }
+
+    /// Air, or no items at all.
+    pub fn is_empty(&self) -> bool {
+        self.id == 0 || self.count <= 0
+    }
+}
+
+/// The slots a click changed, as predicted by the client: a count, then each slot index and
+/// the stack now in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangedSlots(pub Vec<(i16, ItemStack)>);
+
+impl Default for ItemStack {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl NetEncode for ItemStack {
+    async fn net_encode<T>(&self, bytes: &mut T) -> Result<(), ferrumc_codec::CodecError>
+    where
+        T: AsyncWrite + Unpin,
+    {
+        if self.is_empty() {
+            return false.net_encode(bytes).await;
+        }
+        true.net_encode(bytes).await?;
+        VarInt::from(self.id).net_encode(bytes).await?;
+        self.count.net_encode(bytes).await?;
+        match &self.nbt {
+            Some(nbt) => bytes
+                .write_all(nbt)
+                .await
+                .map_err(ferrumc_codec::CodecError::from_external_error),
+            None => TAG_END.net_encode(bytes).await,
+        }
+    }
+}
+
+/// Reads an inline NBT compound as is, including its tag type and name. `None` if there's just a
+/// TAG_End, meaning no NBT.
+pub(crate) async fn read_raw_nbt<T>(bytes: &mut T) -> Result<Option<Vec<u8>>, Error>
+where
+    T: AsyncRead + Unpin,
+{
+    let tag = bytes.read_u8().await?;
+    if tag == TAG_END {
+        return Ok(None);
+    }
+    if tag != TAG_COMPOUND {
+        return Err(Error::Generic(format!(
+            "Expected an NBT compound, got tag {}",
+            tag
+        )));
+    }
+
+    let mut nbt = NbtReader {
+        bytes,
+        raw: vec![tag],
+    };
+    let name_length = nbt.read(2).await?;
+    let name_length = u16::from_be_bytes([name_length[0], name_length[1]]);
+    nbt.read(name_length as usize).await?;
+    nbt.payload(TAG_COMPOUND).await?;
+    Ok(Some(nbt.raw))
+}
+
+/// Walks through NBT without parsing it, keeping the bytes it read.
+struct NbtReader<'a, T> {
+    bytes: &'a mut T,
+    raw: Vec<u8>,
+}
+
+impl<'a, T: AsyncRead + Unpin> NbtReader<'a, T> {
+    /// Reads `length` bytes, returning them.
+    async fn read(&mut self, length: usize) -> Result<&[u8], Error> {
+        if length > MAX_NBT_BYTES - self.raw.len() {
+            return Err(Error::Generic(format!(
+                "NBT is larger than {} bytes",
+                MAX_NBT_BYTES
+            )));
+        }
+        let start = self.raw.len();
+        self.raw.resize(start + length, 0);
+        self.bytes.read_exact(&mut self.raw[start..]).await?;
+        Ok(&self.raw[start..])
+    }
+
+    async fn read_length(&mut self) -> Result<usize, Error> {
+        let length = self.read(4).await?;
+        let length = i32::from_be_bytes([length[0], length[1], length[2], length[3]]);
+        usize::try_from(length)
+            .map_err(|_| Error::Generic(format!("Negative NBT length {}", length)))
+    }
+
+    /// Reads the payload of a tag, including everything nested in it.
+    ///
+    /// The compounds and lists it's inside of are kept on a stack instead of recursing, so
+    /// deeply nested NBT can't blow up the stack or need a boxed future.
+    async fn payload(&mut self, tag: u8) -> Result<(), Error> {
+        let mut open = Vec::new();
+        let mut next = Some(tag);
+        loop {
+            if let Some(tag) = next.take() {
+                match tag {
+                    TAG_LIST => {
+                        let element = self.read(1).await?[0];
+                        let remaining = self.read_length().await?;
+                        open.push(Nested::List { element, remaining });
+                    }
+                    TAG_COMPOUND => open.push(Nested::Compound),
+                    tag => self.primitive(tag).await?,
+                }
+                if open.len() > MAX_NBT_DEPTH {
+                    return Err(Error::Generic("NBT is nested too deeply".to_string()));
+                }
+            }
+
+            // The next tag comes from the innermost compound or list that isn't done yet
+            let Some(innermost) = open.last_mut() else {
+                return Ok(());
+            };
+            match innermost {
+                Nested::List { remaining: 0, .. } => {
+                    open.pop();
+                }
+                Nested::List { element, remaining } => {
+                    *remaining -= 1;
+                    next = Some(*element);
+                }
+                Nested::Compound => {
+                    let tag = self.read(1).await?[0];
+                    if tag == TAG_END {
+                        open.pop();
+                        continue;
+                    }
+                    let name_length = self.read(2).await?;
+                    let name_length = u16::from_be_bytes([name_length[0], name_length[1]]);
+                    self.read(name_length as usize).await?;
+                    next = Some(tag);
+                }
+            }
+        }
+    }
+
+    /// Reads the payload of a tag that doesn't contain other tags.
+    async fn primitive(&mut self, tag: u8) -> Result<(), Error> {
+        match tag {
+            1 => {
+                self.read(1).await?;
+            }
+            2 => {
+                self.read(2).await?;
+            }
+            3 | 5 => {
+                self.read(4).await?;
+            }
+            4 | 6 => {
+                self.read(8).await?;
+            }
+            // Byte array
+            7 => {
+                let length = self.read_length().await?;
+                self.read(length).await?;
+            }
+            // String
+            8 => {
+                let length = self.read(2).await?;
+                let length = u16::from_be_bytes([length[0], length[1]]);
+                self.read(length as usize).await?;
+            }
+            // Int array
+            11 => {
+                let length = self.read_length().await?;
+                self.read(length.saturating_mul(4)).await?;
+            }
+            // Long array
+            12 => {
+                let length = self.read_length().await?;
+                self.read(length.saturating_mul(8)).await?;
+            }
+            tag => return Err(Error::Generic(format!("Unknown NBT tag {}", tag))),
+        }
+        Ok(())
+    }
+}
+
+/// A compound or list [NbtReader::payload] is inside of.
+enum Nested {
+    Compound,
+    /// The type of its elements, and how many are left to read.
+    List { element: u8, remaining: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::utils::impls::packet_impls::NetDecode;
+
+    async fn round_trip(stack: &ItemStack) -> (Vec<u8>, ItemStack) {
+        let mut encoded = Vec::new();
+        stack.net_encode(&mut encoded).await.unwrap();
+        let mut cursor = Cursor::new(encoded.clone());
+        let decoded = ItemStack::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(cursor.position() as usize, encoded.len());
+        (encoded, *decoded)
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_empty_stack() {
+        let (encoded, decoded) = round_trip(&ItemStack::EMPTY).await;
+        assert_eq!(encoded, vec![0]);
+        assert_eq!(decoded, ItemStack::EMPTY);
+
+        // A stack without items is empty too
+        let (encoded, decoded) = round_trip(&ItemStack::new(5, 0)).await;
+        assert_eq!(encoded, vec![0]);
+        assert!(decoded.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_stack_with_nbt() {
+        let (encoded, decoded) = round_trip(&ItemStack::new(300, 64)).await;
+        // Present, id 300 as a VarInt, count, no NBT
+        assert_eq!(encoded, vec![1, 0xAC, 0x02, 64, 0]);
+        assert_eq!(decoded, ItemStack::new(300, 64));
+
+        // {display: {Name: "a"}, Damage: 3, Lore: [1b, 2b]}
+        let mut nbt = vec![TAG_COMPOUND, 0, 0];
+        nbt.extend_from_slice(&[TAG_COMPOUND, 0, 7]);
+        nbt.extend_from_slice(b"display");
+        nbt.extend_from_slice(&[8, 0, 4]);
+        nbt.extend_from_slice(b"Name");
+        nbt.extend_from_slice(&[0, 1, b'a', TAG_END]);
+        nbt.extend_from_slice(&[3, 0, 6]);
+        nbt.extend_from_slice(b"Damage");
+        nbt.extend_from_slice(&3i32.to_be_bytes());
+        nbt.extend_from_slice(&[TAG_LIST, 0, 4]);
+        nbt.extend_from_slice(b"Lore");
+        nbt.extend_from_slice(&[1, 0, 0, 0, 2, 1, 2]);
+        nbt.push(TAG_END);
+
+        let stack = ItemStack::new(1, 1).with_nbt(nbt.clone());
+        let (encoded, decoded) = round_trip(&stack).await;
+        // After the present flag, id and count
+        assert_eq!(encoded[3..], nbt);
+        assert_eq!(decoded, stack);
+    }
+
+    /// `{a: [[[...[]...]]]}`, with `depth` lists inside each other.
+    fn nested_lists(depth: usize) -> Vec<u8> {
+        let mut encoded = vec![1, 1, 1, TAG_COMPOUND, 0, 0, TAG_LIST, 0, 1, b'a'];
+        for _ in 1..depth {
+            encoded.extend_from_slice(&[TAG_LIST, 0, 0, 0, 1]);
+        }
+        encoded.extend_from_slice(&[1, 0, 0, 0, 0, TAG_END]);
+        encoded
+    }
+
+    #[tokio::test]
+    async fn test_decode_nested_nbt() {
+        let encoded = nested_lists(100);
+        let decoded = ItemStack::net_decode(&mut Cursor::new(encoded.clone())).await.unwrap();
+        assert_eq!(decoded.nbt.as_deref(), Some(&encoded[3..]));
+
+        let encoded = nested_lists(MAX_NBT_DEPTH + 1);
+        assert!(ItemStack::net_decode(&mut Cursor::new(encoded)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_decode_rejects_truncated_nbt() {
+        let encoded = vec![1, 1, 1, TAG_COMPOUND, 0, 0, 3, 0, 1];
+        assert!(ItemStack::net_decode(&mut Cursor::new(encoded)).await.is_err());
+    }
+}
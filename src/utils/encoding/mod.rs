@@ -1,5 +1,10 @@
 pub mod bitset;
 pub mod position;
+pub mod remaining_bytes;
+pub mod sound_event;
+pub mod item_stack;
+pub mod entity_metadata;
+pub mod particle;
 pub mod velocity;
 
 /*impl<S: NBTSerialize> Encode for &S {
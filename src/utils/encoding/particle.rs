@@ -0,0 +1,46 @@
+use crate::utils::prelude::*;
+use crate::utils::registries::get_registries;
+
+/// Particles that need extra data after the packet, which isn't supported yet.
+const WITH_DATA: &[&str] = &[
+    "block",
+    "block_marker",
+    "dust",
+    "dust_color_transition",
+    "falling_dust",
+    "sculk_charge",
+    "item",
+    "vibration",
+    "shriek",
+];
+
+/// A particle type without extra data, by its registry id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParticleType(pub i32);
+
+impl ParticleType {
+    /// Looks a particle up by name, with or without the `minecraft:` namespace.
+    pub fn from_name(name: &str) -> Result<Self> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        if WITH_DATA.contains(&name) {
+            return Err(Error::Generic(format!("Particle {} needs extra data", name)));
+        }
+        get_registries()
+            .id("particle_type", name)
+            .map(Self)
+            .ok_or_else(|| Error::Generic(format!("Unknown particle {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_particle_names() {
+        assert_eq!(ParticleType::from_name("flame").unwrap(), ParticleType(28));
+        assert_eq!(ParticleType::from_name("minecraft:heart").unwrap(), ParticleType(38));
+        assert!(ParticleType::from_name("dust").is_err());
+        assert!(ParticleType::from_name("confetti").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+/// Everything left in a packet after the fields before it, e.g. the data of a plugin message.
+///
+/// Unlike `Vec<u8>`, there's no length prefix, so this has to be the last field of a packet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemainingBytes(pub Vec<u8>);
@@ -0,0 +1,84 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+use crate::utils::registries::get_registries;
+
+/// A sound to play, by registry id or by name.
+///
+/// Clients resolve names themselves, so sounds missing from the bundled registries, including
+/// ones from resource packs, still play.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SoundEvent {
+    Registered(i32),
+    Named(String),
+}
+
+impl SoundEvent {
+    /// Looks a sound up in the bundled registry. `minecraft:` is assumed without a namespace.
+    pub fn from_name(name: &str) -> Self {
+        let name = match name.contains(':') {
+            true => name.to_string(),
+            false => format!("minecraft:{}", name),
+        };
+        match get_registries().id("sound_event", &name) {
+            Some(id) => Self::Registered(id),
+            None => Self::Named(name),
+        }
+    }
+}
+
+impl NetEncode for SoundEvent {
+    async fn net_encode<T>(&self, bytes: &mut T) -> Result<(), ferrumc_codec::CodecError>
+    where
+        T: AsyncWrite + Unpin,
+    {
+        match self {
+            // Offset by one, 0 means the sound is inlined
+            Self::Registered(id) => VarInt::from(id + 1).net_encode(bytes).await,
+            Self::Named(name) => {
+                VarInt::from(0).net_encode(bytes).await?;
+                name.net_encode(bytes).await?;
+                // No fixed range, it depends on the volume
+                false.net_encode(bytes).await
+            }
+        }
+    }
+}
+
+/// Which volume slider a sound is under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCategory {
+    Master = 0,
+    Music = 1,
+    Records = 2,
+    Weather = 3,
+    Blocks = 4,
+    Hostile = 5,
+    Neutral = 6,
+    Players = 7,
+    Ambient = 8,
+    Voice = 9,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_named_sound() {
+        let sound = SoundEvent::from_name("custom.horn");
+        assert_eq!(sound, SoundEvent::Named("minecraft:custom.horn".to_string()));
+
+        let mut encoded = Vec::new();
+        sound.net_encode(&mut encoded).await.unwrap();
+        let mut expected = vec![0, 21];
+        expected.extend_from_slice(b"minecraft:custom.horn");
+        expected.push(0);
+        assert_eq!(encoded, expected);
+
+        let mut encoded = Vec::new();
+        SoundEvent::Registered(5).net_encode(&mut encoded).await.unwrap();
+        assert_eq!(encoded, vec![6]);
+    }
+}
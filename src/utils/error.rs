@@ -10,6 +10,8 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Config(#[from] ConfigError),
+    #[error("Invalid config value for \"{0}\": {1}")]
+    InvalidConfig(String, String),
     #[error(transparent)]
     TomlSe(#[from] toml::ser::Error),
     #[error(transparent)]
@@ -19,6 +21,10 @@ pub enum Error {
 
     #[error("Connection not found: {0}")]
     ConnectionNotFound(u32),
+    #[error("The outgoing packet queue stayed full, the client is too slow")]
+    SendQueueFull,
+    #[error("The outgoing packet queue is closed")]
+    SendQueueClosed,
     #[error("Invalid packet id: {0}")]
     InvalidPacketId(u32),
     #[error("Invalid state: {0:x}")]
@@ -47,6 +53,8 @@ pub enum Error {
     InvalidChunk(i32, i32, String),
     #[error("Chunk already exists at ({0}, {1})")]
     ChunkExists(i32, i32),
+    #[error("Unknown chunk compression type: {0}")]
+    UnknownChunkCompression(u8),
 
     #[error(transparent)]
     SimdNbtError(#[from] simdnbt::Error),
@@ -62,6 +70,8 @@ pub enum Error {
 
     #[error("Invalid directive: {0}")]
     InvalidDirective(String),
+    #[error("{0}")]
+    InvalidArgument(String),
 
     #[error("TCP Error: {0}")]
     TcpError(String),
@@ -1,10 +1,18 @@
+use ferrumc_codec::dec::{read_string, MAX_STRING_BYTES};
 use ferrumc_codec::network_types::varint::VarInt;
 use ferrumc_codec::network_types::varlong::Varlong;
 use tokio::io::{AsyncRead, AsyncReadExt};
 
+use crate::utils::encoding::item_stack::{read_raw_nbt, ChangedSlots, ItemStack};
 use crate::utils::encoding::position::Position;
+use crate::utils::encoding::remaining_bytes::RemainingBytes;
 use crate::utils::error::Error;
 
+/// The most data the vanilla server accepts in a serverbound plugin message.
+const MAX_REMAINING_BYTES: usize = 32767;
+/// The most slots a single click can change, like vanilla.
+const MAX_CHANGED_SLOTS: i32 = 128;
+
 /// This trait is used to decode a type from a byte stream. It is implemented for all types that
 /// can be decoded from a byte stream.
 ///
@@ -196,14 +204,14 @@ impl NetDecode for String {
     /// Decodes a String from a byte stream. The first byte(s) is a VarInt representing the length of
     /// the string, followed by the string itself. The string is expected to be UTF-8 encoded.
     /// Takes out a variable number of bytes.
+    ///
+    /// The length comes straight from the client, so it's checked against [MAX_STRING_BYTES]
+    /// before anything is allocated for it.
     async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
     where
         T: AsyncRead + Unpin,
     {
-        let remaining_bytes = VarInt::read(bytes).await?;
-        let mut string_buf = vec![0u8; remaining_bytes.into()];
-        bytes.read_exact(&mut string_buf).await?;
-        Ok(Box::from(String::from_utf8(string_buf)?))
+        Ok(Box::from(read_string(bytes, MAX_STRING_BYTES).await?))
     }
 }
 
@@ -269,6 +277,68 @@ impl<V: NetDecode + Unpin> NetDecode for Vec<V> {
     }
 }
 
+impl NetDecode for RemainingBytes {
+    /// Decodes everything left in the byte stream, up to [MAX_REMAINING_BYTES].
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let mut buf = Vec::new();
+        (&mut *bytes)
+            .take(MAX_REMAINING_BYTES as u64 + 1)
+            .read_to_end(&mut buf)
+            .await?;
+        if buf.len() > MAX_REMAINING_BYTES {
+            return Err(Error::Generic(format!(
+                "Too many remaining bytes, expected at most {}",
+                MAX_REMAINING_BYTES
+            )));
+        }
+        Ok(Box::from(RemainingBytes(buf)))
+    }
+}
+
+impl NetDecode for ItemStack {
+    /// Decodes an item stack: a present flag, then the item id, count and inline NBT if it's
+    /// present. Absent stacks decode as [ItemStack::EMPTY].
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        if !*bool::net_decode(bytes).await? {
+            return Ok(Box::from(ItemStack::EMPTY));
+        }
+        let id = VarInt::net_decode(bytes).await?.get_val();
+        let count = *i8::net_decode(bytes).await?;
+        let nbt = read_raw_nbt(bytes).await?;
+        Ok(Box::from(ItemStack { id, count, nbt }))
+    }
+}
+
+impl NetDecode for ChangedSlots {
+    /// Decodes a VarInt count of up to [MAX_CHANGED_SLOTS], then that many slot indices, each
+    /// followed by an [ItemStack].
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let count = VarInt::net_decode(bytes).await?.get_val();
+        if !(0..=MAX_CHANGED_SLOTS).contains(&count) {
+            return Err(Error::Generic(format!(
+                "Expected at most {} changed slots, got {}",
+                MAX_CHANGED_SLOTS, count
+            )));
+        }
+        let mut slots = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let slot = *i16::net_decode(bytes).await?;
+            let item = *ItemStack::net_decode(bytes).await?;
+            slots.push((slot, item));
+        }
+        Ok(Box::from(ChangedSlots(slots)))
+    }
+}
+
 impl NetDecode for Position {
     /// Decodes a Position from a byte stream. A Position is a 64-bit integer, where the 26 MSB
     /// are the x coordinate, the next 26 bits are the z coordinate, and the 12 LSB are
@@ -0,0 +1,127 @@
+//! The order locks are taken in, so tasks can't deadlock each other.
+//!
+//! Connection locks come before component locks. A task holding a connection may look up
+//! components (e.g. [crate::net::drop_conn] saving the player), but a task holding a component
+//! must never wait on a connection, since whoever has that connection may be waiting on the
+//! component. Copy what's needed out of the world and release it first, see
+//! [crate::net::ConnectionWrapper::handles].
+//!
+//! Debug builds check this for tasks run through [tracked], and panic when a lock is taken out of
+//! order. Release builds don't track anything.
+
+use std::cell::RefCell;
+use std::future::Future;
+
+/// The kinds of locks, in the order they have to be taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockRank {
+    /// A connection's `RwLock`, or anything waiting on the connection like sending a packet.
+    Connection,
+    /// A component in the world.
+    Component,
+}
+
+tokio::task_local! {
+    /// The ranks of the locks the current task holds.
+    static HELD: RefCell<Vec<LockRank>>;
+}
+
+/// Runs `task` with the locks it takes checked, in debug builds.
+pub async fn tracked<F: Future>(task: F) -> F::Output {
+    HELD.scope(RefCell::new(Vec::new()), task).await
+}
+
+/// Marks a lock of `rank` as held by the current task until the returned guard is dropped. Call
+/// it before waiting on the lock, so an out of order lock panics instead of deadlocking.
+///
+/// # Panics
+///
+/// In debug builds, if the task holds a lock that has to be taken after this one.
+pub fn acquire(rank: LockRank) -> HeldLock {
+    check(rank);
+    let held = cfg!(debug_assertions)
+        && HELD.try_with(|held| held.borrow_mut().push(rank)).is_ok();
+    HeldLock {
+        rank: held.then_some(rank),
+    }
+}
+
+/// Checks that a lock of `rank` may be taken now, without marking it as held. For locks that are
+/// released again right away.
+///
+/// # Panics
+///
+/// In debug builds, if the task holds a lock that has to be taken after this one.
+pub fn check(rank: LockRank) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let _ = HELD.try_with(|held| {
+        if let Some(later) = held.borrow().iter().find(|held| **held > rank) {
+            panic!(
+                "Lock order violated: took a {:?} lock while holding a {:?} lock",
+                rank, later
+            );
+        }
+    });
+}
+
+/// A lock held by the current task, see [acquire].
+#[derive(Debug)]
+pub struct HeldLock {
+    /// `None` if the lock isn't tracked.
+    rank: Option<LockRank>,
+}
+
+impl Drop for HeldLock {
+    fn drop(&mut self) {
+        let Some(rank) = self.rank else {
+            return;
+        };
+        let _ = HELD.try_with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(index) = held.iter().rposition(|held| *held == rank) {
+                held.remove(index);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+    use crate::tests::helpers::{add_test_player, test_state};
+    use crate::utils::components::player::Player;
+
+    #[tokio::test]
+    async fn test_in_order_locks() {
+        tracked(async {
+            let connection = acquire(LockRank::Connection);
+            let first = acquire(LockRank::Component);
+            let second = acquire(LockRank::Component);
+            drop((first, second));
+
+            // Nothing after it is held anymore
+            check(LockRank::Connection);
+            drop(connection);
+        })
+        .await;
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    #[should_panic(expected = "Lock order violated")]
+    async fn test_out_of_order_lock_panics() {
+        let state = test_state().await;
+        let (player, _client) = add_test_player(&state, "Player").await;
+
+        tracked(async {
+            let _player = state.world.get_component::<Player>(player).await.unwrap();
+            let conn = state.connections.get_connection(player).unwrap();
+            let conn = conn.read().await;
+            let _ = conn.send_packet(KeepAlivePacketOut::new_auto(1)).await;
+        })
+        .await;
+    }
+}
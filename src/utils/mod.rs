@@ -1,10 +1,15 @@
+use tracing::Subscriber;
 use tracing_subscriber::filter::Directive;
+use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer};
 use tracing_subscriber::util::SubscriberInitExt;
 use crate::utils::constants::DEFAULT_LOG_LEVEL;
 use crate::utils::prelude::*;
 
 pub mod binary_utils;
+pub mod clock;
 pub mod components;
 pub mod config;
 pub mod constants;
@@ -12,7 +17,10 @@ pub mod encoding;
 pub mod error;
 pub mod hash;
 pub mod impls;
+pub mod lock_order;
 pub mod prelude;
+pub mod protocol;
+pub mod registries;
 
 /// Sets up the logger. Needs to be run before anything else in order for logging to run end.
 pub fn setup_logger() -> Result<()> {
@@ -21,7 +29,8 @@ pub fn setup_logger() -> Result<()> {
         .map(|arg| arg.replace("--log=", ""));
 
     let mut trace_level: &str = trace_level.as_deref().unwrap_or("");
-    if trace_level.is_empty() {
+    let explicit_level = !trace_level.is_empty();
+    if !explicit_level {
         eprintln!(
             "No log level specified, using default: {}",
             DEFAULT_LOG_LEVEL
@@ -38,9 +47,19 @@ pub fn setup_logger() -> Result<()> {
         }
     };
 
+    let logging = config::configured_logging();
+    let filter = logging.as_ref().map_or("", |logging| logging.filter.as_str());
+
     let env_filter = tracing_subscriber::EnvFilter::from_default_env()
         .add_directive(trace_level.into())
         .add_directive(str_to_directive("sled=off")?);
+    let mut env_filter = add_directives(env_filter, filter)?;
+    if explicit_level {
+        // Added again so an explicit --log level wins over a plain level in the config
+        env_filter = env_filter.add_directive(trace_level.into());
+    }
+
+    let json = logging.is_some_and(|logging| logging.format == "json");
 
     let mut fmt_layer = tracing_subscriber::fmt::Layer::default();
 
@@ -54,14 +73,102 @@ pub fn setup_logger() -> Result<()> {
 
     tracing_subscriber::registry()
         .with(env_filter)
-        .with(fmt_layer)
+        .with((!json).then_some(fmt_layer))
+        .with(json.then(|| json_layer(std::io::stdout)))
         .init();
 
 
     Ok(())
 }
 
+/// Logs one JSON object per line, including the fields of the span it was logged in (e.g.
+/// `conn_id`) and of every span around it.
+fn json_layer<S, W>(writer: W) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_writer(writer)
+}
+
+/// Adds every directive in a `logging.filter` string to `filter`.
+fn add_directives(mut filter: EnvFilter, directives: &str) -> Result<EnvFilter> {
+    for directive in config::log_directives(directives) {
+        filter = filter.add_directive(str_to_directive(directive)?);
+    }
+    Ok(filter)
+}
+
 fn str_to_directive(s: &str) -> Result<Directive> {
     s.parse()
         .map_err(|_| Error::InvalidDirective(s.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{info, info_span};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_log_line() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::registry().with(json_layer(move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _conn = info_span!("conn", conn_id = 7).entered();
+            info!("Player joined");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "Player joined");
+        assert_eq!(line["span"]["name"], "conn");
+        assert_eq!(line["span"]["conn_id"], 7);
+        assert_eq!(line["spans"][0]["conn_id"], 7);
+    }
+
+    #[test]
+    fn test_configured_filter() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let filter = add_directives(EnvFilter::default(), "ferrumc::net=debug, warn").unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer().with_writer(move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "ferrumc::net", "net debug");
+            tracing::info!(target: "ferrumc::world", "world info");
+            tracing::warn!(target: "ferrumc::world", "world warning");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("net debug"));
+        assert!(!output.contains("world info"));
+        assert!(output.contains("world warning"));
+    }
+}
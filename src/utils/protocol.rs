@@ -0,0 +1,175 @@
+//! The data that changes between the Minecraft versions the server supports, picked by a
+//! connection's `metadata.protocol_version`.
+//!
+//! Only the targeted version, [PROTOCOL_VERSION], is supported so far. Another version needs its
+//! generated reports bundled, and outgoing packets to take their ids from [ProtocolData::packet_id]
+//! before it can be added to [SUPPORTED].
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::utils::constants::PROTOCOL_VERSION;
+use crate::utils::registries::{BundledReports, Registries};
+
+/// Clientbound play packets whose id depends on the version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Clientbound {
+    SpawnPlayer,
+    AcknowledgeBlockChange,
+    BossBar,
+    ChangeDifficulty,
+    CommandSuggestionsResponse,
+    SetContainerContent,
+    PluginMessage,
+    Disconnect,
+    GameEvent,
+    KeepAlive,
+    ChunkDataAndUpdateLight,
+    Particle,
+    LoginPlay,
+    UpdateEntityPosition,
+    UpdateEntityPositionAndRotation,
+    UpdateEntityRotation,
+    CombatDeath,
+    PlayerInfoRemove,
+    PlayerInfoUpdate,
+    SynchronizePlayerPosition,
+    RemoveEntities,
+    ResourcePack,
+    Respawn,
+    SetHeadRotation,
+    UpdateSectionBlocks,
+    SetActionBarText,
+    SetCenterChunk,
+    DefaultSpawnPosition,
+    DisplayObjective,
+    SetEntityMetadata,
+    SetHealth,
+    UpdateObjectives,
+    UpdateTeams,
+    UpdateScore,
+    SetSubtitleText,
+    UpdateTime,
+    SetTitleText,
+    SetTitleAnimationTimes,
+    EntitySoundEffect,
+    SoundEffect,
+    SystemChatMessage,
+    TeleportEntity,
+}
+
+/// The play packet ids of 1.20.1.
+fn play_ids_1_20_1(packet: Clientbound) -> i32 {
+    use Clientbound::*;
+    match packet {
+        SpawnPlayer => 0x03,
+        AcknowledgeBlockChange => 0x06,
+        BossBar => 0x0B,
+        ChangeDifficulty => 0x0C,
+        CommandSuggestionsResponse => 0x0F,
+        SetContainerContent => 0x12,
+        PluginMessage => 0x17,
+        Disconnect => 0x1A,
+        GameEvent => 0x1F,
+        KeepAlive => 0x23,
+        ChunkDataAndUpdateLight => 0x24,
+        Particle => 0x26,
+        LoginPlay => 0x28,
+        UpdateEntityPosition => 0x2B,
+        UpdateEntityPositionAndRotation => 0x2C,
+        UpdateEntityRotation => 0x2D,
+        CombatDeath => 0x38,
+        PlayerInfoRemove => 0x39,
+        PlayerInfoUpdate => 0x3A,
+        SynchronizePlayerPosition => 0x3C,
+        RemoveEntities => 0x3E,
+        ResourcePack => 0x40,
+        Respawn => 0x41,
+        SetHeadRotation => 0x42,
+        UpdateSectionBlocks => 0x43,
+        SetActionBarText => 0x46,
+        SetCenterChunk => 0x4E,
+        DefaultSpawnPosition => 0x50,
+        DisplayObjective => 0x51,
+        SetEntityMetadata => 0x52,
+        SetHealth => 0x57,
+        UpdateObjectives => 0x58,
+        UpdateTeams => 0x5A,
+        UpdateScore => 0x5B,
+        SetSubtitleText => 0x5D,
+        UpdateTime => 0x5E,
+        SetTitleText => 0x5F,
+        SetTitleAnimationTimes => 0x60,
+        EntitySoundEffect => 0x61,
+        SoundEffect => 0x62,
+        SystemChatMessage => 0x64,
+        TeleportEntity => 0x68,
+    }
+}
+
+/// Everything that differs for one protocol version.
+pub struct ProtocolData {
+    pub registries: Registries,
+    play_ids: fn(Clientbound) -> i32,
+}
+
+impl ProtocolData {
+    pub fn packet_id(&self, packet: Clientbound) -> i32 {
+        (self.play_ids)(packet)
+    }
+}
+
+const SUPPORTED: &[(i32, BundledReports, fn(Clientbound) -> i32)] = &[
+    (
+        PROTOCOL_VERSION,
+        BundledReports {
+            version: include_str!("../../.etc/reports/1.20.1/version.json"),
+            registries: include_str!("../../.etc/reports/1.20.1/registries.json"),
+            blocks: include_bytes!("../../.etc/reports/1.20.1/blocks.json.bz2"),
+        },
+        play_ids_1_20_1,
+    ),
+];
+
+pub fn is_supported(protocol_version: i32) -> bool {
+    SUPPORTED.iter().any(|(version, ..)| *version == protocol_version)
+}
+
+/// The data for a protocol version, loaded on first use. `None` if it isn't supported.
+pub fn protocol_data(protocol_version: i32) -> Option<&'static ProtocolData> {
+    static LOADED: OnceLock<HashMap<i32, ProtocolData>> = OnceLock::new();
+    LOADED
+        .get_or_init(|| {
+            SUPPORTED
+                .iter()
+                .map(|(version, reports, play_ids)| {
+                    let registries = Registries::bundled(*version, reports)
+                        .expect("Failed to load registries");
+                    let data = ProtocolData {
+                        registries,
+                        play_ids: *play_ids,
+                    };
+                    (*version, data)
+                })
+                .collect()
+        })
+        .get(&protocol_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_per_version() {
+        let current = protocol_data(PROTOCOL_VERSION).unwrap();
+        assert_eq!(current.registries.version.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(current.registries.default_block_state("structure_block"), Some(19215));
+        assert_eq!(current.packet_id(Clientbound::KeepAlive), 0x23);
+
+        // 1.19.4 isn't supported until its reports are bundled
+        assert!(protocol_data(762).is_none());
+        assert!(!is_supported(762));
+        assert!(!is_supported(47));
+    }
+}
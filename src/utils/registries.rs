@@ -0,0 +1,224 @@
+//! Id mappings from the reports the vanilla data generator writes, bundled in
+//! `.etc/reports/<version>` for every version in [crate::utils::protocol].
+//!
+//! Regenerate them with `java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar
+//! --reports`, which writes `registries.json` and `blocks.json` (bundled compressed), and take
+//! `version.json` from the server jar.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+
+use serde::Deserialize;
+
+use crate::utils::constants::PROTOCOL_VERSION;
+use crate::utils::prelude::*;
+use crate::utils::protocol::protocol_data;
+
+/// The reports bundled for one version.
+pub(crate) struct BundledReports {
+    pub version: &'static str,
+    pub registries: &'static str,
+    /// bzip2 compressed.
+    pub blocks: &'static [u8],
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GameVersion {
+    pub name: String,
+    pub protocol_version: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryReport {
+    entries: HashMap<String, EntryReport>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntryReport {
+    protocol_id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockReport {
+    states: Vec<BlockState>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockState {
+    pub id: i32,
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+}
+
+/// One registry, e.g. `minecraft:item`.
+#[derive(Debug, Default)]
+pub struct Registry {
+    ids: HashMap<String, i32>,
+    names: HashMap<i32, String>,
+}
+
+impl Registry {
+    /// The id of an entry, with or without the `minecraft:` namespace.
+    pub fn id(&self, name: &str) -> Option<i32> {
+        self.ids.get(namespaced(name).as_ref()).copied()
+    }
+
+    pub fn name(&self, id: i32) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// Every bundled registry and block state.
+#[derive(Debug)]
+pub struct Registries {
+    pub version: GameVersion,
+    registries: HashMap<String, Registry>,
+    blocks: HashMap<String, Vec<BlockState>>,
+}
+
+impl Registries {
+    /// Parses the reports. Fails if they are for another protocol version than `protocol_version`.
+    pub fn parse(
+        protocol_version: i32,
+        version: &str,
+        registries: &str,
+        blocks: &str,
+    ) -> Result<Self> {
+        let version: GameVersion = serde_json::from_str(version).map_err(invalid_report)?;
+        if version.protocol_version != protocol_version {
+            return Err(Error::Generic(format!(
+                "Registry reports are for {} (protocol {}), expected protocol {}",
+                version.name, version.protocol_version, protocol_version
+            )));
+        }
+
+        let reports: HashMap<String, RegistryReport> =
+            serde_json::from_str(registries).map_err(invalid_report)?;
+        let registries = reports
+            .into_iter()
+            .map(|(name, report)| {
+                let mut registry = Registry::default();
+                for (entry, EntryReport { protocol_id }) in report.entries {
+                    registry.names.insert(protocol_id, entry.clone());
+                    registry.ids.insert(entry, protocol_id);
+                }
+                (name, registry)
+            })
+            .collect();
+
+        let blocks: HashMap<String, BlockReport> =
+            serde_json::from_str(blocks).map_err(invalid_report)?;
+        let blocks = blocks
+            .into_iter()
+            .map(|(name, report)| (name, report.states))
+            .collect();
+
+        Ok(Self {
+            version,
+            registries,
+            blocks,
+        })
+    }
+
+    pub(crate) fn bundled(protocol_version: i32, reports: &BundledReports) -> Result<Self> {
+        let mut blocks = String::new();
+        bzip2::read::BzDecoder::new(reports.blocks).read_to_string(&mut blocks)?;
+        Self::parse(protocol_version, reports.version, reports.registries, &blocks)
+    }
+
+    /// A registry by name, e.g. `"item"` or `"minecraft:sound_event"`.
+    pub fn registry(&self, name: &str) -> Option<&Registry> {
+        self.registries.get(namespaced(name).as_ref())
+    }
+
+    /// The id of an entry in a registry, e.g. `id("particle_type", "flame")`.
+    pub fn id(&self, registry: &str, name: &str) -> Option<i32> {
+        self.registry(registry)?.id(name)
+    }
+
+    pub fn block_states(&self, block: &str) -> Option<&[BlockState]> {
+        self.blocks.get(namespaced(block).as_ref()).map(Vec::as_slice)
+    }
+
+    /// The state a block is placed in without any properties given. Blocks the report doesn't
+    /// mark a default for use their first state.
+    pub fn default_block_state(&self, block: &str) -> Option<i32> {
+        let states = self.block_states(block)?;
+        states
+            .iter()
+            .find(|state| state.default)
+            .or(states.first())
+            .map(|state| state.id)
+    }
+
+    /// The state of a block with some of its properties set, the rest as in the default state.
+    pub fn block_state(&self, block: &str, properties: &[(&str, &str)]) -> Option<i32> {
+        let default = self.default_block_state(block)?;
+        let states = self.block_states(block)?;
+        let mut wanted = states.iter().find(|state| state.id == default)?.properties.clone();
+        for (key, value) in properties {
+            *wanted.get_mut(*key)? = value.to_string();
+        }
+        states
+            .iter()
+            .find(|state| state.properties == wanted)
+            .map(|state| state.id)
+    }
+}
+
+fn namespaced(name: &str) -> std::borrow::Cow<'_, str> {
+    match name.contains(':') {
+        true => name.into(),
+        false => format!("minecraft:{}", name).into(),
+    }
+}
+
+fn invalid_report(e: serde_json::Error) -> Error {
+    Error::Generic(format!("Invalid registry report: {}", e))
+}
+
+/// The registries of the version the server targets, see [PROTOCOL_VERSION]. Use
+/// [protocol_data] for the ones of a client's version.
+pub fn get_registries() -> &'static Registries {
+    &protocol_data(PROTOCOL_VERSION)
+        .expect("The targeted version is always supported")
+        .registries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_ids() {
+        let registries = get_registries();
+        assert_eq!(registries.version.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(registries.id("particle_type", "flame"), Some(28));
+        assert_eq!(
+            registries.registry("minecraft:particle_type").unwrap().name(38),
+            Some("minecraft:heart")
+        );
+        assert_eq!(registries.default_block_state("air"), Some(0));
+        assert_eq!(registries.default_block_state("minecraft:stone"), Some(1));
+        assert_eq!(registries.block_state("grass_block", &[("snowy", "false")]), Some(9));
+        assert_eq!(registries.block_state("grass_block", &[("facing", "up")]), None);
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let version = r#"{ "name": "1.8.9", "protocol_version": 47 }"#;
+        assert!(Registries::parse(PROTOCOL_VERSION, version, "{}", "{}").is_err());
+        let version = format!(r#"{{ "name": "x", "protocol_version": {} }}"#, PROTOCOL_VERSION);
+        assert!(Registries::parse(PROTOCOL_VERSION, &version, "{}", "{}").is_ok());
+    }
+}
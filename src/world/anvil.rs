@@ -0,0 +1,380 @@
+//! Reading chunks from the Anvil NBT that Minecraft stores in region files.
+//!
+//! The on-disk layout changes between versions, so chunks are mapped field by field here instead
+//! of deserializing straight into [Chunk].
+
+use std::collections::{BTreeMap, HashMap};
+
+use fastnbt::Value;
+
+use crate::utils::error::Error;
+use crate::world::chunk_format::{Biomes, BlockStates, Chunk, Heightmaps, Palette, Section};
+
+/// The data version of 1.18, the first release without the `Level` wrapper and with block states
+/// and biomes stored per section. Older chunks aren't supported.
+pub const MIN_DATA_VERSION: i32 = 2860;
+
+type Compound = HashMap<String, Value>;
+
+impl Chunk {
+    /// Builds a chunk from its Anvil NBT, read with the layout of data version `version`.
+    ///
+    /// Fields the server doesn't use are skipped, so chunks with extra data still load.
+    pub fn from_anvil_nbt(value: &Value, version: i32) -> Result<Chunk, Error> {
+        if version < MIN_DATA_VERSION {
+            return Err(Error::InvalidNbt(format!(
+                "chunk data version {} is older than 1.18 ({})",
+                version, MIN_DATA_VERSION
+            )));
+        }
+        let root = as_compound(value, "chunk")?;
+
+        let sections = match list(root, "sections")? {
+            Some(sections) => Some(
+                sections
+                    .iter()
+                    .map(section_from_nbt)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+        let heightmaps = match root.get("Heightmaps") {
+            Some(value) => {
+                let heightmaps = as_compound(value, "Heightmaps")?;
+                Some(Heightmaps {
+                    motion_blocking: long_array(heightmaps, "MOTION_BLOCKING")?,
+                    world_surface: long_array(heightmaps, "WORLD_SURFACE")?,
+                })
+            }
+            None => None,
+        };
+
+        Ok(Chunk {
+            dimension: None,
+            status: normalize_status(required(string(root, "Status")?, "Status")?),
+            data_version: version,
+            heightmaps,
+            is_light_on: int(root, "isLightOn")?.map(|v| v as i8),
+            inhabited_time: int(root, "InhabitedTime")?,
+            y_pos: required(int(root, "yPos")?, "yPos")? as i32,
+            x_pos: required(int(root, "xPos")?, "xPos")? as i32,
+            z_pos: required(int(root, "zPos")?, "zPos")? as i32,
+            structures: None,
+            last_update: int(root, "LastUpdate")?,
+            sections,
+        })
+    }
+}
+
+/// Reads the data version a chunk was saved with, to pass to [Chunk::from_anvil_nbt].
+pub fn data_version(value: &Value) -> Result<i32, Error> {
+    let root = as_compound(value, "chunk")?;
+    Ok(required(int(root, "DataVersion")?, "DataVersion")? as i32)
+}
+
+/// Since 1.20 statuses are namespaced, e.g. "minecraft:full" instead of "full".
+fn normalize_status(status: &str) -> String {
+    status
+        .strip_prefix("minecraft:")
+        .unwrap_or(status)
+        .to_string()
+}
+
+fn section_from_nbt(value: &Value) -> Result<Section, Error> {
+    let section = as_compound(value, "section")?;
+
+    let block_states = match section.get("block_states") {
+        Some(value) => {
+            let states = as_compound(value, "block_states")?;
+            let palette = match list(states, "palette")? {
+                Some(palette) => Some(
+                    palette
+                        .iter()
+                        .map(palette_from_nbt)
+                        .collect::<Result<Vec<_>, _>>()?,
+                ),
+                None => None,
+            };
+            Some(BlockStates {
+                non_air_blocks: None,
+                bits_per_block: None,
+                data: long_array(states, "data")?,
+                palette,
+                net_palette: None,
+            })
+        }
+        None => None,
+    };
+    let biomes = match section.get("biomes") {
+        Some(value) => {
+            let biomes = as_compound(value, "biomes")?;
+            let palette = required(list(biomes, "palette")?, "palette")?
+                .iter()
+                .map(|biome| match biome {
+                    Value::String(name) => Ok(name.clone()),
+                    other => Err(wrong_type("palette", other)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Some(Biomes { palette })
+        }
+        None => None,
+    };
+
+    Ok(Section {
+        block_states,
+        biomes,
+        y: required(int(section, "Y")?, "Y")? as i8,
+        block_light: byte_array(section, "BlockLight")?,
+        sky_light: byte_array(section, "SkyLight")?,
+    })
+}
+
+fn palette_from_nbt(value: &Value) -> Result<Palette, Error> {
+    let entry = as_compound(value, "palette entry")?;
+    let properties = match entry.get("Properties") {
+        Some(value) => Some(
+            as_compound(value, "Properties")?
+                .iter()
+                .map(|(key, value)| match value {
+                    Value::String(value) => Ok((key.clone(), value.clone())),
+                    other => Err(wrong_type(key, other)),
+                })
+                .collect::<Result<BTreeMap<_, _>, _>>()?,
+        ),
+        None => None,
+    };
+
+    Ok(Palette {
+        name: required(string(entry, "Name")?, "Name")?.to_string(),
+        properties,
+    })
+}
+
+fn wrong_type(key: &str, value: &Value) -> Error {
+    Error::InvalidNbt(format!("\"{}\" has the wrong type: {:?}", key, value))
+}
+
+fn required<T>(value: Option<T>, key: &str) -> Result<T, Error> {
+    value.ok_or_else(|| Error::InvalidNbt(format!("missing \"{}\"", key)))
+}
+
+fn as_compound<'a>(value: &'a Value, what: &str) -> Result<&'a Compound, Error> {
+    match value {
+        Value::Compound(compound) => Ok(compound),
+        other => Err(wrong_type(what, other)),
+    }
+}
+
+/// Any integer tag, since some fields changed width between versions.
+fn int(compound: &Compound, key: &str) -> Result<Option<i64>, Error> {
+    match compound.get(key) {
+        None => Ok(None),
+        Some(Value::Byte(v)) => Ok(Some(*v as i64)),
+        Some(Value::Short(v)) => Ok(Some(*v as i64)),
+        Some(Value::Int(v)) => Ok(Some(*v as i64)),
+        Some(Value::Long(v)) => Ok(Some(*v)),
+        Some(other) => Err(wrong_type(key, other)),
+    }
+}
+
+fn string<'a>(compound: &'a Compound, key: &str) -> Result<Option<&'a str>, Error> {
+    match compound.get(key) {
+        None => Ok(None),
+        Some(Value::String(v)) => Ok(Some(v.as_str())),
+        Some(other) => Err(wrong_type(key, other)),
+    }
+}
+
+fn list<'a>(compound: &'a Compound, key: &str) -> Result<Option<&'a [Value]>, Error> {
+    match compound.get(key) {
+        None => Ok(None),
+        Some(Value::List(v)) => Ok(Some(v.as_slice())),
+        Some(other) => Err(wrong_type(key, other)),
+    }
+}
+
+fn long_array(compound: &Compound, key: &str) -> Result<Option<Vec<i64>>, Error> {
+    match compound.get(key) {
+        None => Ok(None),
+        Some(Value::LongArray(v)) => Ok(Some(v.to_vec())),
+        Some(other) => Err(wrong_type(key, other)),
+    }
+}
+
+fn byte_array(compound: &Compound, key: &str) -> Result<Option<Vec<i8>>, Error> {
+    match compound.get(key) {
+        None => Ok(None),
+        Some(Value::ByteArray(v)) => Ok(Some(v.to_vec())),
+        Some(other) => Err(wrong_type(key, other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fastnbt::{ByteArray, LongArray};
+
+    use super::*;
+
+    fn compound<const N: usize>(entries: [(&str, Value); N]) -> Value {
+        Value::Compound(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    fn block(name: &str) -> Value {
+        compound([("Name", Value::String(name.to_string()))])
+    }
+
+    /// A chunk as 1.18.2 saves it, one stone section and one empty one.
+    fn fixture_1_18() -> Value {
+        compound([
+            ("DataVersion", Value::Int(2975)),
+            ("Status", Value::String("full".to_string())),
+            ("xPos", Value::Int(3)),
+            ("zPos", Value::Int(-2)),
+            ("yPos", Value::Int(-4)),
+            ("isLightOn", Value::Byte(1)),
+            ("InhabitedTime", Value::Long(120)),
+            ("LastUpdate", Value::Long(5000)),
+            (
+                "Heightmaps",
+                compound([("WORLD_SURFACE", Value::LongArray(LongArray::new(vec![7; 37])))]),
+            ),
+            (
+                "sections",
+                Value::List(vec![
+                    compound([
+                        ("Y", Value::Byte(-4)),
+                        (
+                            "block_states",
+                            compound([
+                                (
+                                    "palette",
+                                    Value::List(vec![
+                                        block("minecraft:stone"),
+                                        block("minecraft:bedrock"),
+                                    ]),
+                                ),
+                                ("data", Value::LongArray(LongArray::new(vec![0; 256]))),
+                            ]),
+                        ),
+                        (
+                            "biomes",
+                            compound([(
+                                "palette",
+                                Value::List(vec![Value::String("minecraft:plains".to_string())]),
+                            )]),
+                        ),
+                        ("SkyLight", Value::ByteArray(ByteArray::new(vec![0; 2048]))),
+                    ]),
+                    compound([
+                        ("Y", Value::Byte(0)),
+                        (
+                            "block_states",
+                            compound([("palette", Value::List(vec![block("minecraft:air")]))]),
+                        ),
+                    ]),
+                ]),
+            ),
+            ("structures", compound([])),
+        ])
+    }
+
+    /// A chunk as 1.20.1 saves it, with a namespaced status and data the server doesn't use.
+    fn fixture_1_20() -> Value {
+        let log = compound([
+            ("Name", Value::String("minecraft:oak_log".to_string())),
+            ("Properties", compound([("axis", Value::String("y".to_string()))])),
+        ]);
+        compound([
+            ("DataVersion", Value::Int(3465)),
+            ("Status", Value::String("minecraft:full".to_string())),
+            ("xPos", Value::Int(-1)),
+            ("zPos", Value::Int(0)),
+            ("yPos", Value::Int(-4)),
+            ("isLightOn", Value::Byte(1)),
+            ("blending_data", compound([("min_section", Value::Int(-4))])),
+            ("PostProcessing", Value::List(vec![])),
+            (
+                "sections",
+                Value::List(vec![compound([
+                    ("Y", Value::Byte(2)),
+                    (
+                        "block_states",
+                        compound([
+                            ("palette", Value::List(vec![block("minecraft:air"), log])),
+                            ("data", Value::LongArray(LongArray::new(vec![1; 256]))),
+                        ]),
+                    ),
+                    ("BlockLight", Value::ByteArray(ByteArray::new(vec![15; 2048]))),
+                ])]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_parse_1_18_chunk() {
+        let value = fixture_1_18();
+        let version = data_version(&value).unwrap();
+        let chunk = Chunk::from_anvil_nbt(&value, version).unwrap();
+
+        assert_eq!(chunk.data_version, 2975);
+        assert_eq!((chunk.x_pos, chunk.y_pos, chunk.z_pos), (3, -4, -2));
+        assert_eq!(chunk.status, "full");
+        assert_eq!(chunk.is_light_on, Some(1));
+        assert_eq!(chunk.inhabited_time, Some(120));
+        assert_eq!(chunk.last_update, Some(5000));
+        let heightmaps = chunk.heightmaps.unwrap();
+        assert_eq!(heightmaps.world_surface, Some(vec![7; 37]));
+        assert_eq!(heightmaps.motion_blocking, None);
+
+        let sections = chunk.sections.unwrap();
+        assert_eq!(sections.len(), 2);
+        let states = sections[0].block_states.as_ref().unwrap();
+        let palette = states.palette.as_ref().unwrap();
+        let names: Vec<_> = palette.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["minecraft:stone", "minecraft:bedrock"]);
+        assert_eq!(states.data.as_ref().unwrap().len(), 256);
+        assert_eq!(sections[0].biomes.as_ref().unwrap().palette, ["minecraft:plains"]);
+        assert_eq!(sections[0].sky_light.as_ref().unwrap().len(), 2048);
+        assert_eq!(sections[1].y, 0);
+        assert_eq!(sections[1].block_states.as_ref().unwrap().data, None);
+    }
+
+    #[test]
+    fn test_parse_1_20_chunk() {
+        let value = fixture_1_20();
+        let version = data_version(&value).unwrap();
+        let chunk = Chunk::from_anvil_nbt(&value, version).unwrap();
+
+        assert_eq!(chunk.data_version, 3465);
+        assert_eq!((chunk.x_pos, chunk.y_pos, chunk.z_pos), (-1, -4, 0));
+        assert_eq!(chunk.status, "full");
+        assert_eq!(chunk.heightmaps, None);
+
+        let sections = chunk.sections.unwrap();
+        assert_eq!(sections[0].y, 2);
+        assert_eq!(sections[0].block_light, Some(vec![15; 2048]));
+        let palette = sections[0].block_states.as_ref().unwrap().palette.clone().unwrap();
+        assert_eq!(palette[1].name, "minecraft:oak_log");
+        let axis = palette[1].properties.as_ref().unwrap().get("axis");
+        assert_eq!(axis.map(String::as_str), Some("y"));
+    }
+
+    #[test]
+    fn test_rejects_old_and_malformed_chunks() {
+        let value = fixture_1_18();
+        assert!(Chunk::from_anvil_nbt(&value, 2586).is_err());
+
+        let Value::Compound(mut root) = value else {
+            unreachable!()
+        };
+        root.insert("xPos".to_string(), Value::String("3".to_string()));
+        assert!(Chunk::from_anvil_nbt(&Value::Compound(root.clone()), 2975).is_err());
+        root.remove("xPos");
+        assert!(Chunk::from_anvil_nbt(&Value::Compound(root), 2975).is_err());
+    }
+}
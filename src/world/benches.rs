@@ -0,0 +1,56 @@
+//! Benchmarks for reading chunks during an import. Run them with:
+//! `cargo test --release region_reading_benchmarks -- --ignored --nocapture`
+
+use std::io::Cursor;
+
+use criterion::{black_box, Criterion};
+use fastanvil::Region;
+
+use crate::world::region::{AnvilRegion, RegionReader, DECODE_POOL};
+
+/// A full region, every chunk a few dozen KiB of NBT-like data once decompressed.
+fn full_region() -> Vec<u8> {
+    let mut region = Region::new(Cursor::new(Vec::new())).unwrap();
+    for x in 0..32 {
+        for z in 0..32 {
+            let data: Vec<u8> = (0..48 * 1024u32)
+                .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8 & 0x0F)
+                .collect();
+            region.write_chunk(x, z, &data).unwrap();
+        }
+    }
+    region.into_inner().unwrap().into_inner()
+}
+
+fn bench_region_reading(c: &mut Criterion) {
+    let bytes = full_region();
+
+    let allocations = DECODE_POOL.allocations();
+    c.bench_function("read a full anvil region", |b| {
+        b.iter(|| {
+            let mut region = AnvilRegion::new(Cursor::new(black_box(&bytes))).unwrap();
+            assert_eq!(region.read_all().unwrap().len(), 32 * 32);
+        })
+    });
+
+    // Without the pool, every chunk allocated a buffer for its compressed data and grew a fresh
+    // one while decompressing it
+    let mut region = AnvilRegion::new(Cursor::new(&bytes)).unwrap();
+    let before = DECODE_POOL.allocations();
+    region.read_all().unwrap();
+    println!(
+        "Scratch buffers allocated per chunk read: {:.4} ({} over all runs)",
+        (DECODE_POOL.allocations() - before) as f64 / (32.0 * 32.0),
+        DECODE_POOL.allocations() - allocations
+    );
+}
+
+#[test]
+#[ignore]
+fn region_reading_benchmarks() {
+    let mut criterion = Criterion::default().sample_size(10);
+
+    bench_region_reading(&mut criterion);
+
+    criterion.final_summary();
+}
@@ -0,0 +1,96 @@
+//! Blocks changed in chunks players may already have. Instead of resending the chunks, the changes
+//! are collected and sent once per tick by [crate::net::systems::block_changes::BlockChangeSystem],
+//! as one Update Section Blocks packet per changed section.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::net::packets::outgoing::update_section_blocks::UpdateSectionBlocks;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+use crate::world::chunk_format::Palette;
+use crate::world::conversions::block_state_id;
+
+/// The block state ids of changed blocks that haven't been sent yet, by chunk and world position.
+/// A block changed twice is only sent as it ended up.
+#[derive(Debug, Default)]
+pub struct PendingBlockChanges(Mutex<HashMap<(i32, i32), BTreeMap<(i32, i32, i32), i32>>>);
+
+impl PendingBlockChanges {
+    pub fn record(&self, x: i32, y: i32, z: i32, state_id: i32) {
+        self.0
+            .lock()
+            .unwrap()
+            .entry((x >> 4, z >> 4))
+            .or_default()
+            .insert((x, y, z), state_id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().is_empty()
+    }
+
+    /// Takes every pending change, as one packet per section along with the chunk it's in.
+    pub fn take(&self) -> Vec<((i32, i32), UpdateSectionBlocks)> {
+        let chunks = std::mem::take(&mut *self.0.lock().unwrap());
+        let mut packets = Vec::new();
+        for (chunk, blocks) in chunks {
+            let mut sections: BTreeMap<i32, Vec<((i32, i32, i32), i32)>> = BTreeMap::new();
+            for ((x, y, z), state_id) in blocks {
+                sections
+                    .entry(y >> 4)
+                    .or_default()
+                    .push(((x & 15, y & 15, z & 15), state_id));
+            }
+            for (section_y, blocks) in sections {
+                let section = (chunk.0, section_y, chunk.1);
+                packets.push((chunk, UpdateSectionBlocks::new(section, &blocks)));
+            }
+        }
+        packets
+    }
+}
+
+/// Changes a block in the default dimension, and queues the change to be sent to the players
+/// that have its chunk.
+pub async fn set_block(state: &GlobalState, x: i32, y: i32, z: i32, block: Palette) -> Result<()> {
+    let state_id = block_state_id(&block)
+        .ok_or_else(|| Error::Generic(format!("Unknown block: {}", block.name)))?;
+
+    let mut result = Ok(());
+    state
+        .database
+        .modify_chunk_default(x >> 4, z >> 4, |chunk| {
+            result = chunk.set_block(x, y, z, block);
+        })
+        .await?;
+    result?;
+
+    state.block_changes.record(x, y, z, state_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_are_grouped_by_section() {
+        let changes = PendingBlockChanges::default();
+        changes.record(1, 64, 1, 1);
+        changes.record(2, 64, 1, 1);
+        // Replaced by the later change
+        changes.record(1, 64, 1, 2);
+        changes.record(1, 80, 1, 1);
+        changes.record(-1, 64, 1, 1);
+
+        let mut packets = changes.take();
+        packets.sort_by_key(|(chunk, packet)| (*chunk, packet.section));
+        let sizes: Vec<_> = packets
+            .iter()
+            .map(|(chunk, packet)| (*chunk, packet.blocks.len()))
+            .collect();
+        assert_eq!(sizes, vec![((-1, 0), 1), ((0, 0), 2), ((0, 0), 1)]);
+        assert!(changes.is_empty());
+    }
+}
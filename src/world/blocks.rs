@@ -1,15 +1,142 @@
+use ferrumc_codec::network_types::varint::VarInt;
 use tracing::debug;
 
 use crate::state::GlobalState;
 use crate::utils::binary_utils::read_n_bits_u16;
 use crate::utils::error::Error;
+use crate::world::chunk_format::{BlockStates, Chunk, Palette, Section};
+use crate::world::conversions::block_state_id;
+
+const SECTION_VOLUME: usize = 16 * 16 * 16;
+
+/// Blocks the client doesn't count towards a section's block count.
+const AIR: [&str; 3] = ["minecraft:air", "minecraft:cave_air", "minecraft:void_air"];
+
+fn is_air(block: &Palette) -> bool {
+    AIR.contains(&block.name.as_str())
+}
+
+/// How many bits each palette index takes in a section's data, at least 4 like vanilla.
+fn bits_per_entry(palette_len: usize) -> usize {
+    (palette_len as f32).log2().ceil().max(4.0) as usize
+}
+
+/// Unpacks the palette indices of a section. Without data, every block is the first entry.
+fn unpack(block_states: &BlockStates) -> Vec<usize> {
+    let Some(data) = &block_states.data else {
+        return vec![0; SECTION_VOLUME];
+    };
+    let bits = bits_per_entry(block_states.palette.as_ref().map_or(1, Vec::len));
+    let per_long = 64 / bits;
+    (0..SECTION_VOLUME)
+        .map(|i| {
+            let long = data.get(i / per_long).copied().unwrap_or(0) as u64;
+            ((long >> (i % per_long * bits)) & ((1 << bits) - 1)) as usize
+        })
+        .collect()
+}
+
+/// Packs palette indices, entries never spanning two longs.
+fn pack(entries: &[usize], palette_len: usize) -> Vec<i64> {
+    let bits = bits_per_entry(palette_len);
+    let per_long = 64 / bits;
+    let mut data = vec![0i64; entries.len().div_ceil(per_long)];
+    for (i, entry) in entries.iter().enumerate() {
+        data[i / per_long] |= (*entry as i64) << (i % per_long * bits);
+    }
+    data
+}
+
+impl Section {
+    /// Counts the blocks in the section that aren't air, as the chunk data packet needs them.
+    ///
+    /// Sections without a palette are left alone.
+    pub fn recompute_block_count(&mut self) {
+        let Some(block_states) = self.block_states.as_mut() else {
+            return;
+        };
+        let Some(palette) = &block_states.palette else {
+            return;
+        };
+        let non_air = unpack(block_states)
+            .into_iter()
+            .filter(|entry| palette.get(*entry).is_some_and(|block| !is_air(block)))
+            .count();
+        block_states.non_air_blocks = Some(non_air as i16);
+    }
+}
+
+impl Chunk {
+    /// Recounts the non-air blocks of every section, see [Section::recompute_block_count].
+    pub fn recompute_block_counts(&mut self) {
+        for section in self.sections.iter_mut().flatten() {
+            section.recompute_block_count();
+        }
+    }
+
+    /// Sets the block at world coordinates `x`, `y`, `z`, which must be in this chunk, growing the
+    /// palette if needed. Works on the disk format, and keeps the block state ids of chunks already
+    /// converted with [Chunk::convert_to_net_mode] up to date.
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: Palette) -> Result<(), Error> {
+        let (chunk_x, chunk_z) = (self.x_pos, self.z_pos);
+        let section = self
+            .sections
+            .iter_mut()
+            .flatten()
+            .find(|section| section.y as i32 == y >> 4)
+            .ok_or_else(|| {
+                Error::InvalidChunk(chunk_x, chunk_z, format!("No section at y {}", y))
+            })?;
+        let block_states = section.block_states.get_or_insert_with(|| BlockStates {
+            non_air_blocks: None,
+            bits_per_block: None,
+            data: None,
+            palette: None,
+            net_palette: None,
+        });
+        let mut entries = unpack(block_states);
+        let palette = block_states.palette.get_or_insert_with(|| {
+            vec![Palette {
+                name: AIR[0].to_string(),
+                properties: None,
+            }]
+        });
+        let entry = match palette.iter().position(|existing| *existing == block) {
+            Some(entry) => entry,
+            None => {
+                if let Some(net_palette) = block_states.net_palette.as_mut() {
+                    let id = block_state_id(&block).ok_or_else(|| {
+                        Error::InvalidChunk(
+                            chunk_x,
+                            chunk_z,
+                            format!("Block {} not found in block mappings", block.name),
+                        )
+                    })?;
+                    net_palette.push(VarInt::from(id));
+                }
+                palette.push(block);
+                palette.len() - 1
+            }
+        };
+
+        let index = ((y & 15) * 256 + (z & 15) * 16 + (x & 15)) as usize;
+        entries[index] = entry;
+        block_states.data = Some(pack(&entries, palette.len()));
+        if block_states.bits_per_block.is_some() {
+            block_states.bits_per_block = Some(bits_per_entry(palette.len()) as i8);
+        }
+
+        section.recompute_block_count();
+        Ok(())
+    }
+}
 
 pub async fn read_block(
     state: GlobalState,
     x: i32,
     y: i32,
     z: i32,
-    dimension: String,
+    dimension: &str,
 ) -> Result<String, Error> {
     let (chunk_x, chunk_z) = (x / 16, z / 16);
     debug!("Getting chunk: {} {}", chunk_x, chunk_z);
@@ -115,9 +242,77 @@ mod tests {
     use tokio::net::TcpListener;
     use tracing::{info, warn};
 
+    use super::*;
+    use crate::database::tests::test_chunk;
     use crate::utils::setup_logger;
     use crate::world::blocks::read_block;
 
+    fn block(name: &str) -> Palette {
+        Palette {
+            name: format!("minecraft:{}", name),
+            properties: None,
+        }
+    }
+
+    #[test]
+    fn test_set_block_updates_block_count() {
+        let mut chunk = test_chunk(2, -1);
+        let mut section = Section {
+            block_states: None,
+            biomes: None,
+            y: 4,
+            block_light: None,
+            sky_light: None,
+        };
+        section.set_empty();
+        section.block_states.as_mut().unwrap().palette = Some(vec![block("air")]);
+        chunk.sections = Some(vec![section]);
+
+        chunk.set_block(35, 70, -9, block("stone")).unwrap();
+        let count = |chunk: &Chunk| {
+            chunk.sections.as_ref().unwrap()[0]
+                .block_states
+                .as_ref()
+                .unwrap()
+                .non_air_blocks
+        };
+        assert_eq!(count(&chunk), Some(1));
+        chunk.set_block(35, 71, -9, block("stone")).unwrap();
+        assert_eq!(count(&chunk), Some(2));
+
+        // Replacing a block doesn't change the count, removing it does
+        chunk.set_block(35, 70, -9, block("dirt")).unwrap();
+        assert_eq!(count(&chunk), Some(2));
+        chunk.set_block(35, 70, -9, block("cave_air")).unwrap();
+        assert_eq!(count(&chunk), Some(1));
+
+        let entries = unpack(chunk.sections.as_ref().unwrap()[0].block_states.as_ref().unwrap());
+        assert_eq!(entries.iter().filter(|entry| **entry != 0).count(), 2);
+        assert_eq!(entries[7 * 256 + 7 * 16 + 3], 1);
+    }
+
+    #[test]
+    fn test_wrong_counts_are_fixed() {
+        let mut chunk = crate::database::benches::representative_chunk(0, 0);
+        let expected: Vec<usize> = chunk
+            .sections
+            .iter()
+            .flatten()
+            .map(|section| unpack(section.block_states.as_ref().unwrap()))
+            // Air is the third palette entry
+            .map(|entries| entries.iter().filter(|entry| **entry != 2).count())
+            .collect();
+
+        chunk.recompute_block_counts();
+        let counts: Vec<usize> = chunk
+            .sections
+            .iter()
+            .flatten()
+            .map(|section| section.block_states.as_ref().unwrap().non_air_blocks.unwrap() as usize)
+            .collect();
+        assert_eq!(counts, expected);
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_reading() {
@@ -129,7 +324,7 @@ mod tests {
             .unwrap();
         info!(
             "{}",
-            read_block(state, -537, 69, 51, "overworld".to_string())
+            read_block(state, -537, 69, 51, "overworld")
                 .await
                 .unwrap()
         );
@@ -25,6 +25,11 @@ lazy_static! {
         ID2BLOCK.iter().map(|(k, v)| (v.clone(), *k)).collect();
 }
 
+/// The id `block` is sent to clients with, if it's a known block state.
+pub fn block_state_id(block: &Palette) -> Option<i32> {
+    BLOCK2ID.get(block).copied()
+}
+
 impl Section {
     pub fn set_empty(&mut self) {
         self.block_states = Some(BlockStates {
@@ -69,12 +74,6 @@ impl Chunk {
                     set_empty = true;
                 }
                 Some(block_states) => {
-                    // Set the max number of air blocks then decrease it every time we find and air
-                    // block in the data array. This will short-circuit if the section is malformed
-                    // but in that case we set it all to air anyway.
-                    let mut non_air_blocks = 4096i16;
-                    // This is just for readability
-                    let air_id = 0i32;
                     // If the palette is missing, we can't do anything and it's actually fucked
                     if block_states.palette.is_none() {
                         return Err(Error::InvalidChunk(
@@ -108,10 +107,6 @@ impl Chunk {
                                 let block_id = *BLOCK2ID
                                     .get(palette_entry)
                                     .expect("Block not found in block mappings");
-                                // If the block is air, decrease the non-air blocks count
-                                if block_id == air_id {
-                                    non_air_blocks -= 1;
-                                }
                                 checked_palette.push(VarInt::from(block_id));
                             } else {
                                 set_empty = true;
@@ -124,15 +119,6 @@ impl Chunk {
                             ));
                         }
                     }
-                    // This should never happen but if it does, we got some major problems to sort out
-                    if non_air_blocks < 0 {
-                        return Err(Error::InvalidChunk(
-                            self.x_pos,
-                            self.z_pos,
-                            "Too many air blocks. How the crispy kentucky fried fuck did this happen???".to_string(),
-                        ));
-                    }
-                    block_states.non_air_blocks = Some(non_air_blocks);
                 }
             }
             if set_empty {
@@ -140,6 +126,7 @@ impl Chunk {
                 section.set_empty();
             }
         }
+        self.recompute_block_counts();
 
         Ok(())
     }
@@ -0,0 +1,74 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::utils::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum Difficulty {
+    Peaceful = 0,
+    Easy = 1,
+    #[default]
+    Normal = 2,
+    Hard = 3,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Peaceful,
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+    ];
+
+    /// The name used in the config and in commands.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Peaceful => "peaceful",
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    fn from_id(id: u8) -> Self {
+        Self::ALL.get(id as usize).copied().unwrap_or_default()
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.as_str() == s)
+            .ok_or_else(|| Error::Generic(format!("Unknown difficulty: {}", s)))
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The difficulty the server is running at. Starts out as the configured one, and can be changed
+/// with `/difficulty`.
+#[derive(Debug)]
+pub struct CurrentDifficulty(AtomicU8);
+
+impl CurrentDifficulty {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self(AtomicU8::new(difficulty as u8))
+    }
+
+    pub fn get(&self) -> Difficulty {
+        Difficulty::from_id(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, difficulty: Difficulty) {
+        self.0.store(difficulty as u8, Ordering::Relaxed);
+    }
+}
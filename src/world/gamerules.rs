@@ -0,0 +1,151 @@
+//! Gamerules, the switches like `doDaylightCycle` that change how the world behaves. They start
+//! out at vanilla's defaults and are changed with `/gamerule`, which stores them in the database.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::RwLock;
+
+use tracing::warn;
+
+use crate::database::Database;
+use crate::utils::prelude::*;
+
+/// Whether the time of day advances, see [crate::world::time].
+pub const DO_DAYLIGHT_CYCLE: &str = "doDaylightCycle";
+/// Whether the weather changes on its own, see [crate::world::weather].
+pub const DO_WEATHER_CYCLE: &str = "doWeatherCycle";
+/// Whether players are told how they died.
+pub const SHOW_DEATH_MESSAGES: &str = "showDeathMessages";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+impl GameRuleValue {
+    /// Parses `value` as the same type as this one.
+    fn parse_like(&self, value: &str) -> Option<GameRuleValue> {
+        match self {
+            GameRuleValue::Bool(_) => value.parse().ok().map(GameRuleValue::Bool),
+            GameRuleValue::Int(_) => value.parse().ok().map(GameRuleValue::Int),
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            GameRuleValue::Bool(_) => "true or false",
+            GameRuleValue::Int(_) => "a whole number",
+        }
+    }
+}
+
+impl Display for GameRuleValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameRuleValue::Bool(value) => value.fmt(f),
+            GameRuleValue::Int(value) => value.fmt(f),
+        }
+    }
+}
+
+/// Every known gamerule with its default, by name.
+pub const DEFAULTS: &[(&str, GameRuleValue)] = &[
+    (DO_DAYLIGHT_CYCLE, GameRuleValue::Bool(true)),
+    ("doImmediateRespawn", GameRuleValue::Bool(false)),
+    ("doMobSpawning", GameRuleValue::Bool(true)),
+    (DO_WEATHER_CYCLE, GameRuleValue::Bool(true)),
+    ("fallDamage", GameRuleValue::Bool(true)),
+    ("keepInventory", GameRuleValue::Bool(false)),
+    ("naturalRegeneration", GameRuleValue::Bool(true)),
+    ("randomTickSpeed", GameRuleValue::Int(3)),
+    (SHOW_DEATH_MESSAGES, GameRuleValue::Bool(true)),
+    ("spawnRadius", GameRuleValue::Int(10)),
+];
+
+/// The current value of every gamerule.
+#[derive(Debug)]
+pub struct GameRules(RwLock<BTreeMap<&'static str, GameRuleValue>>);
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self(RwLock::new(DEFAULTS.iter().copied().collect()))
+    }
+}
+
+impl GameRules {
+    /// The defaults, with the values stored in `database` on top.
+    pub async fn load(database: &Database) -> Result<Self> {
+        let rules = Self::default();
+        for (name, value) in database.stored_gamerules().await? {
+            match parse(&name, &value) {
+                Ok((name, value)) => {
+                    rules.0.write().unwrap().insert(name, value);
+                }
+                Err(e) => warn!("Ignoring stored gamerule {}: {}", name, e),
+            }
+        }
+        Ok(rules)
+    }
+
+    pub fn get(&self, name: &str) -> Option<GameRuleValue> {
+        self.0.read().unwrap().get(name).copied()
+    }
+
+    /// The value of a true or false rule. Unknown rules are false.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.get(name) == Some(GameRuleValue::Bool(true))
+    }
+
+    /// Changes a rule to `value`, parsed as the rule's type, and stores it in `database`.
+    pub async fn set(&self, database: &Database, name: &str, value: &str) -> Result<GameRuleValue> {
+        let (name, value) = parse(name, value)?;
+        database.store_gamerule(name, &value.to_string()).await?;
+        self.0.write().unwrap().insert(name, value);
+        Ok(value)
+    }
+}
+
+/// Checks that `name` is a known rule and `value` has its type.
+fn parse(name: &str, value: &str) -> Result<(&'static str, GameRuleValue)> {
+    let (name, default) = DEFAULTS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .ok_or_else(|| Error::InvalidArgument(format!("Unknown gamerule: {}", name)))?;
+    let value = default.parse_like(value).ok_or_else(|| {
+        Error::InvalidArgument(format!(
+            "{} must be {}, got \"{}\"",
+            name,
+            default.type_name(),
+            value
+        ))
+    })?;
+    Ok((name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::temp_config;
+
+    #[tokio::test]
+    async fn test_rules_are_validated_and_stored() {
+        let database = Database::open(&temp_config(), "world").await.unwrap();
+        let rules = GameRules::load(&database).await.unwrap();
+        assert!(rules.is_enabled(DO_DAYLIGHT_CYCLE));
+        assert_eq!(rules.get("randomTickSpeed"), Some(GameRuleValue::Int(3)));
+
+        assert!(rules.set(&database, "noSuchRule", "true").await.is_err());
+        assert!(rules.set(&database, DO_DAYLIGHT_CYCLE, "3").await.is_err());
+        assert!(rules.set(&database, "randomTickSpeed", "fast").await.is_err());
+        assert!(rules.is_enabled(DO_DAYLIGHT_CYCLE));
+
+        rules.set(&database, DO_DAYLIGHT_CYCLE, "false").await.unwrap();
+        rules.set(&database, "randomTickSpeed", "10").await.unwrap();
+        assert!(!rules.is_enabled(DO_DAYLIGHT_CYCLE));
+
+        let reloaded = GameRules::load(&database).await.unwrap();
+        assert!(!reloaded.is_enabled(DO_DAYLIGHT_CYCLE));
+        assert_eq!(reloaded.get("randomTickSpeed"), Some(GameRuleValue::Int(10)));
+    }
+}
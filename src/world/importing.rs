@@ -1,16 +1,14 @@
-use crate::database::encoding::ZstdCodec;
+use crate::database::encoding::{Codec, Compression};
 use crate::state::GlobalState;
 use crate::utils::hash::hash;
 use crate::utils::prelude::*;
+use crate::world::anvil;
 use crate::world::chunk_format::Chunk;
-use fastanvil::{ChunkData, Region};
+use crate::utils::config::get_global_config;
+use crate::world::region::RegionFormat;
 use indicatif::{ProgressBar, ProgressStyle};
-use nbt_lib::NBTDeserializeBytes;
-use rayon::prelude::*;
 use std::env;
-use std::fs::File;
-use std::io::Cursor;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
@@ -66,13 +64,14 @@ fn format_duration(duration: std::time::Duration) -> String {
     }
 }
 
-async fn get_total_chunks(dir: &PathBuf) -> Result<usize> {
+/// Count the chunks to import from the region headers alone, without reading any chunk data.
+async fn get_total_chunks(dir: &PathBuf, format: RegionFormat) -> Result<usize> {
     let files = std::fs::read_dir(dir)?;
-    let regions: Vec<Region<File>> = files
+    let total = files
         .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().is_file() && entry.path().extension() == Some("mca".as_ref()))
-        .filter_map(|entry| match File::open(entry.path()) {
-            Ok(file) => Region::from_stream(file).ok(),
+        .filter(|entry| is_region_file(&entry.path(), format))
+        .filter_map(|entry| match format.count_chunks(entry.path()) {
+            Ok(count) => Some(count),
             Err(_) => {
                 warn!(
                     "(Skipped) Could not read region file: {}",
@@ -81,20 +80,28 @@ async fn get_total_chunks(dir: &PathBuf) -> Result<usize> {
                 None
             }
         })
-        .collect();
+        .sum();
 
-    Ok(regions
-        .into_par_iter()
-        .map(|mut region| region.iter().count())
-        .sum())
+    Ok(total)
+}
+
+fn is_region_file(path: &Path, format: RegionFormat) -> bool {
+    path.is_file() && path.extension() == Some(format.extension().as_ref())
+}
+
+fn read_anvil_chunk(data: &[u8]) -> Result<Chunk> {
+    let value: fastnbt::Value =
+        fastnbt::from_bytes(data).map_err(|e| Error::InvalidNbt(e.to_string()))?;
+    Chunk::from_anvil_nbt(&value, anvil::data_version(&value)?)
 }
 
 async fn process_chunk(
     chunk_data: Vec<u8>,
     file_name: &str,
     bar: Arc<ProgressBar>,
+    compression: Compression,
 ) -> Result<SerializedChunk> {
-    let mut chunk = Chunk::read_from_bytes(&mut Cursor::new(chunk_data)).map_err(|e| {
+    let mut chunk = read_anvil_chunk(&chunk_data).map_err(|e| {
         bar.abandon_with_message(format!("Chunk {} failed to import", file_name));
         Error::Generic(format!("Could not read chunk {} {}", e, file_name))
     })?;
@@ -110,7 +117,7 @@ async fn process_chunk(
         ))
     })?;
 
-    chunk.dimension = Some("overworld".to_string());
+    chunk.dimension = Some(get_global_config().default_dimension.clone());
 
     let hash = hash((
         chunk
@@ -120,7 +127,7 @@ async fn process_chunk(
         chunk.x_pos,
         chunk.z_pos,
     ));
-    let chunk_data = ZstdCodec::compress_data(chunk)
+    let chunk_data = Codec::compress_data(chunk, compression)
         .await
         .expect("Failed to compress chunk");
 
@@ -129,17 +136,19 @@ async fn process_chunk(
 
 //noinspection RsBorrowChecker
 pub async fn import_regions(state: GlobalState) -> Result<()> {
-    let dir = get_import_directory()?;
+    let dir = get_global_config().paths.import()?;
     debug!("Starting import from: {}", dir.display());
 
     let start = std::time::Instant::now();
     info!("Analyzing world data... (this won't take long)");
 
-    let total_chunks = get_total_chunks(&dir).await?;
+    let format: RegionFormat = get_global_config().region_format.parse()?;
+    let total_chunks = get_total_chunks(&dir, format).await?;
     info!("Preparing to import {} chunks", total_chunks);
     info!("This process may take a while for large worlds. Please be patient.");
 
     let batch_size = get_batch_size() as usize;
+    let compression = state.database.compression();
     let bar = Arc::new(create_progress_bar(total_chunks));
 
     let mut region_files = tokio::fs::read_dir(dir)
@@ -147,25 +156,27 @@ pub async fn import_regions(state: GlobalState) -> Result<()> {
         .map_err(|_| Error::Generic("Could not read the imports directory".to_string()))?;
 
     while let Some(dir_file) = region_files.next_entry().await? {
+        if !is_region_file(&dir_file.path(), format) {
+            continue;
+        }
         let file_name = dir_file.file_name();
         let file_name = file_name.to_str().unwrap_or("unknown file");
-        let file = File::open(dir_file.path())?;
-        let mut region = Region::from_stream(file)?;
+        let mut region = format.open(dir_file.path())?;
 
-        let mut chunks: Vec<ChunkData> = region.iter().filter_map(|chunk| chunk.ok()).collect();
+        let mut chunks = region.read_all()?;
         while !chunks.is_empty() {
-            let chunk_batch: Vec<ChunkData> = chunks
+            let chunk_batch: Vec<Vec<u8>> = chunks
                 .drain(..std::cmp::min(batch_size, chunks.len()))
                 .collect();
 
             let processed_chunks_futures: Vec<_> = chunk_batch
                 .into_iter()
-                .map(|chunk| {
-                    let data = chunk.data.clone();
+                .map(|data| {
                     let bar_clone = Arc::clone(&bar);
                     let file_name = file_name.to_string();
                     tokio::spawn(async move {
-                        match process_chunk(data, &file_name, Arc::clone(&bar_clone)).await {
+                        let bar = Arc::clone(&bar_clone);
+                        match process_chunk(data, &file_name, bar, compression).await {
                             Ok(processed) => {
                                 bar_clone.inc(1);
                                 Some(processed)
@@ -194,17 +205,6 @@ pub async fn import_regions(state: GlobalState) -> Result<()> {
     Ok(())
 }
 
-fn get_import_directory() -> Result<PathBuf> {
-    if let Ok(root) = env::var("FERRUMC_ROOT") {
-        Ok(PathBuf::from(root).join("import"))
-    } else {
-        env::current_exe()?
-            .parent()
-            .ok_or_else(|| Error::Generic("Failed to get exe directory".to_string()))
-            .map(|path| path.join("import"))
-    }
-}
-
 fn create_progress_bar(total_chunks: usize) -> ProgressBar {
     let bar = ProgressBar::new(total_chunks as u64);
     bar.set_style(
@@ -262,7 +262,7 @@ mod test {
 
         let chunk = state
             .database
-            .get_chunk(0, 0, "overworld".to_string())
+            .get_chunk(0, 0, "overworld")
             .await?
             .unwrap();
 
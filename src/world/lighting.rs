@@ -0,0 +1,260 @@
+//! Sky and block light for a chunk, computed from its blocks.
+//!
+//! Light only spreads within the chunk, light coming in from neighbouring chunks isn't taken into
+//! account. Enabled with `compute_light` in the config.
+
+use std::collections::VecDeque;
+
+use crate::utils::prelude::*;
+use crate::world::chunk_format::{Chunk, Palette};
+
+const MAX_LIGHT: u8 = 15;
+const SECTION_VOLUME: usize = 16 * 16 * 16;
+
+/// How much light a block takes away passing through it, and how much it gives off.
+fn light_properties(block: &Palette) -> (u8, u8) {
+    let name = block.name.strip_prefix("minecraft:").unwrap_or(&block.name);
+    let lit = block
+        .properties
+        .as_ref()
+        .and_then(|properties| properties.get("lit"))
+        .map(String::as_str)
+        != Some("false");
+
+    let emission = match name {
+        "glowstone" | "sea_lantern" | "jack_o_lantern" | "lava" | "fire" | "beacon"
+        | "shroomlight" | "lantern" | "conduit" | "end_gateway" | "end_portal"
+        | "ochre_froglight" | "verdant_froglight" | "pearlescent_froglight" => MAX_LIGHT,
+        "campfire" | "redstone_lamp" if lit => MAX_LIGHT,
+        "torch" | "wall_torch" | "end_rod" => 14,
+        "soul_torch" | "soul_wall_torch" | "soul_lantern" | "soul_fire" => 10,
+        "soul_campfire" if lit => 10,
+        "redstone_torch" | "redstone_wall_torch" if lit => 7,
+        "magma_block" => 3,
+        _ => 0,
+    };
+
+    let opacity = match name {
+        "air" | "cave_air" | "void_air" | "glass" | "glass_pane" | "lantern" | "soul_lantern"
+        | "end_rod" | "fire" | "soul_fire" | "ladder" | "lever" | "redstone_wire" | "grass"
+        | "tall_grass" | "fern" | "large_fern" | "dead_bush" | "snow" | "vine" | "barrier" => 0,
+        "water" | "ice" | "frosted_ice" | "cobweb" => 1,
+        _ if name.ends_with("torch")
+            || name.ends_with("_glass")
+            || name.ends_with("_glass_pane")
+            || name.ends_with("rail")
+            || name.ends_with("_sign")
+            || name.ends_with("_button")
+            || name.ends_with("_pressure_plate")
+            || name.ends_with("_carpet")
+            || name.ends_with("_sapling")
+            || name.ends_with("_flower")
+            || name.ends_with("_tulip") =>
+        {
+            0
+        }
+        _ if name.ends_with("_leaves") => 1,
+        _ => MAX_LIGHT,
+    };
+    (opacity, emission)
+}
+
+/// The blocks of a chunk, as a column of sections from the lowest one up.
+struct Blocks {
+    opacity: Vec<u8>,
+    emission: Vec<u8>,
+    height: usize,
+}
+
+impl Blocks {
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        (y * 16 + z) * 16 + x
+    }
+
+    fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, z, y) = (index % 16, index / 16 % 16, index / 256);
+        [
+            (x > 0).then(|| index - 1),
+            (x < 15).then(|| index + 1),
+            (z > 0).then(|| index - 16),
+            (z < 15).then(|| index + 16),
+            (y > 0).then(|| index - 256),
+            (y + 1 < self.height).then(|| index + 256),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Spreads light out from `queue`, losing at least one level per block.
+    fn flood(&self, light: &mut [u8], mut queue: VecDeque<usize>) {
+        while let Some(index) = queue.pop_front() {
+            let level = light[index];
+            for neighbour in self.neighbours(index) {
+                let spread = level.saturating_sub(self.opacity[neighbour].max(1));
+                if spread > light[neighbour] {
+                    light[neighbour] = spread;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+}
+
+/// Reads the blocks of every section. Sections without data are one block throughout.
+fn read_blocks(chunk: &Chunk, lowest: i8, height: usize) -> Result<Blocks> {
+    let mut blocks = Blocks {
+        opacity: vec![0; height * 16 * 16],
+        emission: vec![0; height * 16 * 16],
+        height,
+    };
+
+    for section in chunk.sections.iter().flatten() {
+        let Some(block_states) = &section.block_states else {
+            continue;
+        };
+        let Some(palette) = block_states.palette.as_ref().filter(|p| !p.is_empty()) else {
+            continue;
+        };
+        let properties: Vec<(u8, u8)> = palette.iter().map(light_properties).collect();
+        let offset = (section.y - lowest) as usize * SECTION_VOLUME;
+
+        let bits = (palette.len() as f32).log2().ceil().max(4.0) as usize;
+        let per_long = 64 / bits;
+        for i in 0..SECTION_VOLUME {
+            let entry = match &block_states.data {
+                Some(data) => {
+                    let long = *data.get(i / per_long).ok_or_else(|| {
+                        Error::InvalidChunk(chunk.x_pos, chunk.z_pos, "Short block data".into())
+                    })? as u64;
+                    ((long >> (i % per_long * bits)) & ((1 << bits) - 1)) as usize
+                }
+                None => 0,
+            };
+            let (opacity, emission) = properties.get(entry).copied().unwrap_or((MAX_LIGHT, 0));
+            blocks.opacity[offset + i] = opacity;
+            blocks.emission[offset + i] = emission;
+        }
+    }
+    Ok(blocks)
+}
+
+/// Computes the sky and block light of every section of a chunk, replacing what it had.
+pub fn compute_light(chunk: &mut Chunk) -> Result<()> {
+    let Some(sections) = &chunk.sections else {
+        return Err(Error::InvalidChunk(
+            chunk.x_pos,
+            chunk.z_pos,
+            "Chunk is missing sections".to_string(),
+        ));
+    };
+    let (Some(lowest), Some(highest)) = (
+        sections.iter().map(|s| s.y).min(),
+        sections.iter().map(|s| s.y).max(),
+    ) else {
+        return Ok(());
+    };
+    let height = (highest - lowest + 1) as usize * 16;
+    let blocks = read_blocks(chunk, lowest, height)?;
+
+    // Sky light comes straight down every column until something stops it
+    let mut sky_light = vec![0u8; blocks.opacity.len()];
+    let mut queue = VecDeque::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in (0..height).rev() {
+                let index = Blocks::index(x, y, z);
+                if blocks.opacity[index] > 0 {
+                    break;
+                }
+                sky_light[index] = MAX_LIGHT;
+                queue.push_back(index);
+            }
+        }
+    }
+    blocks.flood(&mut sky_light, queue);
+
+    let mut block_light = blocks.emission.clone();
+    let queue = (0..block_light.len()).filter(|i| block_light[*i] > 0).collect();
+    blocks.flood(&mut block_light, queue);
+
+    for section in chunk.sections.iter_mut().flatten() {
+        let offset = (section.y - lowest) as usize * SECTION_VOLUME;
+        let range = offset..offset + SECTION_VOLUME;
+        section.sky_light = Some(to_nibbles(&sky_light[range.clone()]));
+        section.block_light = Some(to_nibbles(&block_light[range]));
+    }
+    Ok(())
+}
+
+/// Packs light levels two to a byte, the first in the low half.
+fn to_nibbles(levels: &[u8]) -> Vec<i8> {
+    levels
+        .chunks(2)
+        .map(|pair| (pair[0] | (pair[1] << 4)) as i8)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::test_chunk;
+    use crate::world::chunk_format::{BlockStates, Section};
+
+    fn block(name: &str) -> Palette {
+        Palette {
+            name: format!("minecraft:{}", name),
+            properties: None,
+        }
+    }
+
+    /// A section of air with stone wherever `is_stone` says.
+    fn section(y: i8, is_stone: impl Fn(usize, usize, usize) -> bool) -> Section {
+        let mut data = vec![0i64; SECTION_VOLUME / 16];
+        for i in 0..SECTION_VOLUME {
+            if is_stone(i % 16, i / 256, i / 16 % 16) {
+                data[i / 16] |= 1 << (i % 16 * 4);
+            }
+        }
+        Section {
+            block_states: Some(BlockStates {
+                non_air_blocks: None,
+                bits_per_block: None,
+                data: Some(data),
+                palette: Some(vec![block("air"), block("stone")]),
+                net_palette: None,
+            }),
+            biomes: None,
+            y,
+            block_light: None,
+            sky_light: None,
+        }
+    }
+
+    fn level(section: &Section, x: usize, y: usize, z: usize) -> u8 {
+        let index = Blocks::index(x, y, z);
+        let byte = section.sky_light.as_ref().unwrap()[index / 2] as u8;
+        (byte >> (index % 2 * 4)) & 0xF
+    }
+
+    #[test]
+    fn test_sky_light_falls_off_under_a_roof() {
+        let mut chunk = test_chunk(0, 0);
+        // A stone roof at the top of the lower section, with one hole at x = z = 0
+        let roof = section(0, |x, y, z| y == 15 && (x, z) != (0, 0));
+        chunk.sections = Some(vec![roof, section(1, |_, _, _| false)]);
+
+        compute_light(&mut chunk).unwrap();
+
+        let sections = chunk.sections.as_ref().unwrap();
+        assert_eq!(level(&sections[1], 7, 0, 7), 15);
+        let lower = &sections[0];
+        // Straight down the hole the sky is fully visible
+        assert_eq!(level(lower, 0, 15, 0), 15);
+        assert_eq!(level(lower, 0, 0, 0), 15);
+        // Under the roof it falls off by one per block
+        assert_eq!(level(lower, 1, 14, 0), 14);
+        assert_eq!(level(lower, 3, 14, 0), 12);
+        assert_eq!(level(lower, 3, 14, 4), 8);
+        assert_eq!(level(lower, 5, 15, 5), 0);
+    }
+}
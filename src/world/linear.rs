@@ -0,0 +1,195 @@
+//! Reading region files in the Linear format (`.linear`).
+//!
+//! Instead of padding every chunk out to 4KiB sectors like Anvil, a Linear file is a single zstd
+//! frame holding all the chunks of a region back to back:
+//!
+//! - A 32 byte header: signature (u64), version (u8), newest timestamp (i64), compression level
+//!   (i8), chunk count (i16), compressed length (i32) and 8 reserved bytes.
+//! - The zstd compressed region: a table of 1024 `(size: i32, timestamp: i32)` entries, followed
+//!   by the uncompressed NBT of each chunk with a non-zero size, in table order.
+//! - The signature again, to detect truncated files.
+//!
+//! All numbers are big endian.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::utils::prelude::*;
+use crate::world::region::{RegionReader, REGION_WIDTH};
+
+/// The magic number at the start and end of every Linear file.
+pub const LINEAR_SIGNATURE: u64 = 0xc3ff13183cca9d9a;
+/// The only version of the format supported so far.
+pub const LINEAR_VERSION: u8 = 1;
+
+const HEADER_SIZE: usize = 32;
+const CHUNK_COUNT: usize = (REGION_WIDTH * REGION_WIDTH) as usize;
+
+/// A fully decompressed Linear region.
+pub struct LinearRegion {
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+/// The fields of the header needed to read the rest of the file.
+struct LinearHeader {
+    chunk_count: i16,
+    compressed_length: usize,
+}
+
+impl LinearHeader {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut header = [0; HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+
+        let signature = u64::from_be_bytes(header[0..8].try_into().unwrap());
+        if signature != LINEAR_SIGNATURE {
+            return Err(Error::Generic("Not a Linear region file".to_string()));
+        }
+        let version = header[8];
+        if version != LINEAR_VERSION {
+            return Err(Error::Generic(format!(
+                "Unsupported Linear region version {}, expected {}",
+                version, LINEAR_VERSION
+            )));
+        }
+
+        let chunk_count = i16::from_be_bytes(header[18..20].try_into().unwrap());
+        let compressed_length = i32::from_be_bytes(header[20..24].try_into().unwrap());
+
+        Ok(Self {
+            chunk_count,
+            compressed_length: compressed_length.max(0) as usize,
+        })
+    }
+}
+
+impl LinearRegion {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let header = LinearHeader::read(reader)?;
+
+        let mut compressed = vec![0; header.compressed_length];
+        reader.read_exact(&mut compressed)?;
+
+        let mut footer = [0; 8];
+        reader.read_exact(&mut footer)?;
+        if u64::from_be_bytes(footer) != LINEAR_SIGNATURE {
+            return Err(Error::Generic(
+                "Linear region file is truncated or corrupted".to_string(),
+            ));
+        }
+
+        let data = zstd::decode_all(compressed.as_slice()).map_err(Error::CompressionError)?;
+        if data.len() < CHUNK_COUNT * 8 {
+            return Err(Error::Generic(
+                "Linear region is too short for its chunk table".to_string(),
+            ));
+        }
+
+        let (table, mut body) = data.split_at(CHUNK_COUNT * 8);
+        let mut chunks = Vec::with_capacity(CHUNK_COUNT);
+        for entry in table.chunks_exact(8) {
+            let size = i32::from_be_bytes(entry[0..4].try_into().unwrap()).max(0) as usize;
+            if size == 0 {
+                chunks.push(None);
+                continue;
+            }
+            if body.len() < size {
+                return Err(Error::Generic(
+                    "Linear region chunk runs past the end of the file".to_string(),
+                ));
+            }
+            let (chunk, rest) = body.split_at(size);
+            chunks.push(Some(chunk.to_vec()));
+            body = rest;
+        }
+
+        Ok(Self { chunks })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read(&mut File::open(path)?)
+    }
+
+    /// How many chunks the file says it holds, reading only its header.
+    pub fn count_chunks(path: impl AsRef<Path>) -> Result<usize> {
+        let header = LinearHeader::read(&mut File::open(path)?)?;
+        Ok(header.chunk_count.max(0) as usize)
+    }
+}
+
+impl RegionReader for LinearRegion {
+    fn read_chunk(&mut self, x: i32, z: i32) -> Result<Option<Vec<u8>>> {
+        let index = (x & (REGION_WIDTH - 1)) + (z & (REGION_WIDTH - 1)) * REGION_WIDTH;
+        Ok(self.chunks[index as usize].clone())
+    }
+
+    fn read_all(&mut self) -> Result<Vec<Vec<u8>>> {
+        Ok(self.chunks.iter().flatten().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Builds a Linear file holding `chunks`, given as `(x, z, data)` relative to the region.
+    pub(crate) fn linear_fixture(chunks: &[(i32, i32, &[u8])]) -> Vec<u8> {
+        let mut sizes = vec![0i32; CHUNK_COUNT];
+        let mut datas: Vec<&[u8]> = vec![&[]; CHUNK_COUNT];
+        for (x, z, data) in chunks {
+            let index = (x + z * REGION_WIDTH) as usize;
+            sizes[index] = data.len() as i32;
+            datas[index] = data;
+        }
+
+        let mut region = Vec::new();
+        for size in &sizes {
+            region.extend_from_slice(&size.to_be_bytes());
+            region.extend_from_slice(&0i32.to_be_bytes());
+        }
+        for data in datas {
+            region.extend_from_slice(data);
+        }
+        let compressed = zstd::encode_all(region.as_slice(), 1).unwrap();
+
+        let mut file = Vec::new();
+        file.extend_from_slice(&LINEAR_SIGNATURE.to_be_bytes());
+        file.push(LINEAR_VERSION);
+        file.extend_from_slice(&0i64.to_be_bytes());
+        file.push(1);
+        file.extend_from_slice(&(chunks.len() as i16).to_be_bytes());
+        file.extend_from_slice(&(compressed.len() as i32).to_be_bytes());
+        file.extend_from_slice(&0i64.to_be_bytes());
+        file.extend_from_slice(&compressed);
+        file.extend_from_slice(&LINEAR_SIGNATURE.to_be_bytes());
+        file
+    }
+
+    #[test]
+    fn test_read_chunk() {
+        let bytes = linear_fixture(&[(0, 0, b"first chunk"), (3, 30, b"second")]);
+        let mut region = LinearRegion::read(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(region.read_chunk(0, 0).unwrap(), Some(b"first chunk".to_vec()));
+        assert_eq!(region.read_chunk(3, 30).unwrap(), Some(b"second".to_vec()));
+        // Absolute coordinates work too
+        assert_eq!(region.read_chunk(-29, 62).unwrap(), Some(b"second".to_vec()));
+        assert_eq!(region.read_chunk(1, 0).unwrap(), None);
+        assert_eq!(region.read_all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_rejects_truncated_file() {
+        let mut bytes = linear_fixture(&[(0, 0, b"chunk")]);
+        bytes.truncate(bytes.len() - 4);
+        assert!(LinearRegion::read(&mut Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_anvil_file() {
+        assert!(LinearRegion::read(&mut Cursor::new(vec![0; 4096])).is_err());
+    }
+}
@@ -1,7 +1,21 @@
+#[cfg(test)]
+mod benches;
+pub mod anvil;
+pub mod block_changes;
 pub mod blocks;
 pub mod chunk_format;
 pub mod conversions;
+pub mod difficulty;
+pub mod gamerules;
 pub mod importing;
+pub mod linear;
+pub mod lighting;
+pub mod protection;
+pub mod region;
+pub mod spawn;
+pub mod ticks;
+pub mod time;
+pub mod weather;
 
 
 #[cfg(test)]
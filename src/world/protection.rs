@@ -0,0 +1,46 @@
+//! Keeps players from changing blocks in protected regions they aren't members of.
+//!
+//! Regions are defined with `/region` and stored by [crate::database::protection]. Blocks aren't
+//! changed on the server yet, so a rejected edit is undone by sending the client the chunk again.
+
+use tracing::debug;
+
+use crate::net::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLight;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::prelude::*;
+
+/// Whether a player may change the block at `position`, which they can unless it's in a region
+/// they aren't a member of.
+pub async fn can_edit(
+    entity_id: u32,
+    position: (i32, i32, i32),
+    state: &GlobalState,
+) -> Result<bool> {
+    let uuid = state.world.get_component::<Player>(entity_id).await?.uuid;
+    let regions = state.database.protected_regions().await?;
+    Ok(regions
+        .iter()
+        .filter(|region| region.contains(position))
+        .all(|region| region.is_member(uuid)))
+}
+
+/// Checks a block change the client already made on its side, sending back the block it
+/// replaced if the player wasn't allowed to. Returns whether it was allowed.
+pub async fn check_edit(
+    entity_id: u32,
+    position: (i32, i32, i32),
+    state: &GlobalState,
+) -> Result<bool> {
+    if can_edit(entity_id, position, state).await? {
+        return Ok(true);
+    }
+
+    debug!("Rejected block change at {:?} by {}", position, entity_id);
+    let (chunk_x, chunk_z) = (position.0 >> 4, position.2 >> 4);
+    let chunk = ChunkDataAndUpdateLight::new(state.clone(), chunk_x, chunk_z).await?;
+    let conn = state.connections.get_connection(entity_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(chunk).await?;
+    Ok(false)
+}
@@ -0,0 +1,446 @@
+//! Reading region files, the 32x32 chunk files worlds are stored in.
+//!
+//! Anvil (`.mca`) is the vanilla format, [crate::world::linear] is supported as well.
+//! Which one is read is picked with `region_format` in the config.
+//!
+//! The first 4KiB of an Anvil file is a table of where each of its chunks is stored.
+//! A zeroed entry means the chunk was never generated, so that table alone is enough to tell
+//! which chunks exist, without decompressing any of them. See [RegionHeader].
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+use std::time::SystemTime;
+
+use dashmap::DashMap;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use tracing::warn;
+
+use crate::net::utils::buffer_pool::BufferPool;
+use crate::utils::prelude::*;
+use crate::world::linear::LinearRegion;
+
+/// How many idle buffers the decode pool holds on to. Chunks are read one at a time per region.
+const DECODE_POOL_SIZE: usize = 8;
+/// Chunks are rarely more than a few hundred KiB uncompressed, bigger buffers aren't kept.
+const DECODE_POOL_MAX_CAPACITY: usize = 1024 * 1024;
+
+/// Scratch buffers for reading and decompressing chunks, so importing a world doesn't allocate
+/// (and grow) fresh buffers for every chunk.
+pub static DECODE_POOL: LazyLock<BufferPool> =
+    LazyLock::new(|| BufferPool::new(DECODE_POOL_SIZE, DECODE_POOL_MAX_CAPACITY));
+
+/// Reads chunks out of a region file, whatever its format.
+pub trait RegionReader {
+    /// The uncompressed NBT of a chunk, if it exists.
+    ///
+    /// Takes either absolute chunk coordinates or ones relative to the region.
+    fn read_chunk(&mut self, x: i32, z: i32) -> Result<Option<Vec<u8>>>;
+
+    /// The uncompressed NBT of every chunk in the region.
+    fn read_all(&mut self) -> Result<Vec<Vec<u8>>>;
+}
+
+/// The compression types a chunk can be stored with in an Anvil file.
+pub const COMPRESSION_GZIP: u8 = 1;
+pub const COMPRESSION_ZLIB: u8 = 2;
+pub const COMPRESSION_NONE: u8 = 3;
+
+/// Set on the compression type of chunks stored in a separate `.mcc` file.
+const EXTERNAL_CHUNK_FLAG: u8 = 0x80;
+
+/// The unit chunks are laid out in within an Anvil file.
+pub const SECTOR_SIZE: u64 = 4096;
+
+/// Reads chunks out of an Anvil region file.
+///
+/// Each chunk is stored as its length (u32), its compression type (u8) and then the compressed
+/// NBT, starting at the sector the header points to.
+pub struct AnvilRegion<R> {
+    reader: R,
+    header: RegionHeader,
+}
+
+impl<R: Read + Seek> AnvilRegion<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let header = RegionHeader::read(&mut reader)?;
+        Ok(Self { reader, header })
+    }
+
+    pub fn header(&self) -> &RegionHeader {
+        &self.header
+    }
+}
+
+impl AnvilRegion<File> {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> RegionReader for AnvilRegion<R> {
+    fn read_chunk(&mut self, x: i32, z: i32) -> Result<Option<Vec<u8>>> {
+        let Some(location) = self.header.location(x, z) else {
+            return Ok(None);
+        };
+
+        self.reader
+            .seek(SeekFrom::Start(location.sector_offset as u64 * SECTOR_SIZE))?;
+        let mut prefix = [0; 5];
+        self.reader.read_exact(&mut prefix)?;
+
+        let length = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as u64;
+        let available = location.sector_count as u64 * SECTOR_SIZE - 4;
+        if length == 0 || length > available {
+            return Err(Error::Generic(format!(
+                "Chunk ({}, {}) claims {} bytes, but only has {} allocated",
+                x, z, length, available
+            )));
+        }
+
+        let mut data = DECODE_POOL.get();
+        data.resize(length as usize - 1, 0);
+        self.reader.read_exact(&mut data)?;
+
+        decompress_chunk(prefix[4], &data).map(Some)
+    }
+
+    fn read_all(&mut self) -> Result<Vec<Vec<u8>>> {
+        let positions: Vec<_> = self.header.chunks().collect();
+        let mut chunks = Vec::with_capacity(positions.len());
+        for (x, z) in positions {
+            match self.read_chunk(x, z) {
+                Ok(Some(chunk)) => chunks.push(chunk),
+                Ok(None) => {}
+                Err(e) => warn!("(Skipped) Could not read chunk ({}, {}): {}", x, z, e),
+            }
+        }
+        Ok(chunks)
+    }
+}
+
+/// Decompress a chunk as stored in an Anvil file, according to its compression type.
+///
+/// Decompresses into a buffer from [DECODE_POOL], so the result is allocated once at its final
+/// size instead of growing while the decoder runs.
+pub fn decompress_chunk(compression: u8, data: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed = DECODE_POOL.get();
+    let read = match compression {
+        COMPRESSION_GZIP => GzDecoder::new(data).read_to_end(&mut decompressed),
+        COMPRESSION_ZLIB => ZlibDecoder::new(data).read_to_end(&mut decompressed),
+        COMPRESSION_NONE => return Ok(data.to_vec()),
+        external if external & EXTERNAL_CHUNK_FLAG != 0 => {
+            return Err(Error::Generic(
+                "Chunks stored in external .mcc files are not supported".to_string(),
+            ))
+        }
+        unknown => return Err(Error::UnknownChunkCompression(unknown)),
+    };
+    read.map_err(Error::CompressionError)?;
+
+    Ok(decompressed.to_vec())
+}
+
+/// The on-disk format of region files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionFormat {
+    Anvil,
+    Linear,
+}
+
+impl FromStr for RegionFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "anvil" => Ok(RegionFormat::Anvil),
+            "linear" => Ok(RegionFormat::Linear),
+            other => Err(Error::InvalidConfig(
+                "region_format".to_string(),
+                format!("expected \"anvil\" or \"linear\", got \"{}\"", other),
+            )),
+        }
+    }
+}
+
+impl RegionFormat {
+    /// The extension of region files in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RegionFormat::Anvil => "mca",
+            RegionFormat::Linear => "linear",
+        }
+    }
+
+    pub fn open(&self, path: impl AsRef<Path>) -> Result<Box<dyn RegionReader + Send>> {
+        Ok(match self {
+            RegionFormat::Anvil => Box::new(AnvilRegion::from_file(path)?),
+            RegionFormat::Linear => Box::new(LinearRegion::from_file(path)?),
+        })
+    }
+
+    /// How many chunks a region file holds, reading as little of it as possible.
+    pub fn count_chunks(&self, path: impl AsRef<Path>) -> Result<usize> {
+        match self {
+            RegionFormat::Anvil => Ok(RegionHeader::from_file(path)?.chunk_count()),
+            RegionFormat::Linear => LinearRegion::count_chunks(path),
+        }
+    }
+}
+
+/// The size of the location table at the start of every region file.
+pub const HEADER_SIZE: usize = 4096;
+/// How many chunks a region spans along each axis.
+pub const REGION_WIDTH: i32 = 32;
+
+/// Where a chunk is stored in its region file, in 4KiB sectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkLocation {
+    pub sector_offset: u32,
+    pub sector_count: u8,
+}
+
+/// The location table of a region file.
+#[derive(Debug, Clone)]
+pub struct RegionHeader {
+    locations: Vec<u32>,
+}
+
+impl RegionHeader {
+    /// Read the location table from the start of a region file.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut bytes = vec![0; HEADER_SIZE];
+        reader.read_exact(&mut bytes)?;
+
+        let locations = bytes
+            .chunks_exact(4)
+            .map(|entry| u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]))
+            .collect();
+
+        Ok(Self { locations })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read(&mut File::open(path)?)
+    }
+
+    /// Where the chunk is stored, if it exists.
+    ///
+    /// Takes either absolute chunk coordinates or ones relative to the region.
+    pub fn location(&self, x: i32, z: i32) -> Option<ChunkLocation> {
+        let entry = self.locations[Self::index(x, z)];
+        let location = ChunkLocation {
+            sector_offset: entry >> 8,
+            sector_count: (entry & 0xFF) as u8,
+        };
+
+        (location.sector_offset != 0 && location.sector_count != 0).then_some(location)
+    }
+
+    /// Whether the chunk exists in this region.
+    pub fn contains(&self, x: i32, z: i32) -> bool {
+        self.location(x, z).is_some()
+    }
+
+    /// The coordinates (relative to the region) of every chunk that exists in it.
+    pub fn chunks(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (0..REGION_WIDTH)
+            .flat_map(|z| (0..REGION_WIDTH).map(move |x| (x, z)))
+            .filter(|(x, z)| self.contains(*x, *z))
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks().count()
+    }
+
+    fn index(x: i32, z: i32) -> usize {
+        ((x & (REGION_WIDTH - 1)) + (z & (REGION_WIDTH - 1)) * REGION_WIDTH) as usize
+    }
+}
+
+/// The name of the region file holding the chunk at the given chunk coordinates.
+pub fn region_file_name(chunk_x: i32, chunk_z: i32) -> String {
+    format!("r.{}.{}.mca", chunk_x >> 5, chunk_z >> 5)
+}
+
+/// Keeps parsed region headers around, so repeated existence checks don't re-read the file.
+///
+/// A header is read again if its file was modified since it was cached.
+#[derive(Default)]
+pub struct RegionHeaderCache {
+    headers: DashMap<PathBuf, (SystemTime, Arc<RegionHeader>)>,
+}
+
+impl RegionHeaderCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Result<Arc<RegionHeader>> {
+        let path = path.as_ref();
+        let modified = std::fs::metadata(path)?.modified()?;
+
+        if let Some(entry) = self.headers.get(path) {
+            if entry.0 == modified {
+                return Ok(entry.1.clone());
+            }
+        }
+
+        let header = Arc::new(RegionHeader::from_file(path)?);
+        self.headers
+            .insert(path.to_path_buf(), (modified, header.clone()));
+        Ok(header)
+    }
+
+    /// Whether the chunk exists in the region files under `dir`. A missing region file means the
+    /// chunk doesn't exist.
+    pub fn chunk_exists(&self, dir: impl AsRef<Path>, chunk_x: i32, chunk_z: i32) -> Result<bool> {
+        let path = dir.as_ref().join(region_file_name(chunk_x, chunk_z));
+        if !path.exists() {
+            return Ok(false);
+        }
+        Ok(self.get(path)?.contains(chunk_x, chunk_z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::io::{Cursor, Write};
+
+    use fastanvil::Region;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+
+    use super::*;
+
+    /// A region with a scattering of chunks, written by fastanvil like a real world would be.
+    fn fixture_region() -> Vec<u8> {
+        let mut region = Region::new(Cursor::new(Vec::new())).unwrap();
+        for (x, z) in [(0, 0), (1, 0), (31, 31), (5, 17), (0, 31)] {
+            region
+                .write_chunk(x, z, format!("chunk {} {}", x, z).as_bytes())
+                .unwrap();
+        }
+        region.into_inner().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_header_matches_full_reads() {
+        let bytes = fixture_region();
+        let header = RegionHeader::read(&mut Cursor::new(&bytes)).unwrap();
+
+        let mut region = Region::from_stream(Cursor::new(bytes)).unwrap();
+        let mut read = HashSet::new();
+        for x in 0..REGION_WIDTH {
+            for z in 0..REGION_WIDTH {
+                if region.read_chunk(x as usize, z as usize).unwrap().is_some() {
+                    read.insert((x, z));
+                }
+            }
+        }
+
+        let from_header: HashSet<_> = header.chunks().collect();
+        assert_eq!(from_header, read);
+        assert_eq!(header.chunk_count(), 5);
+    }
+
+    /// A region holding a single chunk at (0, 0), stored with the given compression type.
+    fn region_with_chunk(compression: u8, data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0; HEADER_SIZE * 2];
+        // Sector 2, 1 sector long
+        bytes[0..4].copy_from_slice(&[0, 0, 2, 1]);
+
+        bytes.extend_from_slice(&(data.len() as u32 + 1).to_be_bytes());
+        bytes.push(compression);
+        bytes.extend_from_slice(data);
+        bytes.resize(HEADER_SIZE * 3, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_read_gzip_chunk() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"gzip chunk").unwrap();
+        let bytes = region_with_chunk(COMPRESSION_GZIP, &encoder.finish().unwrap());
+
+        let mut region = AnvilRegion::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(region.read_chunk(0, 0).unwrap(), Some(b"gzip chunk".to_vec()));
+        assert_eq!(region.read_chunk(1, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_zlib_chunk() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"zlib chunk").unwrap();
+        let bytes = region_with_chunk(COMPRESSION_ZLIB, &encoder.finish().unwrap());
+
+        let mut region = AnvilRegion::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(region.read_chunk(0, 0).unwrap(), Some(b"zlib chunk".to_vec()));
+    }
+
+    #[test]
+    fn test_read_uncompressed_chunk() {
+        let bytes = region_with_chunk(COMPRESSION_NONE, b"raw chunk");
+        let mut region = AnvilRegion::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(region.read_all().unwrap(), vec![b"raw chunk".to_vec()]);
+    }
+
+    #[test]
+    fn test_unknown_compression() {
+        let bytes = region_with_chunk(9, b"chunk");
+        let mut region = AnvilRegion::new(Cursor::new(bytes)).unwrap();
+        assert!(matches!(
+            region.read_chunk(0, 0),
+            Err(Error::UnknownChunkCompression(9))
+        ));
+    }
+
+    #[test]
+    fn test_reader_matches_fastanvil() {
+        let mut region = AnvilRegion::new(Cursor::new(fixture_region())).unwrap();
+        assert_eq!(
+            region.read_chunk(5, 17).unwrap(),
+            Some(b"chunk 5 17".to_vec())
+        );
+        assert_eq!(region.read_all().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_absolute_coordinates() {
+        let header = RegionHeader::read(&mut Cursor::new(fixture_region())).unwrap();
+        // Chunk (-27, 49) is (5, 17) in region (-1, 1)
+        assert!(header.contains(-27, 49));
+        assert!(!header.contains(-26, 49));
+        assert_eq!(region_file_name(-27, 49), "r.-1.1.mca");
+    }
+
+    #[test]
+    fn test_cache_rereads_modified_files() {
+        let dir = std::env::temp_dir().join(format!("ferrumc-region-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(region_file_name(0, 0));
+
+        let mut file = std::fs::File::create(&path).unwrap();
+        std::io::Write::write_all(&mut file, &[0; HEADER_SIZE]).unwrap();
+        drop(file);
+
+        let cache = RegionHeaderCache::new();
+        assert!(!cache.chunk_exists(&dir, 1, 0).unwrap());
+        assert!(!cache.chunk_exists(&dir, 100, 100).unwrap());
+
+        let mut file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(4)).unwrap();
+        std::io::Write::write_all(&mut file, &[0, 0, 2, 1]).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(10))
+            .unwrap();
+        drop(file);
+
+        assert!(cache.chunk_exists(&dir, 1, 0).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
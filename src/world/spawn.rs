@@ -0,0 +1,51 @@
+use std::time::Instant;
+
+use tracing::info;
+
+use crate::database::Database;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::config::get_global_config;
+use crate::utils::constants::init;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Where players join, and respawn after dying.
+pub fn spawn_point() -> (Position, Rotation) {
+    (
+        Position::new(
+            init::DEFAULT_SPAWN_X_POS,
+            init::DEFAULT_SPAWN_Y_POS,
+            init::DEFAULT_SPAWN_Z_POS,
+        ),
+        Rotation::new(init::DEFAULT_SPAWN_YAW, init::DEFAULT_SPAWN_PITCH),
+    )
+}
+
+/// The chunk the world spawn is in.
+pub fn spawn_chunk() -> (i32, i32) {
+    let (position, _) = spawn_point();
+    (position.x >> 4, position.z >> 4)
+}
+
+/// Load the chunks around spawn into the cache, so the first player to join doesn't have to wait
+/// on the database, and keep them loaded like vanilla does. A radius of 0 skips preloading.
+pub async fn preload_spawn_chunks(database: &Database, radius: u32) -> Result<()> {
+    if radius == 0 {
+        return Ok(());
+    }
+
+    let start = Instant::now();
+    let config = get_global_config();
+    let loaded = database
+        .keep_spawn_chunks_loaded(spawn_chunk(), radius as i32, &config.default_dimension)
+        .await?;
+
+    info!(
+        "Preloaded {} spawn chunks within a radius of {} in {:?}",
+        loaded,
+        radius,
+        start.elapsed()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! The game tick: how fast it runs, and freezing and stepping it for debugging with `/tick`.
+//!
+//! [crate::net::systems::tick_system::TickSystem] calls [GameTicks::advance] at the configured
+//! rate, and systems that do something every tick wait for it with [GameTicks::subscribe].
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use crate::utils::config::MAX_TICK_RATE;
+use crate::utils::prelude::*;
+
+#[derive(Debug)]
+pub struct GameTicks {
+    /// Ticks per second.
+    rate: AtomicU32,
+    frozen: AtomicBool,
+    /// Ticks still to run while frozen, queued by [GameTicks::step].
+    steps: AtomicU32,
+    /// How many ticks have run.
+    count: watch::Sender<u64>,
+}
+
+impl Default for GameTicks {
+    /// 20 ticks per second, like vanilla.
+    fn default() -> Self {
+        Self::new(20)
+    }
+}
+
+impl GameTicks {
+    pub fn new(rate: u32) -> Self {
+        Self {
+            rate: AtomicU32::new(rate),
+            frozen: AtomicBool::new(false),
+            steps: AtomicU32::new(0),
+            count: watch::Sender::new(0),
+        }
+    }
+
+    pub fn rate(&self) -> u32 {
+        self.rate.load(Ordering::Relaxed)
+    }
+
+    pub fn set_rate(&self, rate: u32) -> Result<()> {
+        if !(1..=MAX_TICK_RATE).contains(&rate) {
+            return Err(Error::InvalidArgument(format!(
+                "The tick rate must be between 1 and {}, got {}",
+                MAX_TICK_RATE, rate
+            )));
+        }
+        self.rate.store(rate, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// How long a tick takes at the current rate.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(1) / self.rate()
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::Relaxed)
+    }
+
+    /// Stops or resumes the game. Unfreezing drops any steps that haven't run yet.
+    pub fn set_frozen(&self, frozen: bool) {
+        self.frozen.store(frozen, Ordering::Relaxed);
+        if !frozen {
+            self.steps.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Runs `ticks` more ticks while frozen, one per interval like normal.
+    pub fn step(&self, ticks: u32) -> Result<()> {
+        if !self.is_frozen() {
+            return Err(Error::InvalidArgument(
+                "The game can only be stepped while it's frozen".to_string(),
+            ));
+        }
+        self.steps.fetch_add(ticks, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Ticks still queued by [GameTicks::step].
+    pub fn pending_steps(&self) -> u32 {
+        self.steps.load(Ordering::Relaxed)
+    }
+
+    /// Runs a tick, unless the game is frozen with no steps left. Returns whether it ran.
+    pub fn advance(&self) -> bool {
+        if self.is_frozen() {
+            let step = self.steps.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |steps| {
+                steps.checked_sub(1)
+            });
+            if step.is_err() {
+                return false;
+            }
+        }
+        self.count.send_modify(|count| *count += 1);
+        true
+    }
+
+    /// How many ticks have run.
+    pub fn count(&self) -> u64 {
+        *self.count.borrow()
+    }
+
+    /// Notified after every tick. Ticks that run before the receiver catches up are only seen
+    /// once.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.count.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frozen_ticks_only_advance_by_steps() {
+        let ticks = GameTicks::default();
+        assert!(ticks.advance());
+        assert_eq!(ticks.count(), 1);
+
+        ticks.set_frozen(true);
+        for _ in 0..10 {
+            assert!(!ticks.advance());
+        }
+        assert_eq!(ticks.count(), 1);
+
+        ticks.step(3).unwrap();
+        let ran = (0..10).filter(|_| ticks.advance()).count();
+        assert_eq!(ran, 3);
+        assert_eq!(ticks.count(), 4);
+        assert_eq!(ticks.pending_steps(), 0);
+
+        ticks.set_frozen(false);
+        assert!(ticks.step(1).is_err());
+        assert!(ticks.advance());
+        assert_eq!(ticks.count(), 5);
+    }
+
+    #[test]
+    fn test_rate_sets_the_interval() {
+        let ticks = GameTicks::default();
+        assert_eq!(ticks.interval(), Duration::from_millis(50));
+        ticks.set_rate(10).unwrap();
+        assert_eq!(ticks.interval(), Duration::from_millis(100));
+        assert!(ticks.set_rate(0).is_err());
+        assert!(ticks.set_rate(MAX_TICK_RATE + 1).is_err());
+        assert_eq!(ticks.rate(), 10);
+    }
+}
@@ -0,0 +1,37 @@
+//! The world's age and time of day, advanced every tick by
+//! [crate::net::systems::time::TimeSystem].
+
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// How many ticks a full day and night take.
+pub const TICKS_PER_DAY: i64 = 24000;
+
+#[derive(Debug, Default)]
+pub struct WorldTime {
+    age: AtomicI64,
+    time_of_day: AtomicI64,
+}
+
+impl WorldTime {
+    /// How many ticks the world has been running for.
+    pub fn age(&self) -> i64 {
+        self.age.load(Ordering::Relaxed)
+    }
+
+    /// The time of day in ticks, counting up across days. 0 is sunrise, see [TICKS_PER_DAY].
+    pub fn time_of_day(&self) -> i64 {
+        self.time_of_day.load(Ordering::Relaxed)
+    }
+
+    pub fn set_time_of_day(&self, time: i64) {
+        self.time_of_day.store(time, Ordering::Relaxed);
+    }
+
+    /// Advances one tick. The time of day stands still unless `daylight_cycle` is on.
+    pub fn tick(&self, daylight_cycle: bool) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+        if daylight_cycle {
+            self.time_of_day.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Clear skies, rain and thunderstorms, tracked per dimension. While `doWeatherCycle` is on,
+//! [crate::net::systems::weather::WeatherSystem] moves each dimension on to new weather once the
+//! current one has lasted long enough.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use rand::Rng;
+
+use crate::net::packets::outgoing::game_event::GameEvent;
+use crate::utils::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Thunder,
+}
+
+impl Weather {
+    pub const ALL: [Weather; 3] = [Weather::Clear, Weather::Rain, Weather::Thunder];
+
+    /// The name used in commands.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Weather::Clear => "clear",
+            Weather::Rain => "rain",
+            Weather::Thunder => "thunder",
+        }
+    }
+
+    /// How many ticks this weather lasts for when it starts on its own, like vanilla.
+    fn natural_duration(&self) -> Range<u32> {
+        match self {
+            Weather::Clear => 12_000..180_000,
+            Weather::Rain => 12_000..24_000,
+            Weather::Thunder => 3_600..15_600,
+        }
+    }
+
+    /// The Game Events that show this weather to a client.
+    pub fn packets(&self) -> [GameEvent; 3] {
+        let (raining, rain_level, thunder_level) = match self {
+            Weather::Clear => (false, 0.0, 0.0),
+            Weather::Rain => (true, 1.0, 0.0),
+            Weather::Thunder => (true, 1.0, 1.0),
+        };
+        [
+            GameEvent::raining(raining),
+            GameEvent::rain_level(rain_level),
+            GameEvent::thunder_level(thunder_level),
+        ]
+    }
+}
+
+impl FromStr for Weather {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|weather| weather.as_str() == s)
+            .ok_or_else(|| Error::Generic(format!("Unknown weather: {}", s)))
+    }
+}
+
+impl Display for Weather {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DimensionWeather {
+    weather: Weather,
+    /// Ticks until the weather changes on its own.
+    ticks_left: u32,
+}
+
+impl DimensionWeather {
+    fn starting(weather: Weather) -> Self {
+        let ticks_left = rand::thread_rng().gen_range(weather.natural_duration());
+        Self {
+            weather,
+            ticks_left,
+        }
+    }
+}
+
+/// The weather in every dimension. Dimensions start out clear.
+#[derive(Debug, Default)]
+pub struct WorldWeather(Mutex<HashMap<String, DimensionWeather>>);
+
+impl WorldWeather {
+    pub fn get(&self, dimension: &str) -> Weather {
+        self.0
+            .lock()
+            .unwrap()
+            .get(dimension)
+            .map_or(Weather::Clear, |current| current.weather)
+    }
+
+    /// Changes the weather, for `duration` ticks if given, otherwise for as long as it would
+    /// naturally last.
+    pub fn set(&self, dimension: &str, weather: Weather, duration: Option<u32>) {
+        let mut current = DimensionWeather::starting(weather);
+        if let Some(duration) = duration {
+            current.ticks_left = duration;
+        }
+        self.0.lock().unwrap().insert(dimension.to_string(), current);
+    }
+
+    /// Advances a dimension's weather by one tick, returning the new weather if it changed.
+    ///
+    /// Clear skies turn to rain, or sometimes a thunderstorm, and both clear up again.
+    pub fn tick(&self, dimension: &str) -> Option<Weather> {
+        let mut dimensions = self.0.lock().unwrap();
+        let current = dimensions
+            .entry(dimension.to_string())
+            .or_insert_with(|| DimensionWeather::starting(Weather::Clear));
+
+        current.ticks_left = current.ticks_left.saturating_sub(1);
+        if current.ticks_left > 0 {
+            return None;
+        }
+
+        let next = match current.weather {
+            Weather::Clear if rand::thread_rng().gen_bool(0.2) => Weather::Thunder,
+            Weather::Clear => Weather::Rain,
+            Weather::Rain | Weather::Thunder => Weather::Clear,
+        };
+        *current = DimensionWeather::starting(next);
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weather_changes_when_its_time_is_up() {
+        let weather = WorldWeather::default();
+        assert_eq!(weather.get("overworld"), Weather::Clear);
+
+        weather.set("overworld", Weather::Rain, Some(3));
+        assert_eq!(weather.get("the_nether"), Weather::Clear);
+        assert_eq!(weather.tick("overworld"), None);
+        assert_eq!(weather.tick("overworld"), None);
+        assert_eq!(weather.tick("overworld"), Some(Weather::Clear));
+        assert_eq!(weather.get("overworld"), Weather::Clear);
+        // Clear weather lasts a while
+        assert_eq!(weather.tick("overworld"), None);
+    }
+}